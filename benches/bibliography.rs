@@ -0,0 +1,77 @@
+//! Benchmarks the rendering of a large bibliography. Run them with
+//! `cargo bench --features bench`, adding the `rayon` feature to compare
+//! parallel and sequential rendering.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use hayagriva::archive::{locales, ArchivedStyle};
+use hayagriva::citationberg::{IndependentStyle, Locale, Style};
+use hayagriva::io::from_yaml_str;
+use hayagriva::{
+    BibliographyDriver, BibliographyRequest, CitationItem, CitationRequest, Library,
+};
+
+/// How many times the entries of the test library are repeated.
+const COPIES: usize = 100;
+
+/// Build a large library by repeating the test entries under new keys.
+fn library() -> Library {
+    let data = std::fs::read_to_string("tests/data/basic.yml").unwrap();
+    let mut yaml = String::new();
+    for i in 0..COPIES {
+        for line in data.lines() {
+            match line.strip_suffix(':') {
+                Some(key) if !line.starts_with([' ', '#']) => {
+                    yaml.push_str(&format!("{key}-{i}:\n"));
+                }
+                _ => {
+                    yaml.push_str(line);
+                    yaml.push('\n');
+                }
+            }
+        }
+    }
+
+    from_yaml_str(&yaml).unwrap()
+}
+
+fn render(library: &Library, style: &IndependentStyle, locales: &[Locale]) {
+    let mut driver = BibliographyDriver::new();
+    for entry in library.iter() {
+        driver.citation(CitationRequest::from_items(
+            vec![CitationItem::with_entry(entry)],
+            style,
+            locales,
+        ));
+    }
+
+    let rendered = driver.finish(BibliographyRequest::new(style, None, locales));
+    assert!(rendered.bibliography.is_some());
+}
+
+fn bibliography(c: &mut Criterion) {
+    let library = library();
+    let Style::Independent(style) = ArchivedStyle::ChicagoFullnotes.get() else {
+        unreachable!()
+    };
+    let locales = locales();
+
+    let mut group = c.benchmark_group("bibliography");
+    group.sample_size(10);
+
+    // Without the `rayon` feature, this renders all entries sequentially.
+    group.bench_function("chicago-fullnotes", |b| {
+        b.iter(|| render(&library, &style, &locales))
+    });
+
+    // Compare against a single-threaded pool to show the speedup.
+    #[cfg(feature = "rayon")]
+    group.bench_function("chicago-fullnotes-single-thread", |b| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        b.iter(|| pool.install(|| render(&library, &style, &locales)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bibliography);
+criterion_main!(benches);
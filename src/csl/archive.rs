@@ -2,6 +2,8 @@
 // This file is generated by tests/generate.rs
 // Do not edit by hand!
 
+use std::sync::{Arc, Mutex, PoisonError};
+
 use citationberg::{Locale, Style};
 use serde::de::DeserializeOwned;
 
@@ -606,8 +608,12 @@ impl ArchivedStyle {
         ]
     }
 
-    /// Get the CBOR representation of this style.
-    fn bytes(self) -> &'static [u8] {
+    /// Get the CBOR representation of this style without deserializing
+    /// it.
+    ///
+    /// The archive only contains the CBOR encoding of each style, not the XML
+    /// it was created from, so the XML of an archived style is not available.
+    pub fn bytes(self) -> &'static [u8] {
         match self {
             Self::Alphanumeric => include_bytes!("../../archive/styles/alphanumeric.cbor"),
             Self::AmericanAnthropologicalAssociation => include_bytes!("../../archive/styles/american-anthropological-association.cbor"),
@@ -698,6 +704,34 @@ impl ArchivedStyle {
         from_cbor(self.bytes()).unwrap()
     }
 
+    /// Retrieve the style from a cache of the most recently used styles.
+    ///
+    /// Unlike [`get`](Self::get), repeated calls for the same style only
+    /// deserialize it once, as long as it is among the last
+    /// [`STYLE_CACHE_CAPACITY`] styles retrieved this way.
+    pub fn get_cached(self) -> Arc<Style> {
+        let lock = || STYLE_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        let hit = touch_cached_style(&mut lock(), self);
+        if let Some(style) = hit {
+            return style;
+        }
+
+        // Deserialize without holding the lock so that different styles can
+        // be loaded at the same time. If another thread loaded this style in
+        // the meantime, its copy is returned instead.
+        let style = Arc::new(self.get());
+        let mut cache = lock();
+        if let Some(style) = touch_cached_style(&mut cache, self) {
+            return style;
+        }
+
+        if cache.len() >= STYLE_CACHE_CAPACITY {
+            cache.remove(0);
+        }
+        cache.push((self, Arc::clone(&style)));
+        style
+    }
+
     /// Get the style's names in Hayagriva.
     pub fn names(self) -> &'static [&'static str] {
         match self {
@@ -1126,7 +1160,38 @@ impl ArchivedStyle {
             Self::VancouverSuperscript => "http://www.zotero.org/styles/vancouver-superscript",
         }
     }
+
+    /// Find all styles whose full name, CSL ID, or names contain the
+    /// query, ignoring case.
+    pub fn search(query: &str) -> impl Iterator<Item = Self> {
+        let query = query.to_lowercase();
+        Self::all().iter().copied().filter(move |style| {
+            style.display_name().to_lowercase().contains(&query)
+                || style.csl_id().to_lowercase().contains(&query)
+                || style.names().iter().any(|name| name.contains(&query))
+        })
+    }
 }
+
+/// How many parsed styles [`ArchivedStyle::get_cached`] keeps.
+pub const STYLE_CACHE_CAPACITY: usize = 8;
+
+/// The styles parsed by [`ArchivedStyle::get_cached`], the most recently used
+/// last.
+static STYLE_CACHE: Mutex<Vec<(ArchivedStyle, Arc<Style>)>> = Mutex::new(Vec::new());
+
+/// Look up a style in the cache and mark it as the most recently used.
+fn touch_cached_style(
+    cache: &mut Vec<(ArchivedStyle, Arc<Style>)>,
+    style: ArchivedStyle,
+) -> Option<Arc<Style>> {
+    let idx = cache.iter().position(|(cached, _)| *cached == style)?;
+    let hit = cache.remove(idx);
+    let style = Arc::clone(&hit.1);
+    cache.push(hit);
+    Some(style)
+}
+
 fn from_cbor<T: DeserializeOwned>(
     reader: &[u8],
 ) -> Result<T, ciborium::de::Error<std::io::Error>> {
@@ -1193,6 +1258,74 @@ pub const LOCALES: &[&[u8]] = &[
     include_bytes!("../../archive/locales/zh-TW.cbor"),
 ];
 
+/// The codes of the locales in [`LOCALES`], in the same order.
+#[rustfmt::skip]
+const LOCALE_CODES: &[&str] = &[
+    "af-ZA",
+    "ar",
+    "bg-BG",
+    "ca-AD",
+    "cs-CZ",
+    "cy-GB",
+    "da-DK",
+    "de-AT",
+    "de-CH",
+    "de-DE",
+    "el-GR",
+    "en-GB",
+    "en-US",
+    "es-CL",
+    "es-ES",
+    "es-MX",
+    "et-EE",
+    "eu",
+    "fa-IR",
+    "fi-FI",
+    "fr-CA",
+    "fr-FR",
+    "gl-ES",
+    "he-IL",
+    "hi-IN",
+    "hr-HR",
+    "hu-HU",
+    "id-ID",
+    "is-IS",
+    "it-IT",
+    "ja-JP",
+    "km-KH",
+    "ko-KR",
+    "la",
+    "lt-LT",
+    "lv-LV",
+    "mn-MN",
+    "nb-NO",
+    "nl-NL",
+    "nn-NO",
+    "pa-PK",
+    "pl-PL",
+    "pt-BR",
+    "pt-PT",
+    "ro-RO",
+    "ru-RU",
+    "sk-SK",
+    "sl-SI",
+    "sr-RS",
+    "sv-SE",
+    "th-TH",
+    "tr-TR",
+    "uk-UA",
+    "vi-VN",
+    "zh-CN",
+    "zh-TW",
+];
+
+/// Get the CSL locale with exactly the given code, like `de-DE`, without
+/// deserializing any of the others.
+pub fn locale(code: &str) -> Option<Locale> {
+    let index = LOCALE_CODES.iter().position(|&c| c == code)?;
+    Some(from_cbor(LOCALES[index]).unwrap())
+}
+
 /// Get all CSL locales.
 pub fn locales() -> Vec<Locale> {
     LOCALES
@@ -0,0 +1,484 @@
+//! Validation of CSL style source code.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+
+use citationberg::taxonomy::{
+    DateVariable, Kind, Locator, NameVariable, NumberVariable, OtherTerm, PageVariable,
+    StandardVariable, Term, Variable,
+};
+use citationberg::Style;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, Error as _, IntoDeserializer, Visitor};
+use unscanny::Scanner;
+
+/// A problem found in the source code of a CSL style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleDiagnostic {
+    /// How severe the problem is.
+    pub severity: Severity,
+    /// The byte range in the source the problem applies to.
+    pub span: Range<usize>,
+    /// The line and column at which the span starts.
+    pub location: Location,
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for StyleDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.location, self.severity, self.message)
+    }
+}
+
+/// How severe a [`StyleDiagnostic`] is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The style cannot be used.
+    Error,
+    /// The style can be used, but probably contains a mistake.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// A one-based line and column in a source file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The line number.
+    pub line: usize,
+    /// The column, counted in characters.
+    pub column: usize,
+}
+
+impl Location {
+    /// Compute the location of a byte offset in a string.
+    pub fn from_offset(src: &str, offset: usize) -> Self {
+        let before = &src[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        Self { line, column }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Check the source code of a CSL style for unknown variables, terms, and
+/// macros as well as unused macros. If none of these are found but the style
+/// still cannot be parsed, the parser error is reported instead.
+///
+/// The diagnostics are sorted by their position in the source.
+pub fn check_style(src: &str) -> Vec<StyleDiagnostic> {
+    let tags = scan(src);
+    let macros: Vec<&Attr> = tags
+        .iter()
+        .filter(|tag| tag.name == "macro")
+        .filter_map(|tag| tag.attr("name"))
+        .collect();
+
+    let mut checker = Checker { src, diagnostics: vec![] };
+    let mut used_macros = HashSet::new();
+    for tag in &tags {
+        for attr in &tag.attrs {
+            match (tag.name, attr.name) {
+                (_, "variable" | "is-numeric" | "is-uncertain-date") => {
+                    for (name, offset) in attr.names() {
+                        checker.name(
+                            name,
+                            offset,
+                            "variable",
+                            variable_names(),
+                            parses::<Variable>,
+                        );
+                    }
+                }
+                (_, "term") | ("term", "name") => checker.name(
+                    attr.value,
+                    attr.start,
+                    "term",
+                    term_names(),
+                    parses::<Term>,
+                ),
+                (_, "macro") => {
+                    used_macros.insert(attr.value);
+                    let names = macros.iter().map(|m| m.value);
+                    checker.name(attr.value, attr.start, "macro", names, |name| {
+                        macros.iter().any(|m| m.value == name)
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for attr in macros {
+        if !used_macros.contains(attr.value) {
+            checker.push(
+                Severity::Warning,
+                attr.span(),
+                format!("macro `{}` is never used", attr.value),
+            );
+        }
+    }
+
+    let mut diagnostics = checker.diagnostics;
+    if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        if let Err(err) = Style::from_xml(src) {
+            diagnostics.push(StyleDiagnostic {
+                severity: Severity::Error,
+                span: 0..0,
+                location: Location::from_offset(src, 0),
+                message: format!("style could not be parsed: {err}"),
+            });
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+/// Whether a name can be parsed into `T` the same way the style parser reads
+/// it from an attribute.
+fn parses<T: DeserializeOwned>(name: &str) -> bool {
+    let deserializer: StrDeserializer<ValueError> = name.into_deserializer();
+    T::deserialize(deserializer).is_ok()
+}
+
+/// The names of all variables, to suggest corrections for unknown ones.
+fn variable_names() -> impl Iterator<Item = &'static str> {
+    [
+        variant_names::<StandardVariable>(),
+        variant_names::<NumberVariable>(),
+        variant_names::<DateVariable>(),
+        variant_names::<NameVariable>(),
+        variant_names::<PageVariable>(),
+    ]
+    .into_iter()
+    .flatten()
+    .copied()
+}
+
+/// The names of all terms, to suggest corrections for unknown ones. The
+/// numbered ordinal, month, and season terms are left out, as a misspelled
+/// number is better reported without a suggestion.
+fn term_names() -> impl Iterator<Item = &'static str> {
+    [
+        variant_names::<OtherTerm>(),
+        variant_names::<Kind>(),
+        variant_names::<NameVariable>(),
+        variant_names::<NumberVariable>(),
+        variant_names::<Locator>(),
+    ]
+    .into_iter()
+    .flatten()
+    .copied()
+    .filter(|name| !name.ends_with(|c: char| c.is_ascii_digit()))
+}
+
+/// The names of the variants of an enum as they appear in a style. This is
+/// empty if `T` is not deserialized as an enum.
+fn variant_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut names: &'static [&'static str] = &[];
+    let _ = T::deserialize(VariantNames(&mut names));
+    names
+}
+
+/// A deserializer that only records the variant names an enum passes to it.
+struct VariantNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for VariantNames<'_> {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(ValueError::custom("expected an enum"))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = variants;
+        Err(ValueError::custom("only the variant names are recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+/// Collects diagnostics for a style.
+struct Checker<'s> {
+    src: &'s str,
+    diagnostics: Vec<StyleDiagnostic>,
+}
+
+impl Checker<'_> {
+    /// Check a name that starts at the given offset in the source.
+    fn name<'a>(
+        &mut self,
+        name: &str,
+        offset: usize,
+        kind: &str,
+        candidates: impl Iterator<Item = &'a str>,
+        known: impl Fn(&str) -> bool,
+    ) {
+        if name.is_empty() || known(name) {
+            return;
+        }
+
+        let mut message = format!("unknown {kind} `{name}`");
+        if let Some(suggestion) = suggest(name, candidates) {
+            message.push_str(&format!(", did you mean `{suggestion}`?"));
+        }
+        self.push(Severity::Error, offset..offset + name.len(), message);
+    }
+
+    /// Add a diagnostic.
+    fn push(&mut self, severity: Severity, span: Range<usize>, message: String) {
+        let location = Location::from_offset(self.src, span.start);
+        self.diagnostics
+            .push(StyleDiagnostic { severity, span, location, message });
+    }
+}
+
+/// Find the candidate that is most similar to a misspelled name.
+pub(crate) fn suggest<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    candidates
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= 2 && distance < name.chars().count())
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            row.push((prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// An opening XML tag.
+struct Tag<'s> {
+    name: &'s str,
+    attrs: Vec<Attr<'s>>,
+}
+
+impl<'s> Tag<'s> {
+    /// Find an attribute by name.
+    fn attr(&self, name: &str) -> Option<&Attr<'s>> {
+        self.attrs.iter().find(|attr| attr.name == name)
+    }
+}
+
+/// An attribute of an XML tag.
+struct Attr<'s> {
+    name: &'s str,
+    value: &'s str,
+    /// The byte offset at which the value starts.
+    start: usize,
+}
+
+impl Attr<'_> {
+    /// The byte range of the value.
+    fn span(&self) -> Range<usize> {
+        self.start..self.start + self.value.len()
+    }
+
+    /// The space-separated names in the value along with their byte offsets.
+    fn names(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.value.split(' ').scan(self.start, |offset, name| {
+            let start = *offset;
+            *offset += name.len() + 1;
+            Some((name, start))
+        })
+    }
+}
+
+/// Collect the opening tags of an XML document along with the positions of
+/// their attribute values.
+fn scan(src: &str) -> Vec<Tag<'_>> {
+    let is_name = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.');
+    let mut s = Scanner::new(src);
+    let mut tags = vec![];
+
+    loop {
+        s.eat_until('<');
+        if s.done() {
+            break;
+        }
+
+        if s.eat_if("<!--") {
+            s.eat_until("-->");
+            s.eat_if("-->");
+            continue;
+        } else if s.eat_if("<![CDATA[") {
+            s.eat_until("]]>");
+            s.eat_if("]]>");
+            continue;
+        }
+
+        s.eat();
+        if s.eat_if(|c: char| matches!(c, '?' | '!' | '/')) {
+            s.eat_until('>');
+            s.eat_if('>');
+            continue;
+        }
+
+        let mut tag = Tag { name: s.eat_while(is_name), attrs: vec![] };
+        loop {
+            s.eat_whitespace();
+            if s.done() || s.eat_if('>') || s.eat_if("/>") {
+                break;
+            }
+
+            let name = s.eat_while(is_name);
+            if name.is_empty() {
+                s.eat();
+                continue;
+            }
+
+            s.eat_whitespace();
+            if !s.eat_if('=') {
+                continue;
+            }
+
+            s.eat_whitespace();
+            let quote = match s.eat() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => continue,
+            };
+
+            let start = s.cursor();
+            let value = s.eat_until(quote);
+            s.eat_if(quote);
+            tag.attrs.push(Attr { name, value, start });
+        }
+
+        tags.push(tag);
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn valid_style() {
+        let src = fs::read_to_string("tests/data/art-history.csl").unwrap();
+        let diagnostics = check_style(&src);
+        assert!(
+            diagnostics.iter().all(|d| d.severity == Severity::Warning),
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn typos() {
+        let src = r#"<?xml version="1.0" encoding="utf-8"?>
+<style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0">
+  <!-- <text variable="commented-out"/> -->
+  <macro name="unused">
+    <text term="in"/>
+  </macro>
+  <macro name="publisher">
+    <names variable="author editr"/>
+  </macro>
+  <citation>
+    <layout>
+      <text variable="pubisher"/>
+      <text macro="publisher"/>
+      <text macro="publsher"/>
+      <text term="retreived"/>
+      <text term="month-13"/>
+    </layout>
+  </citation>
+</style>"#;
+
+        let diagnostics = check_style(src);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.location.to_string(), d.severity, d.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                ("4:16".into(), Severity::Warning, "macro `unused` is never used"),
+                (
+                    "8:29".into(),
+                    Severity::Error,
+                    "unknown variable `editr`, did you mean `editor`?"
+                ),
+                (
+                    "12:23".into(),
+                    Severity::Error,
+                    "unknown variable `pubisher`, did you mean `publisher`?"
+                ),
+                (
+                    "14:20".into(),
+                    Severity::Error,
+                    "unknown macro `publsher`, did you mean `publisher`?"
+                ),
+                (
+                    "15:19".into(),
+                    Severity::Error,
+                    "unknown term `retreived`, did you mean `retrieved`?"
+                ),
+                ("16:19".into(), Severity::Error, "unknown term `month-13`"),
+            ]
+        );
+
+        assert_eq!(&src[diagnostics[2].span.clone()], "pubisher");
+    }
+
+    #[test]
+    fn multi_word_terms() {
+        let src = r#"<text term="and others"/><text term="and other"/>"#;
+        let found: Vec<_> = check_style(src)
+            .into_iter()
+            .filter(|d| d.message.starts_with("unknown"))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(found, ["unknown term `and other`, did you mean `and others`?"]);
+    }
+
+    #[test]
+    fn location() {
+        let src = "ab\ncä\nd";
+        assert_eq!(Location::from_offset(src, 0), Location { line: 1, column: 1 });
+        assert_eq!(Location::from_offset(src, 6), Location { line: 2, column: 3 });
+        assert_eq!(Location::from_offset(src, 7), Location { line: 3, column: 1 });
+    }
+}
@@ -37,25 +37,27 @@ impl Elem {
         &self,
         w: &mut impl fmt::Write,
         format: BufWriteFormat,
+    ) -> Result<(), fmt::Error> {
+        self.write_buf_at(w, format, false)
+    }
+
+    /// Write the element. If `line_start` is set, the preceding output already
+    /// ends with a line break, so a block does not need to start a new line.
+    fn write_buf_at(
+        &self,
+        w: &mut impl fmt::Write,
+        format: BufWriteFormat,
+        line_start: bool,
     ) -> Result<(), fmt::Error> {
         match (format, self.display) {
-            (BufWriteFormat::Html, Some(Display::Block)) => w.write_str("<div>")?,
-            (BufWriteFormat::Html, Some(Display::Indent)) => {
-                w.write_str("<div style=\"padding-left: 4em;\">")?
+            (BufWriteFormat::Html, Some(display)) => {
+                write!(w, "<div class=\"{}\">", display_class(display))?
             }
-            (BufWriteFormat::Html, Some(Display::LeftMargin)) => {
-                w.write_str("<div style=\"float: left;\">")?
-            }
-            (BufWriteFormat::Html, Some(Display::RightInline)) => {
-                w.write_str("<div style=\"float: right; clear: both;\">")?
-            }
-            (_, Some(Display::Block)) => w.write_char('\n')?,
+            (_, Some(Display::Block)) if !line_start => w.write_char('\n')?,
             (_, _) => {}
         }
 
-        for child in &self.children.0 {
-            child.write_buf(w, format)?;
-        }
+        write_children(&self.children.0, w, format)?;
 
         match (format, self.display) {
             (BufWriteFormat::Html, Some(_)) => w.write_str("</div>")?,
@@ -97,38 +99,51 @@ impl fmt::Display for Elem {
     }
 }
 
-/// Merge adjacent text nodes with the same formatting.
+/// The class of the `div` that wraps an element with the given display in HTML
+/// output. The names are the same as citeproc-js uses.
+pub(super) fn display_class(display: Display) -> &'static str {
+    match display {
+        Display::Block => "csl-block",
+        Display::LeftMargin => "csl-left-margin",
+        Display::RightInline => "csl-right-inline",
+        Display::Indent => "csl-indent",
+    }
+}
+
+/// Merge adjacent text nodes with the same formatting and inline elements
+/// without display or meta. The result does not keep any spare capacity since
+/// it is usually kept around for the rest of the document.
 pub(super) fn simplify_children(children: ElemChildren) -> ElemChildren {
-    ElemChildren(children.0.into_iter().fold(Vec::new(), |mut acc, child| {
-        match (child, acc.last_mut()) {
-            (ElemChild::Text(t), Some(ElemChild::Text(last)))
-                if last.formatting == t.formatting =>
-            {
-                last.text.push_str(&t.text);
-                return acc;
-            }
-            (ElemChild::Elem(e), _) if e.may_inline() => {
-                acc.extend(e.children.0);
-            }
-            (ElemChild::Elem(e), _) => {
-                acc.push(ElemChild::Elem(e.simplify()));
-            }
-            (child, _) => {
-                acc.push(child);
-            }
+    let mut res = ElemChildren(Vec::with_capacity(children.0.len()));
+    for child in children.0 {
+        res.push_merged(child);
+    }
+
+    res.0.shrink_to_fit();
+    for child in &mut res.0 {
+        if let ElemChild::Text(t) = child {
+            t.text.shrink_to_fit();
         }
-        acc
-    }))
+    }
+
+    res
 }
 
 /// Which CSL construct created an element.
+///
+/// An element with meta wraps the complete output of the construct, including
+/// its affixes and quotation marks, and nothing else. Consumers can use this
+/// to restyle or link parts of a citation, for example all names or the title.
+/// More variants may be added in the future.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ElemMeta {
     /// The element is the output of `cs:names`.
     Names,
     /// The element is the output of `cs:date`.
     Date,
-    /// The element is the output of `cs:text`.
+    /// The element is the output of `cs:text` with a `variable` that does not
+    /// have a more specific variant.
     Text,
     /// The element is the output of `cs:number`.
     Number,
@@ -144,6 +159,12 @@ pub enum ElemMeta {
     /// The element is the output of `cs:text` with a `variable` set to
     /// `citation-label`.
     CitationLabel,
+    /// The element is the output of `cs:text` with a `variable` set to
+    /// `title` or `title-short`.
+    Title,
+    /// The element is the output of `cs:text` with a `variable` set to
+    /// `container-title` or `container-title-short`.
+    ContainerTitle,
 }
 
 /// A container for element children with useful methods.
@@ -161,6 +182,43 @@ impl ElemChildren {
         self.0.iter().all(|e| e.is_empty())
     }
 
+    /// Append the children of another container, merging text at the seam if
+    /// the formatting matches.
+    pub(super) fn append(&mut self, other: ElemChildren) {
+        let mut iter = other.0.into_iter();
+        match (iter.next(), self.0.last_mut()) {
+            (Some(ElemChild::Text(t)), Some(ElemChild::Text(last)))
+                if last.formatting == t.formatting =>
+            {
+                last.text.push_str(&t.text);
+            }
+            (Some(child), _) => self.0.push(child),
+            (None, _) => {}
+        }
+
+        self.0.extend(iter);
+    }
+
+    /// Push a child, merging it into the previous text if the formatting
+    /// matches and inlining its children if it is an element that may be
+    /// inlined.
+    fn push_merged(&mut self, child: ElemChild) {
+        match (child, self.0.last_mut()) {
+            (ElemChild::Text(t), Some(ElemChild::Text(last)))
+                if last.formatting == t.formatting =>
+            {
+                last.text.push_str(&t.text);
+            }
+            (ElemChild::Elem(e), _) if e.may_inline() => {
+                for child in e.children.0 {
+                    self.push_merged(child);
+                }
+            }
+            (ElemChild::Elem(e), _) => self.0.push(ElemChild::Elem(e.simplify())),
+            (child, _) => self.0.push(child),
+        }
+    }
+
     /// Whether this container has any content other than whitespace.
     pub(super) fn has_content(&self) -> bool {
         self.0.iter().any(|e| e.has_content())
@@ -199,16 +257,45 @@ impl ElemChildren {
         None
     }
 
+    /// Make elements with the `left-margin` and `right-inline` displays
+    /// siblings at the top level, even if they were produced by nested
+    /// elements without a display. The content after a left margin is wrapped
+    /// in a right-inline element if there is none.
+    pub(super) fn align_margins(&mut self) {
+        if self.0.iter().any(has_nested_margin) {
+            self.0 = lift_margins(mem::take(&mut self.0));
+        }
+
+        let Some(idx) = self.0.iter().position(|c| is_display(c, Display::LeftMargin))
+        else {
+            return;
+        };
+
+        let rest = self.0.split_off(idx + 1);
+        if rest.iter().any(|c| is_display(c, Display::RightInline))
+            || !rest.iter().any(ElemChild::has_content)
+        {
+            self.0.extend(rest);
+            return;
+        }
+
+        self.0.push(
+            Elem {
+                children: ElemChildren(rest),
+                display: Some(Display::RightInline),
+                meta: None,
+            }
+            .into(),
+        );
+    }
+
     /// Write the children to the given buffer.
     pub fn write_buf(
         &self,
         w: &mut impl fmt::Write,
         format: BufWriteFormat,
     ) -> Result<(), fmt::Error> {
-        for child in &self.0 {
-            child.write_buf(w, format)?;
-        }
-        Ok(())
+        write_children(&self.0, w, format)
     }
 
     /// Get a mutable reference on the last text leaf.
@@ -220,6 +307,82 @@ impl ElemChildren {
     pub(super) fn last_text(&self) -> Option<&Formatted> {
         last_text_child(&self.0)
     }
+
+    /// Uppercase the first letter, skipping leading punctuation. Names are
+    /// left alone so that a cite starting with a particle like "von Neumann"
+    /// keeps it lowercase.
+    pub(super) fn capitalize_first(&mut self) {
+        capitalize_first_child(&mut self.0);
+    }
+
+    /// Whether the content ends with a period, a question mark, or an
+    /// exclamation mark, possibly followed by closing quotes.
+    pub(super) fn ends_with_terminal_punctuation(&self) -> bool {
+        last_leaf_text(&self.0).is_some_and(|text| {
+            text.trim_end()
+                .trim_end_matches(['"', '\'', '”', '’', '»'])
+                .ends_with(['.', '?', '!', '…'])
+        })
+    }
+}
+
+/// Write the children to the given buffer. In plain text, a block that
+/// follows another block or a line break does not add another line break, so
+/// that adjacent blocks are not separated by a blank line.
+fn write_children(
+    children: &[ElemChild],
+    w: &mut impl fmt::Write,
+    format: BufWriteFormat,
+) -> Result<(), fmt::Error> {
+    let mut line_start = false;
+    for child in children {
+        match child {
+            ElemChild::Elem(e) => e.write_buf_at(w, format, line_start)?,
+            child => child.write_buf(w, format)?,
+        }
+
+        line_start = format != BufWriteFormat::Html
+            && match child {
+                ElemChild::Text(t) => t.text.ends_with('\n'),
+                child => is_display(child, Display::Block),
+            };
+    }
+
+    Ok(())
+}
+
+/// Whether the child is an element with the given display.
+fn is_display(child: &ElemChild, display: Display) -> bool {
+    matches!(child, ElemChild::Elem(e) if e.display == Some(display))
+}
+
+/// Whether the child is an element without a display that contains a
+/// left-margin or right-inline element.
+fn has_nested_margin(child: &ElemChild) -> bool {
+    match child {
+        ElemChild::Elem(e) if e.display.is_none() => e.children.0.iter().any(|c| {
+            is_display(c, Display::LeftMargin)
+                || is_display(c, Display::RightInline)
+                || has_nested_margin(c)
+        }),
+        _ => false,
+    }
+}
+
+/// Replace the elements without a display that contain margins with their
+/// children.
+fn lift_margins(children: Vec<ElemChild>) -> Vec<ElemChild> {
+    let mut res = Vec::with_capacity(children.len());
+    for child in children {
+        if has_nested_margin(&child) {
+            let ElemChild::Elem(e) = child else { unreachable!() };
+            res.extend(lift_margins(e.children.0));
+        } else {
+            res.push(child);
+        }
+    }
+
+    res
 }
 
 pub(crate) fn last_text_mut_child(children: &mut [ElemChild]) -> Option<&mut Formatted> {
@@ -230,6 +393,52 @@ pub(crate) fn last_text_mut_child(children: &mut [ElemChild]) -> Option<&mut For
     })
 }
 
+/// Returns whether the first letter or digit has been reached, in which case
+/// the caller must stop looking.
+fn capitalize_first_child(children: &mut [ElemChild]) -> bool {
+    for child in children {
+        match child {
+            ElemChild::Text(t) | ElemChild::Link { text: t, .. } => {
+                let Some((idx, c)) =
+                    t.text.char_indices().find(|(_, c)| c.is_alphanumeric())
+                else {
+                    continue;
+                };
+
+                if c.is_lowercase() {
+                    let upper = c.to_uppercase().to_string();
+                    t.text.replace_range(idx..idx + c.len_utf8(), &upper);
+                }
+                return true;
+            }
+            ElemChild::Elem(e)
+                if matches!(e.meta, Some(ElemMeta::Names | ElemMeta::Name(..))) =>
+            {
+                if e.has_content() {
+                    return true;
+                }
+            }
+            ElemChild::Elem(e) => {
+                if capitalize_first_child(&mut e.children.0) {
+                    return true;
+                }
+            }
+            ElemChild::Markup(_) | ElemChild::Transparent { .. } => return true,
+        }
+    }
+
+    false
+}
+
+fn last_leaf_text(children: &[ElemChild]) -> Option<&str> {
+    children.iter().rev().find(|c| c.has_content()).and_then(|c| match c {
+        ElemChild::Text(t) | ElemChild::Link { text: t, .. } => Some(t.text.as_str()),
+        ElemChild::Markup(m) => Some(m.as_str()),
+        ElemChild::Elem(e) => last_leaf_text(&e.children.0),
+        ElemChild::Transparent { .. } => None,
+    })
+}
+
 pub(crate) fn last_text_child(children: &[ElemChild]) -> Option<&Formatted> {
     children.last().and_then(|c| match c {
         ElemChild::Text(t) => Some(t),
@@ -255,7 +464,9 @@ pub enum ElemChild {
     Text(Formatted),
     /// A child element.
     Elem(Elem),
-    /// This should be processed by Typst.
+    /// Math markup without delimiters. It is kept as written and should be
+    /// processed by the consumer, e.g. by Typst. Plain text output wraps it in
+    /// dollars and HTML output in `\(…\)` for MathJax and KaTeX.
     Markup(String),
     /// This is a link.
     Link {
@@ -289,7 +500,10 @@ impl ElemChild {
                 Ok(())
             }
             ElemChild::Elem(e) => e.write_buf(w, format),
-            ElemChild::Markup(m) => w.write_str(m),
+            ElemChild::Markup(m) => match format {
+                BufWriteFormat::Html => write!(w, "\\({m}\\)"),
+                BufWriteFormat::Plain | BufWriteFormat::VT100 => write!(w, "${m}$"),
+            },
             ElemChild::Link { text, url } if format == BufWriteFormat::Html => {
                 w.write_str("<a href=\"")?;
                 w.write_str(url)?;
@@ -0,0 +1,159 @@
+//! Support for the `cs:intext` element of CSL-M and CSL 1.0.2 styles.
+
+use std::ops::Range;
+
+use citationberg::{Citation, IndependentStyle, XmlError};
+use unscanny::Scanner;
+
+/// The `cs:intext` layout of a style, which is used for citations that
+/// mention the author in prose, e.g. "Doe (2001) argues …".
+///
+/// Citationberg does not know about this element, so it is extracted from the
+/// source code of the style. It accepts the same attributes as
+/// `cs:citation`.
+///
+/// ```
+/// use hayagriva::IntextLayout;
+///
+/// let intext = IntextLayout::from_xml(
+///     r#"<style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" class="in-text">
+///       <info><title>Narrative</title><id>narrative</id><updated>2024-01-01T00:00:00+00:00</updated></info>
+///       <citation><layout><text variable="title"/></layout></citation>
+///       <intext><layout><names variable="author"/></layout></intext>
+///     </style>"#,
+/// ).unwrap();
+///
+/// assert!(intext.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntextLayout(Citation);
+
+impl IntextLayout {
+    /// Extract the `cs:intext` layout from the source code of a style.
+    /// Returns `None` if the style does not define one.
+    pub fn from_xml(src: &str) -> Result<Option<Self>, XmlError> {
+        let Some(intext) = find_element(src, "intext") else {
+            return Ok(None);
+        };
+
+        // Let citationberg parse the layout by passing it off as the
+        // `cs:citation` element of the style.
+        let mut removed: Vec<_> = find_element(src, "citation")
+            .into_iter()
+            .chain([intext.clone()])
+            .collect();
+        removed.sort_by_key(|range| range.start);
+
+        let mut synthetic = String::with_capacity(src.len());
+        let mut last = 0;
+        for range in removed {
+            synthetic.push_str(&src[last..range.start]);
+            last = range.end;
+        }
+        synthetic.push_str(&src[last..]);
+
+        let element = &src[intext];
+        let (open, rest) = element.split_at(element.find("intext").unwrap_or(0));
+        let rest = rest.strip_prefix("intext").unwrap_or(rest);
+        let rest = match rest.strip_suffix("intext>") {
+            Some(rest) => format!("{rest}citation>"),
+            None => rest.to_string(),
+        };
+
+        let close = synthetic.rfind("</").unwrap_or(synthetic.len());
+        synthetic.insert_str(close, &format!("{open}citation{rest}"));
+
+        Ok(Some(Self(IndependentStyle::from_xml(&synthetic)?.citation)))
+    }
+
+    /// The layout and options of the element.
+    pub(crate) fn citation(&self) -> &Citation {
+        &self.0
+    }
+}
+
+/// Find the byte range of the first element with the given name, from the
+/// start of its opening tag to the end of its closing tag.
+fn find_element(src: &str, name: &str) -> Option<Range<usize>> {
+    let is_name = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.');
+    let local = |tag: &str| tag.rsplit(':').next().unwrap_or(tag) == name;
+    let mut s = Scanner::new(src);
+    let mut start = None;
+    let mut depth = 0usize;
+
+    loop {
+        s.eat_until('<');
+        if s.done() {
+            return None;
+        }
+
+        let tag_start = s.cursor();
+        if s.eat_if("<!--") {
+            s.eat_until("-->");
+            s.eat_if("-->");
+            continue;
+        } else if s.eat_if("<![CDATA[") {
+            s.eat_until("]]>");
+            s.eat_if("]]>");
+            continue;
+        }
+
+        s.eat();
+        let closing = s.eat_if('/');
+        if s.eat_if(|c: char| matches!(c, '?' | '!')) {
+            s.eat_until('>');
+            s.eat_if('>');
+            continue;
+        }
+
+        let matches = local(s.eat_while(is_name));
+        let mut self_closing = false;
+        while let Some(c) = s.eat() {
+            match c {
+                '"' | '\'' => {
+                    s.eat_until(c);
+                    s.eat_if(c);
+                }
+                '/' if s.peek() == Some('>') => self_closing = true,
+                '>' => break,
+                _ => {}
+            }
+        }
+
+        if !matches {
+            continue;
+        }
+
+        if closing {
+            depth = depth.saturating_sub(1);
+            if depth == 0 {
+                if let Some(start) = start {
+                    return Some(start..s.cursor());
+                }
+            }
+        } else if self_closing {
+            if start.is_none() {
+                return Some(tag_start..s.cursor());
+            }
+        } else {
+            start.get_or_insert(tag_start);
+            depth += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_elements() {
+        let src = r#"<style><!-- <intext/> --><citation et-al-min="3"><layout>
+            <text value="/>"/></layout></citation><cs:intext><layout/></cs:intext></style>"#;
+        let citation = find_element(src, "citation").unwrap();
+        assert!(src[citation].starts_with("<citation et-al-min"));
+        let intext = find_element(src, "intext").unwrap();
+        assert_eq!(&src[intext], "<cs:intext><layout/></cs:intext>");
+        assert_eq!(find_element(src, "bibliography"), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Loading CSL styles and locales from user-provided files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use citationberg::{
+    DependentStyle, IndependentStyle, Locale, LocaleFile, Style, XmlError,
+};
+use thiserror::Error;
+
+#[cfg(feature = "archive")]
+use crate::csl::archive::{self, ArchivedStyle};
+
+/// Errors that may occur when loading a style or locale.
+#[derive(Debug, Error)]
+pub enum StyleLoadError {
+    /// A file could not be read.
+    #[error("could not read {}: {error}", path.display())]
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        error: std::io::Error,
+    },
+    /// A style or locale file is malformed.
+    #[error("malformed CSL file: {0}")]
+    Xml(#[from] XmlError),
+    /// The parent of a dependent style could not be found in the archive or
+    /// any of the style directories.
+    #[error("could not find the parent style {0} of the dependent style")]
+    MissingParent(String),
+    /// The parent of a dependent style is itself a dependent style.
+    #[error("the parent style {0} is a dependent style itself")]
+    DependentParent(String),
+}
+
+/// Loads CSL styles and locales, resolving dependent styles to their parents.
+///
+/// Dependent styles only link to an independent parent style and may override
+/// its default locale. When such a style is loaded, the parent is looked up
+/// first in the bundled archive (if the `archive` feature is enabled), then in
+/// the directory of the dependent style file, and lastly in the style
+/// directories that were registered with [`StyleLoader::add_style_dir`]. In a
+/// directory, the parent is expected to be stored in a file named after the
+/// last segment of its ID, like the [official CSL
+/// repository](https://github.com/citation-style-language/styles) does (e.g.
+/// `apa.csl` for `http://www.zotero.org/styles/apa`).
+///
+/// ```
+/// use hayagriva::StyleLoader;
+///
+/// let mut loader = StyleLoader::new();
+/// loader.add_style_dir("tests/data");
+/// let style = loader.load_xml(
+///     r#"<?xml version="1.0" encoding="utf-8"?>
+///     <style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" default-locale="de-DE">
+///       <info>
+///         <title>Art History (German)</title>
+///         <id>http://www.zotero.org/styles/art-history-de</id>
+///         <link href="http://www.zotero.org/styles/art-history" rel="independent-parent"/>
+///         <updated>2024-01-01T00:00:00+00:00</updated>
+///       </info>
+///     </style>"#,
+/// ).unwrap();
+///
+/// assert_eq!(style.info.title.value, "Art History");
+/// assert_eq!(style.default_locale.unwrap().0, "de-DE");
+/// ```
+#[derive(Debug, Clone)]
+pub struct StyleLoader {
+    style_dirs: Vec<PathBuf>,
+    locales: Vec<Locale>,
+}
+
+impl StyleLoader {
+    /// Create a new loader. If the `archive` feature is enabled, the bundled
+    /// locales are already registered.
+    pub fn new() -> Self {
+        #[cfg(feature = "archive")]
+        let locales = archive::locales();
+        #[cfg(not(feature = "archive"))]
+        let locales = vec![];
+
+        Self { style_dirs: vec![], locales }
+    }
+
+    /// Add a directory to search for the parents of dependent styles.
+    pub fn add_style_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.style_dirs.push(dir.into());
+    }
+
+    /// Register a locale. Registered locales take precedence over the bundled
+    /// ones and over locales that were registered before.
+    pub fn add_locale(&mut self, locale: Locale) {
+        self.locales.insert(0, locale);
+    }
+
+    /// Parse and register a locale file.
+    pub fn add_locale_xml(&mut self, xml: &str) -> Result<(), StyleLoadError> {
+        self.add_locale(LocaleFile::from_xml(xml)?.into());
+        Ok(())
+    }
+
+    /// Read, parse, and register a locale file.
+    pub fn add_locale_path(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), StyleLoadError> {
+        self.add_locale_xml(&read(path.as_ref())?)
+    }
+
+    /// The registered locales, to be passed to citation and bibliography
+    /// requests.
+    pub fn locales(&self) -> &[Locale] {
+        &self.locales
+    }
+
+    /// Parse a style and resolve it to an independent style.
+    pub fn load_xml(&self, xml: &str) -> Result<IndependentStyle, StyleLoadError> {
+        self.resolve(Style::from_xml(xml)?, None)
+    }
+
+    /// Read and parse a style file and resolve it to an independent style.
+    pub fn load_path(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<IndependentStyle, StyleLoadError> {
+        let path = path.as_ref();
+        self.resolve(Style::from_xml(&read(path)?)?, path.parent())
+    }
+
+    /// Resolve a style, looking up the parent of a dependent style.
+    fn resolve(
+        &self,
+        style: Style,
+        dir: Option<&Path>,
+    ) -> Result<IndependentStyle, StyleLoadError> {
+        let dependent = match style {
+            Style::Independent(independent) => return Ok(independent),
+            Style::Dependent(dependent) => dependent,
+        };
+
+        let id = dependent.parent_link.href.clone();
+        let parent = match self.find_parent(&id, dir)? {
+            Some(Style::Independent(parent)) => parent,
+            Some(Style::Dependent(_)) => return Err(StyleLoadError::DependentParent(id)),
+            None => return Err(StyleLoadError::MissingParent(id)),
+        };
+
+        Ok(merge(dependent, parent))
+    }
+
+    /// Find the parent style with the given ID.
+    fn find_parent(
+        &self,
+        id: &str,
+        dir: Option<&Path>,
+    ) -> Result<Option<Style>, StyleLoadError> {
+        #[cfg(feature = "archive")]
+        if let Some(archived) = ArchivedStyle::by_id(id) {
+            return Ok(Some(archived.get()));
+        }
+
+        let Some(name) = id.trim_end_matches('/').rsplit('/').next() else {
+            return Ok(None);
+        };
+
+        for dir in dir.into_iter().chain(self.style_dirs.iter().map(PathBuf::as_path)) {
+            let path = dir.join(format!("{name}.csl"));
+            if path.is_file() {
+                return Ok(Some(Style::from_xml(&read(&path)?)?));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Default for StyleLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Apply the overrides of a dependent style to its parent.
+fn merge(dependent: DependentStyle, mut parent: IndependentStyle) -> IndependentStyle {
+    if dependent.default_locale.is_some() {
+        parent.default_locale = dependent.default_locale;
+    }
+
+    parent
+}
+
+/// Read a file to a string.
+fn read(path: &Path) -> Result<String, StyleLoadError> {
+    fs::read_to_string(path)
+        .map_err(|error| StyleLoadError::Io { path: path.to_path_buf(), error })
+}
+
+#[cfg(test)]
+mod tests {
+    use citationberg::LocaleCode;
+
+    use super::*;
+
+    #[test]
+    fn dependent_style_from_dir() {
+        let loader = StyleLoader::new();
+        let style = loader.load_path("tests/data/art-history-de.csl").unwrap();
+        assert_eq!(style.info.id, "http://www.zotero.org/styles/art-history");
+        assert_eq!(style.default_locale, Some(LocaleCode("de-DE".into())));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn dependent_style_from_archive() {
+        let loader = StyleLoader::new();
+        let style = loader
+            .load_xml(&dependent("http://www.zotero.org/styles/apa"))
+            .unwrap();
+        assert_eq!(style.info.id, "http://www.zotero.org/styles/apa");
+        assert_eq!(style.default_locale, Some(LocaleCode("de-DE".into())));
+    }
+
+    #[test]
+    fn missing_parent() {
+        let mut loader = StyleLoader::new();
+        loader.add_style_dir("tests/data");
+        let err = loader
+            .load_xml(&dependent("http://www.zotero.org/styles/does-not-exist"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            StyleLoadError::MissingParent(id)
+                if id == "http://www.zotero.org/styles/does-not-exist"
+        ));
+    }
+
+    #[test]
+    fn registered_locales() {
+        let mut loader = StyleLoader::new();
+        loader
+            .add_locale_xml(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+                <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="pt-BR">
+                  <terms>
+                    <term name="and">e</term>
+                  </terms>
+                </locale>"#,
+            )
+            .unwrap();
+        assert_eq!(loader.locales()[0].lang, Some(LocaleCode("pt-BR".into())));
+    }
+
+    fn dependent(parent: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" default-locale="de-DE">
+              <info>
+                <title>Dependent</title>
+                <id>http://www.zotero.org/styles/dependent</id>
+                <link href="{parent}" rel="independent-parent"/>
+                <updated>2024-01-01T00:00:00+00:00</updated>
+              </info>
+            </style>"#
+        )
+    }
+}
@@ -1,10 +1,11 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry as HmEntry;
-use std::collections::{HashMap, HashSet};
-use std::fmt::{Debug, Write};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Debug, Write};
 use std::hash::Hash;
 use std::num::{NonZeroI16, NonZeroUsize};
+use std::sync::{Mutex, PoisonError};
 use std::{mem, vec};
 
 use citationberg::taxonomy::{
@@ -13,30 +14,44 @@ use citationberg::taxonomy::{
 };
 use citationberg::{
     taxonomy as csl_taxonomy, Affixes, BaseLanguage, Citation, CitationFormat, Collapse,
-    CslMacro, Display, GrammarGender, IndependentStyle, InheritableNameOptions, Layout,
-    LayoutRenderingElement, Locale, LocaleCode, Names, SecondFieldAlign, StyleCategory,
-    StyleClass, TermForm, ToAffixes, ToFormatting,
+    CslMacro, DatePartName, Display, GrammarGender, IndependentStyle,
+    InheritableNameOptions, Layout, LayoutRenderingElement, Locale, LocaleCode, Names,
+    SecondFieldAlign, StyleCategory, StyleClass, TermForm, ToAffixes, ToFormatting,
 };
 use citationberg::{DateForm, LongShortForm, OrdinalLookup, TextCase};
 use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
 use crate::csl::elem::{simplify_children, NonEmptyStack};
 use crate::csl::rendering::names::NameDisambiguationProperties;
 use crate::csl::rendering::RenderCsl;
 use crate::csl::taxonomy::NumberOrPageVariableResult;
-use crate::lang::CaseFolder;
+use crate::lang::articles::strip_leading_article;
+use crate::lang::collation::strip_diacritics;
+use crate::lang::{CaseFolder, SpecialCasing};
 use crate::types::{ChunkKind, ChunkedString, Date, MaybeTyped, Person};
 
-use self::elem::last_text_mut_child;
+pub(crate) use self::check::suggest;
+pub use self::check::{check_style, Location, Severity, StyleDiagnostic};
+use self::elem::{display_class, last_text_mut_child};
 pub use self::elem::{
     BufWriteFormat, Elem, ElemChild, ElemChildren, ElemMeta, Formatted, Formatting,
 };
-use self::taxonomy::{EntryLike, NumberVariableResult, PageVariableResult};
+pub use self::intext::IntextLayout;
+pub use self::load::{StyleLoadError, StyleLoader};
+pub(crate) use self::taxonomy::EntryLike;
+use self::taxonomy::{
+    NumberVariableResult, PageVariableResult, StandardValue, VariableCache,
+};
 
 #[cfg(feature = "archive")]
 pub mod archive;
+mod check;
 mod citation_label;
 mod elem;
+mod intext;
+mod load;
 mod rendering;
 mod sort;
 mod taxonomy;
@@ -63,7 +78,7 @@ struct SpeculativeItemRender<'a, T: EntryLike> {
     first_name: Option<NameDisambiguationProperties>,
     delim_override: Option<&'a str>,
     group_idx: Option<usize>,
-    locator: Option<SpecificLocator<'a>>,
+    locators: Vec<SpecificLocator<'a>>,
     hidden: bool,
     locale: Option<LocaleCode>,
     purpose: Option<CitePurpose>,
@@ -168,7 +183,7 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                         initial_idx: item.initial_idx,
                     },
                     speculative: SpeculativeCiteProperties::speculate(
-                        None,
+                        Vec::new(),
                         citation_number(entry),
                         IbidState::with_last(item, last_cite),
                     ),
@@ -195,7 +210,7 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                     first_name: ctx.writing.first_name.clone(),
                     delim_override: None,
                     group_idx: None,
-                    locator: item.locator,
+                    locators: item.locators.clone(),
                     rendered: ctx.flush(),
                     hidden: item.hidden,
                     locale: item.locale.clone(),
@@ -214,7 +229,8 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
         // If we have set the disambiguation state for an item, we need to set
         // the same state for all entries referencing that item.
         for _ in 0..16 {
-            let ambiguous = find_ambiguous_sets(&res);
+            let ambiguous =
+                find_ambiguous_sets(&res, request.diacritic_insensitive_grouping);
             if ambiguous.is_empty() {
                 break;
             }
@@ -295,10 +311,13 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                     continue;
                 };
 
-                let Some(name_elem) = cite.items[i]
-                    .rendered
-                    .find_meta(ElemMeta::Names)
-                    .map(|e| format!("{e:?}"))
+                let Some(name_elem) =
+                    cite.items[i].rendered.find_meta(ElemMeta::Names).map(|e| {
+                        comparison_key(
+                            &ElemChildren(vec![e.clone().into()]),
+                            request.diacritic_insensitive_grouping,
+                        )
+                    })
                 else {
                     continue;
                 };
@@ -342,18 +361,19 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                 } else {
                     Some(&res[i].items[j - 1])
                 }
-                .map(|l| CitationItem::with_locator(l.entry, l.locator));
+                .map(|l| CitationItem::with_locators(l.entry, l.locators.clone()));
 
                 res[i].items[j].cite_props.speculative.ibid = IbidState::with_last(
-                    &CitationItem::with_locator(
+                    &CitationItem::with_locators(
                         res[i].items[j].entry,
-                        res[i].items[j].locator,
+                        res[i].items[j].locators.clone(),
                     ),
                     last.as_ref(),
                 );
 
-                //     - Add final locator
-                res[i].items[j].cite_props.speculative.locator = res[i].items[j].locator;
+                //     - Add final locators
+                res[i].items[j].cite_props.speculative.locators =
+                    res[i].items[j].locators.clone();
             }
         }
 
@@ -381,6 +401,7 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
 
         // Rerender.
         let mut final_citations: Vec<RenderedCitation> = Vec::new();
+        let mut diagnostics = Vec::new();
         for cite in res.iter_mut() {
             let style_ctx = cite.request.style();
             // 5. Collapse grouped citations.
@@ -392,6 +413,22 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                 item.rendered = last_purpose_render(&style_ctx, item, cite.request);
             }
 
+            for item in cite.items.iter() {
+                if !item.hidden && !item.rendered.has_content() {
+                    diagnostics.push(RenderDiagnostic::EmptyCitationItem {
+                        key: item.entry.key().into_owned(),
+                        citation: final_citations.len(),
+                    });
+                }
+                style_ctx.check_locale(&item.entry.key(), item.locale.as_ref());
+            }
+
+            for diagnostic in style_ctx.take_diagnostics() {
+                if !diagnostics.contains(&diagnostic) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+
             // 6. Add affixes.
             let formatting = Formatting::default()
                 .apply(cite.request.style.citation.layout.to_formatting());
@@ -420,13 +457,7 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                                 .items
                                 .get(i)
                                 .and_then(|i: &SpeculativeItemRender<T>| i.delim_override)
-                                .or(cite
-                                    .request
-                                    .style
-                                    .citation
-                                    .layout
-                                    .delimiter
-                                    .as_deref())
+                                .or(cite.request.delimiter())
                             {
                                 elem_children.push(ElemChild::Text(Formatted {
                                     text: delim.to_string(),
@@ -454,40 +485,66 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                         }
                     }
 
-                    simplify_children(ElemChildren(elem_children))
+                    let mut children = simplify_children(ElemChildren(elem_children));
+                    cite.request.note_context.apply(
+                        &mut children,
+                        cite.request.style,
+                        formatting,
+                    );
+                    children
                 },
             })
         }
 
         let bib_render = if let Some(bibliography) = &request.style.bibliography {
-            let mut items = Vec::new();
+            // Collect everything the entries depend on first so that the
+            // entries themselves can be rendered independently.
+            let mut jobs = Vec::new();
+            let mut cited_in: Vec<Vec<usize>> = Vec::new();
             for entry in entries.into_iter() {
+                if !request.includes(&entry.entry.key()) {
+                    continue;
+                }
+
+                cited_in.push(
+                    res.iter()
+                        .enumerate()
+                        .filter(|(_, cite)| {
+                            cite.items
+                                .iter()
+                                .any(|item| item.entry == entry.entry && !item.hidden)
+                        })
+                        .map(|(i, _)| i)
+                        .collect(),
+                );
+
                 let cited_item = res
                     .iter()
                     .flat_map(|cite| cite.items.iter())
                     .find(|item| item.entry == entry.entry)
                     .unwrap();
 
-                items.push((
+                bib_style.check_locale(&entry.entry.key(), cited_item.locale.as_ref());
+                jobs.push((
+                    entry.entry,
+                    CiteProperties {
+                        certain: cited_item.cite_props.certain,
+                        speculative: cited_item.cite_props.speculative.for_bibliography(),
+                    },
+                    cited_item.locale.as_ref(),
+                ));
+            }
+
+            let items = map_ordered(jobs, |(entry, props, locale)| {
+                (
                     simplify_children(
                         bib_style
-                            .bibliography(
-                                entry.entry,
-                                CiteProperties {
-                                    certain: cited_item.cite_props.certain,
-                                    speculative: cited_item
-                                        .cite_props
-                                        .speculative
-                                        .for_bibliography(),
-                                },
-                                cited_item.locale.as_ref(),
-                                request.locale.as_ref(),
-                            )
+                            .bibliography(entry, props, locale, request.locale.as_ref())
                             .unwrap(),
                     ),
-                    entry.entry.key().to_string(),
-                ))
-            }
+                    entry.key().to_string(),
+                )
+            });
 
             Some(RenderedBibliography {
                 hanging_indent: bibliography.hanging_indent,
@@ -496,12 +553,14 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
                 entry_spacing: bibliography.entry_spacing,
                 items: items
                     .into_iter()
-                    .map(|(mut i, key)| {
-                        if bibliography.second_field_align.is_some() {
-                            BibliographyItem::new(key, i.remove_any_meta(), i)
+                    .zip(cited_in)
+                    .map(|((mut i, key), cited_in)| {
+                        let first_field = if bibliography.second_field_align.is_some() {
+                            i.remove_any_meta()
                         } else {
-                            BibliographyItem::new(key, None, i)
-                        }
+                            None
+                        };
+                        BibliographyItem::new(key, first_field, i, cited_in)
                     })
                     .collect(),
             })
@@ -509,13 +568,62 @@ impl<T: EntryLike + Hash + PartialEq + Eq + Debug> BibliographyDriver<'_, T> {
             None
         };
 
+        let mut bib_diagnostics = bib_style.take_diagnostics();
+        if let Some(bibliography) = &bib_render {
+            for item in &bibliography.items {
+                if !item.content.has_content() {
+                    diagnostics.push(RenderDiagnostic::EmptyBibliographyItem {
+                        key: item.key.clone(),
+                    });
+                }
+            }
+
+            // Entries rendered in parallel report their problems in any order.
+            let order: HashMap<&str, usize> = bibliography
+                .items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (item.key.as_str(), i))
+                .collect();
+            bib_diagnostics.sort_by_key(|d| order.get(d.key()).copied());
+        }
+
+        for diagnostic in bib_diagnostics {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+
         Rendered {
             bibliography: bib_render,
             citations: final_citations,
+            diagnostics,
         }
     }
 }
 
+/// Render a single entry as a bibliography item with the style, locale, and
+/// options of the given request.
+///
+/// Disambiguation, citation numbers, and other features that depend on the
+/// other entries of a bibliography are computed as if this was the only entry.
+/// Returns `None` if the style does not have a bibliography.
+pub fn standalone_bibliography_item<T: EntryLike + Hash + PartialEq + Eq + Debug>(
+    entry: &T,
+    request: BibliographyRequest<'_>,
+) -> Option<BibliographyItem> {
+    let mut driver = BibliographyDriver::new();
+    driver.citation(CitationRequest::new(
+        vec![CitationItem::with_entry(entry)],
+        request.style,
+        request.locale.clone(),
+        request.locale_files,
+        None,
+    ));
+
+    driver.finish(request).bibliography?.items.pop()
+}
+
 /// Create a new citation with the given items. Bibliography-wide disambiguation
 /// and some other features will not be applied.
 pub fn standalone_citation<T: EntryLike>(
@@ -538,17 +646,17 @@ pub fn standalone_citation<T: EntryLike>(
         }
 
         res.push(if let Some(CitePurpose::Year) = item.purpose {
-            date_replacement(
+            year_replacement(
                 &style,
                 item.entry,
-                &CiteProperties::for_sorting(item.locator, 0),
+                &CiteProperties::for_sorting(item.locators.clone(), 0),
                 req.locale.as_ref(),
                 item.locale.as_ref(),
             )
         } else {
             style.citation(
                 item.entry,
-                CiteProperties::for_sorting(item.locator, 0),
+                CiteProperties::for_sorting(item.locators.clone(), 0),
                 item.locale.as_ref(),
                 req.locale.as_ref(),
                 item.purpose,
@@ -563,7 +671,8 @@ pub fn standalone_citation<T: EntryLike>(
         Formatting::default().apply(style.csl.citation.layout.to_formatting());
 
     if !non_empty.is_empty() && !all_hidden {
-        let mut res = if let Some(prefix) = style.csl.citation.layout.prefix.as_ref() {
+        let prefix = style.csl.citation.layout.prefix.as_ref();
+        let mut res = if let Some(prefix) = prefix.filter(|_| !req.strip_affixes) {
             ElemChildren(vec![Formatted { text: prefix.clone(), formatting }.into()])
         } else {
             ElemChildren::new()
@@ -574,12 +683,10 @@ pub fn standalone_citation<T: EntryLike>(
             if !first {
                 res.0.push(
                     Formatted {
-                        text: style
-                            .csl
-                            .citation
-                            .layout
-                            .delimiter
+                        text: req
+                            .delimiter_override
                             .as_deref()
+                            .or(style.csl.citation.layout.delimiter.as_deref())
                             .unwrap_or(Citation::DEFAULT_CITE_GROUP_DELIMITER)
                             .to_string(),
                         formatting,
@@ -591,19 +698,45 @@ pub fn standalone_citation<T: EntryLike>(
             res.0.extend(elem_children.0)
         }
 
-        if let Some(suffix) = style.csl.citation.layout.suffix.as_ref() {
+        let suffix = style.csl.citation.layout.suffix.as_ref();
+        if let Some(suffix) = suffix.filter(|_| !req.strip_affixes) {
             let print = res.last_text().is_none_or(|t| !t.text.ends_with(suffix));
             if print {
                 res.0.push(Formatted { text: suffix.clone(), formatting }.into());
             }
         }
 
-        simplify_children(res)
+        let mut res = simplify_children(res);
+        req.note_context.apply(&mut res, style.csl, formatting);
+        res
     } else {
         ElemChildren::new()
     }
 }
 
+/// Apply `f` to all items, in parallel if the `rayon` feature is enabled. The
+/// results are always in the order of the items.
+#[cfg(feature = "rayon")]
+fn map_ordered<I, R, F>(items: Vec<I>, f: F) -> Vec<R>
+where
+    I: Send,
+    R: Send,
+    F: Fn(I) -> R + Sync + Send,
+{
+    use rayon::prelude::*;
+    items.into_par_iter().map(f).collect()
+}
+
+/// Apply `f` to all items, in parallel if the `rayon` feature is enabled. The
+/// results are always in the order of the items.
+#[cfg(not(feature = "rayon"))]
+fn map_ordered<I, R, F>(items: Vec<I>, f: F) -> Vec<R>
+where
+    F: Fn(I) -> R,
+{
+    items.into_iter().map(f).collect()
+}
+
 fn do_rerender<T: EntryLike>(
     ctx: &StyleContext<'_>,
     item: &SpeculativeItemRender<T>,
@@ -619,6 +752,46 @@ fn do_rerender<T: EntryLike>(
     )
 }
 
+/// Render only the first date of a cite, as the citation layout of the style
+/// would print it, including the year suffix but without any locators. If the
+/// layout does not print a date, we fall back to [`date_replacement`]. Numeric
+/// and label styles have no year to show and yield nothing.
+fn year_replacement<'a, T: EntryLike>(
+    ctx: &StyleContext<'a>,
+    entry: &T,
+    cite_props: &CiteProperties<'a>,
+    term_locale: Option<&LocaleCode>,
+    locale: Option<&LocaleCode>,
+) -> ElemChildren {
+    if ctx.csl.info.category.iter().any(|c| {
+        matches!(
+            c,
+            StyleCategory::CitationFormat {
+                format: CitationFormat::Label | CitationFormat::Numeric
+            }
+        )
+    }) {
+        return ElemChildren::new();
+    }
+
+    let mut props = cite_props.clone();
+    props.speculative.locators.clear();
+    let rendered = ctx.citation(
+        entry,
+        props,
+        locale,
+        term_locale,
+        None,
+        Some(CollapseVerdict::First),
+    );
+
+    if rendered.has_content() {
+        rendered
+    } else {
+        date_replacement(ctx, entry, cite_props, term_locale, locale)
+    }
+}
+
 fn date_replacement<T: EntryLike>(
     ctx: &StyleContext<'_>,
     entry: &T,
@@ -653,27 +826,33 @@ pub fn write_year<W: std::fmt::Write>(
     year: i32,
     short: bool,
     w: &mut W,
+) -> std::fmt::Result {
+    write_year_with_eras(year, short, ["BC", "AD"], w)
+}
+
+/// Write a year with the given terms for `BC` and `AD`. Years before 1000 are
+/// written in full with their era even in the short form, while later ones are
+/// shortened to their last two digits, keeping a leading zero.
+pub fn write_year_with_eras<W: std::fmt::Write>(
+    year: i32,
+    short: bool,
+    [bc, ad]: [&str; 2],
+    w: &mut W,
 ) -> std::fmt::Result {
     if short && year >= 1000 {
         return write!(w, "{:02}", year % 100);
     }
 
-    write!(
-        w,
-        "{}{}",
-        if year > 0 { year } else { year.abs() + 1 },
-        if year < 1000 {
-            if year <= 0 {
-                "BC"
-            } else {
-                // AD is used as a postfix, see
-                // https://docs.citationstyles.org/en/stable/specification.html?#ad-and-bc
-                "AD"
-            }
-        } else {
-            ""
-        }
-    )
+    if year <= 0 {
+        // Year zero is 1 BC.
+        write!(w, "{}{bc}", year.abs() + 1)
+    } else if year < 1000 {
+        // AD is used as a postfix, see
+        // https://docs.citationstyles.org/en/stable/specification.html?#ad-and-bc
+        write!(w, "{year}{ad}")
+    } else {
+        write!(w, "{year}")
+    }
 }
 
 fn last_purpose_render<T: EntryLike + Debug>(
@@ -682,7 +861,7 @@ fn last_purpose_render<T: EntryLike + Debug>(
     request: &CitationRequest<'_, T>,
 ) -> ElemChildren {
     if let Some(CitePurpose::Year) = item.purpose {
-        date_replacement(
+        year_replacement(
             ctx,
             item.entry,
             &item.cite_props,
@@ -827,6 +1006,7 @@ fn disambiguate_year_suffix<F, T>(
 /// with cite and item index.
 fn find_ambiguous_sets<T: EntryLike + PartialEq>(
     cites: &[SpeculativeCiteRender<'_, '_, T>],
+    diacritic_insensitive: bool,
 ) -> Vec<AmbiguousGroup> {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     enum PotentialDisambiguation {
@@ -851,7 +1031,7 @@ fn find_ambiguous_sets<T: EntryLike + PartialEq>(
                 continue;
             }
 
-            let buf = format!("{:?}", item.rendered);
+            let buf = comparison_key(&item.rendered, diacritic_insensitive);
             match map.entry(buf) {
                 HmEntry::Occupied(entry) => match *entry.get() {
                     PotentialDisambiguation::Single(pos) => {
@@ -884,6 +1064,39 @@ fn find_ambiguous_sets<T: EntryLike + PartialEq>(
         .collect()
 }
 
+/// Compute the key under which two renders are considered the same for
+/// disambiguation and cite grouping.
+///
+/// All text is compared in NFC, so that precomposed and decomposed characters
+/// are equal. If `diacritic_insensitive` is set, the diacritics in names are
+/// ignored, too.
+fn comparison_key(children: &ElemChildren, diacritic_insensitive: bool) -> String {
+    fn normalize(children: &mut ElemChildren, strip: bool, diacritic_insensitive: bool) {
+        for child in children.0.iter_mut() {
+            match child {
+                ElemChild::Text(formatted) | ElemChild::Link { text: formatted, .. } => {
+                    formatted.text = if strip {
+                        strip_diacritics(&formatted.text)
+                    } else {
+                        formatted.text.nfc().collect()
+                    };
+                }
+                ElemChild::Elem(elem) => normalize(
+                    &mut elem.children,
+                    strip
+                        || (diacritic_insensitive && elem.meta == Some(ElemMeta::Names)),
+                    diacritic_insensitive,
+                ),
+                ElemChild::Markup(_) | ElemChild::Transparent { .. } => {}
+            }
+        }
+    }
+
+    let mut children = children.clone();
+    normalize(&mut children, false, diacritic_insensitive);
+    format!("{children:?}")
+}
+
 fn collapse_items<'a, T: EntryLike>(cite: &mut SpeculativeCiteRender<'a, '_, T>) {
     let style = &cite.request.style;
 
@@ -891,7 +1104,7 @@ fn collapse_items<'a, T: EntryLike>(cite: &mut SpeculativeCiteRender<'a, '_, T>)
         .citation
         .after_collapse_delimiter
         .as_deref()
-        .or(style.citation.layout.delimiter.as_deref());
+        .or(cite.request.delimiter());
 
     let group_delimiter = style.citation.cite_group_delimiter.as_deref();
 
@@ -1016,6 +1229,108 @@ pub struct Rendered {
     pub bibliography: Option<RenderedBibliography>,
     /// The citation items.
     pub citations: Vec<RenderedCitation>,
+    /// Problems found while rendering, usually caused by entries that lack
+    /// data the style needs.
+    pub diagnostics: Vec<RenderDiagnostic>,
+}
+
+/// A problem found while rendering citations and the bibliography.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RenderDiagnostic {
+    /// The item of an entry in a citation rendered empty.
+    EmptyCitationItem {
+        /// The key of the entry.
+        key: String,
+        /// The index of the citation in [`Rendered::citations`].
+        citation: usize,
+    },
+    /// The bibliography item of an entry rendered empty.
+    EmptyBibliographyItem {
+        /// The key of the entry.
+        key: String,
+    },
+    /// The style printed a term that no locale defines.
+    MissingTerm {
+        /// The key of the entry.
+        key: String,
+        /// The term.
+        term: Term,
+    },
+    /// A `cs:number` element printed a variable whose value is not a number.
+    UnparsableNumeric {
+        /// The key of the entry.
+        key: String,
+        /// The variable.
+        variable: NumberOrPageVariable,
+        /// The value that was printed verbatim.
+        value: String,
+    },
+    /// A `cs:date` element asked for a part that the date does not have.
+    MissingDatePart {
+        /// The key of the entry.
+        key: String,
+        /// The date variable.
+        variable: DateVariable,
+        /// The missing part.
+        part: DatePartName,
+    },
+    /// An entry was rendered in a locale that neither the style nor the locale
+    /// files define.
+    UnknownLocale {
+        /// The key of the entry.
+        key: String,
+        /// The locale.
+        locale: LocaleCode,
+    },
+}
+
+impl RenderDiagnostic {
+    /// The key of the entry the diagnostic is about.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::EmptyCitationItem { key, .. }
+            | Self::EmptyBibliographyItem { key }
+            | Self::MissingTerm { key, .. }
+            | Self::UnparsableNumeric { key, .. }
+            | Self::MissingDatePart { key, .. }
+            | Self::UnknownLocale { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for RenderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyCitationItem { key, citation } => {
+                write!(f, "entry `{key}` rendered empty in citation {}", citation + 1)
+            }
+            Self::EmptyBibliographyItem { key } => {
+                write!(f, "entry `{key}` rendered an empty bibliography item")
+            }
+            Self::MissingTerm { key, term } => {
+                write!(f, "entry `{key}` uses the undefined term {term:?}")
+            }
+            Self::UnparsableNumeric { key, variable, value } => {
+                let variable = match variable {
+                    NumberOrPageVariable::Number(v) => v.to_string(),
+                    NumberOrPageVariable::Page(v) => v.to_string(),
+                };
+                write!(f, "entry `{key}` has a non-numeric {variable} `{value}`")
+            }
+            Self::MissingDatePart { key, variable, part } => {
+                let part = match part {
+                    DatePartName::Year => "year",
+                    DatePartName::Month => "month",
+                    DatePartName::Day => "day",
+                };
+                write!(f, "entry `{key}` has no {part} in its {variable}")
+            }
+            Self::UnknownLocale { key, locale } => {
+                write!(f, "entry `{key}` uses the unknown locale `{}`", locale.0)
+            }
+        }
+    }
 }
 
 /// A fully rendered bibliography.
@@ -1036,6 +1351,120 @@ pub struct RenderedBibliography {
     pub items: Vec<BibliographyItem>,
 }
 
+impl RenderedBibliography {
+    /// Join the items into a single block of text for consumers that cannot
+    /// lay out the items themselves.
+    ///
+    /// Each item is an element with the `block` display. Like in
+    /// [`BibliographyItem::write_buf`], its first field, if any, is wrapped in
+    /// a `left-margin` element and the rest of the item in a `right-inline`
+    /// element, separated by a space.
+    ///
+    /// Between the items, there are as many line breaks as the
+    /// `entry_spacing`, so that in plain text, a spacing of `1` (the default)
+    /// puts one blank line between the items and a spacing of `0` puts none.
+    /// In HTML, the line breaks have no effect and the spacing should be
+    /// applied to the `csl-block` elements instead.
+    pub fn join(&self) -> ElemChildren {
+        let text = |text: String| {
+            ElemChild::Text(Formatted { text, formatting: Formatting::default() })
+        };
+        let elem = |children: Vec<ElemChild>, display: Display| {
+            ElemChild::Elem(Elem {
+                children: ElemChildren(children),
+                display: Some(display),
+                meta: None,
+            })
+        };
+
+        let delim = "\n".repeat(self.entry_spacing.max(0) as usize);
+        let mut children = Vec::new();
+        for (i, item) in self.items.iter().enumerate() {
+            if i != 0 && !delim.is_empty() {
+                children.push(text(delim.clone()));
+            }
+
+            let content = item.content.0.clone();
+            let block = match item.first_field.clone() {
+                Some(first_field) => vec![
+                    elem(vec![first_field], Display::LeftMargin),
+                    text(" ".into()),
+                    elem(content, Display::RightInline),
+                ],
+                None => content,
+            };
+
+            children.push(elem(block, Display::Block));
+        }
+
+        ElemChildren(children)
+    }
+
+    /// Split the bibliography into categories, e.g. to print primary sources
+    /// and secondary literature in separate sections.
+    ///
+    /// The `categorize` function maps the key of each item to the identifier
+    /// of its category. Use [`Library::get`](crate::Library::get) to
+    /// categorize by the entry itself. The result contains all `categories` in
+    /// the order they were declared, even if they are empty. Their items are
+    /// in the order of the bibliography, so each category is sorted by the
+    /// style. Items without a declared category are omitted.
+    ///
+    /// Because all items were rendered together, citation numbers and year
+    /// suffixes are consistent across the categories.
+    pub fn categorize<'a, C: PartialEq>(
+        &'a self,
+        categories: &'a [BibliographyCategory<C>],
+        categorize: impl Fn(&str) -> Option<C>,
+    ) -> Vec<RenderedCategory<'a, C>> {
+        let mut res: Vec<_> = categories
+            .iter()
+            .map(|category| RenderedCategory { category, items: Vec::new() })
+            .collect();
+
+        for item in &self.items {
+            let Some(id) = categorize(&item.key) else { continue };
+            if let Some(category) = res.iter_mut().find(|c| c.category.id == id) {
+                category.items.push(item);
+            }
+        }
+
+        res
+    }
+}
+
+/// A category of a bibliography. Use with
+/// [`RenderedBibliography::categorize`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BibliographyCategory<C> {
+    /// The identifier of the category.
+    pub id: C,
+    /// The heading to print above the items of the category.
+    pub heading: Option<String>,
+}
+
+impl<C> BibliographyCategory<C> {
+    /// Create a new category without a heading.
+    pub fn new(id: C) -> Self {
+        Self { id, heading: None }
+    }
+
+    /// Set the heading of the category.
+    pub fn with_heading(mut self, heading: impl Into<String>) -> Self {
+        self.heading = Some(heading.into());
+        self
+    }
+}
+
+/// The items of a bibliography that belong to a category.
+#[derive(Debug, Clone)]
+pub struct RenderedCategory<'a, C> {
+    /// The category.
+    pub category: &'a BibliographyCategory<C>,
+    /// The items in the category, in the order of the bibliography.
+    pub items: Vec<&'a BibliographyItem>,
+}
+
 /// A fully rendered bibliography item.
 #[derive(Debug, Clone)]
 pub struct BibliographyItem {
@@ -1046,11 +1475,73 @@ pub struct BibliographyItem {
     pub first_field: Option<ElemChild>,
     /// The rendered item.
     pub content: ElemChildren,
+    /// The indices of the citations in [`Rendered::citations`] that cite the
+    /// entry, in ascending order. Citations in which the entry is hidden are
+    /// not included.
+    ///
+    /// This can be used for back-references like "cited on pages 4, 7".
+    pub cited_in: Vec<usize>,
 }
 
 impl BibliographyItem {
-    fn new(key: String, first_field: Option<ElemChild>, content: ElemChildren) -> Self {
-        Self { key, first_field, content }
+    fn new(
+        key: String,
+        first_field: Option<ElemChild>,
+        mut content: ElemChildren,
+        cited_in: Vec<usize>,
+    ) -> Self {
+        content.align_margins();
+        Self { key, first_field, content, cited_in }
+    }
+
+    /// Write the item to the given buffer.
+    ///
+    /// In HTML, the item is wrapped in a `csl-entry` div. The first field is
+    /// put in a `csl-left-margin` div and the rest of the item in a
+    /// `csl-right-inline` div, like citeproc-js does.
+    pub fn write_buf(
+        &self,
+        w: &mut impl fmt::Write,
+        format: BufWriteFormat,
+    ) -> Result<(), fmt::Error> {
+        if format == BufWriteFormat::Html {
+            w.write_str("<div class=\"csl-entry\">")?;
+        }
+
+        if let Some(first_field) = &self.first_field {
+            if format == BufWriteFormat::Html {
+                write!(w, "<div class=\"{}\">", display_class(Display::LeftMargin))?;
+                first_field.write_buf(w, format)?;
+                write!(
+                    w,
+                    "</div><div class=\"{}\">",
+                    display_class(Display::RightInline)
+                )?;
+            } else {
+                first_field.write_buf(w, format)?;
+            }
+        }
+
+        self.content.write_buf(w, format)?;
+
+        if format == BufWriteFormat::Html {
+            if self.first_field.is_some() {
+                w.write_str("</div>")?;
+            }
+            w.write_str("</div>")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for BibliographyItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.write_buf(f, BufWriteFormat::Plain)
+        } else {
+            self.write_buf(f, BufWriteFormat::VT100)
+        }
     }
 }
 
@@ -1081,6 +1572,8 @@ struct InstanceContext<'a, T: EntryLike> {
     pub kind: Option<SpecialForm>,
     /// Which labels were written.
     pub identifier_usage: RefCell<IdentifierUsage>,
+    /// Standard variables that were already resolved for the entry.
+    standard_variables: VariableCache<'a>,
 }
 
 impl<'a, T: EntryLike> InstanceContext<'a, T> {
@@ -1099,6 +1592,7 @@ impl<'a, T: EntryLike> InstanceContext<'a, T> {
             term_locale,
             kind,
             identifier_usage: RefCell::new(cite_props.speculative.identifier_usage),
+            standard_variables: VariableCache::default(),
             cite_props,
         }
     }
@@ -1106,7 +1600,7 @@ impl<'a, T: EntryLike> InstanceContext<'a, T> {
     fn sort_instance(item: &CitationItem<'a, T>, idx: usize) -> Self {
         Self::new(
             item.entry,
-            CiteProperties::for_sorting(item.locator, idx),
+            CiteProperties::for_sorting(item.locators.clone(), idx),
             true,
             None,
             None,
@@ -1115,6 +1609,27 @@ impl<'a, T: EntryLike> InstanceContext<'a, T> {
     }
 }
 
+/// Problems found while rendering. They are behind a lock because the
+/// bibliography may be rendered in parallel.
+#[derive(Debug, Default)]
+struct Diagnostics(Mutex<Vec<RenderDiagnostic>>);
+
+impl Clone for Diagnostics {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap_or_else(PoisonError::into_inner).clone()))
+    }
+}
+
+/// The diagnostics do not affect the output, so they are ignored when
+/// comparing contexts.
+impl PartialEq for Diagnostics {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Diagnostics {}
+
 /// A context that contains information about the style we are using to render.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct StyleContext<'a> {
@@ -1125,6 +1640,32 @@ pub(crate) struct StyleContext<'a> {
     locale_files: &'a [Locale],
     /// Which locale we're using.
     locale_override: Option<LocaleCode>,
+    /// Which variables to render as links.
+    pub link_behaviour: LinkBehaviour,
+    /// Whether to omit the `URL` and `accessed` variables for entries with a
+    /// DOI.
+    suppress_url_with_doi: bool,
+    /// Whether to write the handle of posts after their first author.
+    post_handles: bool,
+    /// Which script to render for values with a transliteration.
+    pub preferred_script: PreferredScript,
+    /// Abbreviations for titles and institutions.
+    abbreviations: Option<&'a Abbreviations>,
+    /// Whether to abbreviate even if the style asks for the long form.
+    use_abbreviations: bool,
+    /// Whether to replace straight double quotes in field content with the
+    /// locale's quotation marks.
+    smart_quotes: bool,
+    /// Replacements for terms that take precedence over the locales.
+    term_overrides: Option<&'a TermOverrides>,
+    /// Whether to derive short titles for entries that have none.
+    auto_short_titles: bool,
+    /// A limit on the names rendered per variable.
+    pub name_cap: Option<NameCap>,
+    /// The `cs:intext` element for author-only and prose citations.
+    intext: Option<&'a Citation>,
+    /// Problems found while rendering with this context.
+    diagnostics: Diagnostics,
 }
 
 impl<'a> StyleContext<'a> {
@@ -1132,8 +1673,47 @@ impl<'a> StyleContext<'a> {
         style: &'a IndependentStyle,
         locale: Option<LocaleCode>,
         locale_files: &'a [Locale],
+        link_behaviour: LinkBehaviour,
     ) -> Self {
-        Self { csl: style, locale_files, locale_override: locale }
+        Self {
+            csl: style,
+            locale_files,
+            locale_override: locale,
+            link_behaviour,
+            suppress_url_with_doi: false,
+            post_handles: false,
+            preferred_script: PreferredScript::default(),
+            abbreviations: None,
+            use_abbreviations: false,
+            smart_quotes: false,
+            term_overrides: None,
+            auto_short_titles: false,
+            name_cap: None,
+            intext: None,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Report a problem found while rendering.
+    fn warn(&self, diagnostic: RenderDiagnostic) {
+        let mut diagnostics =
+            self.diagnostics.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Take the problems reported so far.
+    fn take_diagnostics(&self) -> Vec<RenderDiagnostic> {
+        mem::take(&mut *self.diagnostics.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Report an entry whose locale is not defined anywhere.
+    fn check_locale(&self, key: &str, locale: Option<&LocaleCode>) {
+        let locale = locale.cloned().unwrap_or_else(|| self.locale());
+        if self.lookup_locale_in(&locale, false, |_| Some(())).is_none() {
+            self.warn(RenderDiagnostic::UnknownLocale { key: key.into(), locale });
+        }
     }
 
     fn ctx<'b, T: EntryLike>(
@@ -1170,7 +1750,7 @@ impl<'a> StyleContext<'a> {
         Context {
             instance: InstanceContext::new(
                 item.entry,
-                CiteProperties::for_sorting(item.locator, idx),
+                CiteProperties::for_sorting(item.locators.clone(), idx),
                 true,
                 locale,
                 term_locale,
@@ -1314,11 +1894,19 @@ impl<'a> StyleContext<'a> {
             ctx.set_special_form(None);
         };
 
-        match (kind, self.csl.bibliography.as_ref()) {
-            (Some(CitePurpose::Author), _) => {
+        match (kind, self.csl.bibliography.as_ref(), self.intext) {
+            (Some(CitePurpose::Author | CitePurpose::Prose), _, Some(intext)) => {
+                let affixes = intext.layout.to_affixes();
+                let affix_loc = ctx.apply_prefix(&affixes);
+                ctx.writing.push_name_options(&intext.name_options);
+                intext.layout.render(&mut ctx);
+                ctx.writing.pop_name_options();
+                ctx.apply_suffix(&affixes, affix_loc);
+            }
+            (Some(CitePurpose::Author), _, _) => {
                 do_author(&mut ctx);
             }
-            (Some(CitePurpose::Full), Some(bib)) => {
+            (Some(CitePurpose::Full), Some(bib), _) => {
                 ctx.writing.push_name_options(&bib.name_options);
                 bib.layout.render(&mut ctx);
                 ctx.writing.pop_name_options();
@@ -1339,7 +1927,7 @@ impl<'a> StyleContext<'a> {
                     }
                 }
             }
-            (Some(CitePurpose::Prose), _) => {
+            (Some(CitePurpose::Prose), _, _) => {
                 do_author(&mut ctx);
                 if !self
                     .csl
@@ -1382,7 +1970,7 @@ impl<'a> StyleContext<'a> {
                     );
                 }
             }
-            (Some(CitePurpose::Year) | Some(CitePurpose::Full) | None, _) => {
+            (Some(CitePurpose::Year) | Some(CitePurpose::Full) | None, _, _) => {
                 do_regular(&mut ctx);
             }
         }
@@ -1440,6 +2028,46 @@ pub struct CitationRequest<'a, T: EntryLike> {
     /// `near-note` will always test false if this is none for the referenced
     /// note.
     note_number: Option<usize>,
+    /// Which variables to render as links.
+    pub link_behaviour: LinkBehaviour,
+    /// Abbreviations for container titles, collection titles, and
+    /// institutions. They are used if the style asks for the short form.
+    pub abbreviations: Option<&'a Abbreviations>,
+    /// Whether to use the abbreviations even if the style asks for the long
+    /// form.
+    pub use_abbreviations: bool,
+    /// Whether to replace straight double quotes in field content with the
+    /// quotation marks of the locale. Quotes within quotes use the inner
+    /// quotation marks.
+    pub smart_quotes: bool,
+    /// Replacements for terms that take precedence over the style and the
+    /// locales.
+    pub term_overrides: Option<&'a TermOverrides>,
+    /// Whether to omit the prefix and suffix of the `cs:citation` layout,
+    /// e.g. because the surrounding text already adds parentheses. Affixes of
+    /// the elements within the layout are kept.
+    pub strip_affixes: bool,
+    /// A delimiter to place between the cites instead of the one of the
+    /// `cs:citation` layout.
+    pub delimiter_override: Option<String>,
+    /// Where the citation is placed within a footnote. Only used by note
+    /// styles.
+    pub note_context: NoteContext,
+    /// A limit on the names rendered per variable in the citation that
+    /// applies in addition to the et-al settings of the style.
+    pub name_cap: Option<NameCap>,
+    /// Whether to derive a short title for entries without one if the style
+    /// asks for the short form of the title.
+    ///
+    /// The short title is the title without its subtitle, which starts after
+    /// the first colon or question mark followed by a space, and without its
+    /// leading article. Colons and question marks in verbatim chunks do not
+    /// start a subtitle.
+    pub auto_short_titles: bool,
+    /// The `cs:intext` layout of the style. It is used instead of the
+    /// `cs:citation` layout for items with the [`CitePurpose::Author`] or
+    /// [`CitePurpose::Prose`] purpose.
+    pub intext: Option<&'a IntextLayout>,
 }
 
 impl<'a, T: EntryLike> CitationRequest<'a, T> {
@@ -1457,6 +2085,17 @@ impl<'a, T: EntryLike> CitationRequest<'a, T> {
             locale,
             locale_files,
             note_number: note_number.filter(|_| style.settings.class == StyleClass::Note),
+            link_behaviour: LinkBehaviour::default(),
+            abbreviations: None,
+            use_abbreviations: false,
+            smart_quotes: false,
+            term_overrides: None,
+            strip_affixes: false,
+            delimiter_override: None,
+            note_context: NoteContext::default(),
+            name_cap: None,
+            auto_short_titles: false,
+            intext: None,
         }
     }
 
@@ -1469,12 +2108,104 @@ impl<'a, T: EntryLike> CitationRequest<'a, T> {
         Self::new(items, style, None, locale_files, None)
     }
 
+    /// Set which variables will be rendered as links.
+    pub fn with_link_behaviour(mut self, link_behaviour: LinkBehaviour) -> Self {
+        self.link_behaviour = link_behaviour;
+        self
+    }
+
+    /// Set the abbreviations for titles and institutions.
+    pub fn with_abbreviations(mut self, abbreviations: &'a Abbreviations) -> Self {
+        self.abbreviations = Some(abbreviations);
+        self
+    }
+
+    /// Set whether to use the abbreviations even if the style asks for the
+    /// long form.
+    pub fn with_use_abbreviations(mut self, use_abbreviations: bool) -> Self {
+        self.use_abbreviations = use_abbreviations;
+        self
+    }
+
+    /// Set whether to replace straight double quotes in field content with
+    /// the quotation marks of the locale.
+    pub fn with_smart_quotes(mut self, smart_quotes: bool) -> Self {
+        self.smart_quotes = smart_quotes;
+        self
+    }
+
+    /// Set replacements for terms that take precedence over the style and the
+    /// locales.
+    pub fn with_term_overrides(mut self, term_overrides: &'a TermOverrides) -> Self {
+        self.term_overrides = Some(term_overrides);
+        self
+    }
+
+    /// Set whether to omit the prefix and suffix of the citation layout.
+    pub fn with_strip_affixes(mut self, strip_affixes: bool) -> Self {
+        self.strip_affixes = strip_affixes;
+        self
+    }
+
+    /// Set a delimiter to place between the cites instead of the one of the
+    /// citation layout.
+    pub fn with_delimiter_override(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter_override = Some(delimiter.into());
+        self
+    }
+
+    /// Set where the citation is placed within a footnote.
+    pub fn with_note_context(mut self, note_context: NoteContext) -> Self {
+        self.note_context = note_context;
+        self
+    }
+
+    /// Set a limit on the names rendered per variable in the citation.
+    pub fn with_name_cap(mut self, name_cap: NameCap) -> Self {
+        self.name_cap = Some(name_cap);
+        self
+    }
+
+    /// Set whether to derive short titles for entries that have none.
+    pub fn with_auto_short_titles(mut self, auto_short_titles: bool) -> Self {
+        self.auto_short_titles = auto_short_titles;
+        self
+    }
+
+    /// Set the `cs:intext` layout of the style.
+    pub fn with_intext(mut self, intext: &'a IntextLayout) -> Self {
+        self.intext = Some(intext);
+        self
+    }
+
     fn style(&self) -> StyleContext<'a> {
-        StyleContext::new(self.style, self.locale.clone(), self.locale_files)
+        let mut style = StyleContext::new(
+            self.style,
+            self.locale.clone(),
+            self.locale_files,
+            self.link_behaviour,
+        );
+        style.abbreviations = self.abbreviations;
+        style.use_abbreviations = self.use_abbreviations;
+        style.smart_quotes = self.smart_quotes;
+        style.term_overrides = self.term_overrides;
+        style.auto_short_titles = self.auto_short_titles;
+        style.name_cap = self.name_cap;
+        style.intext = self.intext.map(IntextLayout::citation);
+        style
     }
 
     fn shall_affix(&self) -> bool {
-        self.items.iter().all(|p| p.purpose.is_none())
+        !self.strip_affixes && self.items.iter().all(|p| p.purpose.is_none())
+    }
+
+    fn delimiter(&self) -> Option<&str> {
+        self.delimiter_override.as_deref().or(self
+            .style
+            .citation
+            .layout
+            .delimiter
+            .as_deref())
     }
 
     fn prefix(&self) -> Option<&'a str> {
@@ -1511,6 +2242,67 @@ pub struct BibliographyRequest<'a> {
     /// The files used to retrieve locale settings and terms if the style does
     /// not define all necessary items.
     pub locale_files: &'a [Locale],
+    /// Which variables to render as links.
+    pub link_behaviour: LinkBehaviour,
+    /// Whether to treat the `URL` and `accessed` variables as empty in the
+    /// bibliography if the entry has a DOI.
+    ///
+    /// This follows the recommendations of appendix VI of the CSL 1.0.2
+    /// specification. Since the variables are suppressed when they are
+    /// resolved, conditions like `<if variable="URL">` will test false.
+    pub suppress_url_with_doi: bool,
+    /// Whether to write the handle of a post in brackets after its first
+    /// author, like APA does: "National Park Service [@NatlParkService]".
+    ///
+    /// The first author is then rendered as a literal name in the given-first
+    /// order, so the style's name formatting does not apply to it.
+    pub post_handles: bool,
+    /// Which script to render in the bibliography for titles and names that
+    /// have a transliteration.
+    pub preferred_script: PreferredScript,
+    /// Abbreviations for container titles, collection titles, and
+    /// institutions. They are used if the style asks for the short form.
+    pub abbreviations: Option<&'a Abbreviations>,
+    /// Whether to use the abbreviations even if the style asks for the long
+    /// form.
+    pub use_abbreviations: bool,
+    /// Whether to replace straight double quotes in field content with the
+    /// quotation marks of the locale. Quotes within quotes use the inner
+    /// quotation marks.
+    pub smart_quotes: bool,
+    /// Replacements for terms that take precedence over the style and the
+    /// locales.
+    pub term_overrides: Option<&'a TermOverrides>,
+    /// If set, only the cited entries with these keys appear in the
+    /// bibliography.
+    ///
+    /// Citation numbers, disambiguation, and the citations themselves are
+    /// still computed with all cited entries. This way, a bibliography can be
+    /// split into several sections, e.g. by rendering it once for the keys
+    /// matching a [`Selector`](crate::Selector) and once for the rest, without
+    /// the citation numbers changing between them.
+    pub keys: Option<&'a [String]>,
+    /// A limit on the names rendered per variable in the bibliography that
+    /// applies in addition to the et-al settings of the style.
+    ///
+    /// To also limit the names in the citations, set
+    /// [`CitationRequest::name_cap`].
+    pub name_cap: Option<NameCap>,
+    /// Whether to derive a short title for entries without one if the style
+    /// asks for the short form of the title.
+    ///
+    /// The short title is the title without its subtitle, which starts after
+    /// the first colon or question mark followed by a space, and without its
+    /// leading article. Colons and question marks in verbatim chunks do not
+    /// start a subtitle.
+    pub auto_short_titles: bool,
+    /// Whether names that only differ in their diacritics, like "Müller" and
+    /// "Muller", are considered the same for disambiguation and cite grouping.
+    ///
+    /// The names are still rendered as written. Regardless of this setting,
+    /// names are compared in Unicode normalization form C, so precomposed and
+    /// decomposed characters are always considered the same.
+    pub diacritic_insensitive_grouping: bool,
 }
 
 impl<'a> BibliographyRequest<'a> {
@@ -1520,31 +2312,148 @@ impl<'a> BibliographyRequest<'a> {
         locale: Option<LocaleCode>,
         locale_files: &'a [Locale],
     ) -> Self {
-        Self { style, locale, locale_files }
+        Self {
+            style,
+            locale,
+            locale_files,
+            link_behaviour: LinkBehaviour::default(),
+            suppress_url_with_doi: false,
+            post_handles: false,
+            preferred_script: PreferredScript::default(),
+            abbreviations: None,
+            use_abbreviations: false,
+            smart_quotes: false,
+            term_overrides: None,
+            keys: None,
+            name_cap: None,
+            auto_short_titles: false,
+            diacritic_insensitive_grouping: false,
+        }
     }
 
-    fn style(&self) -> StyleContext<'a> {
-        StyleContext::new(self.style, self.locale.clone(), self.locale_files)
+    /// Set which variables will be rendered as links.
+    pub fn with_link_behaviour(mut self, link_behaviour: LinkBehaviour) -> Self {
+        self.link_behaviour = link_behaviour;
+        self
     }
-}
 
-/// A reference to an [`crate::Entry`] within a [`CitationRequest`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct CitationItem<'a, T: EntryLike> {
-    /// The entry to format.
-    pub entry: &'a T,
-    /// The locator that specifies where in the entry the item is found.
-    pub locator: Option<SpecificLocator<'a>>,
-    /// A locale code that overrides the assumed locale of the entry. If this is
-    /// none, this will default to [`CitationRequest::locale`] and then
-    /// [`citationberg::IndependentStyle::default_locale`].
-    pub locale: Option<LocaleCode>,
-    /// Whether this item will be included in the output.
-    pub hidden: bool,
-    /// Format the item in a special way.
-    pub purpose: Option<CitePurpose>,
-    /// The initial index of this item in the list of items.
-    initial_idx: usize,
+    /// Set whether to omit the URL and access date of entries with a DOI.
+    pub fn with_suppress_url_with_doi(mut self, suppress: bool) -> Self {
+        self.suppress_url_with_doi = suppress;
+        self
+    }
+
+    /// Set whether to write the handle of posts after their first author.
+    pub fn with_post_handles(mut self, post_handles: bool) -> Self {
+        self.post_handles = post_handles;
+        self
+    }
+
+    /// Set which script to render for titles and names with a
+    /// transliteration.
+    pub fn with_preferred_script(mut self, preferred_script: PreferredScript) -> Self {
+        self.preferred_script = preferred_script;
+        self
+    }
+
+    /// Set the abbreviations for titles and institutions.
+    pub fn with_abbreviations(mut self, abbreviations: &'a Abbreviations) -> Self {
+        self.abbreviations = Some(abbreviations);
+        self
+    }
+
+    /// Set whether to use the abbreviations even if the style asks for the
+    /// long form.
+    pub fn with_use_abbreviations(mut self, use_abbreviations: bool) -> Self {
+        self.use_abbreviations = use_abbreviations;
+        self
+    }
+
+    /// Set whether to replace straight double quotes in field content with
+    /// the quotation marks of the locale.
+    pub fn with_smart_quotes(mut self, smart_quotes: bool) -> Self {
+        self.smart_quotes = smart_quotes;
+        self
+    }
+
+    /// Set replacements for terms that take precedence over the style and the
+    /// locales.
+    pub fn with_term_overrides(mut self, term_overrides: &'a TermOverrides) -> Self {
+        self.term_overrides = Some(term_overrides);
+        self
+    }
+
+    /// Only include the cited entries with these keys in the bibliography.
+    pub fn with_keys(mut self, keys: &'a [String]) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// Set a limit on the names rendered per variable in the bibliography.
+    pub fn with_name_cap(mut self, name_cap: NameCap) -> Self {
+        self.name_cap = Some(name_cap);
+        self
+    }
+
+    /// Set whether to derive short titles for entries that have none.
+    pub fn with_auto_short_titles(mut self, auto_short_titles: bool) -> Self {
+        self.auto_short_titles = auto_short_titles;
+        self
+    }
+
+    /// Set whether names that only differ in their diacritics are considered
+    /// the same for disambiguation and cite grouping.
+    pub fn with_diacritic_insensitive_grouping(mut self, insensitive: bool) -> Self {
+        self.diacritic_insensitive_grouping = insensitive;
+        self
+    }
+
+    /// Whether the entry with this key appears in the bibliography.
+    fn includes(&self, key: &str) -> bool {
+        self.keys.is_none_or(|keys| keys.iter().any(|k| k == key))
+    }
+
+    fn style(&self) -> StyleContext<'a> {
+        let mut style = StyleContext::new(
+            self.style,
+            self.locale.clone(),
+            self.locale_files,
+            self.link_behaviour,
+        );
+        style.suppress_url_with_doi = self.suppress_url_with_doi;
+        style.post_handles = self.post_handles;
+        style.preferred_script = self.preferred_script;
+        style.abbreviations = self.abbreviations;
+        style.use_abbreviations = self.use_abbreviations;
+        style.smart_quotes = self.smart_quotes;
+        style.term_overrides = self.term_overrides;
+        style.auto_short_titles = self.auto_short_titles;
+        style.name_cap = self.name_cap;
+        style
+    }
+}
+
+/// A reference to an [`crate::Entry`] within a [`CitationRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CitationItem<'a, T: EntryLike> {
+    /// The entry to format.
+    pub entry: &'a T,
+    /// The locators that specify where in the entry the item is found.
+    ///
+    /// Each locator is rendered by the parts of the style that render the
+    /// `locator` variable and no other variable, like a group of the locator
+    /// and its label. These parts are repeated for every locator.
+    pub locators: Vec<SpecificLocator<'a>>,
+    /// A locale code that overrides the assumed locale of the entry. If this is
+    /// none, this will default to [`CitationRequest::locale`] and then
+    /// [`citationberg::IndependentStyle::default_locale`].
+    pub locale: Option<LocaleCode>,
+    /// Whether this item will be included in the output.
+    pub hidden: bool,
+    /// Format the item in a special way.
+    pub purpose: Option<CitePurpose>,
+    /// The initial index of this item in the list of items.
+    initial_idx: usize,
 }
 
 impl<'a, T: EntryLike> CitationItem<'a, T> {
@@ -1552,7 +2461,7 @@ impl<'a, T: EntryLike> CitationItem<'a, T> {
     pub fn with_entry(entry: &'a T) -> Self {
         Self {
             entry,
-            locator: None,
+            locators: Vec::new(),
             locale: None,
             hidden: false,
             purpose: None,
@@ -1562,9 +2471,14 @@ impl<'a, T: EntryLike> CitationItem<'a, T> {
 
     /// Create a new citation item with a locator for the given entry.
     pub fn with_locator(entry: &'a T, locator: Option<SpecificLocator<'a>>) -> Self {
+        Self::with_locators(entry, locator.into_iter().collect())
+    }
+
+    /// Create a new citation item with multiple locators for the given entry.
+    pub fn with_locators(entry: &'a T, locators: Vec<SpecificLocator<'a>>) -> Self {
         Self {
             entry,
-            locator,
+            locators,
             locale: None,
             hidden: false,
             purpose: None,
@@ -1582,7 +2496,7 @@ impl<'a, T: EntryLike> CitationItem<'a, T> {
     ) -> Self {
         Self {
             entry,
-            locator,
+            locators: locator.into_iter().collect(),
             locale,
             hidden,
             purpose,
@@ -1604,23 +2518,44 @@ impl<'a> StyleContext<'a> {
     }
 
     /// Get the locale for the given language in the style.
-    fn lookup_locale<F, R>(&self, mut f: F) -> Option<R>
+    fn lookup_locale<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnMut(&'a Locale) -> Option<R>,
+    {
+        self.lookup_locale_in(&self.locale(), true, f)
+    }
+
+    /// Get the locale for the given language from the style and the locale
+    /// files. Only if `fallback` is set, the style's language-independent
+    /// locale and `en-US` are consulted as a last resort.
+    ///
+    /// For `de-AT`, the order is the one given by the CSL specification:
+    /// the style's `de-AT`, `de`, and language-independent locales, then the
+    /// `de-AT`, `de-DE`, and `en-US` locale files. The closure is called for
+    /// each of them until it returns `Some`, so that something missing in one
+    /// locale is taken from the next.
+    fn lookup_locale_in<F, R>(
+        &self,
+        locale: &LocaleCode,
+        fallback: bool,
+        mut f: F,
+    ) -> Option<R>
     where
         F: FnMut(&'a Locale) -> Option<R>,
     {
         let mut lookup = |file: &'a [Locale], lang: Option<&LocaleCode>| {
             #[allow(clippy::redundant_closure)]
-            file.iter().find(|l| l.lang.as_ref() == lang).and_then(|l| f(l))
+            file.iter().filter(|l| l.lang.as_ref() == lang).find_map(|l| f(l))
         };
 
-        let locale = self.locale();
+        let locale = locale.clone();
         let en_us = LocaleCode::en_us();
 
         for (i, resource) in [self.csl.locale.as_slice(), self.locale_files]
             .into_iter()
             .enumerate()
         {
-            let fallback = if i == 0 {
+            let base = if i == 0 {
                 locale.parse_base().and_then(|base| match base {
                     BaseLanguage::Iso639_1(lang) => {
                         Some(LocaleCode(String::from_utf8(lang.to_vec()).ok()?))
@@ -1636,12 +2571,16 @@ impl<'a> StyleContext<'a> {
                 return Some(output);
             }
 
-            if fallback.is_some() {
-                if let Some(output) = lookup(resource, fallback.as_ref()) {
+            if base.is_some() {
+                if let Some(output) = lookup(resource, base.as_ref()) {
                     return Some(output);
                 }
             }
 
+            if !fallback {
+                continue;
+            }
+
             if i == 0 {
                 if let Some(output) = lookup(resource, None) {
                     return Some(output);
@@ -1654,13 +2593,74 @@ impl<'a> StyleContext<'a> {
         None
     }
 
-    /// Check whether to do punctuation in quotes.
-    fn punctuation_in_quotes(&self) -> bool {
-        self.lookup_locale(|f| f.style_options?.punctuation_in_quote)
+    /// Resolve an option of `cs:style-options` for the given locale or, if
+    /// none is given, the locale of the style.
+    ///
+    /// Locales that do not set the option are skipped, so the in-style
+    /// `cs:locale` elements take precedence over the locale files, which take
+    /// precedence over the default of `false`. If no locale for the given
+    /// language sets the option, the style's locale is used, like for terms.
+    pub(crate) fn locale_option(
+        &self,
+        option: LocaleOption,
+        locale: Option<&LocaleCode>,
+    ) -> bool {
+        let get = |l: &'a Locale| {
+            let options = l.style_options?;
+            match option {
+                LocaleOption::LimitDayOrdinalsToDay1 => {
+                    options.limit_day_ordinals_to_day_1
+                }
+                LocaleOption::PunctuationInQuote => options.punctuation_in_quote,
+            }
+        };
+
+        locale
+            .and_then(|locale| self.lookup_locale_in(locale, false, get))
+            .or_else(|| self.lookup_locale(get))
             .unwrap_or_default()
     }
 }
 
+/// An option of a locale's `cs:style-options`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum LocaleOption {
+    /// Whether to only use the ordinal form for the first day of a month.
+    LimitDayOrdinalsToDay1,
+    /// Whether to move punctuation after a closing quote inside the quotes.
+    PunctuationInQuote,
+}
+
+/// The form to look a term up in if it is not defined in the given form. The
+/// chains are `verb-short`, `verb`, `long` and `symbol`, `short`, `long`.
+fn term_form_fallback(form: TermForm) -> Option<TermForm> {
+    match form {
+        TermForm::VerbShort => Some(TermForm::Verb),
+        TermForm::Symbol => Some(TermForm::Short),
+        TermForm::Verb | TermForm::Short => Some(TermForm::Long),
+        TermForm::Long => None,
+    }
+}
+
+/// Remove the periods that end a word, like in `eds.` or `U.S.`, for
+/// `strip-periods`. Decimal points and the periods of ellipses are kept.
+/// `prev` is the character before the string.
+fn strip_periods(s: &str, mut prev: Option<char>) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let strip = c == '.'
+            && prev.is_some_and(char::is_alphabetic)
+            && !chars.peek().is_some_and(|&n| n == '.' || n.is_numeric());
+        if !strip {
+            res.push(c);
+        }
+        prev = Some(c);
+    }
+
+    res
+}
+
 /// This struct contains all information needed to render a single entry. It
 /// contains buffers and is mutable.
 #[derive(Debug, Clone)]
@@ -1669,8 +2669,9 @@ pub(crate) struct WritingContext {
     /// Whether to watch out for punctuation that should be pulled inside the
     /// preceding quoted content.
     pull_punctuation: bool,
-    /// Whether we should be using inner quotes.
-    inner_quotes: bool,
+    /// How many quotes are open. Quotes at odd depths use the inner quotation
+    /// marks.
+    quote_depth: usize,
     /// Whether to strip periods.
     strip_periods: bool,
     /// Whether to add queried variables to the suppression list.
@@ -1691,10 +2692,13 @@ pub(crate) struct WritingContext {
     /// it did push, must remove. Its formatting shall apply for it and its
     /// children.
     format_stack: NonEmptyStack<Formatting>,
-    /// Text cases.
-    cases: NonEmptyStack<Option<TextCase>>,
+    /// Text cases and the language-specific case mappings they apply with.
+    cases: NonEmptyStack<Option<(TextCase, SpecialCasing)>>,
     /// Inheritable name options.
     name_options: NonEmptyStack<InheritableNameOptions>,
+    /// Name options that take precedence over those of every `cs:name`
+    /// element. Set by the `names-*` attributes of a sort key.
+    name_options_override: Option<InheritableNameOptions>,
     /// Delimiters from ancestor delimiting elements (e.g., `cs:group`).
     /// To be applied within `cs:choose`, but not another delimiting element or a macro.
     delimiters: NonEmptyStack<Option<String>>,
@@ -1712,7 +2716,7 @@ impl Default for WritingContext {
     fn default() -> Self {
         Self {
             pull_punctuation: false,
-            inner_quotes: false,
+            quote_depth: 0,
             strip_periods: false,
             suppress_queried_variables: false,
             suppressed_variables: RefCell::new(Vec::new()),
@@ -1722,6 +2726,7 @@ impl Default for WritingContext {
             format_stack: NonEmptyStack::default(),
             cases: NonEmptyStack::default(),
             name_options: NonEmptyStack::default(),
+            name_options_override: None,
             delimiters: NonEmptyStack::default(),
             buf: CaseFolder::default(),
             elem_stack: NonEmptyStack::default(),
@@ -1875,7 +2880,7 @@ impl WritingContext {
                 .0
                 .push(Elem { children, display, meta }.into());
         } else {
-            self.elem_stack.last_mut().0.extend(children.0);
+            self.elem_stack.last_mut().append(children);
         }
     }
 
@@ -1889,6 +2894,11 @@ impl WritingContext {
         self.name_options.pop();
     }
 
+    /// Override the name options of all `cs:name` elements.
+    pub fn override_name_options(&mut self, options: InheritableNameOptions) {
+        self.name_options_override = Some(options);
+    }
+
     /// Ensure that the buffer is either empty or the last character is a space.
     pub fn ensure_space(&mut self) {
         if !self.buf.is_empty() {
@@ -1937,7 +2947,7 @@ impl WritingContext {
     }
 
     /// Set the case of the next text.
-    fn push_case(&mut self, case: Option<TextCase>) -> CaseIdx {
+    fn push_case(&mut self, case: Option<(TextCase, SpecialCasing)>) -> CaseIdx {
         let idx = self.cases.len();
         self.cases.push(case);
         CaseIdx(idx)
@@ -1953,8 +2963,12 @@ impl WritingContext {
     }
     /// Reconfigures the case folder's case to the current
     fn reconfigure(&mut self) {
-        self.buf
-            .reconfigure((*self.cases.last()).map(Into::into).unwrap_or_default());
+        let (case, special_casing) = match *self.cases.last() {
+            Some((case, special_casing)) => (case.into(), special_casing),
+            None => Default::default(),
+        };
+        self.buf.reconfigure(case);
+        self.buf.set_special_casing(special_casing);
     }
 
     /// Set the delimiter of the children of a [`citationberg::Group`] or
@@ -2036,11 +3050,17 @@ struct CiteProperties<'a> {
 }
 
 impl<'a> CiteProperties<'a> {
-    fn for_sorting(locator: Option<SpecificLocator<'a>>, citation_number: usize) -> Self {
+    fn for_sorting(locators: Vec<SpecificLocator<'a>>, citation_number: usize) -> Self {
         Self {
-            certain: CertainCiteProperties::new(),
+            // Without a citation history, treat the item as cited for the
+            // first time so that the `et-al-subsequent-*` options do not
+            // apply.
+            certain: CertainCiteProperties {
+                is_first: true,
+                ..CertainCiteProperties::new()
+            },
             speculative: SpeculativeCiteProperties::speculate(
-                locator,
+                locators,
                 citation_number,
                 IbidState::Different,
             ),
@@ -2081,11 +3101,11 @@ impl CertainCiteProperties {
 /// These require validation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct SpeculativeCiteProperties<'a> {
-    /// Locator with its type.
+    /// Locators with their types.
     ///
-    /// This will be none during the first render because it would interfere
+    /// This will be empty during the first render because it would interfere
     /// with disambiguation otherwise.
-    pub locator: Option<SpecificLocator<'a>>,
+    pub locators: Vec<SpecificLocator<'a>>,
     /// The position the item appears at in the bibliography.
     ///
     /// We can determine this using bibliography sort and cite order once we
@@ -2109,12 +3129,12 @@ impl<'a> SpeculativeCiteProperties<'a> {
     /// We can guess about the [`IbidState`] under the assumption that neither
     /// this item nor its predecessors will be subject to cite grouping.
     fn speculate(
-        locator: Option<SpecificLocator<'a>>,
+        locators: Vec<SpecificLocator<'a>>,
         citation_number: usize,
         ibid: IbidState,
     ) -> Self {
         Self {
-            locator,
+            locators,
             citation_number,
             ibid,
             disambiguation: DisambiguateState::default(),
@@ -2125,7 +3145,7 @@ impl<'a> SpeculativeCiteProperties<'a> {
     /// Get the speculative cite properties for a bibliography entry.
     fn for_bibliography(&self) -> Self {
         Self {
-            locator: None,
+            locators: Vec::new(),
             citation_number: self.citation_number,
             ibid: self.ibid,
             disambiguation: self.disambiguation.clone(),
@@ -2226,7 +3246,7 @@ impl IbidState {
     {
         if let Some(last) = last {
             if last.entry == this.entry && !last.hidden {
-                if last.locator == this.locator {
+                if last.locators == this.locators {
                     IbidState::Ibid
                 } else {
                     IbidState::IbidWithLocator
@@ -2296,39 +3316,65 @@ impl<'a, T: EntryLike> Context<'a, T> {
         self.writing.ensure_space()
     }
 
+    /// The locale of the entry's content, either from the citation item or
+    /// the entry's `language` field, if its language differs from the
+    /// style's locale.
+    fn foreign_content_locale(&self) -> Option<LocaleCode> {
+        let locale = self.instance.locale.cloned().or_else(|| {
+            self.instance
+                .entry
+                .language_code()
+                .map(|code| LocaleCode(code.into_owned()))
+        })?;
+
+        let lang =
+            |l: &LocaleCode| l.0.split(['-', '_']).next().map(str::to_ascii_lowercase);
+        (lang(&locale) != lang(&self.style.locale())).then_some(locale)
+    }
+
+    /// Get a term that depends on the language of the entry's content, like
+    /// quotation marks. Falls back to the style's locale.
+    fn content_term(&self, term: Term) -> Option<&'a str> {
+        if let Some(locale) = self.foreign_content_locale() {
+            if let Some(localization) = self.style.lookup_locale_in(&locale, false, |l| {
+                Some(l.term(term, TermForm::default())?.single())
+            }) {
+                return localization;
+            }
+        }
+
+        self.term(term, TermForm::default(), false)
+    }
+
     /// Add the appropriate opening quotation marks.
     fn push_quotes(&mut self) {
-        let mark = self.term(
-            if self.writing.inner_quotes {
+        let mark = self.content_term(
+            if self.writing.quote_depth % 2 == 1 {
                 OtherTerm::OpenInnerQuote
             } else {
                 OtherTerm::OpenQuote
             }
             .into(),
-            TermForm::default(),
-            false,
         );
 
         if let Some(mark) = mark {
             self.push_str(mark);
         }
 
-        self.writing.inner_quotes = !self.writing.inner_quotes;
+        self.writing.quote_depth += 1;
     }
 
     /// Add the appropriate closing quotation marks.
     fn pop_quotes(&mut self) {
-        self.writing.inner_quotes = !self.writing.inner_quotes;
+        self.writing.quote_depth = self.writing.quote_depth.saturating_sub(1);
 
-        let mark = self.term(
-            if self.writing.inner_quotes {
+        let mark = self.content_term(
+            if self.writing.quote_depth % 2 == 1 {
                 OtherTerm::CloseInnerQuote
             } else {
                 OtherTerm::CloseQuote
             }
             .into(),
-            TermForm::default(),
-            false,
         );
 
         if let Some(mark) = mark {
@@ -2336,6 +3382,45 @@ impl<'a, T: EntryLike> Context<'a, T> {
         }
     }
 
+    /// Add content to the buffer and replace its straight double quotes with
+    /// the quotation marks of the locale. A quote opens after whitespace and
+    /// opening brackets and closes otherwise. `prev` is the character before
+    /// `s` in the same value. Returns how many quotes are still open.
+    fn push_smart_quoted(
+        &mut self,
+        s: &str,
+        prev: Option<char>,
+        mut open: usize,
+    ) -> usize {
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            if c != '"' {
+                continue;
+            }
+
+            let prev = s[..i].chars().next_back().or(prev);
+            self.push_str(&s[start..i]);
+            start = i + c.len_utf8();
+
+            if open > 0 && prev.is_some_and(|p| !p.is_whitespace() && !"([{".contains(p))
+            {
+                self.pop_quotes();
+                self.may_pull_punctuation();
+                open -= 1;
+            } else {
+                self.push_quotes();
+                open += 1;
+            }
+        }
+
+        // Do not reset the punctuation pulling after a closing quote.
+        if start < s.len() {
+            self.push_str(&s[start..]);
+        }
+
+        open
+    }
+
     /// Pull punctuation into a quote if applicable
     fn do_pull_punctuation<'s>(&mut self, mut s: &'s str) -> &'s str {
         if self.writing.pull_punctuation && s.starts_with(['.', ',', ';', '!', '?']) {
@@ -2373,22 +3458,27 @@ impl<'a, T: EntryLike> Context<'a, T> {
                 self.writing.buf.as_string_mut()
             };
 
-            for quote in [close_quote, close_inner_quote].iter().flatten() {
-                // Check if buf ends with a close quote.
-                // If it does, replace it with the punctuation.
-                if let Some(head) = buf.strip_suffix(quote) {
-                    buf.truncate(head.len());
-
-                    let punctuation = s.chars().next().unwrap();
-                    s = &s[punctuation.len_utf8()..];
-                    buf.push(punctuation);
-                    buf.push_str(quote);
+            // Find the closing quotes at the end of the buffer, possibly
+            // nested, and put the punctuation before them.
+            let quotes = [close_quote, close_inner_quote];
+            let mut head = buf.as_str();
+            while let Some(h) = quotes
+                .iter()
+                .flatten()
+                .filter(|q| !q.is_empty())
+                .find_map(|q| head.strip_suffix(q))
+            {
+                head = h;
+            }
 
-                    if used_buf {
-                        self.writing.buf.mark_changed();
-                    }
+            let end = head.len();
+            if end < buf.len() {
+                let punctuation = s.chars().next().unwrap();
+                s = &s[punctuation.len_utf8()..];
+                buf.insert(end, punctuation);
 
-                    break;
+                if used_buf {
+                    self.writing.buf.mark_changed();
                 }
             }
         }
@@ -2396,8 +3486,80 @@ impl<'a, T: EntryLike> Context<'a, T> {
         s
     }
 
+    /// Merge punctuation at the start of `s` with the punctuation that ends
+    /// the output so far, looking through closing quotes and element
+    /// boundaries. A period after a period, question mark, or exclamation mark
+    /// is dropped, as are repeated commas, colons, and semicolons. Other
+    /// punctuation after a question or exclamation mark is kept. A period
+    /// replaces a preceding comma, colon, or semicolon.
+    fn merge_punctuation<'s>(&mut self, s: &'s str) -> &'s str {
+        let Some(next) = s.chars().next() else { return s };
+        let rest = &s[next.len_utf8()..];
+        if !matches!(next, '.' | ',' | ';' | ':')
+            || rest.chars().next().is_some_and(char::is_alphanumeric)
+        {
+            return s;
+        }
+
+        let quotes: Vec<&str> = [OtherTerm::CloseQuote, OtherTerm::CloseInnerQuote]
+            .into_iter()
+            .filter_map(|t| self.term(t.into(), TermForm::default(), false))
+            .filter(|q| !q.is_empty())
+            .collect();
+
+        let used_buf = !self.writing.buf.is_empty();
+        let last = if used_buf {
+            self.writing.buf.as_string_mut()
+        } else {
+            match self
+                .writing
+                .elem_stack
+                .last_mut_predicate(|e| !e.is_empty())
+                .and_then(|e| e.last_text_mut())
+            {
+                Some(last) => &mut last.text,
+                None => return s,
+            }
+        };
+
+        // Periods and commas eat spaces.
+        let len = last.len();
+        if matches!(next, '.' | ',') {
+            last.truncate(last.trim_end().len());
+        }
+
+        let mut body = last.as_str();
+        while let Some(head) = quotes.iter().find_map(|q| body.strip_suffix(q)) {
+            body = head;
+        }
+
+        let mut chars = body.chars();
+        let res = match (chars.next_back(), next) {
+            (Some('.' | '?' | '!'), '.') => rest,
+            (Some(','), ',') | (Some(';'), ';') | (Some(':'), ':') => rest,
+            (Some(prev @ (',' | ';' | ':')), '.') => {
+                let terminal = matches!(chars.next_back(), Some('.' | '?' | '!'));
+                let end = body.len();
+                last.replace_range(end - prev.len_utf8()..end, "");
+                if terminal {
+                    rest
+                } else {
+                    s
+                }
+            }
+            _ => s,
+        };
+
+        if used_buf && self.writing.buf.as_string_mut().len() != len {
+            self.writing.buf.mark_changed();
+        }
+
+        res
+    }
+
     /// Add a string to the buffer.
     fn push_str(&mut self, s: &str) {
+        let s = self.merge_punctuation(s);
         let s = self.do_pull_punctuation(s);
 
         self.writing.reconfigure();
@@ -2441,16 +3603,8 @@ impl<'a, T: EntryLike> Context<'a, T> {
         }
 
         if self.writing.strip_periods {
-            // Replicate citeproc.js behavior: remove a period if the
-            // preceding character in the original string is not a period.
-            let mut last_period = false;
-            for c in s.chars() {
-                let is_period = c == '.';
-                if !is_period || last_period {
-                    self.writing.buf.push(c);
-                }
-                last_period = is_period;
-            }
+            let prev = last_buffer(&mut self.writing).and_then(|s| s.chars().next_back());
+            self.writing.buf.push_str(&strip_periods(s, prev));
         } else {
             self.writing.buf.push_str(s);
         }
@@ -2460,8 +3614,18 @@ impl<'a, T: EntryLike> Context<'a, T> {
 
     /// Push a chunked string to the buffer.
     pub fn push_chunked(&mut self, chunked: &ChunkedString) {
+        let mut open_quotes = 0;
+        let mut prev = None;
         for chunk in &chunked.0 {
+            let format = (!chunk.format.is_empty()).then(|| {
+                self.writing.buf.prevent_trimming();
+                self.push_format(chunk.format.into())
+            });
+
             match chunk.kind {
+                ChunkKind::Normal if self.style.smart_quotes => {
+                    open_quotes = self.push_smart_quoted(&chunk.value, prev, open_quotes);
+                }
                 ChunkKind::Normal => self.push_str(&chunk.value),
                 ChunkKind::Verbatim => {
                     self.writing.buf.push_verbatim(&chunk.value);
@@ -2479,7 +3643,19 @@ impl<'a, T: EntryLike> Context<'a, T> {
                     self.writing.buf.prevent_trimming();
                 }
             }
+
+            if let Some(format) = format {
+                self.pop_format(format);
+                self.writing.reconfigure();
+                self.writing.buf.prevent_trimming();
+            }
+
+            prev = chunk.value.chars().next_back().or(prev);
         }
+
+        // Quotes that were not closed in the value do not affect the nesting
+        // of the following quotes.
+        self.writing.quote_depth = self.writing.quote_depth.saturating_sub(open_quotes);
     }
 
     /// Push a link into the buffer.
@@ -2490,7 +3666,9 @@ impl<'a, T: EntryLike> Context<'a, T> {
             .elem_stack
             .last_mut()
             .0
-            .push(ElemChild::Link { text: format.add_text(chunked.to_string()), url })
+            .push(ElemChild::Link { text: format.add_text(chunked.to_string()), url });
+        // Punctuation is not pulled into quotes before the link.
+        self.writing.pull_punctuation = false;
     }
 
     /// Push a transparent element child into the buffer.
@@ -2501,7 +3679,8 @@ impl<'a, T: EntryLike> Context<'a, T> {
             .elem_stack
             .last_mut()
             .0
-            .push(ElemChild::Transparent { cite_idx: idx, format })
+            .push(ElemChild::Transparent { cite_idx: idx, format });
+        self.writing.pull_punctuation = false;
     }
 
     /// Folds all remaining elements into the first element and returns it.
@@ -2512,26 +3691,41 @@ impl<'a, T: EntryLike> Context<'a, T> {
     /// Get a term from the style.
     fn term(&self, mut term: Term, form: TermForm, plural: bool) -> Option<&'a str> {
         if term == Term::NumberVariable(csl_taxonomy::NumberVariable::Locator) {
-            if let Some(locator) = self.instance.cite_props.speculative.locator {
+            if let Some(locator) = self.instance.cite_props.speculative.locators.first() {
                 term = locator.0.into();
             }
         }
 
+        let overrides = self.style.term_overrides;
         let mut form = Some(form);
         while let Some(current_form) = form {
+            if let Some(value) = overrides.and_then(|o| o.get(term, current_form, plural))
+            {
+                return Some(value);
+            }
+
             if let Some(localization) = self.style.lookup_locale(|l| {
                 let term = l.term(term, current_form)?;
-                Some(if plural { term.multiple() } else { term.single() })
+                if plural {
+                    term.multiple()
+                } else {
+                    term.single()
+                }
             }) {
-                return localization;
+                return Some(localization);
             }
 
-            form = current_form.fallback();
+            form = term_form_fallback(current_form);
         }
 
         None
     }
 
+    /// Report a problem with the current entry.
+    fn warn(&self, diagnostic: impl FnOnce(String) -> RenderDiagnostic) {
+        self.style.warn(diagnostic(self.instance.entry.key().into_owned()));
+    }
+
     /// Get the gender of a term.
     fn gender(&self, term: Term) -> Option<GrammarGender> {
         if let Some(localization) =
@@ -2559,7 +3753,10 @@ impl<'a, T: EntryLike> Context<'a, T> {
     /// Pull the next punctuation character into the preceding quoted content
     /// if appropriate for the locale.
     fn may_pull_punctuation(&mut self) {
-        self.writing.pull_punctuation |= self.style.punctuation_in_quotes();
+        let locale = self.foreign_content_locale();
+        self.writing.pull_punctuation = self
+            .style
+            .locale_option(LocaleOption::PunctuationInQuote, locale.as_ref());
     }
 
     /// Set whether to strip periods.
@@ -2586,12 +3783,29 @@ impl<'a, T: EntryLike> Context<'a, T> {
                 })
                 .unwrap_or(true)
         {
-            self.writing.push_case(case)
+            self.writing.push_case(case.map(|case| (case, self.special_casing())))
         } else {
             self.writing.push_case(None)
         }
     }
 
+    /// The language-specific case mappings for the current item, selected by
+    /// the language of the entry or, failing that, the locale.
+    fn special_casing(&self) -> SpecialCasing {
+        self.instance
+            .entry
+            .language_code()
+            .map(|code| SpecialCasing::from_locale(&code))
+            .or_else(|| {
+                self.instance
+                    .locale
+                    .or(self.instance.term_locale)
+                    .or(self.style.csl.default_locale.as_ref())
+                    .map(|locale| SpecialCasing::from_locale(&locale.0))
+            })
+            .unwrap_or_default()
+    }
+
     /// Clear the case of the next text.
     fn pop_case(&mut self, idx: CaseIdx) {
         self.writing.pop_case(idx)
@@ -2663,7 +3877,7 @@ impl<'a, T: EntryLike> Context<'a, T> {
         &self,
         form: LongShortForm,
         variable: csl_taxonomy::StandardVariable,
-    ) -> Option<Cow<'a, ChunkedString>> {
+    ) -> Option<StandardValue<'a>> {
         // Replace the citation label with citation number if necessary.
         if variable == StandardVariable::CitationLabel {
             if self.bibliography {
@@ -2676,7 +3890,7 @@ impl<'a, T: EntryLike> Context<'a, T> {
                         .resolve_number_variable(NumberVariable::CitationNumber)
                         .map(|n| match n {
                             NumberVariableResult::Regular(n) => {
-                                Cow::Owned(ChunkedString::from(n.to_string()))
+                                ChunkedString::from(n.to_string()).into()
                             }
                             NumberVariableResult::Transparent(_) => {
                                 panic!("unexpected transparent")
@@ -2689,10 +3903,167 @@ impl<'a, T: EntryLike> Context<'a, T> {
             }
         }
 
+        if variable == StandardVariable::URL && self.url_suppressed_by_doi() {
+            return None;
+        }
         self.writing.prepare_variable_query(variable)?;
+
         let res = self.instance.resolve_standard_variable(form, variable);
+        let res = self.abbreviate(form, variable, res);
+        let res = self.auto_short_title(form, variable, res);
 
-        res
+        self.apply_preferred_script(form, variable, res)
+    }
+
+    /// Derive the short title from the title if the style asks for the short
+    /// form, the entry has none, and the request enables automatic short
+    /// titles.
+    fn auto_short_title(
+        &self,
+        form: LongShortForm,
+        variable: csl_taxonomy::StandardVariable,
+        res: Option<StandardValue<'a>>,
+    ) -> Option<StandardValue<'a>> {
+        if !self.style.auto_short_titles
+            || !(variable == StandardVariable::TitleShort
+                || (variable == StandardVariable::Title && form == LongShortForm::Short))
+        {
+            return res;
+        }
+
+        let Some(long) = self
+            .instance
+            .resolve_standard_variable(LongShortForm::Long, StandardVariable::Title)
+        else {
+            return res;
+        };
+
+        if res.as_deref().is_some_and(|res| res != &*long) {
+            return res;
+        }
+
+        let locale = self
+            .instance
+            .entry
+            .language_code()
+            .map(Cow::into_owned)
+            .or_else(|| {
+                self.instance
+                    .locale
+                    .or(self.instance.term_locale)
+                    .map(|l| l.0.clone())
+            })
+            .unwrap_or_else(|| self.style.locale().0);
+
+        Some(derive_short_title(&long, &locale).into())
+    }
+
+    /// Replace a container or collection title with its abbreviation from the
+    /// request if the style asks for the short form or abbreviations are always
+    /// used. A short form of the entry takes precedence.
+    fn abbreviate(
+        &self,
+        form: LongShortForm,
+        variable: csl_taxonomy::StandardVariable,
+        res: Option<StandardValue<'a>>,
+    ) -> Option<StandardValue<'a>> {
+        let Some(abbreviations) = self.style.abbreviations else { return res };
+        let (long_variable, list) = match variable {
+            StandardVariable::ContainerTitle | StandardVariable::ContainerTitleShort => {
+                (StandardVariable::ContainerTitle, &abbreviations.container_title)
+            }
+            StandardVariable::CollectionTitle => {
+                (StandardVariable::CollectionTitle, &abbreviations.collection_title)
+            }
+            _ => return res,
+        };
+
+        let short = form == LongShortForm::Short
+            || variable == StandardVariable::ContainerTitleShort;
+        if !short && !self.style.use_abbreviations {
+            return res;
+        }
+
+        let Some(long) = self
+            .instance
+            .resolve_standard_variable(LongShortForm::Long, long_variable)
+        else {
+            return res;
+        };
+
+        if short && res.as_deref().is_some_and(|res| res != &*long) {
+            return res;
+        }
+
+        match list.get(long.to_str().trim()) {
+            Some(abbreviation) => Some(ChunkedString::from(abbreviation.clone()).into()),
+            None => res,
+        }
+    }
+
+    /// The abbreviation of an institution's name from the request if the style
+    /// asks for the short form or abbreviations are always used.
+    fn abbreviate_institution(&self, name: &str, short: bool) -> Option<&'a str> {
+        if !short && !self.style.use_abbreviations {
+            return None;
+        }
+
+        self.style
+            .abbreviations?
+            .institution
+            .get(name.trim())
+            .map(String::as_str)
+    }
+
+    /// Replace a value with its transliteration or append the transliteration
+    /// in square brackets, depending on the preferred script.
+    fn apply_preferred_script(
+        &self,
+        form: LongShortForm,
+        variable: csl_taxonomy::StandardVariable,
+        value: Option<StandardValue<'a>>,
+    ) -> Option<StandardValue<'a>> {
+        let script = self.style.preferred_script;
+        if script == PreferredScript::Original {
+            return value;
+        }
+
+        let Some(transliterated) = self
+            .instance
+            .entry
+            .resolve_transliterated_standard_variable(form, variable)
+        else {
+            return value;
+        };
+
+        match (script, value) {
+            (PreferredScript::OriginalWithTransliteration, Some(value))
+                if !self.instance.sorting =>
+            {
+                let mut value = value.into_owned();
+                value.push_str(" [", ChunkKind::Normal);
+                for chunk in &transliterated.0 {
+                    value.push_chunk(chunk.clone());
+                }
+                value.push_str("]", ChunkKind::Normal);
+                Some(value.into())
+            }
+            (PreferredScript::Transliterated, _) => Some(transliterated.into()),
+            (_, value) => value,
+        }
+    }
+
+    /// Whether the URL and access date of the entry should be suppressed
+    /// because it has a DOI.
+    fn url_suppressed_by_doi(&self) -> bool {
+        self.style.suppress_url_with_doi
+            && self
+                .instance
+                .resolve_standard_variable(
+                    LongShortForm::default(),
+                    StandardVariable::DOI,
+                )
+                .is_some()
     }
 
     /// Resolve a date variable.
@@ -2702,7 +4073,11 @@ impl<'a, T: EntryLike> Context<'a, T> {
         &self,
         variable: csl_taxonomy::DateVariable,
     ) -> Option<Cow<'a, Date>> {
+        if variable == DateVariable::Accessed && self.url_suppressed_by_doi() {
+            return None;
+        }
         self.writing.prepare_variable_query(variable)?;
+
         let res = self.instance.entry.resolve_date_variable(variable);
 
         res
@@ -2719,10 +4094,25 @@ impl<'a, T: EntryLike> Context<'a, T> {
             return Vec::new();
         }
 
-        let res = self.instance.entry.resolve_name_variable(variable);
+        let mut res = self.instance.entry.resolve_name_variable(variable);
+        if variable == csl_taxonomy::NameVariable::Author && self.style.post_handles {
+            self.add_post_handle(&mut res);
+        }
+
         res
     }
 
+    /// Replace the first author of a post with a literal name followed by the
+    /// handle in brackets. Posts attributed to their handle are left alone.
+    fn add_post_handle(&self, names: &mut [Cow<'a, Person>]) {
+        let Some(handle) = self.instance.entry.post_handle() else { return };
+        let Some(first) = names.first_mut() else { return };
+        if first.name.strip_prefix('@') != Some(&*handle) {
+            let name = format!("{} [@{handle}]", first.given_first(false));
+            *first = Cow::Owned(Person::literal(name));
+        }
+    }
+
     /// Apply a prefix, but return a tuple that allows us to undo it if it
     /// wasn't followed by anything.
     fn apply_prefix(&mut self, affixes: &Affixes) -> (DisplayLoc, usize) {
@@ -2874,26 +4264,276 @@ impl Brackets {
     }
 }
 
-/// For what purpose to generate a citation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum CitePurpose {
-    /// The citation will only contain the name of the author.
-    Author,
-    /// The citation will only contain the year. Year Affixes will not be
-    /// included.
-    Year,
-    /// The citation will equate to the bibliography entry of the item.
-    Full,
-    /// The citation will be well-suited for inclusion in prose.
-    Prose,
+/// Which variables are rendered as hyperlinks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkBehaviour {
+    /// Never create links. All variables are rendered as plain text.
+    Never,
+    /// Only link identifiers (DOI, PMID, PMCID, and arXiv) to their resolvers. The
+    /// `URL` variable is rendered as plain text.
+    IdentifiersOnly,
+    /// Link both the `URL` variable and identifiers.
+    #[default]
+    UrlsAndIdentifiers,
 }
 
-/// A special citation form to use for the [`CitationItem`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum SpecialForm {
-    /// Only output the author.
-    VarOnly(Variable),
-    /// Only print the first date.
+impl LinkBehaviour {
+    /// Whether the `URL` variable should become a link.
+    pub fn link_urls(self) -> bool {
+        self == Self::UrlsAndIdentifiers
+    }
+
+    /// Whether DOI, PMID, PMCID, and arXiv should link to their resolvers.
+    pub fn link_identifiers(self) -> bool {
+        self != Self::Never
+    }
+}
+
+/// Which script to render for titles and names that have a transliteration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PreferredScript {
+    /// Render the original script.
+    #[default]
+    Original,
+    /// Render the transliteration instead of the original script.
+    Transliterated,
+    /// Render the original script followed by the transliteration in square
+    /// brackets, for example `Иванов, И. [Ivanov, I.]`. Sorting uses the
+    /// original script.
+    OriginalWithTransliteration,
+}
+
+/// Derive a short title by removing the subtitle after the first colon or
+/// question mark that is followed by a space, and the leading article in the
+/// language of the locale. Verbatim and math chunks keep their kind, and
+/// colons and question marks within them do not start a subtitle.
+fn derive_short_title(title: &ChunkedString, locale: &str) -> ChunkedString {
+    let mut short = ChunkedString::new();
+    'chunks: for (i, chunk) in title.0.iter().enumerate() {
+        if chunk.kind == ChunkKind::Normal {
+            let next = title.0.get(i + 1).and_then(|c| c.value.chars().next());
+            for (idx, c) in chunk.value.char_indices() {
+                if !matches!(c, ':' | '?') {
+                    continue;
+                }
+
+                let end = idx + c.len_utf8();
+                let after = chunk.value[end..].chars().next().or(next);
+                if after.is_some_and(char::is_whitespace) {
+                    // The question mark belongs to the main title.
+                    let end = if c == '?' { end } else { idx };
+                    let mut head = chunk.clone();
+                    head.value.truncate(end);
+                    short.0.push(head);
+                    break 'chunks;
+                }
+            }
+        }
+
+        short.0.push(chunk.clone());
+    }
+
+    if let Some(last) = short.0.last_mut() {
+        last.value.truncate(last.value.trim_end().len());
+    }
+
+    if let Some(first) = short.0.first_mut().filter(|c| c.kind == ChunkKind::Normal) {
+        first.value = strip_leading_article(&first.value, locale).to_string();
+    }
+
+    short.0.retain(|chunk| !chunk.value.is_empty());
+    if short.is_empty() {
+        return title.clone();
+    }
+
+    short
+}
+
+/// Abbreviations that replace the full form of titles and names, like
+/// `Phys. Rev. Lett.` for `Physical Review Letters`.
+///
+/// They apply when the style asks for the short form of a title or an
+/// institutional name, unless the entry itself has a short form, or whenever
+/// the request sets `use_abbreviations`. Values without an abbreviation are
+/// rendered in full. The list can be read from JSON:
+///
+/// ```json
+/// {
+///     "container-title": { "Physical Review Letters": "Phys. Rev. Lett." },
+///     "institution": { "World Health Organization": "WHO" }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Abbreviations {
+    /// Abbreviations of container titles, e.g. journal names.
+    #[serde(default)]
+    pub container_title: BTreeMap<String, String>,
+    /// Abbreviations of collection titles, e.g. series names.
+    #[serde(default)]
+    pub collection_title: BTreeMap<String, String>,
+    /// Abbreviations of the names of institutions.
+    #[serde(default)]
+    pub institution: BTreeMap<String, String>,
+}
+
+impl Abbreviations {
+    /// Create an empty list of abbreviations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Replacements for terms that take precedence over the locale files and the
+/// locales defined in the style, like `and others` for `et al.`.
+///
+/// A replacement applies to a term in one form and number. If there is none
+/// for the form the style asks for, the usual fallback to other forms applies.
+///
+/// ```
+/// use hayagriva::citationberg::taxonomy::{OtherTerm, Term};
+/// use hayagriva::citationberg::TermForm;
+/// use hayagriva::TermOverrides;
+///
+/// let et_al = Term::Other(OtherTerm::EtAl);
+/// let mut overrides = TermOverrides::new();
+/// overrides.insert(et_al, TermForm::Long, false, "and others");
+/// assert_eq!(overrides.get(et_al, TermForm::Long, false), Some("and others"));
+/// assert_eq!(overrides.get(et_al, TermForm::Short, false), None);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct TermOverrides(Vec<(Term, TermForm, bool, String)>);
+
+impl TermOverrides {
+    /// Create an empty list of replacements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the singular or, with `plural`, the plural of a term in the
+    /// given form.
+    pub fn insert(
+        &mut self,
+        term: Term,
+        form: TermForm,
+        plural: bool,
+        value: impl Into<String>,
+    ) {
+        let value = value.into();
+        match self
+            .0
+            .iter_mut()
+            .find(|(t, f, p, _)| (*t, *f, *p) == (term, form, plural))
+        {
+            Some(existing) => existing.3 = value,
+            None => self.0.push((term, form, plural, value)),
+        }
+    }
+
+    /// Get the replacement for a term, if any.
+    pub fn get(&self, term: Term, form: TermForm, plural: bool) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(t, f, p, _)| (*t, *f, *p) == (term, form, plural))
+            .map(|(.., value)| value.as_str())
+    }
+}
+
+/// For what purpose to generate a citation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CitePurpose {
+    /// The citation will only contain the name of the author.
+    Author,
+    /// The citation will only contain the first date the style prints,
+    /// usually the year, including its year suffix. Affixes of the citation
+    /// layout and locators will not be included. Numeric and label styles
+    /// yield an empty citation.
+    Year,
+    /// The citation will equate to the bibliography entry of the item.
+    Full,
+    /// The citation will be well-suited for inclusion in prose.
+    Prose,
+}
+
+/// Where a citation of a note style is placed within its footnote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NoteContext {
+    /// Whether the citation is the start of the footnote. Its first letter
+    /// is capitalized, e.g. for the `ibid` term, unless it belongs to a name.
+    pub starts_note: bool,
+    /// Whether the citation ends a sentence. A period is appended unless the
+    /// citation already ends with terminal punctuation.
+    pub ends_sentence: bool,
+}
+
+impl NoteContext {
+    /// Apply the capitalization and punctuation to a rendered citation if the
+    /// style is a note style.
+    fn apply(
+        self,
+        children: &mut ElemChildren,
+        style: &IndependentStyle,
+        formatting: Formatting,
+    ) {
+        if style.settings.class != StyleClass::Note || !children.has_content() {
+            return;
+        }
+
+        if self.starts_note {
+            children.capitalize_first();
+        }
+
+        if self.ends_sentence && !children.ends_with_terminal_punctuation() {
+            children.0.push(Formatted { text: ".".into(), formatting }.into());
+        }
+    }
+}
+
+/// A limit on the number of names rendered for each name variable, e.g. for a
+/// compact list of further reading.
+///
+/// The limit applies in addition to the et-al settings of the style, so
+/// whichever renders fewer names wins. Names for sorting are not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NameCap {
+    /// The maximum number of names to render per variable. At least one name
+    /// is always rendered.
+    pub max: usize,
+    /// How to indicate that names were omitted.
+    pub overflow: NameOverflow,
+}
+
+impl NameCap {
+    /// Create a new limit that ends truncated lists with the et-al term.
+    pub fn new(max: usize) -> Self {
+        Self { max, overflow: NameOverflow::default() }
+    }
+
+    /// Set how to indicate that names were omitted.
+    pub fn with_overflow(mut self, overflow: NameOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// How a [`NameCap`] indicates that names were omitted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameOverflow {
+    /// Append the et-al term of the style, e.g. "Doe et al.".
+    #[default]
+    EtAl,
+    /// Append an ellipsis and the last name like the `et-al-use-last` CSL
+    /// option, e.g. "Doe, … Roe". Falls back to the et-al term if only one
+    /// name would be omitted.
+    Ellipsis,
+}
+
+/// A special citation form to use for the [`CitationItem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SpecialForm {
+    /// Only output the author.
+    VarOnly(Variable),
+    /// Only print the first date.
     OnlyFirstDate,
     /// Only print the year suffix.
     OnlyYearSuffix,
@@ -2901,76 +4541,4365 @@ enum SpecialForm {
     SuppressAuthor,
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{fs, path::Path};
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use citationberg::LocaleFile;
+
+    use super::*;
+    use crate::io::from_yaml_str;
+    use crate::{Entry, Library};
+
+    /// Parse an in-text style with the given elements after `cs:info`.
+    fn test_style(body: &str) -> IndependentStyle {
+        test_style_with(r#"class="in-text""#, body)
+    }
+
+    /// Parse a style with the given attributes, which must include the class,
+    /// and the given elements after `cs:info`.
+    fn test_style_with(attrs: &str, body: &str) -> IndependentStyle {
+        IndependentStyle::from_xml(&format!(
+            r#"<style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" {attrs}>
+                <info>
+                    <title>Test</title>
+                    <id>test</id>
+                    <updated>2024-01-01T00:00:00+00:00</updated>
+                </info>
+                {body}
+            </style>"#
+        ))
+        .unwrap()
+    }
+
+    /// Cite each entry on its own and render the citations and the
+    /// bibliography for the request.
+    fn cite_each<'a>(
+        entries: impl IntoIterator<Item = &'a Entry>,
+        request: BibliographyRequest<'a>,
+    ) -> Rendered {
+        let mut driver = BibliographyDriver::new();
+        for entry in entries {
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_entry(entry)],
+                request.style,
+                request.locale_files,
+            ));
+        }
+
+        driver.finish(request)
+    }
+
+    #[test]
+    fn test_csl() {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let en_locale =
+            fs::read_to_string(workspace.join("tests/data/locales-en-US.xml")).unwrap();
+        let en_locale = LocaleFile::from_xml(&en_locale).unwrap();
+
+        let yaml = fs::read_to_string(workspace.join("tests/data/basic.yml")).unwrap();
+        let bib = from_yaml_str(&yaml).unwrap();
+        let en_locale = [en_locale.into()];
+
+        for style_thing in fs::read_dir(workspace.join("styles/")).unwrap().take(100) {
+            let thing = style_thing.unwrap();
+            if thing.file_type().unwrap().is_dir() {
+                continue;
+            }
+
+            let path = thing.path();
+            let extension = path.extension();
+            if let Some(extension) = extension {
+                if extension.to_str() != Some("csl") {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+
+            println!("testing {path:?}");
+            let style = fs::read_to_string(path).unwrap();
+            let style = IndependentStyle::from_xml(&style).unwrap();
+            let mut driver = BibliographyDriver::new();
+
+            for n in (0..bib.len()).step_by(2) {
+                let items = vec![
+                    CitationItem::with_entry(bib.nth(n).unwrap()),
+                    CitationItem::with_entry(bib.nth(n + 1).unwrap()),
+                ];
+                driver.citation(CitationRequest::new(
+                    items, &style, None, &en_locale, None,
+                ));
+            }
+
+            driver.finish(BibliographyRequest::new(&style, None, &en_locale));
+
+            // for cite in finished.citations {
+            //     println!("{}", cite.citation.to_string(BufWriteFormat::Plain))
+            // }
+
+            // if let Some(bib) = finished.bibliography {
+            //     for (prefix, item) in bib.items {
+            //         if let Some(prefix) = prefix {
+            //             print!("{} ", prefix.to_string(BufWriteFormat::Plain))
+            //         }
+            //         println!("{}", item.to_string(BufWriteFormat::Html))
+            //     }
+            // }
+        }
+    }
+
+    #[test]
+    fn link_behaviour() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="DOI" prefix="doi:"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <text variable="DOI" prefix="doi:"/>
+                        <text variable="URL" prefix=" &lt;" suffix="&gt;"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            linked:
+                type: Article
+                title: Linked
+                url: https://example.com/
+                serial-number:
+                    doi: 10.1000/xyz
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("linked").unwrap();
+
+        let render = |links: LinkBehaviour| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(
+                CitationRequest::from_items(
+                    vec![CitationItem::with_entry(entry)],
+                    &style,
+                    &[],
+                )
+                .with_link_behaviour(links),
+            );
+            let finished = driver.finish(
+                BibliographyRequest::new(&style, None, &[]).with_link_behaviour(links),
+            );
+
+            let mut cite = String::new();
+            finished.citations[0]
+                .citation
+                .write_buf(&mut cite, BufWriteFormat::Html)
+                .unwrap();
+            let mut item = String::new();
+            finished.bibliography.unwrap().items[0]
+                .content
+                .write_buf(&mut item, BufWriteFormat::Html)
+                .unwrap();
+            (cite, item)
+        };
+
+        let (cite, item) = render(LinkBehaviour::UrlsAndIdentifiers);
+        assert!(cite.contains(r#"<a href="https://doi.org/10.1000/xyz">"#));
+        assert!(item.contains(r#"<a href="https://doi.org/10.1000/xyz">"#));
+        assert!(item.contains(r#"<a href="https://example.com/">"#));
+
+        let (cite, item) = render(LinkBehaviour::IdentifiersOnly);
+        assert!(cite.contains(r#"<a href="https://doi.org/10.1000/xyz">"#));
+        assert!(item.contains(r#"<a href="https://doi.org/10.1000/xyz">"#));
+        assert!(!item.contains(r#"<a href="https://example.com/">"#));
+        assert!(item.contains("<https://example.com/>"));
+
+        let (cite, item) = render(LinkBehaviour::Never);
+        assert_eq!(cite, "doi:10.1000/xyz");
+        assert!(!item.contains("<a "));
+        assert!(item.starts_with("doi:10.1000/xyz"));
+    }
+
+    #[test]
+    fn annotated_bibliography() {
+        let style = fs::read_to_string("tests/data/annotated.csl").unwrap();
+        let style = IndependentStyle::from_xml(&style).unwrap();
+        let bib = from_yaml_str(
+            r#"
+            knuth:
+                type: Thesis
+                title: Analysis of Algorithms
+                author: Knuth, Donald
+                genre: PhD dissertation
+                abstract: A study of the running time of algorithms.
+                annote: Foundational for the later volumes.
+            "#,
+        )
+        .unwrap();
+
+        let rendered = cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+        let item = rendered.bibliography.unwrap().items[0].content.to_string();
+        assert!(item.contains("PhD dissertation"), "{item}");
+        assert!(item.contains("A study of the running time of algorithms."), "{item}");
+        assert!(item.contains("Foundational for the later volumes."), "{item}");
+        assert!(
+            item.find("Analysis of Algorithms").unwrap() < item.find("A study").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn original_date() {
+        let library = crate::io::from_biblatex_str(
+            r#"@book{james2010,
+            author = {James, William},
+            title = {The Principles of Psychology},
+            date = {2010},
+            publisher = {Cosimo},
+            origdate = {1890},
+            }"#,
+        )
+        .unwrap();
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let finished =
+            cite_each(library.iter(), BibliographyRequest::new(&apa, None, &locales));
+        let mut citation = String::new();
+        finished.citations[0]
+            .citation
+            .write_buf(&mut citation, BufWriteFormat::Plain)
+            .unwrap();
+        assert!(citation.contains("1890") && citation.contains("2010"), "{citation}");
+
+        let item = finished.bibliography.unwrap().items[0].content.to_string();
+        assert!(item.contains("(2010)"), "{item}");
+        assert!(item.contains("(Original work published 1890)"), "{item}");
+    }
+
+    #[test]
+    fn serial_number_variables() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout delimiter="|">
+                        <text variable="ISBN"/>
+                        <text variable="ISSN"/>
+                        <text variable="number"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            preprint:
+                type: Article
+                title: Preprint
+                serial-number:
+                    arxiv: "2104.01234"
+                parent:
+                    type: Periodical
+                    title: Journal of Tests
+                    serial-number:
+                        ISSN: 1234-5678
+            report:
+                type: Report
+                title: Report
+                serial-number: 1701
+            chapter:
+                type: Chapter
+                title: Chapter
+                parent:
+                    type: Book
+                    title: Book
+                    serial-number:
+                        isbn: 978-3-16-148410-0
+            "#,
+        )
+        .unwrap();
+
+        let render = |links: LinkBehaviour| {
+            let finished = cite_each(
+                bib.iter(),
+                BibliographyRequest::new(&style, None, &[]).with_link_behaviour(links),
+            );
+            finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| {
+                    let mut buf = String::new();
+                    item.content.write_buf(&mut buf, BufWriteFormat::Html).unwrap();
+                    (item.key.clone(), buf)
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        let items = render(LinkBehaviour::IdentifiersOnly);
+        assert_eq!(
+            items["preprint"],
+            r#"1234-5678|<a href="https://arxiv.org/abs/2104.01234">arXiv:2104.01234</a>"#
+        );
+        assert_eq!(items["report"], "1701");
+        assert_eq!(items["chapter"], "978-3-16-148410-0");
+
+        let items = render(LinkBehaviour::Never);
+        assert_eq!(items["preprint"], "1234-5678|arXiv:2104.01234");
+    }
+
+    #[test]
+    fn suppress_url_with_doi() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout delimiter=" ">
+                        <text variable="DOI"/>
+                        <choose>
+                            <if variable="URL">
+                                <text value="has URL"/>
+                            </if>
+                            <else>
+                                <text value="no URL"/>
+                            </else>
+                        </choose>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            both:
+                type: Article
+                title: Both
+                url: https://example.com/
+                serial-number:
+                    doi: 10.1000/xyz
+            url-only:
+                type: Web
+                title: URL only
+                url: https://example.com/
+            "#,
+        )
+        .unwrap();
+
+        let render = |suppress: bool| {
+            cite_each(
+                bib.iter(),
+                BibliographyRequest::new(&style, None, &[])
+                    .with_suppress_url_with_doi(suppress),
+            )
+            .bibliography
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|i| format!("{:#}", i.content))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(render(false), ["10.1000/xyz has URL", "has URL"]);
+        assert_eq!(render(true), ["10.1000/xyz no URL", "has URL"]);
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn suppress_url_with_doi_apa() {
+        let bib = from_yaml_str(
+            r#"
+            both:
+                type: Article
+                title: Both identifiers
+                author: Doe, Jane
+                date: 2020
+                url:
+                    value: https://example.com/article
+                    date: 2021-01-01
+                serial-number:
+                    doi: 10.1000/xyz
+                parent:
+                    type: Periodical
+                    title: Journal of Tests
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+
+        let finished = cite_each(
+            [bib.get("both").unwrap()],
+            BibliographyRequest::new(&apa, None, &[]).with_suppress_url_with_doi(true),
+        );
+        let item = format!("{:#}", finished.bibliography.unwrap().items[0].content);
+
+        assert!(item.contains("https://doi.org/10.1000/xyz"));
+        assert!(!item.contains("example.com"));
+        assert!(!item.contains("Retrieved"));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn categorized_bibliography() {
+        let bib = from_yaml_str(
+            r#"
+            roe:
+                type: Book
+                title: Secondary Book
+                author: Roe, Rick
+                date: 2019
+            first:
+                type: Manuscript
+                title: First Manuscript
+                author: Doe, Jane
+                date: 2020
+            second:
+                type: Book
+                title: Second Book
+                author: Doe, Jane
+                date: 2020
+            misc:
+                type: Misc
+                title: Uncategorized
+                author: Able, Anne
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let finished =
+            cite_each(bib.iter(), BibliographyRequest::new(&style, None, &locales));
+        let bibliography = finished.bibliography.unwrap();
+
+        let categories = [
+            BibliographyCategory::new("primary").with_heading("Primary Sources"),
+            BibliographyCategory::new("secondary").with_heading("Secondary Literature"),
+            BibliographyCategory::new("empty"),
+        ];
+        let categorized = bibliography.categorize(&categories, |key| {
+            match bib.get(key)?.entry_type() {
+                crate::types::EntryType::Manuscript => Some("primary"),
+                crate::types::EntryType::Book => Some("secondary"),
+                _ => None,
+            }
+        });
+
+        let sections: Vec<_> = categorized
+            .iter()
+            .map(|category| {
+                let items: Vec<_> =
+                    category.items.iter().map(|item| item.content.to_string()).collect();
+                (category.category.heading.as_deref(), items)
+            })
+            .collect();
+
+        // The year suffixes are assigned across the categories.
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].0, Some("Primary Sources"));
+        assert_eq!(sections[0].1.len(), 1);
+        assert!(sections[0].1[0].contains("(2020a)"));
+        assert!(sections[0].1[0].contains("First Manuscript"));
+        assert_eq!(sections[1].0, Some("Secondary Literature"));
+        assert_eq!(sections[1].1.len(), 2);
+        assert!(sections[1].1[0].contains("(2020b)"));
+        assert!(sections[1].1[0].contains("Second Book"));
+        assert!(sections[1].1[1].starts_with("Roe"));
+        assert_eq!(sections[2], (None, vec![]));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn unicode_name_disambiguation() {
+        let bib = from_yaml_str(
+            "
+            precomposed:
+                type: Book
+                title: First Book
+                author: M\u{fc}ller, Hans
+                date: 2020
+            decomposed:
+                type: Book
+                title: Second Book
+                author: Mu\u{308}ller, Hans
+                date: 2020
+            transliterated:
+                type: Book
+                title: Third Book
+                author: Muller, Hans
+                date: 2021
+            other:
+                type: Book
+                title: Fourth Book
+                author: M\u{fc}ller, Hans
+                date: 2021
+            ",
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let cite = |keys: &[&str], insensitive: bool| {
+            let mut driver = BibliographyDriver::new();
+            for key in keys {
+                driver.citation(CitationRequest::from_items(
+                    vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                    &style,
+                    &locales,
+                ));
+            }
+            let finished = driver.finish(
+                BibliographyRequest::new(&style, None, &locales)
+                    .with_diacritic_insensitive_grouping(insensitive),
+            );
+            finished
+                .citations
+                .iter()
+                .map(|c| format!("{:#}", c.citation))
+                .collect::<Vec<_>>()
+        };
+
+        // Precomposed and decomposed names are always the same.
+        let citations = cite(&["precomposed", "decomposed"], false);
+        assert!(citations[0].contains("2020a"), "{citations:?}");
+        assert!(citations[1].contains("2020b"), "{citations:?}");
+
+        // Names that differ in their diacritics are only the same on request,
+        // but are still rendered as written.
+        let citations = cite(&["transliterated", "other"], false);
+        assert!(citations.iter().all(|c| c.contains("2021)")), "{citations:?}");
+        let citations = cite(&["transliterated", "other"], true);
+        assert!(citations[0].contains("Muller") && citations[0].contains("2021a"));
+        assert!(citations[1].contains("M\u{fc}ller") && citations[1].contains("2021b"));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn name_cap() {
+        let names = [
+            "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
+            "Ten", "Eleven", "Twelve",
+        ];
+        let authors: Vec<_> = names
+            .iter()
+            .zip('A'..)
+            .map(|(family, given)| format!("\"{family}, {given}.\""))
+            .collect();
+        let bib = from_yaml_str(&format!(
+            r#"
+            paper:
+                type: Article
+                title: A Paper with Many Authors
+                author: [{}]
+                date: 2020
+            "#,
+            authors.join(", ")
+        ))
+        .unwrap();
+        let entry = bib.get("paper").unwrap();
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let render = |citation_cap: Option<NameCap>,
+                      bibliography_cap: Option<NameCap>| {
+            let mut request = CitationRequest::from_items(
+                vec![CitationItem::with_entry(entry)],
+                &style,
+                &locales,
+            );
+            request.name_cap = citation_cap;
+            let mut driver = BibliographyDriver::new();
+            driver.citation(request);
+
+            let mut request = BibliographyRequest::new(&style, None, &locales);
+            request.name_cap = bibliography_cap;
+            let finished = driver.finish(request);
+            (
+                finished.citations[0].citation.to_string(),
+                finished.bibliography.unwrap().items[0].content.to_string(),
+            )
+        };
+
+        let (citation, item) = render(None, None);
+        assert!(citation.starts_with("(One et al., 2020"));
+        assert!(item.contains("Eleven, K., & Twelve, L."));
+
+        // The cap only applies where it is requested.
+        let (capped_citation, item) = render(None, Some(NameCap::new(1)));
+        assert_eq!(capped_citation, citation);
+        assert!(item.starts_with("One, A."));
+        assert!(item.contains("et al."));
+        assert!(!item.contains("Two"));
+        assert!(!item.contains("Twelve"));
+
+        let (_, item) = render(None, Some(NameCap::new(3)));
+        assert!(item.contains("Three, C."));
+        assert!(item.contains("et al."));
+        assert!(!item.contains("Four"));
+        assert!(!item.contains("Twelve"));
+
+        let (_, item) =
+            render(None, Some(NameCap::new(3).with_overflow(NameOverflow::Ellipsis)));
+        assert!(item.contains("Three, C."));
+        assert!(item.contains("… Twelve, L."));
+        assert!(!item.contains("Four"));
+
+        // The style's et-al settings still apply if they are stricter.
+        let (capped_citation, _) = render(Some(NameCap::new(3)), None);
+        assert_eq!(capped_citation, citation);
+        let (_, item) = render(None, Some(NameCap::new(20)));
+        assert!(item.contains("Eleven, K., & Twelve, L."));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn et_al_use_last() {
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let render = |count: usize| {
+            let authors: Vec<_> = (1..=count)
+                .zip('A'..)
+                .map(|(i, given)| format!("\"Author{i}, {given}.\""))
+                .collect();
+            let bib = from_yaml_str(&format!(
+                r#"
+                paper:
+                    type: Article
+                    title: A Paper with Many Authors
+                    author: [{}]
+                    date: 2020
+                "#,
+                authors.join(", ")
+            ))
+            .unwrap();
+
+            let finished = cite_each(
+                [bib.get("paper").unwrap()],
+                BibliographyRequest::new(&style, None, &locales),
+            );
+            finished.bibliography.unwrap().items[0].content.to_string()
+        };
+
+        // APA lists the first 19 authors, an ellipsis, and the last author.
+        let item = render(21);
+        assert!(item.starts_with("Author1, A., Author2, B., "));
+        assert!(item.contains("Author19, S., … Author21, U. (2020)"));
+        assert!(!item.contains("Author20,"));
+        assert!(!item.contains("et al."));
+        assert!(!item.contains('&'));
+
+        // Up to 20 authors are listed in full.
+        let item = render(20);
+        assert!(item.contains("Author19, S., & Author20, T. (2020)"));
+        assert!(!item.contains('…'));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn year_and_author_purposes() {
+        let bib = from_yaml_str(
+            r#"
+            first:
+                type: Book
+                title: First Book
+                author: Doe, Jane
+                date: 2020
+            second:
+                type: Book
+                title: Second Book
+                author: Doe, Jane
+                date: 2020
+            "#,
+        )
+        .unwrap();
+        let first = bib.get("first").unwrap();
+        let second = bib.get("second").unwrap();
+        let locales = archive::locales();
+
+        let purposes = |style: archive::ArchivedStyle| {
+            let citationberg::Style::Independent(style) = style.get() else {
+                unreachable!()
+            };
+
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_entry(first), CitationItem::with_entry(second)],
+                &style,
+                &locales,
+            ));
+            for (entry, purpose) in [
+                (first, CitePurpose::Year),
+                (second, CitePurpose::Year),
+                (first, CitePurpose::Author),
+            ] {
+                driver.citation(CitationRequest::from_items(
+                    vec![CitationItem::new(
+                        entry,
+                        Some(SpecificLocator(Locator::Page, LocatorPayload::Str("5"))),
+                        None,
+                        false,
+                        Some(purpose),
+                    )],
+                    &style,
+                    &locales,
+                ));
+            }
+
+            let finished =
+                driver.finish(BibliographyRequest::new(&style, None, &locales));
+            finished.citations[1..]
+                .iter()
+                .map(|c| format!("{:#}", c.citation))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            purposes(archive::ArchivedStyle::AmericanPsychologicalAssociation),
+            ["2020a", "2020b", "Doe"]
+        );
+        assert_eq!(
+            purposes(archive::ArchivedStyle::ChicagoAuthorDate),
+            ["2020a", "2020b", "Doe"]
+        );
+
+        let ieee = purposes(
+            archive::ArchivedStyle::InstituteOfElectricalAndElectronicsEngineers,
+        );
+        assert_eq!(ieee[..2], ["", ""]);
+    }
+
+    #[test]
+    fn author_purpose_in_nested_macro() {
+        let style = test_style(
+            r#"<macro name="author-short">
+                    <group>
+                        <names variable="author"><name form="short"/></names>
+                    </group>
+                </macro>
+                <macro name="author">
+                    <text macro="author-short"/>
+                </macro>
+                <citation>
+                    <layout prefix="(" suffix=")">
+                        <group delimiter=" ">
+                            <text value="see"/>
+                            <text macro="author"/>
+                            <date variable="issued" prefix="[" suffix="]">
+                                <date-part name="year"/>
+                            </date>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title: A Book
+                author: Doe, Jane
+                date: 2001
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("doe").unwrap();
+
+        let cite = |purpose| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::new(entry, None, None, false, purpose)],
+                &style,
+                &[],
+            ));
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(cite(None), "(see Doe [2001])");
+        assert_eq!(cite(Some(CitePurpose::Author)), "Doe");
+        assert_eq!(cite(Some(CitePurpose::Year)), "[2001]");
+    }
+
+    #[test]
+    fn intext_layout() {
+        let src = r#"<style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" class="in-text">
+            <info>
+                <title>Test</title>
+                <id>test</id>
+                <updated>2024-01-01T00:00:00+00:00</updated>
+            </info>
+            <citation>
+                <layout prefix="(" suffix=")" delimiter="; ">
+                    <group delimiter=", ">
+                        <names variable="author"><name form="short"/></names>
+                        <date variable="issued"><date-part name="year"/></date>
+                    </group>
+                </layout>
+            </citation>
+            <intext>
+                <layout>
+                    <group delimiter=" ">
+                        <names variable="author"><name form="short"/></names>
+                        <date variable="issued" prefix="(" suffix=")">
+                            <date-part name="year"/>
+                        </date>
+                    </group>
+                </layout>
+            </intext>
+        </style>"#;
+        let style = IndependentStyle::from_xml(src).unwrap();
+        let intext = IntextLayout::from_xml(src).unwrap().unwrap();
+
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title: A Book
+                author: Doe, Jane
+                date: 2001
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("doe").unwrap();
+
+        let cite = |purpose, intext| {
+            let mut driver = BibliographyDriver::new();
+            let mut request = CitationRequest::from_items(
+                vec![CitationItem::new(entry, None, None, false, purpose)],
+                &style,
+                &[],
+            );
+            request.intext = intext;
+            driver.citation(request);
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(cite(None, Some(&intext)), "(Doe, 2001)");
+        assert_eq!(cite(Some(CitePurpose::Author), Some(&intext)), "Doe (2001)");
+        assert_eq!(cite(Some(CitePurpose::Prose), Some(&intext)), "Doe (2001)");
+        assert_eq!(cite(Some(CitePurpose::Author), None), "Doe");
+        assert_eq!(cite(Some(CitePurpose::Prose), None), "Doe (2001)");
+    }
+
+    #[test]
+    fn intext_layout_missing() {
+        let src = r#"<style xmlns="http://purl.org/net/xbiblio/csl" version="1.0" class="in-text">
+            <info><title>Test</title><id>test</id></info>
+            <citation><layout><text variable="title"/></layout></citation>
+        </style>"#;
+        assert_eq!(IntextLayout::from_xml(src).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn accessed_date_apa() {
+        let bib = from_yaml_str(
+            r#"
+            page:
+                type: Web
+                title: Changing page
+                author: Doe, Jane
+                url:
+                    value: https://example.com/page
+                    date: 2021-05-03
+            identified:
+                type: Web
+                title: Identified page
+                author: Roe, Rick
+                url:
+                    value: https://example.com/identified
+                    date: 2021-05-03
+                serial-number:
+                    doi: 10.1000/abc
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let render = |suppress: bool| {
+            cite_each(
+                bib.iter(),
+                BibliographyRequest::new(&apa, None, &locales)
+                    .with_suppress_url_with_doi(suppress),
+            )
+            .bibliography
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|i| format!("{:#}", i.content))
+            .collect::<Vec<_>>()
+        };
+
+        let items = render(false);
+        assert!(
+            items[0].contains("Retrieved May 3, 2021, from https://example.com/page"),
+            "{}",
+            items[0]
+        );
+
+        // Only the entry with a DOI loses its access date.
+        let items = render(true);
+        assert!(items[0].contains("Retrieved May 3, 2021"), "{}", items[0]);
+        assert!(!items[1].contains("Retrieved"), "{}", items[1]);
+        assert!(items[1].contains("https://doi.org/10.1000/abc"), "{}", items[1]);
+    }
+
+    #[test]
+    fn event_variables() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout delimiter="|">
+                        <text variable="event-title"/>
+                        <text variable="event"/>
+                        <text variable="event-place"/>
+                        <date variable="event-date" form="numeric" date-parts="year"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            talk:
+                type: Article
+                title: A Talk
+                parent:
+                    - type: Misc
+                      title: Lecture Series
+                    - type: Conference
+                      title: 12th Conference on X
+                      location: Berlin
+                      date: 2019-05
+            "#,
+        )
+        .unwrap();
+
+        let finished = cite_each(
+            [bib.get("talk").unwrap()],
+            BibliographyRequest::new(&style, None, &[]),
+        );
+        let item = finished.bibliography.unwrap().items[0].content.to_string();
+        assert_eq!(item, "12th Conference on X|12th Conference on X|Berlin|2019");
+    }
+
+    #[test]
+    fn demote_non_dropping_particle() {
+        let bib = from_yaml_str(
+            r#"
+            mozart:
+                type: Book
+                author: Mozart, Wolfgang Amadeus
+            beethoven:
+                type: Book
+                author: van Beethoven, Ludwig
+            bach:
+                type: Book
+                author: Bach, Johann Sebastian
+            "#,
+        )
+        .unwrap();
+
+        let cases = [
+            ("never", ["Bach, J. S.", "Mozart, W. A.", "van Beethoven, L."]),
+            ("sort-only", ["Bach, J. S.", "van Beethoven, L.", "Mozart, W. A."]),
+            ("display-and-sort", ["Bach, J. S.", "Beethoven, L. van", "Mozart, W. A."]),
+        ];
+
+        for (mode, expected) in cases {
+            let style = test_style_with(
+                &format!(r#"class="in-text" demote-non-dropping-particle="{mode}""#),
+                r#"<citation>
+                        <layout><text variable="title"/></layout>
+                    </citation>
+                    <bibliography>
+                        <sort><key variable="author"/></sort>
+                        <layout>
+                            <names variable="author">
+                                <name name-as-sort-order="all" initialize-with=". "/>
+                            </names>
+                        </layout>
+                    </bibliography>"#,
+            );
+
+            let finished =
+                cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+            let items: Vec<_> = finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.content.to_string())
+                .collect();
+            assert_eq!(items, expected, "{mode}");
+        }
+    }
+
+    #[test]
+    fn editor_translator() {
+        let style = test_style(
+            r#"<locale>
+                    <terms>
+                        <term name="editor" form="short">
+                            <single>ed.</single>
+                            <multiple>eds.</multiple>
+                        </term>
+                        <term name="translator" form="short">
+                            <single>trans.</single>
+                            <multiple>trans.</multiple>
+                        </term>
+                        <term name="editortranslator" form="short">
+                            <single>ed. &amp; trans.</single>
+                            <multiple>eds. &amp; trans.</multiple>
+                        </term>
+                    </terms>
+                </locale>
+                <citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <names variable="editor translator" delimiter="; ">
+                            <name initialize-with=". " and="symbol"/>
+                            <label form="short" prefix=" (" suffix=")"/>
+                        </names>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            same:
+                type: Book
+                editor: Doe, Jane
+                affiliated:
+                    - role: translator
+                      names: Doe, Jane
+            same-plural:
+                type: Book
+                editor: ["Doe, Jane", "Roe, Richard"]
+                affiliated:
+                    - role: translator
+                      names: ["Doe, Jane", "Roe, Richard"]
+            overlapping:
+                type: Book
+                editor: ["Doe, Jane", "Roe, Richard"]
+                affiliated:
+                    - role: translator
+                      names: Doe, Jane
+            "#,
+        )
+        .unwrap();
+
+        let finished = cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+        let items: Vec<_> = finished
+            .bibliography
+            .unwrap()
+            .items
+            .iter()
+            .map(|item| item.content.to_string())
+            .collect();
+        assert_eq!(
+            items,
+            [
+                "J. Doe (ed. & trans.)",
+                "J. Doe & R. Roe (eds. & trans.)",
+                "J. Doe & R. Roe (eds.); J. Doe (trans.)",
+            ]
+        );
+    }
+
+    #[test]
+    fn names_groups_et_al() {
+        let bib = from_yaml_str(
+            r#"
+            book:
+                type: Book
+                editor: ["Doe, Jane", "Roe, Richard", "Poe, Paula"]
+                affiliated:
+                    - role: translator
+                      names: ["Smith, Anne", "Jones, Bob"]
+            "#,
+        )
+        .unwrap();
+
+        let render = |name: &str| {
+            let style = test_style(&format!(
+                r#"<locale>
+                        <terms>
+                            <term name="et-al">et al.</term>
+                            <term name="editor" form="short">
+                                <single>ed.</single>
+                                <multiple>eds.</multiple>
+                            </term>
+                            <term name="translator" form="short">
+                                <single>trans.</single>
+                                <multiple>trans.</multiple>
+                            </term>
+                        </terms>
+                    </locale>
+                    <citation>
+                        <layout>
+                            <names variable="editor translator" delimiter="; ">
+                                {name}
+                                <label form="short" prefix=" (" suffix=")"/>
+                            </names>
+                        </layout>
+                    </citation>"#
+            ));
+
+            standalone_citation(CitationRequest::from_items(
+                vec![CitationItem::with_entry(bib.get("book").unwrap())],
+                &style,
+                &[],
+            ))
+            .to_string()
+        };
+
+        // Each variable is truncated and labeled on its own.
+        assert_eq!(
+            render(
+                r#"<name initialize-with=". " and="symbol" et-al-min="3" et-al-use-first="1"/>"#
+            ),
+            "J. Doe et al. (eds.); A. Smith & B. Jones (trans.)"
+        );
+        assert_eq!(
+            render(r#"<name form="count" et-al-min="3" et-al-use-first="1"/>"#),
+            "3"
+        );
+        assert_eq!(render(r#"<name form="count"/>"#), "5");
+    }
+
+    #[test]
+    fn name_options_inheritance() {
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title: Alpha
+                author: ["Doe, Jane", "Roe, Rick"]
+                editor: Poe, Paula
+            "#,
+        )
+        .unwrap();
+
+        let render = |style_attrs: &str,
+                      citation_attrs: &str,
+                      bib_attrs: &str,
+                      name_attrs: &str| {
+            let style = test_style_with(
+                &format!(r#"class="in-text" {style_attrs}"#),
+                &format!(
+                    r#"<locale>
+                        <terms>
+                            <term name="and">and</term>
+                            <term name="et-al">et al.</term>
+                        </terms>
+                    </locale>
+                    <citation {citation_attrs}>
+                        <layout>
+                            <names variable="author editor">
+                                <name {name_attrs}/>
+                            </names>
+                        </layout>
+                    </citation>
+                    <bibliography {bib_attrs}>
+                        <layout>
+                            <names variable="author editor">
+                                <name {name_attrs}/>
+                            </names>
+                        </layout>
+                    </bibliography>"#
+                ),
+            );
+
+            let finished = cite_each(
+                [bib.get("doe").unwrap()],
+                BibliographyRequest::new(&style, None, &[]),
+            );
+            (
+                finished.citations[0].citation.to_string(),
+                finished.bibliography.unwrap().items[0].content.to_string(),
+            )
+        };
+
+        // Options of `cs:style` apply everywhere.
+        let (citation, item) = render(r#"and="symbol" names-delimiter="; ""#, "", "", "");
+        assert_eq!(citation, "Jane Doe & Rick Roe; Paula Poe");
+        assert_eq!(item, "Jane Doe & Rick Roe; Paula Poe");
+
+        // Options of `cs:citation` only apply to citations.
+        let (citation, item) =
+            render(r#"names-delimiter="; ""#, r#"and="symbol""#, "", "");
+        assert_eq!(citation, "Jane Doe & Rick Roe; Paula Poe");
+        assert_eq!(item, "Jane Doe, Rick Roe; Paula Poe");
+
+        // Options of `cs:bibliography` only apply to the bibliography and take
+        // precedence over those of `cs:style`.
+        let (citation, item) = render(
+            r#"names-delimiter="; ""#,
+            "",
+            r#"and="symbol" names-delimiter=" / ""#,
+            "",
+        );
+        assert_eq!(citation, "Jane Doe, Rick Roe; Paula Poe");
+        assert_eq!(item, "Jane Doe & Rick Roe / Paula Poe");
+
+        // Options of `cs:name` take precedence over all others.
+        let (citation, item) = render(
+            r#"and="symbol" initialize-with=". " name-form="short" names-delimiter="; ""#,
+            r#"name-form="long""#,
+            "",
+            r#"and="text""#,
+        );
+        assert_eq!(citation, "J. Doe and R. Roe; P. Poe");
+        assert_eq!(item, "Doe and Roe; Poe");
+
+        let (citation, item) = render(
+            r#"et-al-min="2" et-al-use-first="1" names-delimiter="; ""#,
+            "",
+            r#"et-al-min="3""#,
+            "",
+        );
+        assert_eq!(citation, "Jane Doe et al.; Paula Poe");
+        assert_eq!(item, "Jane Doe, Rick Roe; Paula Poe");
+    }
+
+    #[test]
+    fn sort_macro_inherits_name_options() {
+        let style = test_style(
+            r#"<macro name="author">
+                    <names variable="author">
+                        <name/>
+                    </names>
+                </macro>
+                <citation>
+                    <layout>
+                        <text variable="title"/>
+                    </layout>
+                </citation>
+                <bibliography et-al-min="2" et-al-use-first="1">
+                    <sort>
+                        <key macro="author"/>
+                        <key variable="title"/>
+                    </sort>
+                    <layout>
+                        <text variable="title"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            alpha:
+                type: Book
+                title: Alpha
+                author: ["Doe, Jane", "Zed, Zoe"]
+            beta:
+                type: Book
+                title: Beta
+                author: ["Doe, Jane", "Able, Adam"]
+            "#,
+        )
+        .unwrap();
+
+        let entries: Vec<_> = bib.iter().collect();
+        let mut driver = BibliographyDriver::new();
+        for entry in entries.into_iter().rev() {
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_entry(entry)],
+                &style,
+                &[],
+            ));
+        }
+
+        // Both authors render as "Jane Doe" with the et-al options of the
+        // bibliography, so the title decides.
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        let keys: Vec<_> = finished
+            .bibliography
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|i| i.key)
+            .collect();
+        assert_eq!(keys, ["alpha", "beta"]);
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn role_labels() {
+        let style = test_style(
+            r#"<locale>
+                    <terms>
+                        <term name="editor">
+                            <single>editor</single>
+                            <multiple>editors</multiple>
+                        </term>
+                        <term name="editor" form="verb">edited by</term>
+                        <term name="translator">
+                            <single>translator</single>
+                            <multiple>translators</multiple>
+                        </term>
+                        <term name="translator" form="short">
+                            <single>tr.</single>
+                            <multiple>trs.</multiple>
+                        </term>
+                    </terms>
+                </locale>
+                <citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <group delimiter="; ">
+                            <names variable="editor">
+                                <label form="verb-short" suffix=" "/>
+                                <name/>
+                            </names>
+                            <names variable="translator">
+                                <name/>
+                                <label form="symbol" prefix=", "/>
+                            </names>
+                            <names variable="editor">
+                                <name/>
+                                <label form="short" prefix=", "/>
+                            </names>
+                        </group>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            translated:
+                type: Book
+                title: Collected Letters
+                author: Smith, John
+                editor: Doe, Jane
+                affiliated:
+                    - role: translator
+                      names: ["Roe, Rick", "Poe, Paula"]
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("translated").unwrap();
+
+        let bibliography = |style: &IndependentStyle, locales: &[Locale]| {
+            let finished =
+                cite_each([entry], BibliographyRequest::new(style, None, locales));
+            finished.bibliography.unwrap().items[0].content.to_string()
+        };
+
+        assert_eq!(
+            bibliography(&style, &[]),
+            "edited by Jane Doe; Rick Roe, Paula Poe, trs.; Jane Doe, editor"
+        );
+
+        let chicago = archive::ArchivedStyle::ChicagoNotes.get();
+        let citationberg::Style::Independent(chicago) = chicago else { unreachable!() };
+        let item = bibliography(&chicago, &archive::locales());
+        assert!(item.contains("Edited by Jane Doe"), "{item}");
+    }
+
+    #[test]
+    fn group_suppression_through_macros() {
+        let style = test_style(
+            r#"<locale>
+                    <terms>
+                        <term name="retrieved">retrieved</term>
+                        <term name="from">from</term>
+                        <term name="online">online</term>
+                    </terms>
+                </locale>
+                <macro name="url">
+                    <text term="from" suffix=" "/>
+                    <text variable="URL"/>
+                </macro>
+                <macro name="access">
+                    <text macro="url"/>
+                </macro>
+                <citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <group delimiter=". ">
+                            <text variable="title"/>
+                            <group delimiter=" ">
+                                <text term="retrieved" text-case="capitalize-first"/>
+                                <text macro="access"/>
+                            </group>
+                            <group>
+                                <text term="online" text-case="capitalize-first"/>
+                            </group>
+                        </group>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            offline:
+                type: Web
+                title: No Link
+            linked:
+                type: Web
+                title: Some Page
+                url: https://example.com/page
+            "#,
+        )
+        .unwrap();
+
+        let bibliography = |key: &str| {
+            let entry = bib.get(key).unwrap();
+            let finished =
+                cite_each([entry], BibliographyRequest::new(&style, None, &[]));
+            finished.bibliography.unwrap().items[0].content.to_string()
+        };
+
+        assert_eq!(bibliography("offline"), "No Link. Online");
+        assert_eq!(
+            bibliography("linked"),
+            "Some Page. Retrieved from https://example.com/page. Online"
+        );
+    }
+
+    #[test]
+    fn delimiter_after_empty_child() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <group delimiter=", ">
+                            <text variable="title"/>
+                            <date variable="issued" prefix="(" suffix=")">
+                                <date-part name="month" form="numeric"/>
+                            </date>
+                            <text variable="publisher"/>
+                        </group>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            year-only:
+                type: Book
+                title: A Book
+                date: 2020
+                publisher: Publisher
+            full-date:
+                type: Book
+                title: A Book
+                date: 2020-05
+                publisher: Publisher
+            "#,
+        )
+        .unwrap();
+
+        let bibliography = |key: &str| {
+            let entry = bib.get(key).unwrap();
+            let finished =
+                cite_each([entry], BibliographyRequest::new(&style, None, &[]));
+            finished.bibliography.unwrap().items[0].content.to_string()
+        };
+
+        assert_eq!(bibliography("year-only"), "A Book, Publisher");
+        assert_eq!(bibliography("full-date"), "A Book, (5), Publisher");
+    }
+
+    #[test]
+    fn choose_condition_pairs() {
+        // Each condition kind with an attribute that holds and one that does
+        // not hold for the cite below.
+        let conditions = [
+            ("disambiguate", r#"disambiguate="false""#, r#"disambiguate="true""#),
+            ("is-numeric", r#"is-numeric="edition""#, r#"is-numeric="title""#),
+            (
+                "is-uncertain-date",
+                r#"is-uncertain-date="issued""#,
+                r#"is-uncertain-date="accessed""#,
+            ),
+            ("locator", r#"locator="page""#, r#"locator="chapter""#),
+            ("position", r#"position="first""#, r#"position="subsequent""#),
+            ("type", r#"type="book""#, r#"type="article-journal""#),
+            ("variable", r#"variable="locator""#, r#"variable="URL""#),
+        ];
+
+        let bib = from_yaml_str(
+            r#"
+            book:
+                type: Book
+                title: A Book
+                edition: 2
+                date: ~2020
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("book").unwrap();
+
+        for mode in ["any", "all", "none"] {
+            let mut layout = String::new();
+            let mut expected = String::new();
+            let mut cases = vec![];
+
+            for (i, (a, a_true, a_false)) in conditions.iter().enumerate() {
+                for (b, b_true, b_false) in &conditions[i + 1..] {
+                    for (a_attr, a_holds) in [(a_true, true), (a_false, false)] {
+                        for (b_attr, b_holds) in [(b_true, true), (b_false, false)] {
+                            let taken = match mode {
+                                "any" => a_holds || b_holds,
+                                "all" => a_holds && b_holds,
+                                _ => !a_holds && !b_holds,
+                            };
+
+                            write!(
+                                layout,
+                                r#"<choose><if {a_attr} {b_attr} match="{mode}"><text value="1"/></if><else><text value="0"/></else></choose>"#,
+                            )
+                            .unwrap();
+                            expected.push(if taken { '1' } else { '0' });
+                            cases.push(format!("{a}={a_holds} {b}={b_holds}"));
+                        }
+                    }
+                }
+            }
+
+            let style = test_style_with(
+                r#"class="note""#,
+                &format!(
+                    r#"<citation>
+                        <layout>{layout}</layout>
+                    </citation>"#
+                ),
+            );
+
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_locator(
+                    entry,
+                    Some(SpecificLocator(Locator::Page, LocatorPayload::Str("5"))),
+                )],
+                &style,
+                &[],
+            ));
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            let mut actual = String::new();
+            finished.citations[0]
+                .citation
+                .write_buf(&mut actual, BufWriteFormat::Plain)
+                .unwrap();
+
+            assert_eq!(actual.len(), expected.len());
+            for ((case, a), e) in cases.iter().zip(actual.chars()).zip(expected.chars()) {
+                assert_eq!(a, e, "match=\"{mode}\" with {case}");
+            }
+        }
+    }
+
+    #[test]
+    fn note_context() {
+        let body = r#"<locale>
+                    <terms><term name="ibid">ibid.</term></terms>
+                </locale>
+                <citation>
+                    <layout delimiter="; ">
+                        <choose>
+                            <if position="ibid"><text term="ibid"/></if>
+                            <else>
+                                <group delimiter=", ">
+                                    <names variable="author"><name form="short"/></names>
+                                    <text variable="title"/>
+                                </group>
+                            </else>
+                        </choose>
+                    </layout>
+                </citation>"#;
+
+        let bib = from_yaml_str(
+            r#"
+            neumann:
+                type: Book
+                title: Computing Machinery
+                author: von Neumann, John
+            doe:
+                type: Book
+                title: Who Knows?
+                author: Doe, Jane
+            "#,
+        )
+        .unwrap();
+
+        let cite = |attrs: &str, cites: &[(&str, bool, bool)]| {
+            let style = test_style_with(attrs, body);
+            let mut driver = BibliographyDriver::new();
+            for (i, &(key, starts_note, ends_sentence)) in cites.iter().enumerate() {
+                driver.citation(
+                    CitationRequest::new(
+                        vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                        &style,
+                        None,
+                        &[],
+                        Some(i + 1),
+                    )
+                    .with_note_context(NoteContext { starts_note, ends_sentence }),
+                );
+            }
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            finished
+                .citations
+                .iter()
+                .map(|c| format!("{:#}", c.citation))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            cite(
+                r#"class="note""#,
+                &[
+                    ("neumann", true, true),
+                    ("neumann", true, true),
+                    ("neumann", false, false),
+                    ("doe", true, true),
+                    ("doe", false, true),
+                ]
+            ),
+            [
+                "von Neumann, Computing Machinery.",
+                "Ibid.",
+                "ibid.",
+                "Doe, Who Knows?",
+                "ibid.",
+            ]
+        );
+
+        assert_eq!(
+            cite(r#"class="in-text""#, &[("neumann", true, true)]),
+            ["von Neumann, Computing Machinery"]
+        );
+    }
+
+    #[test]
+    fn et_al_subsequent() {
+        let style = test_style_with(
+            r#"class="in-text" et-al-min="6" et-al-use-first="3" et-al-subsequent-min="3" et-al-subsequent-use-first="1""#,
+            r#"<locale>
+                    <terms>
+                        <term name="et-al">et al.</term>
+                    </terms>
+                </locale>
+                <citation>
+                    <layout>
+                        <names variable="author">
+                            <name form="short" delimiter=", "/>
+                        </names>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            paper:
+                type: Article
+                author: ["First, A.", "Second, B.", "Third, C.", "Fourth, D.", "Fifth, E."]
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("paper").unwrap();
+
+        let mut driver = BibliographyDriver::new();
+        for _ in 0..2 {
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_entry(entry)],
+                &style,
+                &[],
+            ));
+        }
+
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        let citations: Vec<_> = finished
+            .citations
+            .iter()
+            .map(|c| {
+                let mut s = String::new();
+                c.citation.write_buf(&mut s, BufWriteFormat::Plain).unwrap();
+                s
+            })
+            .collect();
+        assert_eq!(citations, ["First, Second, Third, Fourth, Fifth", "First et al."]);
+
+        let standalone = standalone_citation(CitationRequest::from_items(
+            vec![CitationItem::with_entry(entry)],
+            &style,
+            &[],
+        ));
+        let mut s = String::new();
+        standalone.write_buf(&mut s, BufWriteFormat::Plain).unwrap();
+        assert_eq!(s, "First, Second, Third, Fourth, Fifth");
+    }
+
+    /// Render the titles of a bibliography sorted with the given `cs:sort`
+    /// and `cs:macro` elements by a style with the given default locale.
+    fn sorted_titles(
+        bib: &Library,
+        sort: &str,
+        macros: &str,
+        default_locale: &str,
+    ) -> Vec<String> {
+        let style = test_style_with(
+            &format!(r#"class="in-text" default-locale="{default_locale}""#),
+            &format!(
+                r#"{macros}
+                <citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    {sort}
+                    <layout><text variable="title"/></layout>
+                </bibliography>"#
+            ),
+        );
+
+        cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]))
+            .bibliography
+            .unwrap()
+            .items
+            .iter()
+            .map(|item| item.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn standalone_bibliography() {
+        let library = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title: A Book
+                author: Doe, Jane
+                date: 2020
+            roe:
+                type: Book
+                title: Another Book
+                author: Doe, Jane
+                date: 2020
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+
+        let entry = library.get("roe").unwrap();
+        let item = standalone_bibliography_item(
+            entry,
+            BibliographyRequest::new(&apa, None, &locales),
+        )
+        .unwrap();
+        assert_eq!(item.key, "roe");
+        let s = item.content.to_string();
+        assert!(s.contains("(2020)") && s.contains("Another Book"), "{s}");
+
+        // No year suffix is added since the other entry is not considered.
+        let mut driver = BibliographyDriver::new();
+        driver.citation(CitationRequest::from_items(
+            library.iter().map(CitationItem::with_entry).collect(),
+            &apa,
+            &locales,
+        ));
+        let finished = driver.finish(BibliographyRequest::new(&apa, None, &locales));
+        let full = finished.bibliography.unwrap().items[1].content.to_string();
+        assert!(full.contains("(2020b)"), "{full}");
+
+        let ieee =
+            archive::ArchivedStyle::InstituteOfElectricalAndElectronicsEngineers.get();
+        let citationberg::Style::Independent(ieee) = ieee else { unreachable!() };
+        let item = standalone_bibliography_item(
+            entry,
+            BibliographyRequest::new(&ieee, None, &locales),
+        )
+        .unwrap();
+        assert_eq!(item.first_field.map(|f| format!("{f:#}")).as_deref(), Some("[1]"));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn missing_authors() {
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title: Known
+                author: Doe, Jane
+                date: 2020
+            untitled:
+                type: Book
+                date: 2021
+            unknown:
+                type: Book
+                title: Mystery
+                date: 2022
+            nothing:
+                type: Book
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+
+        let render = |substitute: &str| {
+            let style = test_style(&format!(
+                r#"<citation>
+                        <layout>
+                            <group delimiter=", ">
+                                <names variable="author">
+                                    <name form="short"/>
+                                    <substitute>{substitute}</substitute>
+                                </names>
+                                <date variable="issued"><date-part name="year"/></date>
+                            </group>
+                        </layout>
+                    </citation>
+                    <bibliography>
+                        <layout><text variable="title"/></layout>
+                    </bibliography>"#
+            ));
+
+            let finished =
+                cite_each(bib.iter(), BibliographyRequest::new(&style, None, &locales));
+            let citations = finished
+                .citations
+                .iter()
+                .map(|c| format!("{:#}", c.citation))
+                .collect::<Vec<_>>();
+            (citations, finished.diagnostics)
+        };
+
+        let (citations, diagnostics) =
+            render(r#"<names variable="editor"/><text variable="title"/>"#);
+        assert_eq!(citations, ["Doe, 2020", "2021", "Mystery, 2022", ""]);
+        assert_eq!(
+            diagnostics,
+            [
+                RenderDiagnostic::EmptyCitationItem {
+                    key: "nothing".into(),
+                    citation: 3
+                },
+                RenderDiagnostic::EmptyBibliographyItem { key: "untitled".into() },
+                RenderDiagnostic::EmptyBibliographyItem { key: "nothing".into() },
+            ]
+        );
+
+        let (citations, _) = render(r#"<text term="anonymous"/>"#);
+        assert_eq!(
+            citations,
+            ["Doe, 2020", "anonymous, 2021", "anonymous, 2022", "anonymous"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn deterministic_output() {
+        let library = from_yaml_str(
+            r#"
+            roe:
+                type: Book
+                title: Sorting Things Out
+                author: Roe, Richard
+                date: 2020
+            first:
+                type: Book
+                title: Notes on Sorting
+                author: Doe, Jane
+                date: 2020
+            second:
+                type: Book
+                title: Notes on Sorting
+                author: Doe, Jane
+                date: 2020
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+
+        let render = || {
+            let finished =
+                cite_each(library.iter(), BibliographyRequest::new(&apa, None, &locales));
+            let mut out = String::new();
+            for citation in &finished.citations {
+                writeln!(out, "{:#}", citation.citation).unwrap();
+            }
+            for item in finished.bibliography.unwrap().items {
+                writeln!(out, "{}: {:#}", item.key, item.content).unwrap();
+            }
+            out
+        };
+
+        let first = render();
+        let keys: Vec<_> =
+            first.lines().skip(3).filter_map(|l| l.split_once(':')).collect();
+        assert_eq!(
+            keys.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            ["first", "second", "roe"]
+        );
+        assert!(keys[0].1.contains("2020a") && keys[1].1.contains("2020b"), "{first}");
+
+        for _ in 0..100 {
+            assert_eq!(render(), first);
+        }
+    }
+
+    #[test]
+    fn render_warnings() {
+        let bib = from_yaml_str(
+            r#"
+            vague:
+                type: Book
+                volume: second
+                date: 2020
+            exact:
+                type: Book
+                volume: 2
+                date: 2020-05-04
+            "#,
+        )
+        .unwrap();
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <group delimiter=", ">
+                            <text term="no date"/>
+                            <number variable="volume"/>
+                            <date variable="issued">
+                                <date-part name="year"/>
+                                <date-part name="day" prefix="/"/>
+                            </date>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let render = |key: &str, locales: &[Locale]| {
+            let finished = cite_each(
+                [bib.get(key).unwrap()],
+                BibliographyRequest::new(&style, None, locales),
+            );
+            (format!("{:#}", finished.citations[0].citation), finished.diagnostics)
+        };
+
+        let (citation, diagnostics) = render("vague", &[]);
+        assert_eq!(citation, "second, 2020");
+        assert_eq!(
+            diagnostics,
+            [
+                RenderDiagnostic::MissingTerm {
+                    key: "vague".into(),
+                    term: Term::Other(OtherTerm::NoDate),
+                },
+                RenderDiagnostic::UnparsableNumeric {
+                    key: "vague".into(),
+                    variable: NumberOrPageVariable::Number(NumberVariable::Volume),
+                    value: "second".into(),
+                },
+                RenderDiagnostic::MissingDatePart {
+                    key: "vague".into(),
+                    variable: DateVariable::Issued,
+                    part: DatePartName::Day,
+                },
+                RenderDiagnostic::UnknownLocale {
+                    key: "vague".into(),
+                    locale: LocaleCode::en_us(),
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "entry `vague` has a non-numeric volume `second`"
+        );
+
+        #[cfg(feature = "archive")]
+        {
+            let (citation, diagnostics) = render("exact", &archive::locales());
+            assert_eq!(citation, "n.d., 2, 2020/4");
+            assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        }
+    }
+
+    #[test]
+    fn locale_fallback() {
+        let locale = |lang: &str, body: &str| -> Locale {
+            LocaleFile::from_xml(&format!(
+                r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="{lang}">
+                    {body}
+                </locale>"#
+            ))
+            .unwrap()
+            .into()
+        };
+
+        let locales = [
+            locale(
+                "en-US",
+                r#"<terms>
+                    <term name="no date">n.d.</term>
+                    <term name="edition">ed.</term>
+                    <term name="in">in</term>
+                    <term name="and">and</term>
+                </terms>
+                <date form="numeric" delimiter="/">
+                    <date-part name="month" form="numeric"/>
+                    <date-part name="day"/>
+                    <date-part name="year"/>
+                </date>"#,
+            ),
+            locale(
+                "de-DE",
+                r#"<terms>
+                    <term name="no date">o. J.</term>
+                    <term name="edition">Aufl.</term>
+                </terms>
+                <date form="numeric" delimiter=".">
+                    <date-part name="day"/>
+                    <date-part name="month" form="numeric"/>
+                    <date-part name="year"/>
+                </date>"#,
+            ),
+            locale("de-AT", r#"<terms><term name="no date">ohne Jahr</term></terms>"#),
+            locale("pt-PT", r#"<terms><term name="in">em</term></terms>"#),
+        ];
+
+        let style = test_style(
+            r#"<locale xml:lang="de">
+                    <terms><term name="in">in (de)</term></terms>
+                </locale>
+                <locale>
+                    <terms><term name="and">+</term></terms>
+                </locale>
+                <citation>
+                    <layout>
+                        <group delimiter=", ">
+                            <text term="no date"/>
+                            <text term="edition"/>
+                            <text term="in"/>
+                            <text term="and"/>
+                            <date variable="issued" form="numeric"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            dated:
+                type: Book
+                date: 2020-05-04
+            "#,
+        )
+        .unwrap();
+
+        let cite = |locale: &str| {
+            let locale = Some(LocaleCode(locale.into()));
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::new(
+                vec![CitationItem::with_entry(bib.get("dated").unwrap())],
+                &style,
+                locale.clone(),
+                &locales,
+                None,
+            ));
+            let finished =
+                driver.finish(BibliographyRequest::new(&style, locale, &locales));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(cite("de-AT"), "ohne Jahr, Aufl., in (de), +, 4.5.2020");
+        assert_eq!(cite("pt-PT"), "n.d., ed., em, +, 5/4/2020");
+        assert_eq!(cite("xx-YY"), "n.d., ed., in, +, 5/4/2020");
+    }
+
+    #[test]
+    fn locale_override() {
+        let locale = |lang: &str, body: &str| -> Locale {
+            LocaleFile::from_xml(&format!(
+                r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="{lang}">
+                    {body}
+                </locale>"#
+            ))
+            .unwrap()
+            .into()
+        };
+
+        let locales = [
+            locale(
+                "en-US",
+                r#"<terms>
+                    <term name="and">and</term>
+                    <term name="month-05">May</term>
+                    <term name="ordinal">th</term>
+                    <term name="ordinal-01">st</term>
+                </terms>"#,
+            ),
+            locale(
+                "de-DE",
+                r#"<terms>
+                    <term name="and">und</term>
+                    <term name="month-05">Mai</term>
+                    <term name="ordinal">.</term>
+                </terms>"#,
+            ),
+            locale(
+                "fr-FR",
+                r#"<style-options limit-day-ordinals-to-day-1="true"/>
+                <terms>
+                    <term name="and">et</term>
+                    <term name="month-05">mai</term>
+                    <term name="ordinal">e</term>
+                    <term name="ordinal-01">er</term>
+                </terms>"#,
+            ),
+        ];
+
+        let style = test_style_with(
+            r#"class="in-text" default-locale="en-US""#,
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <group delimiter=" ">
+                            <text term="and"/>
+                            <date variable="issued">
+                                <date-part name="day" form="ordinal" suffix=" "/>
+                                <date-part name="month" suffix=" "/>
+                                <date-part name="year"/>
+                            </date>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            first:
+                type: Book
+                date: 2020-05-01
+            fourth:
+                type: Book
+                date: 2020-05-04
+            "#,
+        )
+        .unwrap();
+
+        let cite = |locale: Option<&str>| {
+            let locale = locale.map(|l| LocaleCode(l.into()));
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::new(
+                bib.iter().map(CitationItem::with_entry).collect(),
+                &style,
+                locale.clone(),
+                &locales,
+                None,
+            ));
+            let finished =
+                driver.finish(BibliographyRequest::new(&style, locale, &locales));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(cite(None), "and 1st May 2020; and 4th May 2020");
+        assert_eq!(cite(Some("de-DE")), "und 1. Mai 2020; und 4. Mai 2020");
+        assert_eq!(cite(Some("fr-FR")), "et 1er mai 2020; et 4 mai 2020");
+    }
+
+    #[test]
+    fn locale_style_options() {
+        let locales = [LocaleFile::from_xml(
+            r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
+                <style-options limit-day-ordinals-to-day-1="false" punctuation-in-quote="true"/>
+                <terms>
+                    <term name="open-quote">“</term>
+                    <term name="close-quote">”</term>
+                    <term name="month-05">May</term>
+                    <term name="ordinal">th</term>
+                    <term name="ordinal-01">st</term>
+                </terms>
+            </locale>"#,
+        )
+        .unwrap()
+        .into()];
+
+        let bib = from_yaml_str(
+            r#"
+            fourth:
+                type: Article
+                title: Title
+                date: 2020-05-04
+            "#,
+        )
+        .unwrap();
+
+        let cite = |locale: &str| {
+            let style = test_style_with(
+                r#"class="in-text" default-locale="en-US""#,
+                &format!(
+                    r#"{locale}
+                    <citation>
+                        <layout>
+                            <group delimiter=", ">
+                                <text variable="title" quotes="true"/>
+                                <date variable="issued">
+                                    <date-part name="day" form="ordinal" suffix=" "/>
+                                    <date-part name="month" suffix=" "/>
+                                    <date-part name="year"/>
+                                </date>
+                            </group>
+                        </layout>
+                    </citation>"#
+                ),
+            );
+
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                bib.iter().map(CitationItem::with_entry).collect(),
+                &style,
+                &locales,
+            ));
+            let finished =
+                driver.finish(BibliographyRequest::new(&style, None, &locales));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        // The options of the locale file apply unless the style overrides them.
+        assert_eq!(cite(""), "“Title,” 4th May 2020");
+        assert_eq!(
+            cite(r#"<locale><terms><term name="ordinal">th</term></terms></locale>"#),
+            "“Title,” 4th May 2020"
+        );
+        assert_eq!(
+            cite(
+                r#"<locale>
+                    <style-options limit-day-ordinals-to-day-1="true" punctuation-in-quote="false"/>
+                </locale>"#
+            ),
+            "“Title”, 4 May 2020"
+        );
+    }
+
+    #[test]
+    fn term_overrides() {
+        let locales = [LocaleFile::from_xml(
+            r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
+                <terms>
+                    <term name="et-al">et al.</term>
+                    <term name="page-range-delimiter">–</term>
+                    <term name="edition" form="short">ed.</term>
+                </terms>
+            </locale>"#,
+        )
+        .unwrap()
+        .into()];
+
+        let style = test_style(
+            r#"<locale>
+                    <terms><term name="et-al">u. a.</term></terms>
+                </locale>
+                <citation et-al-min="3" et-al-use-first="1">
+                    <layout>
+                        <group delimiter=", ">
+                            <names variable="author"/>
+                            <text variable="page"/>
+                            <text term="edition" form="short"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            crowded:
+                type: Article
+                author: ["Doe, Jane", "Roe, Rick", "Poe, Paula"]
+                page-range: 10-12
+            "#,
+        )
+        .unwrap();
+
+        let cite = |overrides: &TermOverrides| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(
+                CitationRequest::from_items(
+                    vec![CitationItem::with_entry(bib.get("crowded").unwrap())],
+                    &style,
+                    &locales,
+                )
+                .with_term_overrides(overrides),
+            );
+            let finished = driver.finish(
+                BibliographyRequest::new(&style, None, &locales)
+                    .with_term_overrides(overrides),
+            );
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(cite(&TermOverrides::new()), "Jane Doe u. a., 10–12, ed.");
+
+        let mut overrides = TermOverrides::new();
+        overrides.insert(OtherTerm::EtAl.into(), TermForm::Long, false, "and others");
+        overrides.insert(
+            OtherTerm::PageRangeDelimiter.into(),
+            TermForm::Long,
+            false,
+            " to ",
+        );
+        overrides.insert(
+            NumberVariable::Edition.into(),
+            TermForm::Short,
+            false,
+            "edition",
+        );
+        assert_eq!(cite(&overrides), "Jane Doe and others, 10 to 12, edition");
+    }
+
+    #[test]
+    fn citation_layout_overrides() {
+        let style = test_style(
+            r#"<citation>
+                    <layout prefix="(" suffix=")" delimiter="; ">
+                        <group delimiter=" ">
+                            <names variable="author"><name form="short"/></names>
+                            <date variable="issued" prefix="[" suffix="]">
+                                <date-part name="year"/>
+                            </date>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                author: Doe, Jane
+                date: 2020
+            roe:
+                type: Book
+                author: Roe, Rick
+                date: 2021
+            "#,
+        )
+        .unwrap();
+
+        let request = || {
+            CitationRequest::from_items(
+                vec![
+                    CitationItem::with_entry(bib.get("doe").unwrap()),
+                    CitationItem::with_entry(bib.get("roe").unwrap()),
+                ],
+                &style,
+                &[],
+            )
+        };
+        let cite = |strip_affixes: bool, delimiter: Option<&str>| {
+            let configure = |mut request: CitationRequest<'_, _>| {
+                request.strip_affixes = strip_affixes;
+                request.delimiter_override = delimiter.map(ToString::to_string);
+                request
+            };
+
+            let standalone = format!("{:#}", standalone_citation(configure(request())));
+            let mut driver = BibliographyDriver::new();
+            driver.citation(configure(request()));
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            let rendered = format!("{:#}", finished.citations[0].citation);
+            assert_eq!(rendered, standalone);
+            rendered
+        };
+
+        assert_eq!(cite(false, None), "(Doe [2020]; Roe [2021])");
+        assert_eq!(cite(true, None), "Doe [2020]; Roe [2021]");
+        assert_eq!(cite(true, Some(", ")), "Doe [2020], Roe [2021]");
+        assert_eq!(cite(false, Some(" & ")), "(Doe [2020] & Roe [2021])");
+
+        let request = request().with_strip_affixes(true).with_delimiter_override(", ");
+        assert!(request.strip_affixes);
+        assert_eq!(request.delimiter_override.as_deref(), Some(", "));
+    }
+
+    #[test]
+    fn citation_sort() {
+        let bib = from_yaml_str(
+            r#"
+            brown:
+                type: Book
+                title: Brown
+            adams:
+                type: Book
+                title: Adams
+            "#,
+        )
+        .unwrap();
+
+        let cite = |sort: &str| {
+            let style = test_style(&format!(
+                r#"<citation>
+                        {sort}
+                        <layout delimiter="; "><text variable="title"/></layout>
+                    </citation>"#
+            ));
+
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                bib.iter().map(CitationItem::with_entry).collect(),
+                &style,
+                &[],
+            ));
+
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            let mut s = String::new();
+            finished.citations[0]
+                .citation
+                .write_buf(&mut s, BufWriteFormat::Plain)
+                .unwrap();
+            s
+        };
+
+        assert_eq!(cite(""), "Brown; Adams");
+        assert_eq!(cite(r#"<sort><key variable="title"/></sort>"#), "Adams; Brown");
+        assert_eq!(
+            cite(r#"<sort><key variable="title" sort="descending"/></sort>"#),
+            "Brown; Adams"
+        );
+    }
+
+    #[test]
+    fn sort_keys() {
+        let bib = from_yaml_str(
+            r#"
+            alpha:
+                type: Book
+                title: Alpha
+                author: Doe, Jane
+                date: 2005-02
+            beta:
+                type: Book
+                title: Beta
+                author: Doe, Jane
+                date: 2005-06
+            epsilon:
+                type: Book
+                title: Epsilon
+                author: Doe, Jane
+                date: 2001
+            gamma:
+                type: Book
+                title: Gamma
+                author: Roe, Richard
+                date: 2003
+            delta:
+                type: Book
+                title: Delta
+                author: Poe, Edgar
+            anonymous:
+                type: Book
+                title: Anonymous
+                date: 2010
+            "#,
+        )
+        .unwrap();
+
+        let year_macro = r#"<macro name="year">
+            <date variable="issued"><date-part name="year"/></date>
+        </macro>"#;
+
+        let cases = [
+            (
+                r#"<sort>
+                    <key variable="author"/>
+                    <key variable="issued" sort="descending"/>
+                    <key variable="title"/>
+                </sort>"#,
+                ["Beta", "Alpha", "Epsilon", "Delta", "Gamma", "Anonymous"],
+            ),
+            (
+                r#"<sort>
+                    <key variable="author" sort="descending"/>
+                    <key variable="title"/>
+                </sort>"#,
+                ["Gamma", "Delta", "Alpha", "Beta", "Epsilon", "Anonymous"],
+            ),
+            (
+                r#"<sort>
+                    <key variable="issued" sort="descending"/>
+                    <key variable="title"/>
+                </sort>"#,
+                ["Anonymous", "Beta", "Alpha", "Gamma", "Epsilon", "Delta"],
+            ),
+            (
+                r#"<sort>
+                    <key macro="year" sort="descending"/>
+                    <key variable="title"/>
+                </sort>"#,
+                ["Anonymous", "Alpha", "Beta", "Gamma", "Epsilon", "Delta"],
+            ),
+            (
+                r#"<sort><key variable="title" sort="descending"/></sort>"#,
+                ["Gamma", "Epsilon", "Delta", "Beta", "Anonymous", "Alpha"],
+            ),
+        ];
+
+        for (sort, expected) in cases {
+            assert_eq!(
+                sorted_titles(&bib, sort, year_macro, "en-US"),
+                expected,
+                "{sort}"
+            );
+        }
+    }
+
+    #[test]
+    fn sort_key_name_overrides() {
+        let bib = from_yaml_str(
+            r#"
+            alpha:
+                type: Book
+                title: Alpha
+                author: ["Doe, Jane", "Zed, Zoe"]
+            beta:
+                type: Book
+                title: Beta
+                author: ["Doe, Jane", "Roe, Richard"]
+            "#,
+        )
+        .unwrap();
+
+        let authors_macro = r#"<macro name="authors">
+            <names variable="author">
+                <name et-al-min="10" et-al-use-first="10"/>
+            </names>
+        </macro>"#;
+
+        let full = r#"<sort>
+            <key macro="authors"/>
+            <key variable="title"/>
+        </sort>"#;
+        assert_eq!(sorted_titles(&bib, full, authors_macro, "en-US"), ["Beta", "Alpha"]);
+
+        let first_only = r#"<sort>
+            <key macro="authors" names-min="1" names-use-first="1"/>
+            <key variable="title"/>
+        </sort>"#;
+        assert_eq!(
+            sorted_titles(&bib, first_only, authors_macro, "en-US"),
+            ["Alpha", "Beta"]
+        );
+    }
+
+    #[test]
+    fn sort_collation() {
+        let bib = from_yaml_str(
+            r#"
+            oern:
+                type: Book
+                title: Örn
+                author: Östlund, Ulla
+            zeta:
+                type: Book
+                title: Zeta
+                author: Zander, Zoe
+            asa:
+                type: Book
+                title: Åsa
+                author: Ångström, Anders
+            oel:
+                type: Book
+                title: öl
+                author: Olsen, Ole
+            anna:
+                type: Book
+                title: Anna
+                author: Anderson, Anna
+            "#,
+        )
+        .unwrap();
+
+        let by_title = r#"<sort><key variable="title"/></sort>"#;
+        let by_author = r#"<sort><key variable="author"/></sort>"#;
+
+        assert_eq!(
+            sorted_titles(&bib, by_title, "", "sv-SE"),
+            ["Anna", "Zeta", "Åsa", "öl", "Örn"]
+        );
+        assert_eq!(
+            sorted_titles(&bib, by_author, "", "sv-SE"),
+            ["Anna", "öl", "Zeta", "Åsa", "Örn"]
+        );
+        assert_eq!(
+            sorted_titles(&bib, by_title, "", "de-DE"),
+            ["Anna", "Åsa", "öl", "Örn", "Zeta"]
+        );
+        assert_eq!(
+            sorted_titles(&bib, by_author, "", "de-DE"),
+            ["Anna", "Åsa", "öl", "Örn", "Zeta"]
+        );
+    }
+
+    #[test]
+    fn sort_leading_articles() {
+        let bib = from_yaml_str(
+            r#"
+            gatsby:
+                type: Book
+                title: The Great Gatsby
+            study:
+                type: Book
+                title: A Study in Scarlet
+                language: en
+            process:
+                type: Book
+                title: Der Process
+                language: de
+            miserables:
+                type: Book
+                title: Les Misérables
+                language: fr
+            etranger:
+                type: Book
+                title: L’Étranger
+                language: fr
+            blechtrommel:
+                type: Book
+                title: Die Blechtrommel
+            hamlet:
+                type: Book
+                title: Hamlet
+            "#,
+        )
+        .unwrap();
+
+        let by_title = r#"<sort><key variable="title"/></sort>"#;
+
+        assert_eq!(
+            sorted_titles(&bib, by_title, "", "en-US"),
+            [
+                "Die Blechtrommel",
+                "L’Étranger",
+                "The Great Gatsby",
+                "Hamlet",
+                "Les Misérables",
+                "Der Process",
+                "A Study in Scarlet",
+            ]
+        );
+        assert_eq!(
+            sorted_titles(&bib, by_title, "", "de-DE"),
+            [
+                "Die Blechtrommel",
+                "L’Étranger",
+                "Hamlet",
+                "Les Misérables",
+                "Der Process",
+                "A Study in Scarlet",
+                "The Great Gatsby",
+            ]
+        );
+    }
+
+    /// Render the authors of an entry with a `cs:name` element that has the
+    /// given attributes.
+    fn render_names(name_attrs: &str, authors: &[&str]) -> String {
+        let style = test_style(&format!(
+            r#"<locale>
+                    <terms>
+                        <term name="and">and</term>
+                        <term name="et-al">et al.</term>
+                    </terms>
+                </locale>
+                <citation>
+                    <layout><text variable="title"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <names variable="author"><name {name_attrs}/></names>
+                    </layout>
+                </bibliography>"#
+        ));
+
+        let bib = from_yaml_str(&format!(
+            "entry:\n    type: Article\n    author: [{}]\n",
+            authors
+                .iter()
+                .map(|a| format!("\"{a}\""))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .unwrap();
+
+        let finished = cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+        finished.bibliography.unwrap().items[0].content.to_string()
+    }
+
+    #[test]
+    fn name_part_formatting() {
+        let bib = from_yaml_str(
+            r#"
+            dupont:
+                type: Book
+                author: Dupont, Jean
+            beethoven:
+                type: Book
+                author: van Beethoven, Ludwig
+            "#,
+        )
+        .unwrap();
+
+        let cases = [
+            (
+                "never",
+                r#"name-as-sort-order="all""#,
+                ["DUPONT, [Jean]", "VAN BEETHOVEN, [Ludwig]"],
+            ),
+            (
+                "display-and-sort",
+                r#"name-as-sort-order="all""#,
+                ["DUPONT, [Jean]", "BEETHOVEN, [Ludwig van]"],
+            ),
+            ("display-and-sort", "", ["[Jean] DUPONT", "[Ludwig] VAN BEETHOVEN"]),
+        ];
+
+        for (demote, order, expected) in cases {
+            let style = test_style_with(
+                &format!(r#"class="in-text" demote-non-dropping-particle="{demote}""#),
+                &format!(
+                    r#"<citation>
+                        <layout><text variable="title"/></layout>
+                    </citation>
+                    <bibliography>
+                        <layout>
+                            <names variable="author">
+                                <name {order}>
+                                    <name-part name="family" text-case="uppercase"/>
+                                    <name-part name="given" prefix="[" suffix="]"/>
+                                </name>
+                            </names>
+                        </layout>
+                    </bibliography>"#
+                ),
+            );
+
+            let finished =
+                cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+            let items: Vec<_> = finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.content.to_string())
+                .collect();
+            assert_eq!(items, expected, "{demote} {order}");
+        }
+    }
+
+    #[test]
+    fn delimiter_precedes_last() {
+        let authors = ["Doe, John", "Roe, Jane", "Poe, Edgar"];
+
+        #[rustfmt::skip]
+        let cases = [
+            // (delimiter-precedes-last, and, first name inverted, [two names, three names])
+            ("contextual", "none", false, ["John Doe, Jane Roe", "John Doe, Jane Roe, Edgar Poe"]),
+            ("contextual", "none", true, ["Doe, John, Jane Roe", "Doe, John, Jane Roe, Edgar Poe"]),
+            ("contextual", "text", false, ["John Doe and Jane Roe", "John Doe, Jane Roe, and Edgar Poe"]),
+            ("contextual", "text", true, ["Doe, John and Jane Roe", "Doe, John, Jane Roe, and Edgar Poe"]),
+            ("contextual", "symbol", false, ["John Doe & Jane Roe", "John Doe, Jane Roe, & Edgar Poe"]),
+            ("contextual", "symbol", true, ["Doe, John & Jane Roe", "Doe, John, Jane Roe, & Edgar Poe"]),
+            ("after-inverted-name", "none", false, ["John Doe, Jane Roe", "John Doe, Jane Roe, Edgar Poe"]),
+            ("after-inverted-name", "none", true, ["Doe, John, Jane Roe", "Doe, John, Jane Roe, Edgar Poe"]),
+            ("after-inverted-name", "text", false, ["John Doe and Jane Roe", "John Doe, Jane Roe and Edgar Poe"]),
+            ("after-inverted-name", "text", true, ["Doe, John, and Jane Roe", "Doe, John, Jane Roe and Edgar Poe"]),
+            ("after-inverted-name", "symbol", false, ["John Doe & Jane Roe", "John Doe, Jane Roe & Edgar Poe"]),
+            ("after-inverted-name", "symbol", true, ["Doe, John, & Jane Roe", "Doe, John, Jane Roe & Edgar Poe"]),
+            ("always", "none", false, ["John Doe, Jane Roe", "John Doe, Jane Roe, Edgar Poe"]),
+            ("always", "none", true, ["Doe, John, Jane Roe", "Doe, John, Jane Roe, Edgar Poe"]),
+            ("always", "text", false, ["John Doe, and Jane Roe", "John Doe, Jane Roe, and Edgar Poe"]),
+            ("always", "text", true, ["Doe, John, and Jane Roe", "Doe, John, Jane Roe, and Edgar Poe"]),
+            ("always", "symbol", false, ["John Doe, & Jane Roe", "John Doe, Jane Roe, & Edgar Poe"]),
+            ("always", "symbol", true, ["Doe, John, & Jane Roe", "Doe, John, Jane Roe, & Edgar Poe"]),
+            ("never", "none", false, ["John Doe, Jane Roe", "John Doe, Jane Roe, Edgar Poe"]),
+            ("never", "none", true, ["Doe, John, Jane Roe", "Doe, John, Jane Roe, Edgar Poe"]),
+            ("never", "text", false, ["John Doe and Jane Roe", "John Doe, Jane Roe and Edgar Poe"]),
+            ("never", "text", true, ["Doe, John and Jane Roe", "Doe, John, Jane Roe and Edgar Poe"]),
+            ("never", "symbol", false, ["John Doe & Jane Roe", "John Doe, Jane Roe & Edgar Poe"]),
+            ("never", "symbol", true, ["Doe, John & Jane Roe", "Doe, John, Jane Roe & Edgar Poe"]),
+        ];
+
+        for (precedes_last, and, inverted, expected) in cases {
+            let mut attrs = format!(r#"delimiter-precedes-last="{precedes_last}""#);
+            if and != "none" {
+                attrs += &format!(r#" and="{and}""#);
+            }
+            if inverted {
+                attrs += r#" name-as-sort-order="first""#;
+            }
+
+            for (count, expected) in [2, 3].into_iter().zip(expected) {
+                assert_eq!(render_names(&attrs, &authors[..count]), expected, "{attrs}");
+            }
+        }
+
+        // Short names are never displayed inverted.
+        let attrs = r#"form="short" and="symbol" name-as-sort-order="first" delimiter-precedes-last="after-inverted-name""#;
+        assert_eq!(render_names(attrs, &authors[..2]), "Doe & Roe");
+    }
+
+    #[test]
+    fn delimiter_precedes_et_al() {
+        let authors = ["Doe, John", "Roe, Jane", "Poe, Edgar"];
+
+        #[rustfmt::skip]
+        let cases = [
+            // (delimiter-precedes-et-al, first name inverted, [one name shown, two names shown])
+            ("contextual", false, ["John Doe et al.", "John Doe, Jane Roe, et al."]),
+            ("contextual", true, ["Doe, John et al.", "Doe, John, Jane Roe, et al."]),
+            ("after-inverted-name", false, ["John Doe et al.", "John Doe, Jane Roe et al."]),
+            ("after-inverted-name", true, ["Doe, John, et al.", "Doe, John, Jane Roe et al."]),
+            ("always", false, ["John Doe, et al.", "John Doe, Jane Roe, et al."]),
+            ("always", true, ["Doe, John, et al.", "Doe, John, Jane Roe, et al."]),
+            ("never", false, ["John Doe et al.", "John Doe, Jane Roe et al."]),
+            ("never", true, ["Doe, John et al.", "Doe, John, Jane Roe et al."]),
+        ];
+
+        for (precedes_et_al, inverted, expected) in cases {
+            for (use_first, expected) in [1, 2].into_iter().zip(expected) {
+                let mut attrs = format!(
+                    r#"delimiter-precedes-et-al="{precedes_et_al}" and="text" et-al-min="3" et-al-use-first="{use_first}""#
+                );
+                if inverted {
+                    attrs += r#" name-as-sort-order="first""#;
+                }
+
+                assert_eq!(render_names(&attrs, &authors), expected, "{attrs}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn conference_presentation_apa() {
+        let bib = from_yaml_str(
+            r#"
+            talk:
+                type: Article
+                title: Reading between the lines
+                author: Doe, Jane
+                date: 2019-05
+                parent:
+                    type: Conference
+                    title: 12th Conference on X
+                    location: Berlin
+                    date: 2019-05-14
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let finished = cite_each(
+            [bib.get("talk").unwrap()],
+            BibliographyRequest::new(&apa, None, &locales),
+        );
+        let item = finished.bibliography.unwrap().items[0].content.to_string();
+        assert!(item.starts_with("Doe, J. (2019, May"), "{item}");
+        assert!(item.contains("Reading between the lines"), "{item}");
+        assert!(item.contains("12th Conference on X, Berlin"), "{item}");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn dataset_apa() {
+        let bib = from_yaml_str(
+            r#"
+            survey:
+                type: Dataset
+                title: Global survey of reading habits
+                author: Doe, Jane
+                date: 2021
+                publisher: Zenodo
+                serial-number:
+                    version: "2.1"
+                    doi: 10.5281/zenodo.1234567
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let finished = cite_each(
+            [bib.get("survey").unwrap()],
+            BibliographyRequest::new(&apa, None, &locales),
+        );
+        let item = finished.bibliography.unwrap().items[0].content.to_string();
+        assert!(item.contains("(Version 2.1)"), "{item}");
+        assert!(item.contains("[Data set]"), "{item}");
+        assert!(item.contains("Zenodo"), "{item}");
+        assert!(item.contains("https://doi.org/10.5281/zenodo.1234567"), "{item}");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn article_in_proceedings_in_series() {
+        let bib = from_yaml_str(
+            r#"
+            nested:
+                type: Article
+                title: Typesetting with Rust
+                author: Doe, Jane
+                date: 2019
+                page-range: 12-20
+                parent:
+                    type: Proceedings
+                    title: Proceedings of the Conference on Typesetting
+                    parent:
+                        type: Book
+                        title: Lecture Notes in Computer Science
+                        volume: 1234
+                        publisher:
+                            name: Springer
+                            location: Berlin
+            siblings:
+                type: Article
+                title: Typesetting with Rust
+                author: Doe, Jane
+                date: 2019
+                page-range: 12-20
+                parent:
+                    - type: Proceedings
+                      title: Proceedings of the Conference on Typesetting
+                      publisher:
+                          name: Springer
+                          location: Berlin
+                    - type: Anthology
+                      title: Lecture Notes in Computer Science
+                      volume: 1234
+            "#,
+        )
+        .unwrap();
+
+        let chicago = archive::ArchivedStyle::ChicagoAuthorDate.get();
+        let citationberg::Style::Independent(chicago) = chicago else { unreachable!() };
+        let locales = archive::locales();
+
+        for key in ["nested", "siblings"] {
+            let finished = cite_each(
+                [bib.get(key).unwrap()],
+                BibliographyRequest::new(&chicago, None, &locales),
+            );
+            let item = finished.bibliography.unwrap().items[0].content.to_string();
+            let container = item
+                .find("In Proceedings of the Conference on Typesetting")
+                .unwrap_or_else(|| panic!("{key}: {item}"));
+            let series = item
+                .find("Lecture Notes in Computer Science 1234")
+                .unwrap_or_else(|| panic!("{key}: {item}"));
+            assert!(container < series, "{key}: {item}");
+            assert!(item.contains("Berlin: Springer"), "{key}: {item}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn substituted_editor_is_suppressed() {
+        let bib = from_yaml_str(
+            r#"
+            edited:
+                type: Book
+                title: Collected Essays on Typesetting
+                editor: Doe, Jane
+                date: 2020
+                publisher: Typeset Press
+            authored:
+                type: Book
+                title: Typesetting in Practice
+                author: Roe, Richard
+                editor: Doe, Jane
+                date: 2021
+                publisher: Typeset Press
+            "#,
+        )
+        .unwrap();
+
+        let chicago = archive::ArchivedStyle::ChicagoAuthorDate.get();
+        let citationberg::Style::Independent(chicago) = chicago else { unreachable!() };
+        let locales = archive::locales();
+
+        let finished =
+            cite_each(bib.iter(), BibliographyRequest::new(&chicago, None, &locales));
+        let items = finished.bibliography.unwrap().items;
+        let edited =
+            items.iter().find(|i| i.key == "edited").unwrap().content.to_string();
+        assert!(edited.starts_with("Doe, Jane, ed. 2020."), "{edited}");
+        assert_eq!(edited.matches("Doe").count(), 1, "{edited}");
+
+        // The suppression does not carry over to the next entry.
+        let authored = items
+            .iter()
+            .find(|i| i.key == "authored")
+            .unwrap()
+            .content
+            .to_string();
+        assert!(authored.starts_with("Roe, Richard. 2021."), "{authored}");
+        assert!(authored.contains("Jane Doe"), "{authored}");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn preferred_script() {
+        let bib = from_yaml_str(
+            r#"
+            tolstoy:
+                type: Book
+                title:
+                    value: Война и мир
+                    transliterated: Voyna i mir
+                author:
+                    name: Толстой
+                    given-name: Лев Николаевич
+                    transliterated: Tolstoy, Lev Nikolayevich
+                date: 1869
+                language: ru
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let cases = [
+            (PreferredScript::Original, "Толстой, Л. Н. (1869).", "Война и мир."),
+            (PreferredScript::Transliterated, "Tolstoy, L. N. (1869).", "Voyna i mir."),
+            (
+                PreferredScript::OriginalWithTransliteration,
+                "Толстой, Л. Н. [Tolstoy, L. N.] (1869).",
+                "Война и мир [Voyna i mir].",
+            ),
+        ];
+
+        for (script, names, title) in cases {
+            let finished = cite_each(
+                [bib.get("tolstoy").unwrap()],
+                BibliographyRequest::new(&apa, None, &locales)
+                    .with_preferred_script(script),
+            );
+            let item = finished.bibliography.unwrap().items[0].content.to_string();
+            assert!(item.starts_with(names), "{script:?}: {item}");
+            assert!(item.contains(title), "{script:?}: {item}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn foreign_language_entries() {
+        let bib = from_yaml_str(
+            r#"
+            german:
+                type: Article
+                title: Über die Sprache
+                author: Müller, Hans
+                date: 2001
+                language: de-DE
+                parent:
+                    type: Periodical
+                    title: Zeitschrift für deutsche Philologie
+            english:
+                type: Article
+                title: On language
+                author: Miller, John
+                date: 2001
+                language: en-US
+                parent:
+                    type: Periodical
+                    title: journal of german philology
+            klingon:
+                type: Article
+                title: On Klingon
+                author: Okrand, Marc
+                date: 2001
+                language: tlh
+                parent:
+                    type: Periodical
+                    title: Journal of Constructed Languages
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+
+        let render = |style: archive::ArchivedStyle| {
+            let citationberg::Style::Independent(style) = style.get() else {
+                unreachable!()
+            };
+
+            let finished =
+                cite_each(bib.iter(), BibliographyRequest::new(&style, None, &locales));
+            let items: Vec<_> = finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.content.to_string())
+                .collect();
+            let german = items.iter().find(|i| i.contains("Müller")).unwrap().clone();
+            let english = items.iter().find(|i| i.contains("Miller")).unwrap().clone();
+            let klingon = items.iter().find(|i| i.contains("Okrand")).unwrap().clone();
+            (german, english, klingon)
+        };
+
+        // Only English titles are title-cased.
+        let (german, english, _) =
+            render(archive::ArchivedStyle::AmericanPsychologicalAssociation);
+        assert!(german.contains("Zeitschrift für deutsche Philologie"), "{german}");
+        assert!(english.contains("Journal of German Philology"), "{english}");
+
+        // Quotation marks and punctuation follow the entry's language. There
+        // is no Klingon locale, so that entry uses the style's conventions.
+        let (german, english, klingon) =
+            render(archive::ArchivedStyle::ChicagoAuthorDate);
+        assert!(german.contains("„Über die Sprache“."), "{german}");
+        assert!(english.contains("“On Language.”"), "{english}");
+        assert!(klingon.contains("“On Klingon.”"), "{klingon}");
+    }
+
+    #[test]
+    fn turkic_case_mapping() {
+        let bib = from_yaml_str(
+            r#"
+            turkish:
+                type: Book
+                title: istanbul ve Iğdır
+                language: tr-TR
+            english:
+                type: Book
+                title: istanbul and Iğdır
+                language: en-US
+            "#,
+        )
+        .unwrap();
+
+        let render = |case: &str| {
+            let style = test_style(&format!(
+                r#"<citation>
+                        <layout><text variable="title" text-case="{case}"/></layout>
+                    </citation>"#
+            ));
+
+            cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]))
+                .citations
+                .iter()
+                .map(|c| c.citation.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(render("uppercase"), ["İSTANBUL VE IĞDIR", "ISTANBUL AND IĞDIR"]);
+        assert_eq!(render("lowercase"), ["istanbul ve ığdır", "istanbul and iğdır"]);
+        assert_eq!(
+            render("capitalize-first"),
+            ["İstanbul ve Iğdır", "Istanbul and Iğdır"]
+        );
+    }
+
+    #[test]
+    fn chunk_formatting() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="title"/></layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            water:
+                type: Article
+                title: H<sub>2</sub>O, E=mc<sup>2</sup>, and <sc>Doe</sc>
+            "#,
+        )
+        .unwrap();
+
+        let finished = cite_each(
+            [bib.get("water").unwrap()],
+            BibliographyRequest::new(&style, None, &[]),
+        );
+        let citation = &finished.citations[0].citation;
+
+        let mut html = String::new();
+        citation.write_buf(&mut html, BufWriteFormat::Html).unwrap();
+        assert_eq!(
+            html,
+            "H<span style=\"vertical-align: sub;\">2</span>O, \
+             E=mc<span style=\"vertical-align: super;\">2</span>, and \
+             <span style=\"font-variant: small-caps;\">Doe</span>"
+        );
+
+        let mut plain = String::new();
+        citation.write_buf(&mut plain, BufWriteFormat::Plain).unwrap();
+        assert_eq!(plain, "H2O, E=mc2, and Doe");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn punctuation_merging() {
+        let bib = from_yaml_str(
+            r#"
+            why:
+                type: Article
+                title: Why?
+                parent:
+                    type: Periodical
+                    title: Journal
+            plain:
+                type: Article
+                title: A title
+                parent:
+                    type: Periodical
+                    title: Journal
+            etc:
+                type: Book
+                title: Apples, pears, etc.
+            wow:
+                type: Book
+                title: Wow!
+            "#,
+        )
+        .unwrap();
+
+        let style =
+            |layout: &str| test_style(&format!(r#"<citation>{layout}</citation>"#));
+
+        let locales = archive::locales();
+        let cite = |style: &IndependentStyle, locale: &str, key: &str| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::new(
+                vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                style,
+                Some(LocaleCode(locale.into())),
+                &locales,
+                None,
+            ));
+            let finished = driver.finish(BibliographyRequest::new(
+                style,
+                Some(LocaleCode(locale.into())),
+                &locales,
+            ));
+            finished.citations[0].citation.to_string()
+        };
+
+        let quoted = style(
+            r#"<layout suffix=".">
+                <group delimiter=", ">
+                    <text variable="title" quotes="true"/>
+                    <text variable="container-title"/>
+                </group>
+            </layout>"#,
+        );
+        assert_eq!(cite(&quoted, "en-US", "why"), "“Why?,” Journal.");
+        assert_eq!(cite(&quoted, "en-US", "plain"), "“A title,” Journal.");
+        assert_eq!(cite(&quoted, "en-US", "etc"), "“Apples, pears, etc.”");
+        assert_eq!(cite(&quoted, "en-US", "wow"), "“Wow!”");
+        assert_eq!(cite(&quoted, "en-GB", "plain"), "‘A title’, Journal.");
+        assert_eq!(cite(&quoted, "en-GB", "wow"), "‘Wow!’");
+
+        let suffixed = style(
+            r#"<layout suffix=".">
+                <text variable="title" suffix=", "/>
+                <text variable="container-title"/>
+            </layout>"#,
+        );
+        assert_eq!(cite(&suffixed, "en-US", "plain"), "A title, Journal.");
+        assert_eq!(cite(&suffixed, "en-US", "etc"), "Apples, pears, etc.");
+        assert_eq!(cite(&suffixed, "en-US", "wow"), "Wow!");
+        assert_eq!(cite(&suffixed, "en-US", "why"), "Why?, Journal.");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn nested_quotes() {
+        let bib = from_yaml_str(
+            r#"
+            quoted:
+                type: Article
+                title: The "quoted" phrase
+            hello:
+                type: Article
+                title: Say "hello"
+            zitiert:
+                type: Article
+                title: Der "zitierte" Satz
+            hallo:
+                type: Article
+                title: Sag "hallo"
+            "#,
+        )
+        .unwrap();
+
+        let style = |quotes: bool| {
+            test_style(&format!(
+                r#"<citation>
+                        <layout suffix="."><text variable="title" quotes="{quotes}"/></layout>
+                    </citation>"#
+            ))
+        };
+
+        let locales = archive::locales();
+        let cite = |style: &IndependentStyle, locale: &str, key: &str, smart: bool| {
+            let locale = Some(LocaleCode(locale.into()));
+            let mut driver = BibliographyDriver::new();
+            driver.citation(
+                CitationRequest::new(
+                    vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                    style,
+                    locale.clone(),
+                    &locales,
+                    None,
+                )
+                .with_smart_quotes(smart),
+            );
+            let finished =
+                driver.finish(BibliographyRequest::new(style, locale, &locales));
+            finished.citations[0].citation.to_string()
+        };
+
+        let quoted = style(true);
+        let plain = style(false);
+
+        assert_eq!(cite(&quoted, "en-US", "quoted", true), "“The ‘quoted’ phrase.”");
+        assert_eq!(cite(&quoted, "en-US", "hello", true), "“Say ‘hello.’”");
+        assert_eq!(cite(&quoted, "en-US", "quoted", false), "“The \"quoted\" phrase.”");
+        assert_eq!(cite(&plain, "en-US", "quoted", true), "The “quoted” phrase.");
+        assert_eq!(cite(&plain, "en-US", "hello", true), "Say “hello.”");
+
+        assert_eq!(cite(&quoted, "de-DE", "zitiert", true), "„Der ‚zitierte‘ Satz“.");
+        assert_eq!(cite(&quoted, "de-DE", "hallo", true), "„Sag ‚hallo‘“.");
+        assert_eq!(cite(&plain, "de-DE", "zitiert", true), "Der „zitierte“ Satz.");
+        assert_eq!(cite(&plain, "de-DE", "hallo", true), "Sag „hallo“.");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn question_titles() {
+        let bib = from_yaml_str(
+            r#"
+            sleep:
+                type: Article
+                title: Why do we sleep?
+                author: Doe, Jane
+                date: 2020
+                page-range: 3-14
+                parent:
+                    type: Periodical
+                    title: Journal of Sleep
+                    volume: 1
+                    issue: 2
+            eureka:
+                type: Book
+                title: Eureka!
+                author: Roe, Richard
+                date: 2019
+                publisher: Penguin
+            "#,
+        )
+        .unwrap();
+
+        let locales = archive::locales();
+        let render = |style: archive::ArchivedStyle| {
+            let citationberg::Style::Independent(style) = style.get() else {
+                unreachable!()
+            };
+
+            let finished =
+                cite_each(bib.iter(), BibliographyRequest::new(&style, None, &locales));
+            finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| format!("{:#}", item.content))
+                .collect::<Vec<_>>()
+        };
+
+        for (style, titles) in [
+            (
+                archive::ArchivedStyle::AmericanPsychologicalAssociation,
+                ["Why do we sleep? Journal of Sleep", "Eureka! Penguin"],
+            ),
+            (
+                archive::ArchivedStyle::ChicagoAuthorDate,
+                ["“Why Do We Sleep?” Journal of Sleep", "Eureka! Penguin"],
+            ),
+        ] {
+            let items = render(style);
+            for title in titles {
+                assert!(items.iter().any(|i| i.contains(title)), "{items:?}");
+            }
+
+            for item in &items {
+                assert!(!item.contains("?.") && !item.contains("!."), "{item}");
+            }
+        }
+    }
+
+    #[test]
+    fn display_structure() {
+        let style = test_style(
+            r#"<citation>
+                    <layout><text variable="citation-number"/></layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <group>
+                            <text variable="citation-number" prefix="[" suffix="]" display="left-margin"/>
+                            <text variable="title"/>
+                        </group>
+                        <text variable="note" display="indent"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Article
+                title: A Title
+                author: Doe, Jane
+                date: 2020
+                note: A note
+                parent:
+                    type: Periodical
+                    title: Journal of Interesting Studies
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("doe").unwrap();
+
+        let finished = cite_each([entry], BibliographyRequest::new(&style, None, &[]));
+        let item = &finished.bibliography.unwrap().items[0];
+
+        let mut html = String::new();
+        item.write_buf(&mut html, BufWriteFormat::Html).unwrap();
+        assert_eq!(
+            html,
+            "<div class=\"csl-entry\"><div class=\"csl-left-margin\">[1]</div>\
+             <div class=\"csl-right-inline\">A Title\
+             <div class=\"csl-indent\">A note</div></div></div>"
+        );
+
+        #[cfg(feature = "archive")]
+        {
+            let citationberg::Style::Independent(ieee) =
+                archive::ArchivedStyle::InstituteOfElectricalAndElectronicsEngineers
+                    .get()
+            else {
+                unreachable!()
+            };
+
+            let finished = cite_each([entry], BibliographyRequest::new(&ieee, None, &[]));
+            let item = &finished.bibliography.unwrap().items[0];
+
+            let mut html = String::new();
+            item.write_buf(&mut html, BufWriteFormat::Html).unwrap();
+            assert!(
+                html.starts_with(
+                    "<div class=\"csl-entry\"><div class=\"csl-left-margin\">[1]"
+                ),
+                "{html}"
+            );
+            assert!(
+                html.contains("</div><div class=\"csl-right-inline\">J. Doe, "),
+                "{html}"
+            );
+            assert!(html.ends_with("</div></div>"), "{html}");
+        }
+    }
+
+    #[test]
+    fn stripping_periods() {
+        assert_eq!(strip_periods("Phys. Rev. Lett.", None), "Phys Rev Lett");
+        assert_eq!(strip_periods("U.S.A.", None), "USA");
+        assert_eq!(strip_periods("eds. and trans.", None), "eds and trans");
+        assert_eq!(strip_periods("3.14", None), "3.14");
+        assert_eq!(strip_periods("vol.2", None), "vol.2");
+        assert_eq!(strip_periods("Wait... what…", None), "Wait... what…");
+        assert_eq!(strip_periods("…", None), "…");
+        assert_eq!(strip_periods(". and", None), ". and");
+        assert_eq!(strip_periods(". and", Some('c')), " and");
+        assert_eq!(strip_periods(".5", Some('0')), ".5");
+
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <text variable="title" text-case="uppercase" strip-periods="true"/>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            budget:
+                type: Report
+                title: The U.S. budget, vol. 3.14, and so on...
+            verbatim:
+                type: Report
+                title: "{Node.js} in the U.K."
+            "#,
+        )
+        .unwrap();
+
+        let finished = cite_each(bib.iter(), BibliographyRequest::new(&style, None, &[]));
+        let cites: Vec<_> = finished
+            .citations
+            .iter()
+            .map(|c| format!("{:#}", c.citation))
+            .collect();
+        assert_eq!(cites, ["THE US BUDGET, VOL 3.14, AND SO ON...", "Node.js IN THE UK"]);
+    }
+
+    #[test]
+    fn inline_math() {
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <text variable="title" text-case="title" strip-periods="true"/>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            bounded:
+                type: Article
+                title: On the $L^2$ boundedness of operators on $a.b.c$ spaces
+            "#,
+        )
+        .unwrap();
+
+        let finished = cite_each(
+            [bib.get("bounded").unwrap()],
+            BibliographyRequest::new(&style, None, &[]),
+        );
+        let citation = &finished.citations[0].citation;
+
+        let mut plain = String::new();
+        citation.write_buf(&mut plain, BufWriteFormat::Plain).unwrap();
+        assert_eq!(plain, "On the $L^2$ Boundedness of Operators on $a.b.c$ Spaces");
+
+        let mut html = String::new();
+        citation.write_buf(&mut html, BufWriteFormat::Html).unwrap();
+        assert_eq!(html, r"On the \(L^2\) Boundedness of Operators on \(a.b.c\) Spaces");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn short_titles_in_subsequent_notes() {
+        let bib = from_yaml_str(
+            r#"
+            doe:
+                type: Book
+                title:
+                    value: The Long and Winding Road to Reading Well
+                    short: Winding Road
+                author: Doe, Jane
+                publisher: Penguin
+                date: 2019
+            roe:
+                type: Article
+                title: Notes on margins
+                author: Roe, Richard
+                date: 2020
+                parent:
+                    type: Periodical
+                    title:
+                        value: Journal of Interesting Studies
+                        short: J. Interest. Stud.
+            "#,
+        )
+        .unwrap();
+
+        let citationberg::Style::Independent(chicago) =
+            archive::ArchivedStyle::ChicagoFullnotes.get()
+        else {
+            unreachable!()
+        };
+        let locales = archive::locales();
+
+        let mut driver = BibliographyDriver::new();
+        for (note, key) in ["doe", "roe", "doe"].into_iter().enumerate() {
+            driver.citation(CitationRequest::new(
+                vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                &chicago,
+                None,
+                &locales,
+                Some(note + 1),
+            ));
+        }
+
+        let finished = driver.finish(BibliographyRequest::new(&chicago, None, &locales));
+        let notes: Vec<_> =
+            finished.citations.iter().map(|c| c.citation.to_string()).collect();
+        assert!(
+            notes[0].contains("The Long and Winding Road to Reading Well"),
+            "{}",
+            notes[0]
+        );
+        assert!(notes[2].starts_with("Doe, Winding Road"), "{}", notes[2]);
+        assert!(!notes[2].contains("Reading Well"), "{}", notes[2]);
+
+        let style = test_style(
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <text variable="title" form="short"/>
+                        <text variable="container-title" form="short" prefix=", "/>
+                    </layout>
+                </citation>"#,
+        );
+
+        let mut driver = BibliographyDriver::new();
+        driver.citation(CitationRequest::from_items(
+            vec![
+                CitationItem::with_entry(bib.get("doe").unwrap()),
+                CitationItem::with_entry(bib.get("roe").unwrap()),
+            ],
+            &style,
+            &[],
+        ));
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        assert_eq!(
+            finished.citations[0].citation.to_string(),
+            "Winding Road; Notes on margins, J. Interest. Stud."
+        );
+    }
+
+    #[test]
+    fn volume_and_part_titles() {
+        let bib = from_yaml_str(
+            r#"
+            history:
+                type: Book
+                title: The Cambridge History of Science
+                volume: 3
+                volume-title: Early Modern Science
+            chapter:
+                type: Chapter
+                title: Astronomy
+                parent:
+                    type: Book
+                    title: The Cambridge History of Science
+                    volume: 3
+                    volume-title: Early Modern Science
+            serial:
+                type: Article
+                title: On Computable Numbers
+                part-number: 2
+                part-title: A Correction
+                parent:
+                    type: Periodical
+                    title: Proceedings of the London Mathematical Society
+            "#,
+        )
+        .unwrap();
+
+        let style = test_style(
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <group delimiter=", ">
+                            <text variable="title"/>
+                            <text variable="container-title"/>
+                            <group delimiter=" ">
+                                <text value="vol."/>
+                                <number variable="volume"/>
+                            </group>
+                            <text variable="volume-title"/>
+                            <group delimiter=" ">
+                                <text value="pt."/>
+                                <number variable="part-number"/>
+                            </group>
+                            <text variable="part-title"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let mut driver = BibliographyDriver::new();
+        driver.citation(CitationRequest::from_items(
+            bib.iter().map(CitationItem::with_entry).collect(),
+            &style,
+            &[],
+        ));
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        assert_eq!(
+            format!("{:#}", finished.citations[0].citation),
+            "The Cambridge History of Science, vol. 3, Early Modern Science; \
+             Astronomy, The Cambridge History of Science, vol. 3, Early Modern Science; \
+             On Computable Numbers, Proceedings of the London Mathematical Society, \
+             pt. 2, A Correction"
+        );
+    }
+
+    #[test]
+    fn legal_variables() {
+        let bib = from_yaml_str(
+            r#"
+            obergefell:
+                type: case
+                title: Obergefell v. Hodges
+                authority: Supreme Court of the United States
+                serial-number:
+                    docket: 14-556
+            "#,
+        )
+        .unwrap();
+
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <group delimiter=", ">
+                            <text variable="title"/>
+                            <text variable="number"/>
+                            <text variable="authority"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let finished = cite_each(
+            [bib.get("obergefell").unwrap()],
+            BibliographyRequest::new(&style, None, &[]),
+        );
+        assert_eq!(
+            format!("{:#}", finished.citations[0].citation),
+            "Obergefell v. Hodges, 14-556, Supreme Court of the United States"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn legal_case_apa() {
+        let bib = from_yaml_str(
+            r#"
+            deboer:
+                type: case
+                title: DeBoer v. Snyder
+                authority: 6th Cir.
+                date: 2014
+                page-range: 388
+                parent:
+                    type: reference
+                    title: F.3d
+                    volume: 772
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let finished = cite_each(
+            [bib.get("deboer").unwrap()],
+            BibliographyRequest::new(&style, None, &locales),
+        );
+        let item = format!("{:#}", finished.bibliography.unwrap().items[0].content);
+        assert!(item.contains("DeBoer v. Snyder"), "{item}");
+        assert!(item.contains("772 F.3d 388"), "{item}");
+        assert!(item.contains("(6th Cir. 2014)"), "{item}");
+    }
+
+    #[test]
+    fn interviewer() {
+        let bib = from_yaml_str(
+            r#"
+            interview:
+                type: video
+                title: A Conversation with Ursula K. Le Guin
+                author: Le Guin, Ursula K.
+                affiliated:
+                    - role: interviewer
+                      names: Moyers, Bill
+            film:
+                type: video
+                title: The Left Hand of Darkness
+            "#,
+        )
+        .unwrap();
+
+        let style = test_style(
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <group delimiter=", ">
+                            <text variable="title"/>
+                            <choose>
+                                <if type="interview">
+                                    <names variable="interviewer" prefix="interviewed by ">
+                                        <name/>
+                                    </names>
+                                </if>
+                            </choose>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let mut driver = BibliographyDriver::new();
+        driver.citation(CitationRequest::from_items(
+            bib.iter().map(CitationItem::with_entry).collect(),
+            &style,
+            &[],
+        ));
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        assert_eq!(
+            format!("{:#}", finished.citations[0].citation),
+            "A Conversation with Ursula K. Le Guin, interviewed by Bill Moyers; \
+             The Left Hand of Darkness"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn apa_post() {
+        let bib = from_yaml_str(
+            r#"
+            tweet:
+                type: post
+                title: Colorful sunrise at Grand Canyon National Park
+                author: National Park Service
+                handle: NatlParkService
+                date: 2018-02-02
+                genre: Tweet
+                url: https://twitter.com/NatlParkService/status/959478962581422081
+                parent:
+                    type: thread
+                    title: Twitter
+            anonymous:
+                type: post
+                title: Good morning
+                handle: "@sunrise_bot"
+                date: 2019-05-01
+                parent:
+                    type: thread
+                    title: Twitter
+            "#,
+        )
+        .unwrap();
+        let locales = archive::locales();
+        let citationberg::Style::Independent(style) =
+            archive::ArchivedStyle::AmericanPsychologicalAssociation.get()
+        else {
+            unreachable!()
+        };
+
+        let render = |post_handles: bool| {
+            let mut driver = BibliographyDriver::new();
+            for key in ["tweet", "anonymous"] {
+                driver.citation(CitationRequest::from_items(
+                    vec![CitationItem::with_entry(bib.get(key).unwrap())],
+                    &style,
+                    &locales,
+                ));
+            }
+            driver.finish(
+                BibliographyRequest::new(&style, None, &locales)
+                    .with_post_handles(post_handles),
+            )
+        };
+
+        // Without the option, the handle is only used for posts without an
+        // author.
+        let finished = render(false);
+        let bibliography = finished.bibliography.unwrap();
+        let item = |key: &str| {
+            let item = bibliography.items.iter().find(|i| i.key == key).unwrap();
+            format!("{:#}", item.content)
+        };
+        assert!(item("tweet").starts_with("National Park Service. (2018, February 2)."));
+        assert!(item("anonymous").starts_with("@sunrise_bot. (2019, May 1)."));
+
+        // The handle only appears in the bibliography.
+        let finished = render(true);
+        let citation = format!("{:#}", finished.citations[0].citation);
+        assert_eq!(citation, "(National Park Service, 2018)");
+
+        let bibliography = finished.bibliography.unwrap();
+        let item = |key: &str| {
+            let item = bibliography.items.iter().find(|i| i.key == key).unwrap();
+            format!("{:#}", item.content)
+        };
+        let tweet = item("tweet");
+        assert!(
+            tweet.starts_with(
+                "National Park Service [@NatlParkService]. (2018, February 2)."
+            ),
+            "{tweet}"
+        );
+        assert!(
+            tweet.contains("Colorful sunrise at Grand Canyon National Park [Tweet]"),
+            "{tweet}"
+        );
+        assert!(tweet.contains("Twitter"), "{tweet}");
+        assert!(tweet.contains("https://twitter.com/NatlParkService/status/"), "{tweet}");
+        assert!(item("anonymous").starts_with("@sunrise_bot. (2019, May 1)."));
+    }
 
-    use citationberg::LocaleFile;
+    #[test]
+    fn auto_short_titles() {
+        let short = |title: &str, locale: &str| {
+            derive_short_title(&title.parse().unwrap(), locale).to_string()
+        };
 
-    use super::*;
-    use crate::io::from_yaml_str;
+        assert_eq!(short("The Great Gatsby", "en-US"), "Great Gatsby");
+        assert_eq!(short("Anathem", "en-US"), "Anathem");
+        assert_eq!(short("Why Read? A Study of Habits", "en-US"), "Why Read?");
+        assert_eq!(short("Der Process: Roman", "de-DE"), "Process");
+        assert_eq!(short("Le Monde : Une histoire", "fr-FR"), "Monde");
+        assert_eq!(short("Ratio 3:2 in Art", "en-US"), "Ratio 3:2 in Art");
+        assert_eq!(short("{Re: Zero}: The Novel", "en-US"), "Re: Zero");
+        assert_eq!(short("The {TeX}book: A Guide", "en-US"), "The TeXbook");
+        assert_eq!(short(": Untitled", "en-US"), ": Untitled");
+
+        let bib = from_yaml_str(
+            r#"
+            subtitle:
+                type: Book
+                title: "The Art of Computer Programming: Fundamental Algorithms"
+            question:
+                type: Book
+                title: "Why Read? A Study of Habits"
+            explicit:
+                type: Book
+                title:
+                    value: "The Long and Winding Road: Reading Well"
+                    short: Winding Road
+            verbatim:
+                type: Book
+                title: "{Re: Zero}: The Novel"
+            plain:
+                type: Book
+                title: Anathem
+            "#,
+        )
+        .unwrap();
+
+        let style = test_style(
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <text variable="title" form="short" text-case="uppercase"/>
+                    </layout>
+                </citation>"#,
+        );
+
+        let cite = |auto: bool| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(
+                CitationRequest::from_items(
+                    bib.iter().map(CitationItem::with_entry).collect(),
+                    &style,
+                    &[],
+                )
+                .with_auto_short_titles(auto),
+            );
+            let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+            format!("{:#}", finished.citations[0].citation)
+        };
+
+        assert_eq!(
+            cite(false),
+            "THE ART OF COMPUTER PROGRAMMING: FUNDAMENTAL ALGORITHMS; \
+             WHY READ? A STUDY OF HABITS; WINDING ROAD; Re: Zero: THE NOVEL; ANATHEM"
+        );
+        assert_eq!(
+            cite(true),
+            "ART OF COMPUTER PROGRAMMING; WHY READ?; WINDING ROAD; Re: Zero; ANATHEM"
+        );
+    }
 
     #[test]
-    fn test_csl() {
+    fn abbreviations() {
+        let bib = from_yaml_str(
+            r#"
+            prl:
+                type: Article
+                title: Observation of gravitational waves
+                author: "{World Health Organization}"
+                date: 2016
+                parent:
+                    type: Periodical
+                    title: Physical Review Letters
+            jis:
+                type: Article
+                title: Notes on margins
+                author: Roe, Richard
+                date: 2020
+                parent:
+                    type: Periodical
+                    title:
+                        value: Physical Review Letters
+                        short: PRL
+            other:
+                type: Article
+                title: Unlisted
+                author: Doe, Jane
+                date: 2020
+                parent:
+                    type: Periodical
+                    title: Journal of Interesting Studies
+            "#,
+        )
+        .unwrap();
+
+        let style = |form: &str| {
+            test_style(&format!(
+                r#"<citation>
+                        <layout delimiter="; ">
+                            <names variable="author">
+                                <name form="{form}"/>
+                            </names>
+                            <text variable="container-title" form="{form}" prefix=", "/>
+                        </layout>
+                    </citation>"#
+            ))
+        };
+
+        let mut abbreviations = Abbreviations::new();
+        abbreviations
+            .container_title
+            .insert("Physical Review Letters".into(), "Phys. Rev. Lett.".into());
+        abbreviations
+            .institution
+            .insert("World Health Organization".into(), "WHO".into());
+
+        let cite = |style: &IndependentStyle, use_abbreviations: bool| {
+            let mut driver = BibliographyDriver::new();
+            driver.citation(
+                CitationRequest::from_items(
+                    bib.iter().map(CitationItem::with_entry).collect(),
+                    style,
+                    &[],
+                )
+                .with_abbreviations(&abbreviations)
+                .with_use_abbreviations(use_abbreviations),
+            );
+            let finished = driver.finish(BibliographyRequest::new(style, None, &[]));
+            finished.citations[0].citation.to_string()
+        };
+
+        let short = style("short");
+        let long = style("long");
+        assert_eq!(
+            cite(&short, false),
+            "WHO, Phys. Rev. Lett.; Roe, PRL; Doe, Journal of Interesting Studies"
+        );
+        assert_eq!(
+            cite(&long, false),
+            "World Health Organization, Physical Review Letters; Richard Roe, \
+             Physical Review Letters; Jane Doe, Journal of Interesting Studies"
+        );
+        assert_eq!(
+            cite(&long, true),
+            "WHO, Phys. Rev. Lett.; Richard Roe, Phys. Rev. Lett.; Jane Doe, \
+             Journal of Interesting Studies"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn institutional_authors() {
+        let bib = from_yaml_str(
+            r#"
+            who:
+                type: Report
+                title: World health statistics
+                author: The World Health Organization
+                date: 2020
+            culture:
+                type: Report
+                title: Chiffres clés
+                author: "{Ministère de la Culture}"
+                date: 2020
+            unicef:
+                type: Report
+                title: The state of the world's children
+                author:
+                    name: UNICEF
+                date: 2020
+            doe:
+                type: Report
+                title: Counting names
+                author: Doe, Jane
+                date: 2020
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+
+        let finished = cite_each(bib.iter(), BibliographyRequest::new(&apa, None, &[]));
+        let items = finished.bibliography.unwrap().items;
+        let keys: Vec<_> = items.iter().map(|item| item.key.as_str()).collect();
+        assert_eq!(keys, ["doe", "culture", "unicef", "who"]);
+
+        let culture = items[1].content.to_string();
+        assert!(culture.starts_with("Ministère de la Culture. (2020)"), "{culture}");
+        let who = items[3].content.to_string();
+        assert!(who.starts_with("The World Health Organization. (2020)"), "{who}");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn cjk_names_apa() {
+        let bib = from_yaml_str(
+            r#"
+            mixed:
+                type: Article
+                title: 日本語の書誌
+                author: ["山田, 太郎", "Doe, Jane", "さとう, はなこ"]
+                date: 2020
+                parent:
+                    type: Periodical
+                    title: 情報処理
+            "#,
+        )
+        .unwrap();
+
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+
+        let finished = cite_each(
+            [bib.get("mixed").unwrap()],
+            BibliographyRequest::new(&apa, None, &[]),
+        );
+        let item = finished.bibliography.unwrap().items[0].content.to_string();
+        assert!(item.starts_with("山田太郎, Doe, J., & さとうはなこ"), "{item}");
+
+        let mut citation = String::new();
+        finished.citations[0]
+            .citation
+            .write_buf(&mut citation, BufWriteFormat::Plain)
+            .unwrap();
+        assert!(citation.contains("山田"), "{citation}");
+        assert!(!citation.contains("太郎"), "{citation}");
+    }
+
+    #[test]
+    fn locator_labels() {
         let workspace = Path::new(env!("CARGO_MANIFEST_DIR"));
         let en_locale =
             fs::read_to_string(workspace.join("tests/data/locales-en-US.xml")).unwrap();
-        let en_locale = LocaleFile::from_xml(&en_locale).unwrap();
-
-        let yaml = fs::read_to_string(workspace.join("tests/data/basic.yml")).unwrap();
-        let bib = from_yaml_str(&yaml).unwrap();
-        let en_locale = [en_locale.into()];
-
-        for style_thing in fs::read_dir(workspace.join("styles/")).unwrap().take(100) {
-            let thing = style_thing.unwrap();
-            if thing.file_type().unwrap().is_dir() {
-                continue;
-            }
+        let locales = [LocaleFile::from_xml(&en_locale).unwrap().into()];
+
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <group delimiter=" ">
+                            <label variable="locator" form="short"/>
+                            <text variable="locator"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
 
-            let path = thing.path();
-            let extension = path.extension();
-            if let Some(extension) = extension {
-                if extension.to_str() != Some("csl") {
-                    continue;
-                }
-            } else {
-                continue;
-            }
+        let bib = from_yaml_str(
+            r#"
+            dict:
+                type: Reference
+                title: A Dictionary
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("dict").unwrap();
 
-            println!("testing {path:?}");
-            let style = fs::read_to_string(path).unwrap();
-            let style = IndependentStyle::from_xml(&style).unwrap();
+        let cite = |locator: Locator, value: &str| {
             let mut driver = BibliographyDriver::new();
+            driver.citation(CitationRequest::from_items(
+                vec![CitationItem::with_locator(
+                    entry,
+                    Some(SpecificLocator(locator, LocatorPayload::Str(value))),
+                )],
+                &style,
+                &locales,
+            ));
+            let finished =
+                driver.finish(BibliographyRequest::new(&style, None, &locales));
+            format!("{:#}", finished.citations[0].citation)
+        };
 
-            for n in (0..bib.len()).step_by(2) {
-                let items = vec![
-                    CitationItem::with_entry(bib.nth(n).unwrap()),
-                    CitationItem::with_entry(bib.nth(n + 1).unwrap()),
-                ];
-                driver.citation(CitationRequest::new(
-                    items, &style, None, &en_locale, None,
-                ));
-            }
+        assert_eq!(cite(Locator::Page, "5"), "p. 5");
+        assert_eq!(cite(Locator::Page, "5-7"), "pp. 5–7");
+        assert_eq!(cite(Locator::Paragraph, "14, 16"), "paras. 14, 16");
+        assert_eq!(cite(Locator::SubVerbo, "bank"), "s.v. bank");
+        assert_eq!(cite(Locator::SubVerbo, "bank, river"), "s.vv. bank, river");
+        assert_eq!(cite(Locator::SubVerbo, "well-being"), "s.v. well-being");
+    }
 
-            driver.finish(BibliographyRequest {
-                style: &style,
-                locale: None,
-                locale_files: &en_locale,
-            });
+    #[test]
+    fn multiple_locators() {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let en_locale =
+            fs::read_to_string(workspace.join("tests/data/locales-en-US.xml")).unwrap();
+        let locales = [LocaleFile::from_xml(&en_locale).unwrap().into()];
+
+        let style = test_style_with(
+            r#"class="note""#,
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <choose>
+                            <if position="ibid-with-locator">
+                                <text value="ibid with locator"/>
+                            </if>
+                            <else-if position="ibid">
+                                <text value="ibid"/>
+                            </else-if>
+                            <else>
+                                <text variable="title"/>
+                            </else>
+                        </choose>
+                        <group delimiter=" " prefix=", ">
+                            <label variable="locator" form="short"/>
+                            <text variable="locator"/>
+                        </group>
+                        <choose>
+                            <if locator="chapter">
+                                <text value="(with chapter)" prefix=" "/>
+                            </if>
+                        </choose>
+                    </layout>
+                </citation>"#,
+        );
 
-            // for cite in finished.citations {
-            //     println!("{}", cite.citation.to_string(BufWriteFormat::Plain))
-            // }
+        let bib = from_yaml_str(
+            r#"
+            treatise:
+                type: Book
+                title: Treatise
+            "#,
+        )
+        .unwrap();
+        let entry = bib.get("treatise").unwrap();
 
-            // if let Some(bib) = finished.bibliography {
-            //     for (prefix, item) in bib.items {
-            //         if let Some(prefix) = prefix {
-            //             print!("{} ", prefix.to_string(BufWriteFormat::Plain))
-            //         }
-            //         println!("{}", item.to_string(BufWriteFormat::Html))
-            //     }
-            // }
+        let book = SpecificLocator(Locator::Book, LocatorPayload::Str("3"));
+        let chapter = SpecificLocator(Locator::Chapter, LocatorPayload::Str("2"));
+        let paragraphs = SpecificLocator(Locator::Paragraph, LocatorPayload::Str("7-9"));
+
+        let mut driver = BibliographyDriver::new();
+        for locators in
+            [vec![book, chapter], vec![book, chapter], vec![book, paragraphs], vec![book]]
+        {
+            driver.citation(CitationRequest::new(
+                vec![CitationItem::with_locators(entry, locators)],
+                &style,
+                None,
+                &locales,
+                Some(1),
+            ));
         }
+
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &locales));
+        let actual: Vec<_> = finished
+            .citations
+            .iter()
+            .map(|c| format!("{:#}", c.citation))
+            .collect();
+
+        assert_eq!(
+            actual,
+            [
+                "Treatise, bk. 3, chap. 2 (with chapter)",
+                "ibid, bk. 3, chap. 2 (with chapter)",
+                "ibid with locator, bk. 3, paras. 7–9",
+                "ibid with locator, bk. 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn multiple_locators_in_macro() {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let en_locale =
+            fs::read_to_string(workspace.join("tests/data/locales-en-US.xml")).unwrap();
+        let locales = [LocaleFile::from_xml(&en_locale).unwrap().into()];
+
+        let style = test_style(
+            r#"<macro name="locator">
+                    <label variable="locator" form="long" suffix=" "/>
+                    <text variable="locator" prefix="[" suffix="]"/>
+                </macro>
+                <citation>
+                    <layout>
+                        <group delimiter="; ">
+                            <text variable="title"/>
+                            <text macro="locator"/>
+                        </group>
+                    </layout>
+                </citation>"#,
+        );
+
+        let bib = from_yaml_str(
+            r#"
+            treatise:
+                type: Book
+                title: Treatise
+            "#,
+        )
+        .unwrap();
+
+        let mut driver = BibliographyDriver::new();
+        driver.citation(CitationRequest::from_items(
+            vec![CitationItem::with_locators(
+                bib.get("treatise").unwrap(),
+                vec![
+                    SpecificLocator(Locator::Book, LocatorPayload::Str("3")),
+                    SpecificLocator(Locator::Chapter, LocatorPayload::Str("2")),
+                ],
+            )],
+            &style,
+            &locales,
+        ));
+
+        // Each locator gets the label form, affixes, and delimiter of the
+        // style.
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &locales));
+        assert_eq!(
+            format!("{:#}", finished.citations[0].citation),
+            "Treatise; book [3]; chapter [2]"
+        );
     }
 
     #[test]
@@ -2989,6 +8918,22 @@ mod tests {
         assert_eq!(yield_year(0, true), "1BC");
         assert_eq!(yield_year(-1, true), "2BC");
         assert_eq!(yield_year(1, true), "1AD");
+
+        for (year, long, short) in [
+            (5, "5AD", "5AD"),
+            (95, "95AD", "95AD"),
+            (985, "985AD", "985AD"),
+            (1005, "1005", "05"),
+            (2005, "2005", "05"),
+            (-44, "45BC", "45BC"),
+        ] {
+            assert_eq!(yield_year(year, false), long);
+            assert_eq!(yield_year(year, true), short);
+        }
+
+        let mut s = String::new();
+        write_year_with_eras(-44, false, [" BCE", " CE"], &mut s).unwrap();
+        assert_eq!(s, "45 BCE");
     }
 
     #[test]
@@ -3023,11 +8968,7 @@ mod tests {
             ));
         }
 
-        let finished = driver.finish(BibliographyRequest {
-            style: &alphanumeric,
-            locale: None,
-            locale_files: &[],
-        });
+        let finished = driver.finish(BibliographyRequest::new(&alphanumeric, None, &[]));
 
         let mut c1 = String::new();
         let mut c2 = String::new();
@@ -3045,6 +8986,58 @@ mod tests {
         assert_eq!(c2, "[Che+21b]");
     }
 
+    #[test]
+    #[cfg(feature = "archive")]
+    fn citation_labels() {
+        let bib = from_yaml_str(
+            r#"
+            knuth:
+                type: Book
+                title: The TeXbook
+                author: Knuth, Donald E.
+                date: 1984
+                citation-label: TeXbook
+            doe:
+                type: Article
+                title: On labels
+                author: Doe, Jane
+                date: 2001
+            roe:
+                type: Article
+                title: On more labels
+                author: Roe, Richard
+                date: 2001
+                label: Doe01
+            "#,
+        )
+        .unwrap();
+
+        let alphanumeric = archive::ArchivedStyle::Alphanumeric.get();
+        let citationberg::Style::Independent(alphanumeric) = alphanumeric else {
+            unreachable!()
+        };
+
+        let finished =
+            cite_each(bib.iter(), BibliographyRequest::new(&alphanumeric, None, &[]));
+        let cites: Vec<_> =
+            finished.citations.iter().map(|c| c.citation.to_string()).collect();
+        assert_eq!(cites, ["[TeXbook]", "[Doe01a]", "[Doe01b]"]);
+
+        let rendered: Vec<_> = finished
+            .bibliography
+            .unwrap()
+            .items
+            .iter()
+            .map(|item| {
+                let first = item.first_field.as_ref().map(ToString::to_string);
+                first.unwrap_or_default() + &item.content.to_string()
+            })
+            .collect();
+        assert!(rendered.iter().any(|r| r.contains("[TeXbook]")), "{rendered:?}");
+        assert!(rendered.iter().any(|r| r.contains("[Doe01a]")), "{rendered:?}");
+        assert!(rendered.iter().any(|r| r.contains("[Doe01b]")), "{rendered:?}");
+    }
+
     #[test]
     #[cfg(feature = "archive")]
     /// See https://github.com/typst/hayagriva/issues/243
@@ -3089,11 +9082,7 @@ mod tests {
             ));
         }
 
-        let finished = driver.finish(BibliographyRequest {
-            style: &apa,
-            locale: None,
-            locale_files: &[],
-        });
+        let finished = driver.finish(BibliographyRequest::new(&apa, None, &[]));
 
         let mut c1 = String::new();
         let mut c2 = String::new();
@@ -3153,11 +9142,7 @@ mod tests {
             None,
         ));
 
-        let finished = driver.finish(BibliographyRequest {
-            style: &alphanumeric,
-            locale: None,
-            locale_files: &[],
-        });
+        let finished = driver.finish(BibliographyRequest::new(&alphanumeric, None, &[]));
 
         let mut c1 = String::new();
         let mut c2 = String::new();
@@ -3212,11 +9197,7 @@ mod tests {
             ));
         }
 
-        let finished = driver.finish(BibliographyRequest {
-            style: &style,
-            locale: None,
-            locale_files: &[],
-        });
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
 
         let actual = finished
             .citations
@@ -3230,4 +9211,339 @@ mod tests {
 
         assert_eq!(actual, ["(33)", "()", "(34)"]);
     }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn meta_for_titles_and_names() {
+        let yaml = r#"
+doe:
+    type: article
+    title: Tiny Title
+    author: ["Doe, Jane", "Roe, John"]
+    date: 2020
+    parent:
+        type: periodical
+        title: Journal of Things
+"#;
+        let library = from_yaml_str(yaml).unwrap();
+        let apa = archive::ArchivedStyle::AmericanPsychologicalAssociation.get();
+        let citationberg::Style::Independent(apa) = apa else { unreachable!() };
+        let locales = archive::locales();
+
+        let finished = cite_each(
+            [library.get("doe").unwrap()],
+            BibliographyRequest::new(&apa, None, &locales),
+        );
+        let content = &finished.bibliography.unwrap().items[0].content;
+        let meta_text = |meta| format!("{:#}", content.find_meta(meta).unwrap());
+
+        let title = meta_text(ElemMeta::Title);
+        assert!(title.contains("Tiny Title") && !title.contains("Journal"), "{title}");
+        let container = meta_text(ElemMeta::ContainerTitle);
+        assert!(
+            container.contains("Journal of Things") && !container.contains("Tiny"),
+            "{container}"
+        );
+        let author = meta_text(ElemMeta::Name(NameVariable::Author, 1));
+        assert!(author.contains("Roe") && !author.contains("Doe"), "{author}");
+        assert!(meta_text(ElemMeta::Date).contains("2020"));
+    }
+
+    #[test]
+    fn simplify_merges_text() {
+        let f = Formatting::default();
+        let italic = Formatting { font_style: citationberg::FontStyle::Italic, ..f };
+        let inline = |children| Elem {
+            children: ElemChildren(children),
+            display: None,
+            meta: None,
+        };
+        let children = ElemChildren(vec![
+            f.add_text("a".into()).into(),
+            inline(vec![
+                f.add_text("b".into()).into(),
+                inline(vec![f.add_text("c".into()).into()]).into(),
+            ])
+            .into(),
+            f.add_text("d".into()).into(),
+            italic.add_text("e".into()).into(),
+        ]);
+
+        assert_eq!(
+            simplify_children(children),
+            ElemChildren(vec![
+                f.add_text("abcd".into()).into(),
+                italic.add_text("e".into()).into(),
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "archive", feature = "rayon"))]
+    fn parallel_bibliography() {
+        let data = fs::read_to_string("tests/data/basic.yml").unwrap();
+        let library = from_yaml_str(&data).unwrap();
+        let style = archive::ArchivedStyle::ChicagoFullnotes.get();
+        let citationberg::Style::Independent(style) = style else { unreachable!() };
+        let locales = archive::locales();
+
+        let render = || {
+            let finished = cite_each(
+                library.iter(),
+                BibliographyRequest::new(&style, None, &locales),
+            );
+            finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| {
+                    let mut s = String::new();
+                    item.write_buf(&mut s, BufWriteFormat::Html).unwrap();
+                    (item.key.clone(), s)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // A pool with a single thread renders the entries one after another.
+        let serial = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(render);
+        let parallel = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap()
+            .install(render);
+
+        assert_eq!(serial.len(), library.len());
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn split_bibliography() {
+        let style = test_style(
+            r#"<citation>
+                    <layout>
+                        <text variable="citation-number" prefix="[" suffix="]"/>
+                    </layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <group delimiter=" ">
+                            <text variable="citation-number" prefix="[" suffix="]"/>
+                            <text variable="title"/>
+                        </group>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let library = from_yaml_str(
+            r#"
+            book:
+                type: Book
+                title: Book
+            article:
+                type: Article
+                title: Article
+                parent:
+                    type: Periodical
+                    title: Journal
+            thesis:
+                type: Thesis
+                title: Thesis
+            paper:
+                type: Article
+                title: Paper
+                parent:
+                    type: Periodical
+                    title: Journal
+            "#,
+        )
+        .unwrap();
+
+        let selector = crate::Selector::parse("article > periodical").unwrap();
+        let (articles, rest): (Vec<_>, Vec<_>) = library
+            .keys()
+            .map(ToString::to_string)
+            .partition(|key| selector.matches(library.get(key).unwrap()));
+
+        let render = |keys: Option<&[String]>| {
+            let mut driver = BibliographyDriver::new();
+            for entry in library.iter() {
+                driver.citation(CitationRequest::from_items(
+                    vec![CitationItem::with_entry(entry)],
+                    &style,
+                    &[],
+                ));
+            }
+
+            let mut request = BibliographyRequest::new(&style, None, &[]);
+            if let Some(keys) = keys {
+                request = request.with_keys(keys);
+            }
+
+            let finished = driver.finish(request);
+            let citations: Vec<_> =
+                finished.citations.iter().map(|c| c.citation.to_string()).collect();
+            let items: Vec<_> = finished
+                .bibliography
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.content.to_string())
+                .collect();
+            (citations, items)
+        };
+
+        let (citations, all) = render(None);
+        assert_eq!(citations, ["[1]", "[2]", "[3]", "[4]"]);
+        assert_eq!(all, ["[1] Book", "[2] Article", "[3] Thesis", "[4] Paper"]);
+
+        // The citations and numbers stay the same in every section.
+        let (cited, items) = render(Some(articles.as_slice()));
+        assert_eq!(cited, citations);
+        assert_eq!(items, ["[2] Article", "[4] Paper"]);
+        let (cited, items) = render(Some(rest.as_slice()));
+        assert_eq!(cited, citations);
+        assert_eq!(items, ["[1] Book", "[3] Thesis"]);
+        let (_, items) = render(Some(&[][..]));
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn bibliography_back_references() {
+        let style = test_style_with(
+            r#"class="note""#,
+            r#"<citation>
+                    <layout delimiter="; ">
+                        <text variable="title"/>
+                    </layout>
+                </citation>
+                <bibliography>
+                    <sort>
+                        <key variable="title"/>
+                    </sort>
+                    <layout>
+                        <text variable="title"/>
+                    </layout>
+                </bibliography>"#,
+        );
+
+        let library = from_yaml_str(
+            r#"
+            zeta:
+                type: Book
+                title: Zeta
+            alpha:
+                type: Book
+                title: Alpha
+            hidden:
+                type: Book
+                title: Hidden
+            "#,
+        )
+        .unwrap();
+        let zeta = library.get("zeta").unwrap();
+        let alpha = library.get("alpha").unwrap();
+        let hidden = library.get("hidden").unwrap();
+
+        let mut driver = BibliographyDriver::new();
+        let cites = [
+            vec![CitationItem::with_entry(zeta)],
+            vec![CitationItem::with_entry(alpha), CitationItem::with_entry(zeta)],
+            vec![CitationItem::new(hidden, None, None, true, None)],
+            vec![CitationItem::with_entry(zeta)],
+            vec![CitationItem::with_entry(hidden)],
+        ];
+        for items in cites {
+            driver.citation(CitationRequest::from_items(items, &style, &[]));
+        }
+
+        let finished = driver.finish(BibliographyRequest::new(&style, None, &[]));
+        let items: Vec<_> = finished
+            .bibliography
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| (item.key, item.cited_in))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("alpha".to_string(), vec![1]),
+                ("hidden".to_string(), vec![4]),
+                ("zeta".to_string(), vec![0, 1, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn joined_bibliography() {
+        let library = from_yaml_str(
+            r#"
+            alpha:
+                type: Book
+                title: Alpha
+            beta:
+                type: Book
+                title: Beta
+            "#,
+        )
+        .unwrap();
+
+        let join = |attrs: &str, layout: &str| {
+            let style = test_style(&format!(
+                r#"<citation>
+                        <layout>
+                            <text variable="title"/>
+                        </layout>
+                    </citation>
+                    <bibliography {attrs}>
+                        <layout suffix=".">
+                            {layout}
+                        </layout>
+                    </bibliography>"#
+            ));
+
+            let bibliography =
+                cite_each(library.iter(), BibliographyRequest::new(&style, None, &[]))
+                    .bibliography
+                    .unwrap();
+            let joined = bibliography.join();
+            let mut html = String::new();
+            joined.write_buf(&mut html, BufWriteFormat::Html).unwrap();
+            (bibliography.entry_spacing, format!("{joined:#}").trim().to_string(), html)
+        };
+
+        let title = r#"<text variable="title"/>"#;
+        let (spacing, plain, _) = join("", title);
+        assert_eq!((spacing, plain.as_str()), (1, "Alpha.\n\nBeta."));
+        let (spacing, plain, html) = join(r#"entry-spacing="0""#, title);
+        assert_eq!((spacing, plain.as_str()), (0, "Alpha.\nBeta."));
+        assert_eq!(
+            html,
+            "<div class=\"csl-block\">Alpha.</div><div class=\"csl-block\">Beta.</div>"
+        );
+        let (spacing, plain, _) = join(r#"entry-spacing="2""#, title);
+        assert_eq!((spacing, plain.as_str()), (2, "Alpha.\n\n\nBeta."));
+
+        // The first field is kept apart from the rest of the item.
+        let (_, plain, html) = join(
+            r#"entry-spacing="0" second-field-align="flush""#,
+            r#"<text variable="citation-number" prefix="[" suffix="]"/>
+            <text variable="title"/>"#,
+        );
+        assert_eq!(plain, "[1] Alpha.\n[2] Beta.");
+        assert_eq!(
+            html,
+            concat!(
+                "<div class=\"csl-block\"><div class=\"csl-left-margin\">[1]</div> ",
+                "<div class=\"csl-right-inline\">Alpha.</div></div>",
+                "<div class=\"csl-block\"><div class=\"csl-left-margin\">[2]</div> ",
+                "<div class=\"csl-right-inline\">Beta.</div></div>",
+            )
+        );
+    }
 }
@@ -1,4 +1,3 @@
-use std::borrow::Cow;
 use std::fmt::Write;
 use std::str::FromStr;
 
@@ -16,11 +15,16 @@ use citationberg::{TermForm, TextTarget};
 
 use crate::csl::taxonomy::{NumberVariableResult, PageVariableResult};
 use crate::lang::{Case, SentenceCase, TitleCase};
-use crate::types::{ChunkedString, Date, MaybeTyped, Numeric};
+use crate::types::{Date, MaybeTyped, Numeric, StringChunk};
 use crate::PageRanges;
 
-use super::taxonomy::{EntryLike, NumberOrPageVariableResult};
-use super::{write_year, Context, ElemMeta, IbidState, SpecialForm, UsageInfo};
+use super::taxonomy::{
+    EntryLike, NumberOrPageVariableResult, StandardValue, ARXIV_PREFIX,
+};
+use super::{
+    write_year_with_eras, Context, ElemMeta, IbidState, LocaleOption, RenderDiagnostic,
+    SpecialForm, UsageInfo,
+};
 
 pub mod names;
 
@@ -61,30 +65,53 @@ impl RenderCsl for citationberg::Text {
         let cidx = ctx.push_case(self.text_case);
 
         match target {
-            ResolvedTextTarget::StandardVariable(var, val) => match var {
-                StandardVariable::URL => {
-                    let str = val.to_string();
-                    ctx.push_link(&val, str);
-                }
-                StandardVariable::DOI => {
-                    let url = format!("https://doi.org/{}", val.to_str());
-                    ctx.push_link(&val, url);
+            ResolvedTextTarget::StandardVariable(var, val) => {
+                let links = ctx.style.link_behaviour;
+                match var {
+                    StandardVariable::URL if links.link_urls() => {
+                        let str = val.to_string();
+                        ctx.push_link(&val, str);
+                    }
+                    StandardVariable::DOI if links.link_identifiers() => {
+                        let url = format!("https://doi.org/{}", val.to_str());
+                        ctx.push_link(&val, url);
+                    }
+                    StandardVariable::PMID if links.link_identifiers() => {
+                        let url = format!(
+                            "https://www.ncbi.nlm.nih.gov/pubmed/{}",
+                            val.to_str()
+                        );
+                        ctx.push_link(&val, url);
+                    }
+                    StandardVariable::PMCID if links.link_identifiers() => {
+                        let url = format!(
+                            "https://www.ncbi.nlm.nih.gov/pmc/articles/{}",
+                            val.to_str()
+                        );
+                        ctx.push_link(&val, url);
+                    }
+                    _ if ctx.instance.sorting => {
+                        let text = ctx.style.sort_text(
+                            ctx.instance.entry,
+                            var,
+                            val.to_string(),
+                            ctx.instance.locale.or(ctx.instance.term_locale),
+                        );
+                        ctx.push_str(&text);
+                    }
+                    _ => ctx.push_chunked(&val),
                 }
-                StandardVariable::PMID => {
+            }
+            ResolvedTextTarget::NumberVariable(var, n) => match n {
+                NumberVariableResult::Regular(MaybeTyped::String(s))
+                    if var == NumberVariable::Number
+                        && ctx.style.link_behaviour.link_identifiers()
+                        && s.starts_with(ARXIV_PREFIX) =>
+                {
                     let url =
-                        format!("https://www.ncbi.nlm.nih.gov/pubmed/{}", val.to_str());
-                    ctx.push_link(&val, url);
+                        format!("https://arxiv.org/abs/{}", &s[ARXIV_PREFIX.len()..]);
+                    ctx.push_link(&StringChunk::verbatim(s).into(), url);
                 }
-                StandardVariable::PMCID => {
-                    let url = format!(
-                        "https://www.ncbi.nlm.nih.gov/pmc/articles/{}",
-                        val.to_str()
-                    );
-                    ctx.push_link(&val, url);
-                }
-                _ => ctx.push_chunked(&val),
-            },
-            ResolvedTextTarget::NumberVariable(_, n) => match n {
                 NumberVariableResult::Regular(MaybeTyped::Typed(num))
                     if num.will_transform() =>
                 {
@@ -100,9 +127,7 @@ impl RenderCsl for citationberg::Text {
             ResolvedTextTarget::Macro(mac) => {
                 // Delimiters from ancestor delimiting elements are NOT applied within.
                 let idx = ctx.writing.push_delimiter(None);
-                for child in &mac.children {
-                    child.render(ctx);
-                }
+                for_each_locator(&mac.children, ctx, |child, ctx| child.render(ctx));
                 ctx.writing.pop_delimiter(idx);
             }
             ResolvedTextTarget::Term(s) => ctx.push_str(s),
@@ -134,6 +159,21 @@ impl RenderCsl for citationberg::Text {
                 {
                     Some(ElemMeta::CitationLabel)
                 }
+                TextTarget::Variable {
+                    var:
+                        Variable::Standard(
+                            StandardVariable::Title | StandardVariable::TitleShort,
+                        ),
+                    ..
+                } => Some(ElemMeta::Title),
+                TextTarget::Variable {
+                    var:
+                        Variable::Standard(
+                            StandardVariable::ContainerTitle
+                            | StandardVariable::ContainerTitleShort,
+                        ),
+                    ..
+                } => Some(ElemMeta::ContainerTitle),
                 TextTarget::Variable { .. } => Some(ElemMeta::Text),
                 _ => None,
             },
@@ -200,7 +240,12 @@ impl RenderCsl for citationberg::Text {
                     info = info.merge_child(child_info)
                 }
 
-                (will_print, UsageInfo { has_used_macros: will_print, ..info })
+                // A macro only counts as used if its own content would survive
+                // group suppression. Otherwise, terms from a macro whose
+                // variables are all empty would keep the enclosing group
+                // alive, even though the macro passes its variable usage up.
+                let used = will_print && info.should_render_group();
+                (used, UsageInfo { has_used_macros: used, ..info })
             }
             ResolvedTextTarget::Term(_) => (true, UsageInfo::default()),
             ResolvedTextTarget::Value(v) => (!v.is_empty(), UsageInfo::default()),
@@ -209,7 +254,7 @@ impl RenderCsl for citationberg::Text {
 }
 
 enum ResolvedTextTarget<'a, 'b> {
-    StandardVariable(StandardVariable, Cow<'a, ChunkedString>),
+    StandardVariable(StandardVariable, StandardValue<'a>),
     NumberVariable(NumberVariable, NumberVariableResult<'a>),
     PageVariable(PageVariableResult),
     Macro(&'a CslMacro),
@@ -265,7 +310,11 @@ impl<'a, 'b> ResolvedTextTarget<'a, 'b> {
                 ctx.style.get_macro(name).map(ResolvedTextTarget::Macro)
             }
             TextTarget::Term { term, form, plural } => {
-                ctx.term(*term, *form, *plural).map(ResolvedTextTarget::Term)
+                let resolved = ctx.term(*term, *form, *plural);
+                if resolved.is_none() {
+                    ctx.warn(|key| RenderDiagnostic::MissingTerm { key, term: *term });
+                }
+                resolved.map(ResolvedTextTarget::Term)
             }
             TextTarget::Value { val } => Some(ResolvedTextTarget::Value(val)),
         }
@@ -313,7 +362,14 @@ impl RenderCsl for citationberg::Number {
 
             Some(NumberOrPageVariableResult::Number(NumberVariableResult::Regular(
                 MaybeTyped::String(s),
-            ))) => ctx.push_str(&s),
+            ))) => {
+                ctx.push_str(&s);
+                ctx.warn(|key| RenderDiagnostic::UnparsableNumeric {
+                    key,
+                    variable: self.variable,
+                    value: s,
+                });
+            }
 
             Some(NumberOrPageVariableResult::Number(
                 NumberVariableResult::Transparent(n),
@@ -324,7 +380,12 @@ impl RenderCsl for citationberg::Number {
             }
 
             Some(NumberOrPageVariableResult::Page(MaybeTyped::String(s))) => {
-                ctx.push_str(&s)
+                ctx.push_str(&s);
+                ctx.warn(|key| RenderDiagnostic::UnparsableNumeric {
+                    key,
+                    variable: self.variable,
+                    value: s,
+                });
             }
 
             None => {}
@@ -432,6 +493,12 @@ fn label_pluralization(
         LabelPluralize::Always => true,
         LabelPluralize::Never => false,
         LabelPluralize::Contextual => match variable {
+            NumberVariableResult::Regular(MaybeTyped::String(s))
+                if label.variable
+                    == NumberOrPageVariable::Number(NumberVariable::Locator) =>
+            {
+                is_plural_locator(&s)
+            }
             NumberVariableResult::Regular(MaybeTyped::String(_)) => false,
             NumberVariableResult::Regular(MaybeTyped::Typed(n)) => {
                 if let NumberOrPageVariable::Number(v) = label.variable {
@@ -445,6 +512,21 @@ fn label_pluralization(
     }
 }
 
+/// Whether a locator that could not be parsed as a number, like `ix–xii` or
+/// `foo, bar`, refers to multiple items.
+fn is_plural_locator(locator: &str) -> bool {
+    // Hyphens only delimit ranges if the locator does not contain words.
+    let hyphen_delimits = locator.chars().filter(|c| c.is_alphabetic()).all(|c| {
+        matches!(c.to_ascii_lowercase(), 'i' | 'v' | 'x' | 'l' | 'c' | 'd' | 'm')
+    });
+
+    locator
+        .split(|c| matches!(c, ',' | '&' | '–') || (hyphen_delimits && c == '-'))
+        .filter(|part| !part.trim().is_empty())
+        .nth(1)
+        .is_some()
+}
+
 impl RenderCsl for citationberg::Label {
     fn render<T: EntryLike>(&self, ctx: &mut Context<T>) {
         if !self.will_have_info(ctx).0 {
@@ -514,7 +596,8 @@ impl RenderCsl for citationberg::Label {
                 .instance
                 .cite_props
                 .speculative
-                .locator
+                .locators
+                .first()
                 .is_some_and(|l| l.0 == Locator::Custom)
         {
             return (false, UsageInfo::default());
@@ -583,28 +666,7 @@ impl RenderCsl for citationberg::Date {
         let Some(date) = ctx.resolve_date_variable(variable) else { return };
 
         if ctx.instance.sorting {
-            let year;
-            let mut month = false;
-            let mut day = false;
-
-            if self.is_localized() {
-                match self.parts {
-                    Some(DateParts::Year) => year = true,
-                    Some(DateParts::YearMonth) => {
-                        year = true;
-                        month = true;
-                    }
-                    Some(DateParts::YearMonthDay) | None => {
-                        year = true;
-                        month = true;
-                        day = true;
-                    }
-                }
-            } else {
-                year = self.date_part.iter().any(|i| i.name == DatePartName::Year);
-                month = self.date_part.iter().any(|i| i.name == DatePartName::Month);
-                day = self.date_part.iter().any(|i| i.name == DatePartName::Day);
-            };
+            let (year, month, day) = date_sort_parts(self);
 
             if year {
                 write!(ctx, "{:04}", date.year).unwrap();
@@ -658,6 +720,19 @@ impl RenderCsl for citationberg::Date {
                 _ => {}
             }
 
+            let missing = match part.name {
+                DatePartName::Year => false,
+                DatePartName::Month => date.month.is_none(),
+                DatePartName::Day => date.day.is_none(),
+            };
+            if missing {
+                ctx.warn(|key| RenderDiagnostic::MissingDatePart {
+                    key,
+                    variable,
+                    part: part.name,
+                });
+            }
+
             let cursor = ctx.writing.len();
             if !last_was_empty {
                 if let Some(delim) = &self.delimiter {
@@ -695,29 +770,46 @@ impl RenderCsl for citationberg::Date {
     }
 
     fn will_have_info<T: EntryLike>(&self, ctx: &mut Context<T>) -> (bool, UsageInfo) {
-        let suppressing = ctx.writing.suppress_queried_variables;
-        ctx.writing.stop_suppressing_queried_variables();
         let Some(variable) = self.variable else {
             return (false, UsageInfo { has_vars: true, ..Default::default() });
         };
 
+        if !self.will_render(ctx, variable.into()) {
+            return (false, UsageInfo::default());
+        }
+
+        // If suppress_queried_variables is set to true, we need to perform a
+        // silent lookup, otherwise we need to perform a regular lookup.
+        let suppressing = ctx.writing.suppress_queried_variables;
+        ctx.writing.stop_suppressing_queried_variables();
+        let has_non_empty_vars = ctx.resolve_date_variable(variable).is_some();
         if suppressing {
             ctx.writing.start_suppressing_queried_variables();
         }
 
-        if !self.will_render(ctx, variable.into()) {
-            (false, UsageInfo::default())
-        } else {
-            let has_non_empty_vars = ctx.resolve_date_variable(variable).is_some();
-            (
+        (
+            has_non_empty_vars,
+            UsageInfo {
+                has_vars: true,
                 has_non_empty_vars,
-                UsageInfo {
-                    has_vars: true,
-                    has_non_empty_vars,
-                    ..Default::default()
-                },
-            )
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// Which parts of a date (year, month, and day) a date element contributes
+/// to a sort key.
+pub(crate) fn date_sort_parts(date: &citationberg::Date) -> (bool, bool, bool) {
+    if date.is_localized() {
+        match date.parts {
+            Some(DateParts::Year) => (true, false, false),
+            Some(DateParts::YearMonth) => (true, true, false),
+            Some(DateParts::YearMonthDay) | None => (true, true, true),
         }
+    } else {
+        let has = |name: DatePartName| date.date_part.iter().any(|i| i.name == name);
+        (has(DatePartName::Year), has(DatePartName::Month), has(DatePartName::Day))
     }
 }
 
@@ -764,17 +856,10 @@ fn render_date_part<T: EntryLike>(
                 write!(ctx, "{val:02}").unwrap();
             }
             DateStrongAnyForm::Day(DateDayForm::Ordinal)
-                if val != 1
+                if val == 1
                     || !ctx
                         .style
-                        .lookup_locale(|l| {
-                            Some(
-                                l.style_options
-                                    .and_then(|o| o.limit_day_ordinals_to_day_1)
-                                    .unwrap_or_default(),
-                            )
-                        })
-                        .unwrap_or_default() =>
+                        .locale_option(LocaleOption::LimitDayOrdinalsToDay1, None) =>
             {
                 let gender = date
                     .month
@@ -812,7 +897,12 @@ fn render_date_part<T: EntryLike>(
                 }
             }
             DateStrongAnyForm::Year(brevity) => {
-                write_year(val, brevity == LongShortForm::Short, ctx).unwrap();
+                let era = |term: OtherTerm, default| {
+                    ctx.term(term.into(), TermForm::default(), false).unwrap_or(default)
+                };
+                let eras = [era(OtherTerm::Bc, "BC"), era(OtherTerm::Ad, "AD")];
+                write_year_with_eras(val, brevity == LongShortForm::Short, eras, ctx)
+                    .unwrap();
             }
         }
     }
@@ -839,7 +929,7 @@ fn render_year_suffix_implicitly<T: EntryLike>(ctx: &mut Context<T>) {
             LongShortForm::default(),
             StandardVariable::YearSuffix,
         ) {
-            ctx.push_chunked(year_suffix.as_ref());
+            ctx.push_chunked(&year_suffix);
         }
     }
 }
@@ -909,38 +999,150 @@ fn render_with_delimiter<T: EntryLike>(
     let delimiter = ctx.writing.delimiters.last().clone();
 
     let mut first = true;
-    let mut loc = None;
 
-    for child in children {
+    for_each_locator(children, ctx, |child, ctx| {
         // Do not render the child if it will not print anything.
         if !child.will_have_info(ctx).0 {
-            continue;
+            return;
         }
 
-        if !first {
-            if let Some(delim) = &delimiter {
-                let prev_loc = std::mem::take(&mut loc);
-
-                if let Some(prev_loc) = prev_loc {
-                    ctx.commit_elem(prev_loc, None, None);
-                }
-
-                loc = Some(ctx.push_elem(citationberg::Formatting::default()));
+        let delim_loc = match &delimiter {
+            Some(delim) if !first => {
+                let loc = ctx.push_elem(citationberg::Formatting::default());
                 ctx.push_str(delim);
+                Some(loc)
             }
-        }
-        first = false;
+            _ => None,
+        };
 
+        // The usage info can only tell us whether a variable is set, not
+        // whether the child will actually print any of it (e.g. a date
+        // without the requested date parts). Only keep the delimiter if the
+        // child wrote something other than whitespace or discarded affixes.
         let pos = ctx.push_elem(citationberg::Formatting::default());
         child.render(ctx);
-        ctx.commit_elem(pos, None, None);
+        let has_content = ctx.writing.has_content_since(&(pos, 0));
+
+        if has_content {
+            ctx.commit_elem(pos, None, None);
+            first = false;
+        } else {
+            ctx.discard_elem(pos);
+        }
+
+        if let Some(loc) = delim_loc {
+            if has_content {
+                ctx.commit_elem(loc, None, None);
+            } else {
+                ctx.discard_elem(loc);
+            }
+        }
+    });
+}
+
+/// Call `f` for each of the `children`. If the cite has multiple locators,
+/// runs of children that render the locator and no other variable are
+/// repeated for each locator, so that every locator gets the label, affixes,
+/// and delimiters of the style.
+fn for_each_locator<T: EntryLike>(
+    children: &[LayoutRenderingElement],
+    ctx: &mut Context<T>,
+    mut f: impl FnMut(&LayoutRenderingElement, &mut Context<T>),
+) {
+    if ctx.instance.cite_props.speculative.locators.len() < 2 {
+        for child in children {
+            f(child, ctx);
+        }
+        return;
     }
 
-    if let Some(loc) = loc {
-        ctx.commit_elem(loc, None, None);
+    let mut i = 0;
+    while i < children.len() {
+        let mut end = i;
+        while end < children.len()
+            && locator_use(&children[end], ctx) == LocatorUse::Locator
+        {
+            end += 1;
+        }
+
+        if end == i {
+            f(&children[i], ctx);
+            i += 1;
+            continue;
+        }
+
+        // While a run is repeated, each pass only sees its own locator, so
+        // nested elements do not repeat again.
+        let locators = std::mem::take(&mut ctx.instance.cite_props.speculative.locators);
+        for &locator in &locators {
+            ctx.instance.cite_props.speculative.locators = vec![locator];
+            for child in &children[i..end] {
+                f(child, ctx);
+            }
+        }
+        ctx.instance.cite_props.speculative.locators = locators;
+        i = end;
     }
 }
 
+/// Which variables an element may render, ordered by precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LocatorUse {
+    /// No variables, only terms and values.
+    Nothing,
+    /// Only the locator.
+    Locator,
+    /// Variables other than the locator.
+    Other,
+}
+
+fn locator_use<T: EntryLike>(
+    elem: &LayoutRenderingElement,
+    ctx: &mut Context<T>,
+) -> LocatorUse {
+    fn of_children<T: EntryLike>(
+        children: &[LayoutRenderingElement],
+        ctx: &mut Context<T>,
+    ) -> LocatorUse {
+        children
+            .iter()
+            .map(|child| locator_use(child, ctx))
+            .max()
+            .unwrap_or(LocatorUse::Nothing)
+    }
+
+    let of_variable = |var: Variable| {
+        if var == Variable::Number(NumberVariable::Locator) {
+            LocatorUse::Locator
+        } else {
+            LocatorUse::Other
+        }
+    };
+
+    match elem {
+        LayoutRenderingElement::Text(text) => match &text.target {
+            TextTarget::Variable { var, .. } => of_variable(*var),
+            TextTarget::Macro { name, .. } => match ctx.style.get_macro(name) {
+                Some(mac) => of_children(&mac.children, ctx),
+                None => LocatorUse::Nothing,
+            },
+            _ => LocatorUse::Nothing,
+        },
+        LayoutRenderingElement::Number(num) => of_variable(num.variable.into()),
+        LayoutRenderingElement::Label(label) => of_variable(label.variable.into()),
+        LayoutRenderingElement::Date(_) | LayoutRenderingElement::Names(_) => {
+            LocatorUse::Other
+        }
+        LayoutRenderingElement::Group(group) => of_children(&group.children, ctx),
+        LayoutRenderingElement::Choose(choose) => {
+            choose_children(choose, ctx, of_children).unwrap_or(LocatorUse::Nothing)
+        }
+    }
+}
+
+/// The kinds of conditions of a `cs:if` or `cs:else-if` branch, in the order
+/// in which [`BranchConditionIter`] evaluates them. Advancing past the last
+/// kind, [`Self::Variable`], ends the iteration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BranchConditionPos {
     Disambiguate,
@@ -1015,7 +1217,7 @@ impl<T: EntryLike> Iterator for BranchConditionIter<'_, '_, T> {
     fn next(&mut self) -> Option<Self::Item> {
         match self.pos {
             BranchConditionPos::Disambiguate => {
-                self.pos.next();
+                self.next_case();
                 if let Some(d) = self.cond.disambiguate {
                     Some(d == self.ctx.should_disambiguate())
                 } else {
@@ -1036,7 +1238,7 @@ impl<T: EntryLike> Iterator for BranchConditionIter<'_, '_, T> {
                         Variable::Standard(var) => self
                             .ctx
                             .resolve_standard_variable(LongShortForm::default(), var)
-                            .map(|v| Numeric::from_str(&v.to_string()).is_ok())
+                            .map(|v| Numeric::from_str(&v.to_str()).is_ok())
                             .unwrap_or_default(),
                         Variable::Number(var) => matches!(
                             self.ctx.resolve_number_variable(var),
@@ -1080,8 +1282,13 @@ impl<T: EntryLike> Iterator for BranchConditionIter<'_, '_, T> {
                     self.idx += 1;
 
                     Some(
-                        self.ctx.instance.cite_props.speculative.locator.map(|l| l.0)
-                            == Some(loc),
+                        self.ctx
+                            .instance
+                            .cite_props
+                            .speculative
+                            .locators
+                            .iter()
+                            .any(|l| l.0 == loc),
                     )
                 } else {
                     self.next_case();
@@ -1137,6 +1344,7 @@ impl<T: EntryLike> Iterator for BranchConditionIter<'_, '_, T> {
             }
             BranchConditionPos::Variable => {
                 if let Some(vars) = &self.cond.variable {
+                    // This is the last case, so there is nothing to advance to.
                     if self.idx >= vars.len() {
                         return None;
                     }
@@ -1149,9 +1357,7 @@ impl<T: EntryLike> Iterator for BranchConditionIter<'_, '_, T> {
                             let val = self
                                 .ctx
                                 .resolve_standard_variable(LongShortForm::default(), s);
-                            val.is_some_and(|s| {
-                                !s.to_string().chars().all(char::is_whitespace)
-                            })
+                            val.is_some_and(|s| !s.is_blank())
                         }
                         Variable::Number(n) => {
                             let val = self.ctx.resolve_number_variable(n);
@@ -1286,9 +1492,7 @@ impl RenderCsl for citationberg::Layout {
     fn render<T: EntryLike>(&self, ctx: &mut Context<T>) {
         let format_idx = ctx.push_format(self.to_formatting());
         let delim_idx = ctx.writing.push_delimiter(self.delimiter.clone());
-        for e in &self.elements {
-            e.render(ctx);
-        }
+        for_each_locator(&self.elements, ctx, |e, ctx| e.render(ctx));
         ctx.writing.pop_delimiter(delim_idx);
         ctx.pop_format(format_idx);
     }
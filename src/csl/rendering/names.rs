@@ -11,7 +11,10 @@ use citationberg::{
 use citationberg::{DisambiguationRule, TermForm};
 
 use crate::csl::taxonomy::EntryLike;
-use crate::csl::{Context, DisambiguateState, ElemMeta, SpecialForm, UsageInfo};
+use crate::csl::{
+    Context, DisambiguateState, ElemMeta, NameCap, NameOverflow, PreferredScript,
+    SpecialForm, UsageInfo,
+};
 use crate::types::Person;
 
 use super::{render_label_with_var, RenderCsl};
@@ -208,39 +211,32 @@ fn renders_given_special_form<T: EntryLike>(
 
 impl RenderCsl for Names {
     fn render<T: EntryLike>(&self, ctx: &mut Context<T>) {
-        // The editor and translator variables need to be merged if they are
-        // both present and identical.
-        let people: Vec<(Vec<Cow<'_, Person>>, NameVariable)> = if self.variable.len()
-            == 2
-            && self.variable.contains(&NameVariable::Editor)
-            && self.variable.contains(&NameVariable::Translator)
-            && ctx
-                .term(NameVariable::EditorTranslator.into(), TermForm::default(), false)
-                .is_some()
-        {
-            let editors = ctx.resolve_name_variable(NameVariable::Editor);
-            let translators = ctx.resolve_name_variable(NameVariable::Translator);
-
-            let mut res = Vec::new();
-            if !editors.is_empty() && editors == translators {
-                res.push((editors, NameVariable::EditorTranslator))
-            } else {
-                if !editors.is_empty() {
-                    res.push((editors, NameVariable::Editor));
-                }
+        let mut people: Vec<(Vec<Cow<'_, Person>>, NameVariable)> = self
+            .variable
+            .iter()
+            .map(|v| (ctx.resolve_name_variable(*v), *v))
+            .collect();
 
-                if !translators.is_empty() {
-                    res.push((translators, NameVariable::Translator));
-                }
+        // The editor and translator variables need to be merged if they are
+        // both present and identical. The names are then rendered once at the
+        // position of the editor with the combined label.
+        let editor = people.iter().position(|(_, v)| *v == NameVariable::Editor);
+        let translator = people.iter().position(|(_, v)| *v == NameVariable::Translator);
+        if let (Some(editor), Some(translator)) = (editor, translator) {
+            if !people[editor].0.is_empty()
+                && people[editor].0 == people[translator].0
+                && ctx
+                    .term(
+                        NameVariable::EditorTranslator.into(),
+                        TermForm::default(),
+                        false,
+                    )
+                    .is_some()
+            {
+                people[editor].1 = NameVariable::EditorTranslator;
+                people.remove(translator);
             }
-
-            res
-        } else {
-            self.variable
-                .iter()
-                .map(|v| (ctx.resolve_name_variable(*v), *v))
-                .collect()
-        };
+        }
 
         // Push to the name options stack.
         ctx.writing.push_name_options(&self.options());
@@ -278,24 +274,17 @@ impl RenderCsl for Names {
 
         let depth = ctx.push_elem(self.to_formatting());
         let affix_loc = ctx.apply_prefix(&self.to_affixes());
-        let cs_name = self.name().cloned().unwrap_or_default();
+        let mut cs_name = self.name().cloned().unwrap_or_default();
+        if let Some(overrides) = &ctx.writing.name_options_override {
+            cs_name.options = cs_name.options.apply(overrides);
+        }
         let options = cs_name.options(ctx.writing.name_options.last());
 
         let default_form = DisambiguatedNameForm::from(&options);
 
-        // Return here if we should only count the names.
-        if default_form == DisambiguatedNameForm::Count {
-            write!(ctx, "{}", people.into_iter().fold(0, |acc, curr| acc + curr.0.len()))
-                .unwrap();
-            ctx.apply_suffix(&self.to_affixes(), affix_loc);
-            ctx.commit_elem(depth, self.display, Some(ElemMeta::Names));
-            ctx.writing.pop_name_options();
-            return;
-        }
-
         // If we disambiguate, we need to copy the name forms and otherwise
         // compute them using the options.
-        let props = if let DisambiguateState::NameDisambiguation(props) =
+        let mut props = if let DisambiguateState::NameDisambiguation(props) =
             &ctx.instance.cite_props.speculative.disambiguation
         {
             if props.variables.iter().eq(people.iter().map(|(_, v)| v)) {
@@ -308,7 +297,7 @@ impl RenderCsl for Names {
         }
         .unwrap_or_else(|| NameDisambiguationProperties {
             default_name_form: default_form,
-            variables: self.variable.clone(),
+            variables: people.iter().map(|(_, v)| *v).collect(),
             name_forms: people
                 .iter()
                 .map(|(p, _)| {
@@ -330,6 +319,38 @@ impl RenderCsl for Names {
                 .collect(),
         });
 
+        let use_last = cs_name
+            .options
+            .et_al_use_last
+            .or(ctx.writing.name_options.last().et_al_use_last)
+            .unwrap_or_default();
+        if use_last {
+            for forms in &mut props.name_forms {
+                keep_last_name(forms, default_form);
+            }
+        }
+
+        if let Some(cap) = ctx.style.name_cap.filter(|_| !ctx.instance.sorting) {
+            for forms in &mut props.name_forms {
+                cap_names(cap, forms);
+            }
+        }
+
+        // Return here if we should only count the names. Like the names
+        // themselves, each variable is truncated by et-al separately.
+        if default_form == DisambiguatedNameForm::Count {
+            let count: usize = props
+                .name_forms
+                .iter()
+                .map(|forms| forms.iter().filter(|f| f.is_some()).count())
+                .sum();
+            write!(ctx, "{count}").unwrap();
+            ctx.apply_suffix(&self.to_affixes(), affix_loc);
+            ctx.commit_elem(depth, self.display, Some(ElemMeta::Names));
+            ctx.writing.pop_name_options();
+            return;
+        }
+
         for (i, ((persons, variable), forms)) in people
             .into_iter()
             .zip(props.name_forms.iter())
@@ -388,7 +409,16 @@ impl RenderCsl for Names {
             return true;
         }
 
-        if self.variable.iter().all(|v| ctx.resolve_name_variable(*v).is_empty()) {
+        // Only look at the variables without suppressing them.
+        let suppressing = ctx.writing.suppress_queried_variables;
+        ctx.writing.stop_suppressing_queried_variables();
+        let is_empty =
+            self.variable.iter().all(|v| ctx.resolve_name_variable(*v).is_empty());
+        if suppressing {
+            ctx.writing.start_suppressing_queried_variables();
+        }
+
+        if is_empty {
             if let Some(substitute) = &self.substitute() {
                 return substitute.children.iter().any(|c| c.will_render(ctx, var));
             }
@@ -437,6 +467,42 @@ impl RenderCsl for Names {
     }
 }
 
+/// Render the last name of a truncated list after an ellipsis as requested by
+/// `et-al-use-last`. This only applies if at least two names are omitted, as
+/// the list would otherwise be as long as the untruncated one.
+fn keep_last_name(
+    forms: &mut [Option<DisambiguatedNameForm>],
+    default_form: DisambiguatedNameForm,
+) {
+    let take = forms.iter().position(Option::is_none).unwrap_or(forms.len());
+    if forms.len() >= take + 2 {
+        if let Some(form) = forms.last_mut() {
+            form.get_or_insert(default_form);
+        }
+    }
+}
+
+/// Suppress the names beyond the limit of the cap if the style does not
+/// already suppress them.
+fn cap_names(cap: NameCap, forms: &mut [Option<DisambiguatedNameForm>]) {
+    let max = cap.max.max(1);
+    let take = forms.iter().position(Option::is_none).unwrap_or(forms.len());
+    if take <= max {
+        return;
+    }
+
+    let last = forms.last().copied().flatten().or(forms[max - 1]);
+    for form in &mut forms[max..] {
+        *form = None;
+    }
+
+    if cap.overflow == NameOverflow::Ellipsis && forms.len() > max + 1 {
+        if let Some(form) = forms.last_mut() {
+            *form = last;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum EndDelim {
     Delim,
@@ -529,7 +595,9 @@ fn add_names<T: EntryLike>(
             i,
         );
 
-        last_inverted = reverse;
+        // Only long personal names are actually displayed in sort order.
+        last_inverted =
+            reverse && form.is_long() && !name.is_institutional() && !name.is_cjk();
         first = false;
     }
 
@@ -582,6 +650,46 @@ fn write_name<T: EntryLike>(
     name_opts: &NameOptions,
     variable: NameVariable,
     name_idx: usize,
+) {
+    let (name, transliterated) = match (ctx.style.preferred_script, &name.transliterated)
+    {
+        (PreferredScript::Transliterated, Some(transliterated)) => {
+            (&**transliterated, None)
+        }
+        (PreferredScript::OriginalWithTransliteration, Some(transliterated))
+            if !ctx.instance.sorting =>
+        {
+            (name, Some(&**transliterated))
+        }
+        _ => (name, None),
+    };
+
+    let elem_idx = ctx.push_elem(citationberg::Formatting::default());
+    write_name_parts(name, ctx, form, reverse, demote_non_dropping, cs_name, name_opts);
+    if let Some(transliterated) = transliterated {
+        ctx.push_str(" [");
+        write_name_parts(
+            transliterated,
+            ctx,
+            form,
+            reverse,
+            demote_non_dropping,
+            cs_name,
+            name_opts,
+        );
+        ctx.push_str("]");
+    }
+    ctx.commit_elem(elem_idx, None, Some(ElemMeta::Name(variable, name_idx)))
+}
+
+fn write_name_parts<T: EntryLike>(
+    name: &Person,
+    ctx: &mut Context<T>,
+    form: DisambiguatedNameForm,
+    reverse: bool,
+    demote_non_dropping: bool,
+    cs_name: &citationberg::Name,
+    name_opts: &NameOptions,
 ) {
     let hyphen_init = ctx.style.csl.settings.initialize_with_hyphen;
     let sort_sep = name_opts.sort_separator;
@@ -619,13 +727,13 @@ fn write_name<T: EntryLike>(
         }
     };
 
-    let simple = |ctx: &mut Context<T>| {
+    let simple = |ctx: &mut Context<T>, value: &str| {
         let idx = ctx.push_format(family_format);
         let cidx = ctx.push_case(family_case);
         if let Some(prefix) = family_affixes[0] {
             ctx.push_str(prefix);
         }
-        ctx.push_str(&name.name);
+        ctx.push_str(value);
         ctx.pop_case(cidx);
         ctx.pop_format(idx);
         if let Some(suffix) = family_affixes[1] {
@@ -734,7 +842,6 @@ fn write_name<T: EntryLike>(
         }
     };
 
-    let elem_idx = ctx.push_elem(citationberg::Formatting::default());
     match (form.is_long(), reverse, demote_non_dropping) {
         _ if name.is_institutional() && ctx.instance.sorting => {
             let idx = ctx.push_format(family_format);
@@ -744,13 +851,20 @@ fn write_name<T: EntryLike>(
             ctx.pop_case(cidx);
             ctx.pop_format(idx);
         }
-        _ if name.is_institutional() => simple(ctx),
+        _ if name.is_institutional() => {
+            let value = ctx
+                .abbreviate_institution(&name.name, !form.is_long())
+                .unwrap_or(&name.name);
+            simple(ctx, value)
+        }
         (true, _, _) if name.is_cjk() => {
             let idx = ctx.push_format(family_format);
+            let cidx = ctx.push_case(family_case);
             if let Some(prefix) = family_affixes[0] {
                 ctx.push_str(prefix);
             }
             ctx.push_str(&name.name);
+            ctx.pop_case(cidx);
             ctx.pop_format(idx);
             if let Some(suffix) = family_affixes[1] {
                 ctx.push_str(suffix);
@@ -758,11 +872,13 @@ fn write_name<T: EntryLike>(
 
             if let Some(given) = &name.given_name {
                 let idx = ctx.push_format(first_format);
+                let cidx = ctx.push_case(first_case);
                 if let Some(prefix) = first_affixes[0] {
                     ctx.push_str(prefix);
                 }
 
                 ctx.push_str(given);
+                ctx.pop_case(cidx);
                 ctx.pop_format(idx);
 
                 if let Some(suffix) = first_affixes[1] {
@@ -776,27 +892,32 @@ fn write_name<T: EntryLike>(
         (true, true, false) => reverse_keep_particle(ctx),
         (true, true, true) => reverse_demote_particle(ctx),
         (true, false, _) => {
-            let idx = ctx.push_format(first_format);
-            let cidx = ctx.push_case(first_case);
+            // The given name-part also contains the dropping particle. Its
+            // affixes are omitted when it is empty.
+            if name.given_name.is_some() || name.prefix.is_some() {
+                let idx = ctx.push_format(first_format);
+                let cidx = ctx.push_case(first_case);
 
-            if let Some(prefix) = first_affixes[0] {
-                ctx.push_str(prefix);
-            }
+                if let Some(prefix) = first_affixes[0] {
+                    ctx.push_str(prefix);
+                }
 
-            first_name(ctx);
-            if let Some(prefix) = &name.prefix {
-                ctx.ensure_space();
-                ctx.push_str(prefix);
-            }
+                first_name(ctx);
+                if let Some(prefix) = &name.prefix {
+                    ctx.ensure_space();
+                    ctx.push_str(prefix);
+                }
 
-            ctx.pop_format(idx);
-            ctx.pop_case(cidx);
+                ctx.pop_case(cidx);
+                ctx.pop_format(idx);
 
-            if let Some(suffix) = first_affixes[1] {
-                ctx.push_str(suffix);
+                if let Some(suffix) = first_affixes[1] {
+                    ctx.push_str(suffix);
+                }
+
+                ctx.ensure_space();
             }
 
-            ctx.ensure_space();
             let idx = ctx.push_format(family_format);
             let cidx = ctx.push_case(family_case);
 
@@ -819,8 +940,7 @@ fn write_name<T: EntryLike>(
             }
         }
         (false, _, _) => {
-            simple(ctx);
+            simple(ctx, &name.name);
         }
     }
-    ctx.commit_elem(elem_idx, None, Some(ElemMeta::Name(variable, name_idx)))
 }
@@ -1,99 +1,117 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
-use citationberg::taxonomy::Variable;
+use citationberg::taxonomy::{StandardVariable, Variable};
 use citationberg::{
-    DemoteNonDroppingParticle, InheritableNameOptions, LocaleCode, LongShortForm, Sort,
-    SortDirection, SortKey,
+    DemoteNonDroppingParticle, InheritableNameOptions, LayoutRenderingElement,
+    LocaleCode, LongShortForm, Sort, SortDirection, SortKey,
 };
 
-use crate::csl::rendering::RenderCsl;
+use crate::csl::rendering::{date_sort_parts, RenderCsl};
 use crate::csl::BufWriteFormat;
+use crate::lang::articles::strip_leading_article;
+use crate::lang::collation::Collator;
+use crate::types::{Date, Person};
 
-use super::taxonomy::EntryLike;
-use super::{CitationItem, InstanceContext, StyleContext};
+use super::taxonomy::{EntryLike, NumberVariableResult, PageVariableResult};
+use super::{CitationItem, InstanceContext, PreferredScript, StyleContext};
 
 impl StyleContext<'_> {
-    /// Retrieve the ordering of two entries according to the given sort key.
-    fn cmp_entries<T: EntryLike>(
+    /// The variant of a name that is used for sorting.
+    fn sort_name<'p>(&self, person: &'p Person) -> &'p Person {
+        match (self.preferred_script, &person.transliterated) {
+            (PreferredScript::Transliterated, Some(transliterated)) => transliterated,
+            _ => person,
+        }
+    }
+
+    /// The text of a standard variable that is used for sorting. Titles lose
+    /// their leading article in the language of the entry or, failing that,
+    /// the given locale.
+    pub(super) fn sort_text<T: EntryLike>(
+        &self,
+        entry: &T,
+        variable: StandardVariable,
+        text: String,
+        locale: Option<&LocaleCode>,
+    ) -> String {
+        if !matches!(
+            variable,
+            StandardVariable::Title
+                | StandardVariable::TitleShort
+                | StandardVariable::ContainerTitle
+                | StandardVariable::ContainerTitleShort
+                | StandardVariable::CollectionTitle
+                | StandardVariable::OriginalTitle
+                | StandardVariable::ReviewedTitle
+                | StandardVariable::VolumeTitle
+        ) {
+            return text;
+        }
+
+        let locale = match entry.language_code() {
+            Some(code) => code.into_owned(),
+            None => locale.cloned().unwrap_or_else(|| self.locale()).0,
+        };
+
+        strip_leading_article(&text, &locale).to_string()
+    }
+
+    /// Extract the value an entry is sorted by for the given sort key.
+    /// Macros render names with the given name options of the layout the key
+    /// belongs to.
+    ///
+    /// Returns `None` if the value is empty.
+    fn sort_value<'e, T: EntryLike>(
         &self,
-        a: &CitationItem<T>,
-        a_idx: usize,
-        b: &CitationItem<T>,
-        b_idx: usize,
+        item: &CitationItem<'e, T>,
+        idx: usize,
         key: &SortKey,
         term_locale: Option<&LocaleCode>,
-    ) -> Ordering {
-        let ordering = match key {
+        name_options: &InheritableNameOptions,
+    ) -> Option<SortValue<'e>> {
+        match key {
             SortKey::Variable { variable: Variable::Standard(s), .. } => {
-                let a = InstanceContext::sort_instance(a, a_idx)
-                    .resolve_standard_variable(LongShortForm::default(), *s)
-                    .map(|s| s.to_string().to_lowercase());
-                let b = InstanceContext::sort_instance(b, b_idx)
-                    .resolve_standard_variable(LongShortForm::default(), *s)
-                    .map(|s| s.to_string().to_lowercase());
-
-                a.cmp(&b)
+                let transliterated = (self.preferred_script
+                    == PreferredScript::Transliterated)
+                    .then(|| {
+                        item.entry.resolve_transliterated_standard_variable(
+                            LongShortForm::default(),
+                            *s,
+                        )
+                    })
+                    .flatten();
+
+                transliterated
+                    .map(|t| t.to_string())
+                    .or_else(|| {
+                        InstanceContext::sort_instance(item, idx)
+                            .resolve_standard_variable(LongShortForm::default(), *s)
+                            .map(|t| t.to_string())
+                    })
+                    .map(|t| {
+                        let locale = item.locale.as_ref().or(term_locale);
+                        self.sort_text(item.entry, *s, t, locale)
+                    })
+                    .filter(|t| !t.is_empty())
+                    .map(SortValue::Text)
             }
             SortKey::Variable { variable: Variable::Date(d), .. } => {
-                let a = a.entry.resolve_date_variable(*d);
-                let b = b.entry.resolve_date_variable(*d);
-
-                match (a, b) {
-                    (Some(a), Some(b)) => a.csl_cmp(&b),
-                    (Some(_), None) => Ordering::Greater,
-                    (None, Some(_)) => Ordering::Less,
-                    (None, None) => Ordering::Equal,
-                }
+                item.entry.resolve_date_variable(*d).map(SortValue::Date)
             }
             SortKey::Variable { variable: Variable::Name(n), .. } => {
-                let a = a.entry.resolve_name_variable(*n);
-                let b = b.entry.resolve_name_variable(*n);
-
-                for (a_pers, b_pers) in a.iter().zip(b.iter()) {
-                    let ord = a_pers.csl_cmp(
-                        b_pers,
-                        LongShortForm::Long,
-                        self.csl.settings.demote_non_dropping_particle
-                            != DemoteNonDroppingParticle::Never,
-                    );
-                    if ord != Ordering::Equal {
-                        return ord;
-                    }
-                }
-
-                if a.len() < b.len() {
-                    Ordering::Less
-                } else if a.len() > b.len() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Equal
-                }
+                let names = item.entry.resolve_name_variable(*n);
+                (!names.is_empty()).then_some(SortValue::Names(names))
             }
             SortKey::Variable { variable: Variable::Number(n), .. } => {
-                let a =
-                    InstanceContext::sort_instance(a, a_idx).resolve_number_variable(*n);
-                let b =
-                    InstanceContext::sort_instance(b, b_idx).resolve_number_variable(*n);
-
-                match (a, b) {
-                    (Some(a), Some(b)) => a.csl_cmp(&b),
-                    (Some(_), None) => Ordering::Greater,
-                    (None, Some(_)) => Ordering::Less,
-                    (None, None) => Ordering::Equal,
-                }
+                InstanceContext::sort_instance(item, idx)
+                    .resolve_number_variable(*n)
+                    .map(SortValue::Number)
             }
             SortKey::Variable { variable: Variable::Page(pv), .. } => {
-                let a =
-                    InstanceContext::sort_instance(a, a_idx).resolve_page_variable(*pv);
-                let b =
-                    InstanceContext::sort_instance(b, b_idx).resolve_page_variable(*pv);
-
-                match (a, b) {
-                    (Some(a), Some(b)) => a.csl_cmp(&b),
-                    (Some(_), None) => Ordering::Greater,
-                    (None, Some(_)) => Ordering::Less,
-                    (None, None) => Ordering::Equal,
-                }
+                InstanceContext::sort_instance(item, idx)
+                    .resolve_page_variable(*pv)
+                    .map(SortValue::Page)
             }
             SortKey::MacroName {
                 name,
@@ -102,49 +120,109 @@ impl StyleContext<'_> {
                 names_use_last,
                 ..
             } => {
-                let render = |entry: &CitationItem<T>, idx: usize| {
-                    let mut ctx = self.sorting_ctx(
-                        entry,
-                        idx,
-                        entry.locale.as_ref(),
-                        term_locale,
-                        false,
-                    );
-                    ctx.writing.name_options.push(InheritableNameOptions {
-                        et_al_min: *names_min,
-                        et_al_subsequent_min: *names_min,
-                        et_al_use_first: *names_use_first,
-                        et_al_subsequent_use_first: *names_use_first,
-                        et_al_use_last: *names_use_last,
-                        ..Default::default()
-                    });
+                let m = self.get_macro(name)?;
 
-                    self.get_macro(name).map(|m| {
-                        for child in &m.children {
-                            child.render(&mut ctx)
-                        }
-                        ctx.flush().0.into_iter().fold(String::new(), |mut s, f| {
-                            f.write_buf(&mut s, BufWriteFormat::Plain).unwrap();
-                            s.to_lowercase()
-                        })
-                    })
-                };
+                // A macro that only renders a date is compared like the date
+                // variable, limited to the date parts it renders.
+                if let [LayoutRenderingElement::Date(date)] = m.children.as_slice() {
+                    if let Some(variable) = date.variable {
+                        let (_, month, day) = date_sort_parts(date);
+                        return item.entry.resolve_date_variable(variable).map(|d| {
+                            SortValue::Date(Cow::Owned(Date {
+                                month: d.month.filter(|_| month),
+                                day: d.day.filter(|_| month && day),
+                                ..*d
+                            }))
+                        });
+                    }
+                }
+
+                let mut ctx =
+                    self.sorting_ctx(item, idx, item.locale.as_ref(), term_locale, false);
+                ctx.writing.push_name_options(name_options);
+                ctx.writing.override_name_options(InheritableNameOptions {
+                    et_al_min: *names_min,
+                    et_al_subsequent_min: *names_min,
+                    et_al_use_first: *names_use_first,
+                    et_al_subsequent_use_first: *names_use_first,
+                    et_al_use_last: *names_use_last,
+                    ..Default::default()
+                });
+
+                for child in &m.children {
+                    child.render(&mut ctx)
+                }
 
-                let a_rendered = render(a, a_idx);
-                let b_rendered = render(b, b_idx);
+                let rendered =
+                    ctx.flush().0.into_iter().fold(String::new(), |mut s, f| {
+                        f.write_buf(&mut s, BufWriteFormat::Plain).unwrap();
+                        s
+                    });
+                (!rendered.is_empty()).then_some(SortValue::Text(rendered))
+            }
+        }
+    }
 
-                a_rendered.cmp(&b_rendered)
+    /// Compare two values extracted for the same sort key.
+    fn cmp_values(&self, a: &SortValue, b: &SortValue, collator: &Collator) -> Ordering {
+        match (a, b) {
+            (SortValue::Text(a), SortValue::Text(b)) => collator.compare(a, b),
+            (SortValue::Number(a), SortValue::Number(b)) => a.csl_cmp(b),
+            (SortValue::Page(a), SortValue::Page(b)) => a.csl_cmp(b),
+            (SortValue::Date(a), SortValue::Date(b)) => a.csl_cmp(b),
+            (SortValue::Names(a), SortValue::Names(b)) => {
+                for (a_pers, b_pers) in a.iter().zip(b.iter()) {
+                    let (a_pers, b_pers) =
+                        (self.sort_name(a_pers), self.sort_name(b_pers));
+                    let ord = a_pers.csl_cmp(
+                        b_pers,
+                        LongShortForm::Long,
+                        self.csl.settings.demote_non_dropping_particle
+                            != DemoteNonDroppingParticle::Never,
+                        collator,
+                    );
+                    if ord != Ordering::Equal {
+                        return ord;
+                    }
+                }
+
+                a.len().cmp(&b.len())
             }
-        };
+            // Values for the same key always have the same kind.
+            _ => Ordering::Equal,
+        }
+    }
 
-        if key.sort_direction() == SortDirection::Descending {
-            ordering.reverse()
-        } else {
-            ordering
+    /// Retrieve the ordering of two entries according to the given sort key.
+    ///
+    /// The direction of the key only applies to non-empty values: Entries
+    /// without a value for the key are always sorted last.
+    fn cmp_entries(
+        &self,
+        a: Option<&SortValue>,
+        b: Option<&SortValue>,
+        key: &SortKey,
+        collator: &Collator,
+    ) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = self.cmp_values(a, b, collator);
+                if key.sort_direction() == SortDirection::Descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
         }
     }
 
     /// Sorts the given citation items by the style's sort keys.
+    ///
+    /// The values of all keys are extracted once per item before sorting
+    /// because rendering macros for every comparison is expensive.
     pub fn sort<T: EntryLike>(
         &self,
         cites: &mut [CitationItem<T>],
@@ -152,24 +230,72 @@ impl StyleContext<'_> {
         term_locale: Option<&LocaleCode>,
         citation_number: impl Fn(&T) -> usize,
     ) {
-        if let Some(sort) = sort {
-            cites.sort_by(|a, b| {
-                let mut ordering = Ordering::Equal;
-                for key in &sort.keys {
-                    ordering = self.cmp_entries(
-                        a,
-                        citation_number(a.entry),
-                        b,
-                        citation_number(b.entry),
-                        key,
-                        term_locale,
-                    );
-                    if ordering != Ordering::Equal {
-                        break;
-                    }
+        let Some(sort) = sort else { return };
+        let locale = term_locale.cloned().unwrap_or_else(|| self.locale());
+        let collator = Collator::new(&locale.0);
+
+        // Names in macros inherit the options of the `cs:citation` or
+        // `cs:bibliography` element of the sort.
+        let name_options = match &self.csl.bibliography {
+            Some(bibliography)
+                if bibliography.sort.as_ref().is_some_and(|s| std::ptr::eq(s, sort)) =>
+            {
+                &bibliography.name_options
+            }
+            _ => &self.csl.citation.name_options,
+        };
+
+        let values: Vec<Vec<Option<SortValue>>> = cites
+            .iter()
+            .map(|item| {
+                let idx = citation_number(item.entry);
+                sort.keys
+                    .iter()
+                    .map(|key| self.sort_value(item, idx, key, term_locale, name_options))
+                    .collect()
+            })
+            .collect();
+
+        // The sort is stable, so items with equal keys keep their input order.
+        let mut order: Vec<usize> = (0..cites.len()).collect();
+        order.sort_by(|&a, &b| {
+            let mut ordering = Ordering::Equal;
+            for (i, key) in sort.keys.iter().enumerate() {
+                ordering = self.cmp_entries(
+                    values[a][i].as_ref(),
+                    values[b][i].as_ref(),
+                    key,
+                    &collator,
+                );
+                if ordering != Ordering::Equal {
+                    break;
                 }
-                ordering
-            });
+            }
+            ordering
+        });
+
+        // Move the items into place. Each position follows the permutation
+        // until it finds an item that was not yet moved away.
+        for i in 0..order.len() {
+            let mut j = order[i];
+            while j < i {
+                j = order[j];
+            }
+            cites.swap(i, j);
         }
     }
 }
+
+/// The value of an entry for a single sort key.
+enum SortValue<'a> {
+    /// Text, either from a standard variable or a rendered macro.
+    Text(String),
+    /// A number variable.
+    Number(NumberVariableResult<'a>),
+    /// A page variable.
+    Page(PageVariableResult),
+    /// A date, either from a date variable or a macro rendering one.
+    Date(Cow<'a, Date>),
+    /// A non-empty list of names.
+    Names(Vec<Cow<'a, Person>>),
+}
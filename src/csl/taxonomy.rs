@@ -1,5 +1,9 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::rc::Rc;
 use std::str::FromStr;
 
 use crate::types::{
@@ -19,7 +23,27 @@ use citationberg::json as csl_json;
 use super::citation_label::Alphanumerical;
 use super::{DisambiguateState, InstanceContext, LocatorPayload};
 
-pub trait EntryLike {
+/// The prefix of arXiv identifiers when they are used as the `number`
+/// variable.
+pub(crate) const ARXIV_PREFIX: &str = "arXiv:";
+
+/// Entries must be shareable across threads when the bibliography is
+/// rendered in parallel.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Entries must be shareable across threads when the bibliography is
+/// rendered in parallel.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "rayon"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+pub trait EntryLike: MaybeSync {
     fn resolve_number_variable(
         &self,
         variable: NumberVariable,
@@ -33,10 +57,27 @@ pub trait EntryLike {
         form: LongShortForm,
         variable: StandardVariable,
     ) -> Option<Cow<'_, ChunkedString>>;
+    /// Resolve the transliteration of a standard variable, if there is one.
+    fn resolve_transliterated_standard_variable(
+        &self,
+        _form: LongShortForm,
+        _variable: StandardVariable,
+    ) -> Option<Cow<'_, ChunkedString>> {
+        None
+    }
     fn resolve_name_variable(&self, variable: NameVariable) -> Vec<Cow<'_, Person>>;
     fn resolve_date_variable(&self, variable: DateVariable) -> Option<Cow<'_, Date>>;
     fn matches_entry_type(&self, kind: taxonomy::Kind) -> bool;
     fn is_english(&self) -> Option<bool>;
+    /// The language of the entry as a locale code, e.g. `en-US`.
+    fn language_code(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+    /// The user name of the account that published the entry if it is a
+    /// social media post.
+    fn post_handle(&self) -> Option<Cow<'_, str>> {
+        None
+    }
     fn key(&self) -> Cow<'_, str>;
 }
 
@@ -58,16 +99,20 @@ impl<'a, T: EntryLike> InstanceContext<'a, T> {
                     )))
                 })
             }
-            NumberVariable::Locator => match self.cite_props.speculative.locator?.1 {
-                LocatorPayload::Str(l) => Some(NumberVariableResult::from_regular(
-                    Numeric::from_str(l)
-                        .map(|n| MaybeTyped::Typed(Cow::Owned(n)))
-                        .unwrap_or_else(|_| MaybeTyped::String(l.to_owned())),
-                )),
-                LocatorPayload::Transparent => Some(NumberVariableResult::Transparent(
-                    self.cite_props.certain.initial_idx,
-                )),
-            },
+            NumberVariable::Locator => {
+                match self.cite_props.speculative.locators.first()?.1 {
+                    LocatorPayload::Str(l) => Some(NumberVariableResult::from_regular(
+                        Numeric::from_str(l)
+                            .map(|n| MaybeTyped::Typed(Cow::Owned(n)))
+                            .unwrap_or_else(|_| MaybeTyped::String(l.to_owned())),
+                    )),
+                    LocatorPayload::Transparent => {
+                        Some(NumberVariableResult::Transparent(
+                            self.cite_props.certain.initial_idx,
+                        ))
+                    }
+                }
+            }
             _ => self
                 .entry
                 .resolve_number_variable(variable)
@@ -87,19 +132,112 @@ impl<'a, T: EntryLike> InstanceContext<'a, T> {
         &self,
         form: LongShortForm,
         variable: StandardVariable,
-    ) -> Option<Cow<'a, ChunkedString>> {
+    ) -> Option<StandardValue<'a>> {
         match variable {
             StandardVariable::YearSuffix => {
                 if let DisambiguateState::YearSuffix(s) =
                     self.cite_props.speculative.disambiguation
                 {
-                    Some(Cow::Owned(StringChunk::normal(letter(s)).into()))
+                    Some(ChunkedString::from(StringChunk::normal(letter(s))).into())
                 } else {
                     None
                 }
             }
-            _ => self.entry.resolve_standard_variable(form, variable),
+            _ => self.standard_variables.get_or_resolve(form, variable, || {
+                self.entry.resolve_standard_variable(form, variable).map(Into::into)
+            }),
+        }
+    }
+}
+
+/// The value of a standard variable of an instance.
+///
+/// Values that had to be assembled for the variable are shared with the
+/// [`VariableCache`] instead of being copied whenever the variable is
+/// accessed.
+#[derive(Debug, Clone)]
+pub(super) enum StandardValue<'a> {
+    /// A value that is stored in the entry.
+    Borrowed(&'a ChunkedString),
+    /// A value that was assembled when resolving the variable.
+    Shared(Rc<ChunkedString>),
+}
+
+impl StandardValue<'_> {
+    /// Extract the value, cloning it if it is also used elsewhere.
+    pub fn into_owned(self) -> ChunkedString {
+        match self {
+            Self::Borrowed(value) => value.clone(),
+            Self::Shared(value) => Rc::try_unwrap(value).unwrap_or_else(|v| (*v).clone()),
+        }
+    }
+}
+
+impl Deref for StandardValue<'_> {
+    type Target = ChunkedString;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(value) => value,
+            Self::Shared(value) => value,
+        }
+    }
+}
+
+impl From<ChunkedString> for StandardValue<'_> {
+    fn from(value: ChunkedString) -> Self {
+        Self::Shared(Rc::new(value))
+    }
+}
+
+impl<'a> From<Cow<'a, ChunkedString>> for StandardValue<'a> {
+    fn from(value: Cow<'a, ChunkedString>) -> Self {
+        match value {
+            Cow::Borrowed(value) => Self::Borrowed(value),
+            Cow::Owned(value) => value.into(),
+        }
+    }
+}
+
+/// Standard variables that were already resolved for the entry of an
+/// instance.
+///
+/// Many variables are composed from the entry and its parents on the fly, so
+/// they are only resolved once per instance, even though styles test and
+/// render them many times.
+///
+/// Each assembled value is stored once and shared with all accesses of the
+/// variable.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::type_complexity)]
+pub(super) struct VariableCache<'a>(
+    RefCell<HashMap<(LongShortForm, StandardVariable), Option<StandardValue<'a>>>>,
+);
+
+impl<'a> VariableCache<'a> {
+    /// Retrieve a variable, resolving it with `resolve` if it was not
+    /// resolved yet.
+    fn get_or_resolve(
+        &self,
+        form: LongShortForm,
+        variable: StandardVariable,
+        resolve: impl FnOnce() -> Option<StandardValue<'a>>,
+    ) -> Option<StandardValue<'a>> {
+        if let Some(val) = self.0.borrow().get(&(form, variable)) {
+            return val.clone();
         }
+
+        let val = resolve();
+        self.0.borrow_mut().insert((form, variable), val.clone());
+        val
+    }
+}
+
+/// The cache does not affect the output, so it is ignored when comparing
+/// instances.
+impl PartialEq for VariableCache<'_> {
+    fn eq(&self, _: &Self) -> bool {
+        true
     }
 }
 
@@ -163,13 +301,18 @@ impl EntryLike for Entry {
             }
             NumberVariable::Issue => self.map(|e| e.issue()).map(MaybeTyped::to_cow),
             NumberVariable::Locator => panic!("processor must resolve this"),
-            NumberVariable::Number => {
-                self.serial_number().and_then(|s| s.0.get("serial")).map(|s| {
+            NumberVariable::Number => self
+                .keyed_serial_number("serial")
+                .or_else(|| self.keyed_serial_number("docket"))
+                .map(|s| {
                     Numeric::from_str(s)
                         .map(|n| MaybeTyped::Typed(Cow::Owned(n)))
                         .unwrap_or_else(|_| MaybeTyped::String(s.to_owned()))
                 })
-            }
+                .or_else(|| {
+                    self.arxiv()
+                        .map(|id| MaybeTyped::String(format!("{ARXIV_PREFIX}{id}")))
+                }),
             NumberVariable::NumberOfPages => {
                 self.page_total().map(|n| MaybeTyped::Typed(Cow::Borrowed(n)))
             }
@@ -182,13 +325,16 @@ impl EntryLike for Entry {
                 .and_then(PageRanges::first)
                 .map(|r| MaybeTyped::Typed(Cow::Owned(r.clone()))),
             NumberVariable::PartNumber => self
-                .bound_select(
-                    &select!(
-                        (("e":*) > (Article | Blog | Book | Legislation))
-                    ),
-                    "e",
-                )
-                .and_then(Entry::volume)
+                .part_number()
+                .or_else(|| {
+                    self.bound_select(
+                        &select!(
+                            (("e":*) > (Article | Blog | Book | Legislation))
+                        ),
+                        "e",
+                    )
+                    .and_then(Entry::volume)
+                })
                 .map(MaybeTyped::to_cow),
             NumberVariable::PrintingNumber => self
                 .map(|e| e.serial_number())
@@ -201,7 +347,7 @@ impl EntryLike for Entry {
             NumberVariable::Section => None,
             NumberVariable::SupplementNumber => None,
             NumberVariable::Version => self
-                .bound_select(&select!(("e":Repository)), "e")
+                .bound_select(&select!(("e":(Repository | Software | Dataset))), "e")
                 .and_then(Entry::serial_number)
                 .and_then(|s| s.0.get("version"))
                 .map(|s| {
@@ -239,7 +385,7 @@ impl EntryLike for Entry {
                 .map(|f| f.select(form))
                 .map(Cow::Borrowed),
             StandardVariable::Annote => {
-                entry.map(|e| e.note()).map(|f| f.select(form)).map(Cow::Borrowed)
+                entry.annote().map(|f| f.select(form)).map(Cow::Borrowed)
             }
             StandardVariable::Archive => {
                 entry.map(|e| e.archive()).map(|f| f.select(form)).map(Cow::Borrowed)
@@ -259,7 +405,10 @@ impl EntryLike for Entry {
                 Some(Cow::Owned(StringChunk::verbatim(&entry.key).into()))
             }
             StandardVariable::CitationLabel => {
-                Some(Cow::Owned(Alphanumerical::default().citation(entry).into()))
+                Some(Cow::Owned(match entry.citation_label() {
+                    Some(label) => StringChunk::verbatim(label).into(),
+                    None => Alphanumerical::default().citation(entry).into(),
+                }))
             }
             // Get third-order title first, then second-order title.
             StandardVariable::CollectionTitle => entry
@@ -285,24 +434,24 @@ impl EntryLike for Entry {
                 entry.doi().map(|d| Cow::Owned(StringChunk::verbatim(d).into()))
             }
             StandardVariable::Event | StandardVariable::EventTitle => entry
-                .bound_select(&select!(* > ("p":(Exhibition | Conference | Misc))), "p")
+                .get_event()
                 .and_then(Entry::title)
                 .map(|f| f.select(form))
                 .map(Cow::Borrowed),
             StandardVariable::EventPlace => entry
-                .bound_select(&select!(* > ("p":(Exhibition | Conference | Misc))), "p")
+                .get_event()
                 .and_then(Entry::location)
                 .map(|f| f.select(form))
                 .map(Cow::Borrowed),
             StandardVariable::Genre => {
                 entry.map(|e| e.genre()).map(|f| f.select(form)).map(Cow::Borrowed)
             }
-            StandardVariable::ISBN => {
-                entry.isbn().map(|d| Cow::Owned(StringChunk::verbatim(d).into()))
-            }
-            StandardVariable::ISSN => {
-                entry.issn().map(|d| Cow::Owned(StringChunk::verbatim(d).into()))
-            }
+            StandardVariable::ISBN => entry
+                .map(|e| e.isbn())
+                .map(|d| Cow::Owned(StringChunk::verbatim(d).into())),
+            StandardVariable::ISSN => entry
+                .map(|e| e.issn())
+                .map(|d| Cow::Owned(StringChunk::verbatim(d).into())),
             StandardVariable::Jurisdiction => None,
             StandardVariable::Keyword => None,
             StandardVariable::Language => entry
@@ -329,7 +478,9 @@ impl EntryLike for Entry {
                 .and_then(|e| e.title())
                 .map(|f| f.select(form))
                 .map(Cow::Borrowed),
-            StandardVariable::PartTitle => None,
+            StandardVariable::PartTitle => {
+                entry.part_title().map(|f| f.select(form)).map(Cow::Borrowed)
+            }
             StandardVariable::PMCID => {
                 entry.pmcid().map(|d| Cow::Owned(StringChunk::verbatim(d).into()))
             }
@@ -337,12 +488,12 @@ impl EntryLike for Entry {
                 entry.pmid().map(|d| Cow::Owned(StringChunk::verbatim(d).into()))
             }
             StandardVariable::Publisher => entry
-                .map(|e| e.publisher())
+                .get_publisher()
                 .and_then(Publisher::name)
                 .map(|n| n.select(form))
                 .map(Cow::Borrowed),
             StandardVariable::PublisherPlace => entry
-                .map(|e| e.publisher())
+                .get_publisher()
                 .and_then(|p| p.location())
                 .map(|f| f.select(form))
                 .map(Cow::Borrowed),
@@ -367,6 +518,10 @@ impl EntryLike for Entry {
                 .map(|e| e.url())
                 .map(|d| Cow::Owned(StringChunk::verbatim(d.to_string()).into())),
             StandardVariable::VolumeTitle => {
+                if let Some(title) = self.map(|e| e.volume_title()) {
+                    return Some(Cow::Borrowed(title.select(form)));
+                }
+
                 let selector = select!(
                     (Anthos > ("p":Anthology)) |
                     (Entry  > ("p":*)) |
@@ -387,19 +542,48 @@ impl EntryLike for Entry {
         Some(Cow::Borrowed(match variable {
             DateVariable::Accessed => self.url_any().and_then(|u| u.visit_date.as_ref()),
             DateVariable::AvailableDate => None,
-            DateVariable::EventDate => self
-                .bound_select(&select!(* > ("p":(Exhibition | Conference | Misc))), "p")
-                .and_then(Entry::date),
+            DateVariable::EventDate => self.get_event().and_then(Entry::date),
             DateVariable::Issued => self.date_any(),
             DateVariable::OriginalDate => self.get_original().and_then(|e| e.date()),
             DateVariable::Submitted => None,
         }?))
     }
 
+    fn resolve_transliterated_standard_variable(
+        &self,
+        form: LongShortForm,
+        variable: StandardVariable,
+    ) -> Option<Cow<'_, ChunkedString>> {
+        // Short forms are usually not transliterated.
+        if matches!(form, LongShortForm::Short) {
+            return None;
+        }
+
+        let title = match variable {
+            StandardVariable::Title => self.title(),
+            StandardVariable::ContainerTitle => {
+                self.get_container().and_then(|e| e.title())
+            }
+            StandardVariable::CollectionTitle => {
+                self.get_collection().and_then(|e| e.title())
+            }
+            _ => None,
+        }?;
+
+        title.transliterated.as_deref().map(Cow::Borrowed)
+    }
+
     fn resolve_name_variable(
         &self,
         variable: taxonomy::NameVariable,
     ) -> Vec<Cow<'_, Person>> {
+        // A post without an author is attributed to the account.
+        if variable == NameVariable::Author && self.authors().is_none() {
+            if let Some(handle) = self.post_handle() {
+                return vec![Cow::Owned(Person::literal(format!("@{handle}")))];
+            }
+        }
+
         match variable {
             NameVariable::Author => self.authors().map(|a| a.iter().collect()),
             NameVariable::Chair => self
@@ -469,7 +653,9 @@ impl EntryLike for Entry {
             NameVariable::Illustrator => {
                 self.map(|e| Some(e.affiliated_with_role(PersonRole::Illustrator)))
             }
-            NameVariable::Interviewer => None,
+            NameVariable::Interviewer => {
+                self.map(|e| Some(e.affiliated_with_role(PersonRole::Interviewer)))
+            }
             NameVariable::Narrator => {
                 self.map(|e| Some(e.affiliated_with_role(PersonRole::Narrator)))
             }
@@ -537,7 +723,8 @@ impl EntryLike for Entry {
                     return is_news;
                 }
 
-                let is_conference = select!(Article > Proceedings).matches(self);
+                let is_conference =
+                    select!(Article > (Proceedings | Conference)).matches(self);
                 if kind == Kind::PaperConference {
                     return is_conference;
                 }
@@ -608,7 +795,11 @@ impl EntryLike for Entry {
                 self.entry_type() == &EntryType::Entry && !is_encyclopedia
             }
             Kind::Event => self.entry_type() == &EntryType::Exhibition,
-            Kind::Hearing | Kind::Interview | Kind::Performance | Kind::Speech => false,
+            Kind::Hearing => self.entry_type() == &EntryType::Hearing,
+            Kind::Interview => {
+                !self.affiliated_with_role(PersonRole::Interviewer).is_empty()
+            }
+            Kind::Performance | Kind::Speech => false,
             Kind::Broadcast | Kind::MotionPicture | Kind::MusicalScore | Kind::Song => {
                 let is_music_score =
                     select!(Audio > (Book | Periodical | Reference | Misc | Blog | Web))
@@ -645,7 +836,9 @@ impl EntryLike for Entry {
                 is_published
             }
             Kind::LegalCase => self.entry_type() == &EntryType::Case,
-            Kind::Regulation | Kind::Standard | Kind::Treaty => false,
+            Kind::Regulation => self.entry_type() == &EntryType::Regulation,
+            Kind::Standard => self.entry_type() == &EntryType::Standard,
+            Kind::Treaty => false,
             Kind::Patent => self.entry_type() == &EntryType::Patent,
             Kind::Webpage | Kind::PostWeblog | Kind::Post => {
                 let is_blogpost = select!(* > Blog).matches(self);
@@ -662,7 +855,7 @@ impl EntryLike for Entry {
                     .matches(self)
                     && !(is_blogpost || is_post)
             }
-            Kind::Dataset => false,
+            Kind::Dataset => self.entry_type() == &EntryType::Dataset,
             Kind::Figure | Kind::Graphic | Kind::Map => {
                 let is_figure = select!(Artwork > Article).matches(self);
                 if kind == Kind::Figure {
@@ -678,7 +871,9 @@ impl EntryLike for Entry {
             Kind::Pamphlet => false,
             Kind::PersonalCommunication => false,
             Kind::Review | Kind::ReviewBook => false,
-            Kind::Software => self.entry_type() == &EntryType::Repository,
+            Kind::Software => {
+                matches!(self.entry_type(), EntryType::Repository | EntryType::Software)
+            }
             Kind::Document => self.entry_type() == &EntryType::Misc,
         }
     }
@@ -686,6 +881,16 @@ impl EntryLike for Entry {
     fn is_english(&self) -> Option<bool> {
         self.language().map(|l| l.language.as_str() == "en")
     }
+
+    fn language_code(&self) -> Option<Cow<'_, str>> {
+        self.language().map(|l| Cow::Owned(l.to_string()))
+    }
+
+    fn post_handle(&self) -> Option<Cow<'_, str>> {
+        self.handle()
+            .filter(|_| self.matches_entry_type(Kind::Post))
+            .map(|h| Cow::Borrowed(h.trim_start_matches('@')))
+    }
 }
 
 #[cfg(feature = "csl-json")]
@@ -758,52 +963,7 @@ impl EntryLike for citationberg::json::Item {
         match self.0.get(&variable.to_string()) {
             Some(csl_json::Value::Names(names)) => names
                 .iter()
-                .filter_map(|name| {
-                    Some(Cow::Owned(match name {
-                        csl_json::NameValue::Literal(l) => Person {
-                            name: l.literal.clone(),
-                            prefix: None,
-                            suffix: None,
-                            given_name: None,
-                            alias: None,
-                        },
-                        csl_json::NameValue::Item(csl_json::NameItem {
-                            family,
-                            given,
-                            non_dropping_particle: None,
-                            dropping_particle: None,
-                            suffix,
-                        }) => {
-                            let mut parts = vec![family.as_str()];
-                            if let Some(given) = given {
-                                parts.push(given.as_str());
-                            }
-                            let mut p = Person::from_strings(parts).ok()?;
-                            if let Some(suffix) = suffix {
-                                p.suffix = Some(suffix.as_str().to_owned());
-                            }
-
-                            p
-                        }
-                        csl_json::NameValue::Item(csl_json::NameItem {
-                            family,
-                            given,
-                            non_dropping_particle,
-                            dropping_particle,
-                            suffix,
-                        }) => Person {
-                            name: if let Some(non_drop) = non_dropping_particle {
-                                format!("{non_drop} {family}")
-                            } else {
-                                family.clone()
-                            },
-                            prefix: dropping_particle.clone(),
-                            suffix: suffix.clone(),
-                            given_name: given.clone(),
-                            alias: None,
-                        },
-                    }))
-                })
+                .filter_map(|name| crate::csl_json::person(name).map(Cow::Owned))
                 .collect(),
             _ => vec![],
         }
@@ -880,6 +1040,13 @@ impl EntryLike for citationberg::json::Item {
             .map(|l| l.starts_with("en"))
     }
 
+    fn language_code(&self) -> Option<Cow<'_, str>> {
+        self.0
+            .get("language")
+            .and_then(|l| l.to_str())
+            .map(|l| Cow::Owned(l.to_string()))
+    }
+
     fn key(&self) -> Cow<'_, str> {
         self.id().unwrap_or_default()
     }
@@ -0,0 +1,712 @@
+//! Provides conversion methods for CSL-JSON.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use citationberg::json as csl_json;
+use citationberg::taxonomy::{
+    DateVariable, Kind, NameVariable, NumberVariable, PageVariable, StandardVariable,
+};
+use citationberg::LongShortForm;
+use serde_json::{Map, Value};
+use unic_langid::LanguageIdentifier;
+use url::Url;
+
+use crate::csl::EntryLike;
+use crate::types::*;
+use crate::Entry;
+
+/// The custom field that holds the CSL type of an imported item whose type
+/// has no exact equivalent.
+const CSL_TYPE_FIELD: &str = "csl-type";
+
+/// Convert a CSL-JSON name into a [`Person`].
+pub(crate) fn person(name: &csl_json::NameValue) -> Option<Person> {
+    Some(match name {
+        csl_json::NameValue::Literal(l) => Person::literal(l.literal.as_str()),
+        csl_json::NameValue::Item(csl_json::NameItem {
+            family,
+            given,
+            non_dropping_particle: None,
+            dropping_particle: None,
+            suffix,
+        }) => {
+            let mut parts = vec![family.as_str()];
+            if let Some(given) = given {
+                parts.push(given.as_str());
+            }
+            let mut p = Person::from_strings(parts).ok()?;
+            if let Some(suffix) = suffix {
+                p.suffix = Some(suffix.as_str().to_owned());
+            }
+
+            p
+        }
+        csl_json::NameValue::Item(csl_json::NameItem {
+            family,
+            given,
+            non_dropping_particle,
+            dropping_particle,
+            suffix,
+        }) => Person {
+            name: if let Some(non_drop) = non_dropping_particle {
+                format!("{non_drop} {family}")
+            } else {
+                family.clone()
+            },
+            prefix: dropping_particle.clone(),
+            suffix: suffix.clone(),
+            given_name: given.clone(),
+            alias: None,
+            transliterated: None,
+        },
+    })
+}
+
+/// Map a CSL item type to an entry type, the type of the container the
+/// `container-title` belongs to, and whether the mapping is exact.
+fn entry_type(kind: Kind) -> (EntryType, Option<EntryType>, bool) {
+    match kind {
+        Kind::Article => (EntryType::Article, Some(EntryType::Periodical), true),
+        Kind::ArticleJournal => (EntryType::Article, Some(EntryType::Periodical), true),
+        Kind::ArticleMagazine => (EntryType::Article, Some(EntryType::Periodical), false),
+        Kind::ArticleNewspaper => (EntryType::Article, Some(EntryType::Newspaper), true),
+        Kind::PaperConference => (EntryType::Article, Some(EntryType::Proceedings), true),
+        Kind::PostWeblog => (EntryType::Article, Some(EntryType::Blog), true),
+        Kind::Post => (EntryType::Post, Some(EntryType::Thread), true),
+        Kind::Chapter => (EntryType::Chapter, Some(EntryType::Book), true),
+        Kind::Entry => (EntryType::Entry, Some(EntryType::Reference), true),
+        Kind::EntryEncyclopedia => (EntryType::Entry, Some(EntryType::Reference), true),
+        Kind::EntryDictionary => (EntryType::Entry, Some(EntryType::Reference), false),
+        Kind::Book => (EntryType::Book, Some(EntryType::Book), true),
+        Kind::Classic => (EntryType::Book, Some(EntryType::Book), false),
+        Kind::Collection => (EntryType::Anthology, Some(EntryType::Anthology), true),
+        Kind::Periodical => (EntryType::Periodical, None, true),
+        Kind::Report => (EntryType::Report, Some(EntryType::Book), true),
+        Kind::Thesis => (EntryType::Thesis, None, true),
+        Kind::Manuscript => (EntryType::Manuscript, None, true),
+        Kind::Webpage => (EntryType::Web, Some(EntryType::Web), true),
+        Kind::Patent => (EntryType::Patent, None, true),
+        Kind::LegalCase => (EntryType::Case, Some(EntryType::Reference), true),
+        Kind::Legislation => (EntryType::Legislation, Some(EntryType::Anthology), true),
+        Kind::Bill => (EntryType::Legislation, Some(EntryType::Anthology), true),
+        Kind::MotionPicture => (EntryType::Video, None, true),
+        Kind::Broadcast => (EntryType::Video, Some(EntryType::Video), false),
+        Kind::Song => (EntryType::Audio, Some(EntryType::Audio), true),
+        Kind::MusicalScore => (EntryType::Audio, Some(EntryType::Book), false),
+        Kind::Graphic => (EntryType::Artwork, Some(EntryType::Exhibition), true),
+        Kind::Figure => (EntryType::Artwork, Some(EntryType::Article), true),
+        Kind::Map => (EntryType::Artwork, None, false),
+        Kind::Event => (EntryType::Exhibition, None, true),
+        Kind::Software => (EntryType::Software, None, true),
+        Kind::Dataset => (EntryType::Dataset, None, true),
+        Kind::Standard => (EntryType::Standard, None, true),
+        Kind::Regulation => (EntryType::Regulation, None, true),
+        Kind::Hearing => (EntryType::Hearing, None, true),
+        Kind::Document => (EntryType::Misc, None, true),
+        _ => (EntryType::Misc, None, false),
+    }
+}
+
+/// The type of a parent that [`Entry::get_collection`] recognizes as the
+/// collection of an entry with the given type.
+fn collection_type(kind: EntryType) -> EntryType {
+    match kind {
+        EntryType::Newspaper
+        | EntryType::Performance
+        | EntryType::Periodical
+        | EntryType::Proceedings
+        | EntryType::Book
+        | EntryType::Reference
+        | EntryType::Exhibition => kind,
+        _ => EntryType::Anthology,
+    }
+}
+
+/// Get a non-empty string variable of an item.
+fn string<'a>(item: &'a csl_json::Item, variable: &str) -> Option<&'a str> {
+    match item.0.get(variable)? {
+        csl_json::Value::String(s) if !s.is_empty() => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+/// Get a non-empty string variable of an item as a formattable string.
+fn format_string(item: &csl_json::Item, variable: &str) -> Option<FormatString> {
+    string(item, variable).map(FormatString::with_value)
+}
+
+/// Get a numeric variable of an item.
+fn numeric<T: FromStr>(item: &csl_json::Item, variable: &str) -> Option<MaybeTyped<T>> {
+    match item.0.get(variable)? {
+        csl_json::Value::String(s) if !s.is_empty() => {
+            Some(MaybeTyped::infallible_from_str(s))
+        }
+        csl_json::Value::Number(n) => {
+            Some(MaybeTyped::infallible_from_str(&n.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Get a date variable of an item. Accepts date parts, raw or EDTF strings,
+/// and plain years. Ranges are truncated to their start.
+fn date(item: &csl_json::Item, variable: &str) -> Option<Date> {
+    match item.0.get(variable)? {
+        csl_json::Value::Date(d) => {
+            let d = csl_json::FixedDateRange::try_from(d.clone()).ok()?.start;
+            Some(Date {
+                year: d.year as i32,
+                month: d.month,
+                day: d.day,
+                approximate: false,
+            })
+        }
+        csl_json::Value::String(s) => Date::from_str(s.trim()).ok(),
+        &csl_json::Value::Number(n) => Some(Date::from_year(i32::try_from(n).ok()?)),
+        _ => None,
+    }
+}
+
+/// Get a name variable of an item.
+fn names(item: &csl_json::Item, variable: &str) -> Option<Vec<Person>> {
+    match item.0.get(variable)? {
+        csl_json::Value::Names(names) => {
+            let names: Vec<_> = names.iter().filter_map(person).collect();
+            (!names.is_empty()).then_some(names)
+        }
+        _ => None,
+    }
+}
+
+impl From<&csl_json::Item> for Entry {
+    fn from(item: &csl_json::Item) -> Self {
+        let key = match item.0.get("id") {
+            Some(csl_json::Value::String(s)) => s.clone(),
+            Some(csl_json::Value::Number(n)) => n.to_string(),
+            _ => String::new(),
+        };
+
+        let csl_type = string(item, "type");
+        let kind = csl_type.and_then(|t| Kind::from_str(t).ok());
+        let (kind, container_kind, exact) = match kind {
+            Some(kind) => entry_type(kind),
+            None => (EntryType::Misc, None, false),
+        };
+
+        let mut entry = Entry::new(&key, kind);
+
+        if let Some(genre) = format_string(item, "genre") {
+            entry.set_genre(genre);
+        }
+
+        // Items whose type has no exact equivalent keep it in a field that is
+        // not rendered.
+        if let Some(csl_type) = csl_type.filter(|_| !exact) {
+            entry.set_custom_field(CSL_TYPE_FIELD, csl_type.into());
+        }
+
+        if let Some(mut title) = format_string(item, "title") {
+            if let Some(short) = format_string(item, "title-short") {
+                title.short = Some(Box::new(short.value));
+            }
+            entry.set_title(title);
+        }
+
+        if let Some(authors) = names(item, "author") {
+            entry.set_authors(authors);
+        }
+        if let Some(editors) = names(item, "editor") {
+            entry.set_editors(editors);
+        }
+
+        for (variable, role) in [
+            ("translator", PersonRole::Translator),
+            ("compiler", PersonRole::Compiler),
+            ("composer", PersonRole::Composer),
+            ("contributor", PersonRole::Collaborator),
+            ("director", PersonRole::Director),
+            ("executive-producer", PersonRole::ExecutiveProducer),
+            ("illustrator", PersonRole::Illustrator),
+            ("interviewer", PersonRole::Interviewer),
+            ("narrator", PersonRole::Narrator),
+            ("organizer", PersonRole::Organizer),
+            ("performer", PersonRole::CastMember),
+            ("producer", PersonRole::Producer),
+            ("script-writer", PersonRole::Writer),
+        ] {
+            if let Some(names) = names(item, variable) {
+                entry.add_affiliated_persons((names, role));
+            }
+        }
+
+        if let Some(date) = date(item, "issued") {
+            entry.set_date(date);
+        }
+
+        if let Some(url) = string(item, "URL").and_then(|u| Url::parse(u.trim()).ok()) {
+            entry
+                .set_url(QualifiedUrl { value: url, visit_date: date(item, "accessed") });
+        }
+
+        if let Some(doi) = string(item, "DOI") {
+            entry.set_doi(doi.to_string());
+        }
+        if let Some(isbn) = string(item, "ISBN") {
+            entry.set_isbn(isbn.to_string());
+        }
+        if let Some(issn) = string(item, "ISSN") {
+            entry.set_issn(issn.to_string());
+        }
+        if let Some(pmid) = string(item, "PMID") {
+            entry.set_pmid(pmid.to_string());
+        }
+        if let Some(pmcid) = string(item, "PMCID") {
+            entry.set_pmcid(pmcid.to_string());
+        }
+        if let Some(number) = string(item, "number") {
+            entry.set_keyed_serial_number("serial", number.to_string());
+        }
+        if let Some(version) = string(item, "version") {
+            entry.set_keyed_serial_number("version", version.to_string());
+        }
+
+        if let Some(language) =
+            string(item, "language").and_then(|l| LanguageIdentifier::from_str(l).ok())
+        {
+            entry.set_language(language);
+        }
+
+        if let Some(note) = format_string(item, "note") {
+            entry.set_note(note);
+        }
+        if let Some(abstract_) = format_string(item, "abstract") {
+            entry.set_abstract_(abstract_);
+        }
+        if let Some(annote) = format_string(item, "annote") {
+            entry.set_annote(annote);
+        }
+        if let Some(archive) = format_string(item, "archive") {
+            entry.set_archive(archive);
+        }
+        if let Some(location) = format_string(item, "archive_location") {
+            entry.set_archive_location(location);
+        }
+        if let Some(call_number) = format_string(item, "call-number") {
+            entry.set_call_number(call_number);
+        }
+        if let Some(label) = string(item, "citation-label") {
+            entry.set_citation_label(label.to_string());
+        }
+
+        if let Some(pages) = numeric(item, "page") {
+            entry.set_page_range(pages);
+        }
+        if let Some(MaybeTyped::Typed(total)) = numeric(item, "number-of-pages") {
+            entry.set_page_total(total);
+        }
+        if let Some(edition) = numeric(item, "edition") {
+            entry.set_edition(edition);
+        }
+        if let Some(part) = numeric(item, "part-number") {
+            entry.set_part_number(part);
+        }
+        if let Some(title) = format_string(item, "part-title") {
+            entry.set_part_title(title);
+        }
+
+        // The container holds the volume, issue, and publisher, just like the
+        // BibLaTeX import puts them on the parent.
+        let mut container = match (container_kind, format_string(item, "container-title"))
+        {
+            (Some(kind), Some(mut title)) => {
+                if let Some(short) = format_string(item, "container-title-short") {
+                    title.short = Some(Box::new(short.value));
+                }
+                let mut container = Entry::new(&key, kind);
+                container.set_title(title);
+                if let Some(authors) = names(item, "container-author") {
+                    container.set_authors(authors);
+                }
+                Some(container)
+            }
+            _ => None,
+        };
+
+        let target = container.as_mut().unwrap_or(&mut entry);
+        if let Some(volume) = numeric(item, "volume") {
+            target.set_volume(volume);
+        }
+        if let Some(issue) = numeric(item, "issue") {
+            target.set_issue(issue);
+        }
+        if let Some(MaybeTyped::Typed(total)) = numeric(item, "number-of-volumes") {
+            target.set_volume_total(total);
+        }
+        if let Some(title) = format_string(item, "volume-title") {
+            target.set_volume_title(title);
+        }
+
+        let publisher = format_string(item, "publisher");
+        let location = format_string(item, "publisher-place");
+        if publisher.is_some() || location.is_some() {
+            target.set_publisher(Publisher::new(publisher, location));
+        }
+
+        if let Some(title) = format_string(item, "collection-title") {
+            let mut collection = Entry::new(&key, collection_type(target.entry_type));
+            collection.set_title(title);
+            if let Some(number) = numeric(item, "collection-number") {
+                collection.set_volume(number);
+            }
+            if let Some(editors) = names(item, "collection-editor") {
+                collection.set_editors(editors);
+            }
+            target.add_parent(collection);
+        }
+
+        if let Some(container) = container {
+            entry.add_parent(container);
+        }
+
+        let event_title =
+            format_string(item, "event-title").or_else(|| format_string(item, "event"));
+        let event_place = format_string(item, "event-place");
+        let event_date = date(item, "event-date");
+        if event_title.is_some() || event_place.is_some() || event_date.is_some() {
+            let mut event = Entry::new(&key, EntryType::Conference);
+            if let Some(title) = event_title {
+                event.set_title(title);
+            }
+            if let Some(place) = event_place {
+                event.set_location(place);
+            }
+            if let Some(date) = event_date {
+                event.set_date(date);
+            }
+            entry.add_parent(event);
+        }
+
+        entry
+    }
+}
+
+/// CSL item types and their names in the order in which they are matched
+/// against an entry. Types that [`EntryLike::matches_entry_type`] never
+/// reports for hayagriva entries are omitted.
+const KINDS: &[(Kind, &str)] = &[
+    (Kind::Interview, "interview"),
+    (Kind::ArticleJournal, "article-journal"),
+    (Kind::ArticleNewspaper, "article-newspaper"),
+    (Kind::PaperConference, "paper-conference"),
+    (Kind::Report, "report"),
+    (Kind::Thesis, "thesis"),
+    (Kind::Manuscript, "manuscript"),
+    (Kind::Article, "article"),
+    (Kind::Book, "book"),
+    (Kind::Periodical, "periodical"),
+    (Kind::Collection, "collection"),
+    (Kind::Chapter, "chapter"),
+    (Kind::EntryEncyclopedia, "entry-encyclopedia"),
+    (Kind::Entry, "entry"),
+    (Kind::Event, "event"),
+    (Kind::MusicalScore, "musical_score"),
+    (Kind::MotionPicture, "motion_picture"),
+    (Kind::Song, "song"),
+    (Kind::Broadcast, "broadcast"),
+    (Kind::Bill, "bill"),
+    (Kind::Legislation, "legislation"),
+    (Kind::LegalCase, "legal_case"),
+    (Kind::Patent, "patent"),
+    (Kind::PostWeblog, "post-weblog"),
+    (Kind::Post, "post"),
+    (Kind::Webpage, "webpage"),
+    (Kind::Figure, "figure"),
+    (Kind::Graphic, "graphic"),
+    (Kind::Software, "software"),
+    (Kind::Dataset, "dataset"),
+    (Kind::Standard, "standard"),
+    (Kind::Regulation, "regulation"),
+    (Kind::Hearing, "hearing"),
+    (Kind::Document, "document"),
+];
+
+/// The exported standard variables. Variables that the processor resolves
+/// and aliases of other variables are left out.
+const STANDARD_VARIABLES: &[StandardVariable] = &[
+    StandardVariable::Abstract,
+    StandardVariable::Annote,
+    StandardVariable::Archive,
+    StandardVariable::ArchiveLocation,
+    StandardVariable::Authority,
+    StandardVariable::CallNumber,
+    StandardVariable::CollectionTitle,
+    StandardVariable::ContainerTitle,
+    StandardVariable::ContainerTitleShort,
+    StandardVariable::Dimensions,
+    StandardVariable::DOI,
+    StandardVariable::EventTitle,
+    StandardVariable::EventPlace,
+    StandardVariable::Genre,
+    StandardVariable::ISBN,
+    StandardVariable::ISSN,
+    StandardVariable::Language,
+    StandardVariable::Note,
+    StandardVariable::OriginalPublisher,
+    StandardVariable::OriginalPublisherPlace,
+    StandardVariable::OriginalTitle,
+    StandardVariable::PartTitle,
+    StandardVariable::PMCID,
+    StandardVariable::PMID,
+    StandardVariable::Publisher,
+    StandardVariable::PublisherPlace,
+    StandardVariable::Source,
+    StandardVariable::Title,
+    StandardVariable::TitleShort,
+    StandardVariable::URL,
+    StandardVariable::VolumeTitle,
+];
+
+/// The exported number variables.
+const NUMBER_VARIABLES: &[NumberVariable] = &[
+    NumberVariable::ChapterNumber,
+    NumberVariable::CollectionNumber,
+    NumberVariable::Edition,
+    NumberVariable::Issue,
+    NumberVariable::Number,
+    NumberVariable::NumberOfPages,
+    NumberVariable::NumberOfVolumes,
+    NumberVariable::PartNumber,
+    NumberVariable::PrintingNumber,
+    NumberVariable::Version,
+    NumberVariable::Volume,
+];
+
+/// The exported date variables.
+const DATE_VARIABLES: &[DateVariable] = &[
+    DateVariable::Accessed,
+    DateVariable::EventDate,
+    DateVariable::Issued,
+    DateVariable::OriginalDate,
+];
+
+/// The exported name variables.
+const NAME_VARIABLES: &[NameVariable] = &[
+    NameVariable::Author,
+    NameVariable::Chair,
+    NameVariable::CollectionEditor,
+    NameVariable::Compiler,
+    NameVariable::Composer,
+    NameVariable::ContainerAuthor,
+    NameVariable::Contributor,
+    NameVariable::Curator,
+    NameVariable::Director,
+    NameVariable::Editor,
+    NameVariable::ExecutiveProducer,
+    NameVariable::Illustrator,
+    NameVariable::Interviewer,
+    NameVariable::Narrator,
+    NameVariable::Organizer,
+    NameVariable::OriginalAuthor,
+    NameVariable::Performer,
+    NameVariable::Producer,
+    NameVariable::ScriptWriter,
+    NameVariable::SeriesCreator,
+    NameVariable::Translator,
+];
+
+/// Whether verbatim chunks of the variable should be protected from case
+/// transformations by citeproc-js.
+fn is_title(variable: StandardVariable) -> bool {
+    matches!(
+        variable,
+        StandardVariable::Title
+            | StandardVariable::TitleShort
+            | StandardVariable::ContainerTitle
+            | StandardVariable::ContainerTitleShort
+            | StandardVariable::CollectionTitle
+            | StandardVariable::VolumeTitle
+            | StandardVariable::EventTitle
+            | StandardVariable::OriginalTitle
+            | StandardVariable::PartTitle
+    )
+}
+
+/// Serialize a string, wrapping verbatim chunks in a `nocase` span if
+/// requested.
+fn string_json(string: &ChunkedString, nocase: bool) -> String {
+    let mut res = String::new();
+    for chunk in &string.0 {
+        if nocase && chunk.kind == ChunkKind::Verbatim {
+            res.push_str("<span class=\"nocase\">");
+            res.push_str(&chunk.value);
+            res.push_str("</span>");
+        } else {
+            res.push_str(&chunk.value);
+        }
+    }
+    res
+}
+
+/// Serialize a number as a JSON number if possible and as a string with en
+/// dashes for ranges otherwise.
+fn numeric_json(numeric: &MaybeTyped<Cow<'_, Numeric>>) -> Value {
+    match numeric {
+        MaybeTyped::Typed(n) => match n.single_number() {
+            Some(n) => Value::from(n),
+            None => Value::from(n.to_string()),
+        },
+        MaybeTyped::String(s) => Value::from(s.as_str()),
+    }
+}
+
+/// Serialize page ranges as a string with en dashes.
+fn pages_json(pages: &MaybeTyped<PageRanges>) -> Value {
+    match pages {
+        MaybeTyped::Typed(pages) => {
+            let mut res = String::new();
+            for part in &pages.ranges {
+                match part {
+                    PageRangesPart::Range(s, e) => write!(res, "{s}–{e}").unwrap(),
+                    _ => write!(res, "{part}").unwrap(),
+                }
+            }
+            Value::from(res)
+        }
+        MaybeTyped::String(s) => Value::from(s.as_str()),
+    }
+}
+
+/// Serialize a date in the `date-parts` form.
+fn date_json(date: &Date) -> Value {
+    let mut parts = vec![Value::from(date.year)];
+    if let Some(month) = date.month {
+        parts.push(Value::from(month + 1));
+        if let Some(day) = date.day {
+            parts.push(Value::from(day + 1));
+        }
+    }
+
+    let mut map = Map::new();
+    map.insert("date-parts".into(), Value::Array(vec![Value::Array(parts)]));
+    if date.approximate {
+        map.insert("circa".into(), Value::Bool(true));
+    }
+    Value::Object(map)
+}
+
+/// Serialize a person, splitting the name into its CSL parts.
+fn person_json(person: &Person) -> Value {
+    let mut map = Map::new();
+    let particle = person.name_particle();
+    if person.is_institutional() && particle.is_none() {
+        map.insert("literal".into(), Value::from(person.name.as_str()));
+        return Value::Object(map);
+    }
+
+    map.insert("family".into(), Value::from(person.name_without_particle()));
+    let parts = [
+        ("given", person.given_name.as_deref()),
+        ("dropping-particle", person.prefix.as_deref()),
+        ("non-dropping-particle", particle),
+        ("suffix", person.suffix.as_deref()),
+    ];
+    for (key, part) in parts {
+        if let Some(part) = part {
+            map.insert(key.into(), Value::from(part));
+        }
+    }
+
+    Value::Object(map)
+}
+
+/// Serialize an entry as a CSL-JSON item by resolving all CSL variables the
+/// same way the citation processor does.
+pub(crate) fn to_csl_json(entry: &Entry) -> Value {
+    let mut map = Map::new();
+    map.insert("id".into(), Value::from(entry.key()));
+
+    let kind = entry
+        .custom_field(CSL_TYPE_FIELD)
+        .and_then(serde_yaml::Value::as_str)
+        .or_else(|| {
+            KINDS
+                .iter()
+                .find(|(kind, _)| entry.matches_entry_type(*kind))
+                .map(|&(_, name)| name)
+        })
+        .unwrap_or("document");
+    map.insert("type".into(), Value::from(kind));
+
+    for &variable in STANDARD_VARIABLES {
+        let Some(value) = entry.resolve_standard_variable(LongShortForm::Long, variable)
+        else {
+            continue;
+        };
+
+        // Short forms fall back to the long form. Only export them if they
+        // add information.
+        let long = match variable {
+            StandardVariable::TitleShort => Some(StandardVariable::Title),
+            StandardVariable::ContainerTitleShort => {
+                Some(StandardVariable::ContainerTitle)
+            }
+            _ => None,
+        };
+        if long
+            .and_then(|l| entry.resolve_standard_variable(LongShortForm::Long, l))
+            .is_some_and(|l| l == value)
+        {
+            continue;
+        }
+
+        map.insert(
+            variable.to_string(),
+            Value::from(string_json(&value, is_title(variable))),
+        );
+    }
+
+    // Only explicit labels are exported, the generated ones are left to the
+    // processor that reads the item.
+    if let Some(label) = entry.citation_label() {
+        map.insert("citation-label".into(), Value::from(label.as_str()));
+    }
+
+    for &variable in NUMBER_VARIABLES {
+        if let Some(value) = entry.resolve_number_variable(variable) {
+            map.insert(variable.to_string(), numeric_json(&value));
+        }
+    }
+
+    if let Some(pages) = entry.resolve_page_variable(PageVariable::Page) {
+        map.insert("page".into(), pages_json(&pages));
+    }
+
+    for &variable in DATE_VARIABLES {
+        if let Some(date) = entry.resolve_date_variable(variable) {
+            map.insert(variable.to_string(), date_json(&date));
+        }
+    }
+
+    for &variable in NAME_VARIABLES {
+        let names = entry.resolve_name_variable(variable);
+        if !names.is_empty() {
+            let names = names.iter().map(|p| person_json(p)).collect();
+            map.insert(variable.to_string(), Value::Array(names));
+        }
+    }
+
+    Value::Object(map)
+}
+
+impl Entry {
+    /// Serialize the entry as a CSL-JSON item that can be consumed by
+    /// citeproc-js, pandoc, or Zotero.
+    ///
+    /// Variables are resolved the same way as when the entry is cited, so
+    /// data of parent entries ends up in variables like `container-title`.
+    pub fn to_csl_json_string(&self) -> String {
+        serde_json::to_string_pretty(&to_csl_json(self)).unwrap()
+    }
+}
@@ -0,0 +1,293 @@
+//! Find and merge entries that describe the same work.
+//!
+//! Large bibliographies tend to accumulate duplicates, for example when the
+//! same article is imported from two sources with slightly different titles.
+//! [`find_duplicates`] groups such entries and [`merge`] combines each group
+//! into a single entry.
+//!
+//! ```
+//! use hayagriva::dedupe::{find_duplicates, merge};
+//! use hayagriva::io::from_yaml_str;
+//!
+//! let library = from_yaml_str(r#"
+//! dijkstra:
+//!     type: Article
+//!     title: Go To Statement Considered Harmful
+//!     author: Dijkstra, Edsger W.
+//!     date: 1968
+//! goto:
+//!     type: Article
+//!     title: "Go to statement considered harmful."
+//!     author: Dijkstra, Edsger W.
+//!     date: 1968-03
+//!     serial-number:
+//!         doi: 10.1145/362929.362947
+//! "#).unwrap();
+//!
+//! let groups = find_duplicates(&library);
+//! assert_eq!(groups[0].keys, ["dijkstra", "goto"]);
+//!
+//! let entries: Vec<_> = groups[0].keys.iter().map(|key| library.get(key).unwrap()).collect();
+//! let merged = merge(&entries).unwrap();
+//! assert_eq!(merged.key(), "goto");
+//! assert_eq!(merged.doi(), Some("10.1145/362929.362947"));
+//! ```
+
+use std::cmp::Reverse;
+
+use serde::Serialize;
+
+use crate::lang::collation::base_text;
+use crate::util::normalize_doi;
+use crate::{Entry, Library};
+
+/// Entries of a library that likely describe the same work.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuplicateGroup {
+    /// The keys of the entries, in the order of the library.
+    pub keys: Vec<String>,
+    /// The normalized DOI of the entries, if any of them has one.
+    pub doi: Option<String>,
+}
+
+/// Find groups of entries that likely describe the same work.
+///
+/// Entries are grouped if they have the same DOI, ignoring resolver prefixes
+/// and case. Entries are also grouped if they have the same title, year, and
+/// family name of the first author, ignoring case, punctuation, whitespace,
+/// and diacritics, unless they have different DOIs. The groups are ordered by
+/// their first entry in the library.
+pub fn find_duplicates(library: &Library) -> Vec<DuplicateGroup> {
+    struct Group {
+        keys: Vec<String>,
+        doi: Option<String>,
+        fingerprints: Vec<Fingerprint>,
+    }
+
+    let mut groups: Vec<Group> = vec![];
+    for entry in library.iter() {
+        // DOIs are case-insensitive.
+        let doi = entry.doi().and_then(normalize_doi).map(str::to_lowercase);
+        let fingerprint = Fingerprint::new(entry);
+
+        let by_doi = doi
+            .as_ref()
+            .and_then(|doi| groups.iter().position(|g| g.doi.as_ref() == Some(doi)));
+        let idx = by_doi.or_else(|| {
+            let fingerprint = fingerprint.as_ref()?;
+            groups.iter().position(|g| {
+                (doi.is_none() || g.doi.is_none()) && g.fingerprints.contains(fingerprint)
+            })
+        });
+
+        match idx {
+            Some(idx) => {
+                let group = &mut groups[idx];
+                group.keys.push(entry.key().to_string());
+                group.doi = group.doi.take().or(doi);
+                group.fingerprints.extend(fingerprint);
+            }
+            None => groups.push(Group {
+                keys: vec![entry.key().to_string()],
+                doi,
+                fingerprints: fingerprint.into_iter().collect(),
+            }),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|group| group.keys.len() > 1)
+        .map(|group| DuplicateGroup { keys: group.keys, doi: group.doi })
+        .collect()
+}
+
+/// Merge entries that describe the same work into one.
+///
+/// The entry with the most fields survives and keeps its key, type, and
+/// position. If several entries have the most fields, the first of them
+/// survives. Every field takes the most complete value among the entries,
+/// i.e. the one with the longest serialization, preferring the surviving
+/// entry and then earlier entries if values are equally long. Serial numbers
+/// like the DOI and the ISBN are combined, as are custom fields.
+///
+/// Returns `None` if there are no entries.
+pub fn merge(entries: &[&Entry]) -> Option<Entry> {
+    let (survivor, _) = entries
+        .iter()
+        .enumerate()
+        .max_by_key(|(i, entry)| (entry.field_count(), Reverse(*i)))?;
+
+    let mut merged = entries[survivor].clone();
+    for (i, entry) in entries.iter().enumerate() {
+        if i != survivor {
+            merged.merge_fields(entry);
+        }
+    }
+
+    for entry in entries {
+        let Some(serials) = entry.serial_number() else { continue };
+        for (key, value) in &serials.0 {
+            if merged.keyed_serial_number(key).is_none() {
+                merged.set_keyed_serial_number(key, value.clone());
+            }
+        }
+    }
+
+    Some(merged)
+}
+
+/// Replace `target` with `other` if `other` is more complete.
+pub(crate) fn merge_field<T: Clone + Serialize>(
+    target: &mut Option<T>,
+    other: &Option<T>,
+) {
+    let Some(other) = other else { return };
+    let replace = match target {
+        Some(value) => completeness(other) > completeness(value),
+        None => true,
+    };
+
+    if replace {
+        *target = Some(other.clone());
+    }
+}
+
+/// How much information a field value holds, approximated by the length of
+/// its serialization.
+fn completeness<T: Serialize>(value: &T) -> usize {
+    serde_yaml::to_string(value).map(|s| s.len()).unwrap_or_default()
+}
+
+/// The parts of an entry that identify a work if it has no DOI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    title: String,
+    year: Option<i32>,
+    author: Option<String>,
+}
+
+impl Fingerprint {
+    fn new(entry: &Entry) -> Option<Self> {
+        let title = base_text(&entry.title()?.value.to_str());
+        if title.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            title,
+            year: entry.date().map(|date| date.year),
+            author: entry
+                .authors()
+                .and_then(|authors| authors.first())
+                .map(|author| base_text(&author.name)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::from_yaml_str;
+
+    const DUPLICATES: &str = r#"
+        knuth:
+            type: Book
+            title: The Art of Computer Programming
+            author: Knuth, Donald E.
+            date: 1968
+        taocp:
+            type: Book
+            title: "The art of computer programming."
+            author: ["Knuth, Donald", "Doe, Jane"]
+            date: 1968
+            publisher: Addison-Wesley
+            serial-number:
+                isbn: 978-0-201-03801-1
+        other:
+            type: Book
+            title: The Art of Computer Programming
+            author: Knuth, Donald E.
+            date: 1997
+        shannon:
+            type: Article
+            title: A Mathematical Theory of Communication
+            author: Shannon, Claude
+            serial-number:
+                doi: https://doi.org/10.1002/J.1538-7305.1948.TB01338.X
+        communication:
+            type: Article
+            title: Communication Theory
+            author: Shannon, C. E.
+            date: 1948
+            serial-number:
+                doi: 10.1002/j.1538-7305.1948.tb01338.x
+        élan:
+            type: Article
+            title: Élan vital
+            author: Bergson, Henri
+            date: 1907
+            serial-number:
+                doi: 10.1000/first
+        elan:
+            type: Article
+            title: Elan Vital!
+            author: Bergson, Henri
+            date: 1907
+            serial-number:
+                doi: 10.1000/second
+        "#;
+
+    #[test]
+    fn duplicates() {
+        let library = from_yaml_str(DUPLICATES).unwrap();
+        assert_eq!(
+            find_duplicates(&library),
+            [
+                DuplicateGroup {
+                    keys: vec!["knuth".into(), "taocp".into()],
+                    doi: None,
+                },
+                DuplicateGroup {
+                    keys: vec!["shannon".into(), "communication".into()],
+                    doi: Some("10.1002/j.1538-7305.1948.tb01338.x".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_survivor() {
+        let library = from_yaml_str(DUPLICATES).unwrap();
+        let get = |keys: &[&str]| {
+            keys.iter().map(|key| library.get(key).unwrap()).collect::<Vec<_>>()
+        };
+
+        // The entry with the most fields survives, regardless of the order.
+        for keys in [["knuth", "taocp"], ["taocp", "knuth"]] {
+            let merged = merge(&get(&keys)).unwrap();
+            assert_eq!(merged.key(), "taocp");
+            assert_eq!(merged.authors().unwrap().len(), 2);
+            assert_eq!(merged.isbn(), Some("978-0-201-03801-1"));
+        }
+
+        // On ties, the first entry survives and its values win.
+        let merged = merge(&get(&["knuth", "other"])).unwrap();
+        assert_eq!(merged.key(), "knuth");
+        assert_eq!(merged.date().unwrap().year, 1968);
+        let merged = merge(&get(&["other", "knuth"])).unwrap();
+        assert_eq!(merged.key(), "other");
+        assert_eq!(merged.date().unwrap().year, 1997);
+
+        // The longer title and the date are taken from the other entry.
+        let merged = merge(&get(&["shannon", "communication"])).unwrap();
+        assert_eq!(merged.key(), "communication");
+        assert_eq!(
+            merged.title().unwrap().value.to_str(),
+            "A Mathematical Theory of Communication"
+        );
+        assert_eq!(merged.date().unwrap().year, 1948);
+
+        assert_eq!(merge(&[]), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Create entries from DOIs.
+//!
+//! The metadata is retrieved from the content negotiation endpoint of
+//! `doi.org`, which forwards the request to the registration agency of the DOI
+//! (e.g. Crossref or DataCite). This module does not perform any network
+//! requests itself. Instead, you provide a function that fetches a URL with
+//! the [`CSL_JSON_MEDIA_TYPE`] as its `Accept` header, so that you can use the
+//! HTTP client of your choice.
+//!
+//! ```
+//! use hayagriva::doi::entry_from_doi;
+//!
+//! let response = r#"{
+//!     "type": "article-journal",
+//!     "title": "Go To Statement Considered Harmful",
+//!     "author": [{ "family": "Dijkstra", "given": "Edsger W." }],
+//!     "issued": { "date-parts": [[1968, 3]] }
+//! }"#;
+//!
+//! let entry = entry_from_doi("10.1145/362929.362947", |url| {
+//!     assert_eq!(url, "https://doi.org/10.1145/362929.362947");
+//!     Ok::<_, std::io::Error>(response.to_string())
+//! })
+//! .unwrap();
+//!
+//! assert_eq!(entry.key(), "dijkstra1968");
+//! assert_eq!(entry.doi(), Some("10.1145/362929.362947"));
+//! ```
+
+use citationberg::json as csl_json;
+use serde_json::Value;
+use thiserror::Error;
+
+pub use crate::util::normalize_doi;
+use crate::Entry;
+
+/// The media type to request from the DOI resolver.
+pub const CSL_JSON_MEDIA_TYPE: &str = "application/vnd.citationstyles.csl+json";
+
+/// Errors that may occur when creating an entry from a DOI.
+#[derive(Debug, Error)]
+pub enum DoiError<E> {
+    /// The string is not a DOI.
+    #[error("invalid DOI: {0}")]
+    Invalid(String),
+    /// The fetch function returned an error.
+    #[error("could not fetch DOI metadata: {0}")]
+    Fetch(E),
+    /// The response is not a CSL-JSON item.
+    #[error("invalid CSL-JSON response: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Create an entry from a DOI.
+///
+/// The `fetch` function is called with the URL to request and must send the
+/// request with the [`CSL_JSON_MEDIA_TYPE`] as its `Accept` header and return
+/// the response body. The key of the entry is derived from the last name of
+/// the first author and the year of publication.
+pub fn entry_from_doi<E>(
+    doi: &str,
+    fetch: impl Fn(&str) -> Result<String, E>,
+) -> Result<Entry, DoiError<E>> {
+    let doi = normalize_doi(doi).ok_or_else(|| DoiError::Invalid(doi.to_string()))?;
+    let body = fetch(&format!("https://doi.org/{doi}")).map_err(DoiError::Fetch)?;
+    entry_from_response(doi, &body)
+}
+
+/// Convert the response of the DOI resolver into an entry.
+fn entry_from_response<E>(doi: &str, body: &str) -> Result<Entry, DoiError<E>> {
+    let Value::Object(map) = serde_json::from_str(body)? else {
+        return Err(DoiError::Json(serde::de::Error::custom("expected an object")));
+    };
+
+    // The registration agencies add a lot of fields that are not CSL
+    // variables. Only keep those that can be read as such and use the first
+    // element of lists like `ISSN`.
+    let map = map
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::Array(values) if values.first().is_some_and(|v| v.is_string()) => {
+                    values.into_iter().next().unwrap()
+                }
+                value => value,
+            };
+            serde_json::from_value::<csl_json::Value>(value.clone())
+                .is_ok()
+                .then_some((key, value))
+        })
+        .collect();
+
+    let item: csl_json::Item = serde_json::from_value(Value::Object(map))?;
+    let mut entry = Entry::from(&item);
+    entry.key = derive_key(&entry);
+    if entry.doi().is_none() {
+        entry.set_doi(doi.to_string());
+    }
+
+    Ok(entry)
+}
+
+/// Derive a key like `dijkstra1968` from the first author and the year.
+fn derive_key(entry: &Entry) -> String {
+    let name = entry
+        .authors()
+        .or_else(|| entry.editors())
+        .and_then(|persons| persons.first())
+        .map(|person| person.name.to_lowercase())
+        .unwrap_or_else(|| "doi".to_string());
+
+    let mut key: String = name.chars().filter(|c| c.is_alphanumeric()).collect();
+    if let Some(date) = entry.date() {
+        key.push_str(&date.year.to_string());
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::types::EntryType;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_doi("10.1000/xyz"), Some("10.1000/xyz"));
+        assert_eq!(normalize_doi(" doi:10.1000/xyz "), Some("10.1000/xyz"));
+        assert_eq!(normalize_doi("https://doi.org/10.1000/a/b"), Some("10.1000/a/b"));
+        assert_eq!(normalize_doi("HTTP://DX.DOI.ORG/10.1000/xyz"), Some("10.1000/xyz"));
+        assert_eq!(normalize_doi("11.1000/xyz"), None);
+        assert_eq!(normalize_doi("10.1000/"), None);
+        assert_eq!(normalize_doi("hello"), None);
+    }
+
+    #[test]
+    fn crossref_response() {
+        let body = fs::read_to_string("tests/data/doi-lookup.json").unwrap();
+        let entry = entry_from_doi("https://doi.org/10.1145/362929.362947", |url| {
+            assert_eq!(url, "https://doi.org/10.1145/362929.362947");
+            Ok::<_, ()>(body.clone())
+        })
+        .unwrap();
+
+        assert_eq!(entry.key(), "dijkstra1968");
+        assert_eq!(entry.entry_type(), &EntryType::Article);
+        assert_eq!(
+            entry.title().unwrap().value.to_str(),
+            "Go To Statement Considered Harmful"
+        );
+        assert_eq!(entry.doi(), Some("10.1145/362929.362947"));
+        assert_eq!(entry.issn(), Some("0001-0782"));
+        let journal = &entry.parents()[0];
+        assert_eq!(journal.title().unwrap().value.to_str(), "Communications of the ACM");
+        assert_eq!(journal.volume().unwrap().to_string(), "11");
+    }
+
+    #[test]
+    fn errors() {
+        let fetch = |_: &str| Err("offline");
+        assert!(matches!(entry_from_doi("hello", fetch), Err(DoiError::Invalid(_))));
+        assert!(matches!(
+            entry_from_doi("10.1000/xyz", fetch),
+            Err(DoiError::Fetch("offline"))
+        ));
+        assert!(matches!(
+            entry_from_doi("10.1000/xyz", |_| Ok::<_, ()>("<html>".into())),
+            Err(DoiError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+//! Content hashes for styles, locales, and entries.
+
+use citationberg::{IndependentStyle, Locale};
+use serde::Serialize;
+
+use crate::Entry;
+
+/// A stable hash over the content of a value that is relevant for rendering.
+///
+/// This is intended as a cache key for rendered citations and bibliographies.
+/// Two values with the same fingerprint render the same, and the fingerprint
+/// does not depend on how the value was written, e.g. on whitespace and
+/// attribute order in XML or on the key of an entry.
+///
+/// Fingerprints are the same across runs, processes, and platforms. They are
+/// _not_ guaranteed to stay the same across versions of Hayagriva, so caches
+/// that persist across versions should include the crate version in their key.
+/// This also applies to the version of `serde_yaml`, as the fingerprint is
+/// computed from the YAML serialization of the value.
+pub trait Fingerprint {
+    /// Compute the 128-bit fingerprint.
+    ///
+    /// Returns `None` if the value cannot be serialized to YAML, for example
+    /// because `serde_yaml` does not support an enum nested in another enum.
+    fn fingerprint(&self) -> Option<u128>;
+}
+
+impl Fingerprint for IndependentStyle {
+    /// Hashes the options, macros, layouts, and locale overrides of the
+    /// style. The metadata in `info`, like its title or the date it was last
+    /// updated, does not affect the fingerprint.
+    fn fingerprint(&self) -> Option<u128> {
+        hash_serialized(&(
+            &self.default_locale,
+            &self.settings,
+            &self.citation,
+            &self.bibliography,
+            &self.macros,
+            &self.locale,
+        ))
+    }
+}
+
+impl Fingerprint for Locale {
+    fn fingerprint(&self) -> Option<u128> {
+        hash_serialized(self)
+    }
+}
+
+impl Fingerprint for [Locale] {
+    /// Hashes the locales in order, as earlier locales take precedence when
+    /// looking up terms.
+    fn fingerprint(&self) -> Option<u128> {
+        hash_serialized(self)
+    }
+}
+
+impl Fingerprint for Entry {
+    /// Hashes all fields of the entry and its parents, but not their keys.
+    fn fingerprint(&self) -> Option<u128> {
+        hash_serialized(self)
+    }
+}
+
+/// Hash the serialization of a value, which does not depend on the source the
+/// value was parsed from.
+///
+/// Returns `None` instead of hashing an empty string if the value cannot be
+/// serialized, as that would give all such values the same fingerprint.
+fn hash_serialized<T: Serialize + ?Sized>(value: &T) -> Option<u128> {
+    let serialized = serde_yaml::to_string(value).ok()?;
+    Some(fnv1a(serialized.as_bytes()))
+}
+
+/// The 128-bit FNV-1a hash. Unlike the hashers in the standard library, it is
+/// specified and therefore stable.
+fn fnv1a(bytes: &[u8]) -> u128 {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    bytes
+        .iter()
+        .fold(OFFSET, |hash, &byte| (hash ^ u128::from(byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::from_yaml_str;
+
+    #[test]
+    fn fnv() {
+        assert_eq!(fnv1a(b""), 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(fnv1a(b"a"), 0xd228cb696f1a8caf78912b704e4a8964);
+        assert_eq!(fnv1a(b"foobar"), 0x343e1662793c64bf6f0d3597ba446f18);
+    }
+
+    #[test]
+    fn style_fingerprint() {
+        let style = |head: &str, info: &str| {
+            IndependentStyle::from_xml(&format!(
+                r#"<style {head}>
+                    <info>
+                        <title>{info}</title>
+                        <id>{info}</id>
+                        <updated>2024-01-01T00:00:00+00:00</updated>
+                    </info>
+                    <citation>
+                        <layout>
+                            <text variable="title" font-style="italic" suffix="."/>
+                        </layout>
+                    </citation>
+                </style>"#
+            ))
+            .unwrap()
+            .fingerprint()
+            .unwrap()
+        };
+
+        let head =
+            r#"xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0""#;
+        let reordered = r#"version="1.0"   class="in-text"
+            xmlns="http://purl.org/net/xbiblio/csl""#;
+        let note =
+            r#"xmlns="http://purl.org/net/xbiblio/csl" class="note" version="1.0""#;
+
+        assert_eq!(style(head, "First"), style(head, "First"));
+        assert_eq!(style(head, "First"), style(reordered, "First"));
+        assert_eq!(style(head, "First"), style(head, "Second"));
+        assert_ne!(style(head, "First"), style(note, "First"));
+    }
+
+    #[test]
+    fn entry_fingerprint() {
+        let library = from_yaml_str(
+            r#"
+            first:
+                type: Book
+                title: Title
+                author: Doe, Jane
+            second:
+                author:   "Doe, Jane"
+                title: "Title"
+                type: book
+            third:
+                type: Book
+                title: Other Title
+                author: Doe, Jane
+            "#,
+        )
+        .unwrap();
+
+        let fingerprint = |key| library.get(key).unwrap().fingerprint().unwrap();
+        assert_eq!(fingerprint("first"), fingerprint("second"));
+        assert_ne!(fingerprint("first"), fingerprint("third"));
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn locale_fingerprint() {
+        let locales = crate::archive::locales();
+        let mut reversed = locales.clone();
+        reversed.reverse();
+
+        assert!(locales.fingerprint().is_some());
+        assert_eq!(locales.fingerprint(), crate::archive::locales().fingerprint());
+        assert_ne!(locales.fingerprint(), reversed.fingerprint());
+        assert_ne!(locales[0].fingerprint(), locales[1].fingerprint());
+    }
+}
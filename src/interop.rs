@@ -9,6 +9,7 @@ use tex::{
 };
 use url::Url;
 
+use super::io::BibLaTeXError;
 use super::types::*;
 use super::Entry;
 
@@ -48,12 +49,21 @@ impl From<&tex::Person> for Person {
             }
         }
 
+        // The von part is a non-dropping particle and stays with the family
+        // name so that the style decides whether to demote it.
+        let name = if person.prefix.is_empty() {
+            person.name.clone()
+        } else {
+            format!("{} {}", person.prefix, person.name)
+        };
+
         Self {
-            name: person.name.clone(),
+            name,
             given_name: optional(&person.given_name),
-            prefix: optional(&person.prefix),
+            prefix: None,
             suffix: optional(&person.suffix),
             alias: None,
+            transliterated: None,
         }
     }
 }
@@ -82,20 +92,126 @@ impl From<tex::Date> for Date {
 impl From<&[Spanned<Chunk>]> for ChunkedString {
     fn from(chunks: &[Spanned<Chunk>]) -> Self {
         let mut res = Self::new();
+        // A formatting command whose argument is the next chunk.
+        let mut pending = None;
         for chunk in chunks {
+            let format = pending.take().unwrap_or_default();
             match &chunk.v {
-                Chunk::Normal(s) => res.push_str(s, ChunkKind::Normal),
-                Chunk::Verbatim(s) => res.push_str(s, ChunkKind::Verbatim),
-                Chunk::Math(s) => res.push_str(s, ChunkKind::Math),
+                Chunk::Normal(s) => {
+                    pending = push_tex_str(&mut res, s, ChunkKind::Normal, format)
+                }
+                Chunk::Verbatim(s) => {
+                    pending = push_tex_str(&mut res, s, ChunkKind::Verbatim, format)
+                }
+                Chunk::Math(s) => res.push_formatted_str(s, ChunkKind::Math, format),
             }
         }
         res
     }
 }
 
+/// The formatting commands that map to a [`ChunkFormat`].
+const TEX_FORMAT_COMMANDS: [(&str, ChunkFormat); 3] = [
+    (
+        "\\textsuperscript",
+        ChunkFormat {
+            position: Some(ScriptPosition::Superscript),
+            small_caps: false,
+        },
+    ),
+    (
+        "\\textsubscript",
+        ChunkFormat {
+            position: Some(ScriptPosition::Subscript),
+            small_caps: false,
+        },
+    ),
+    ("\\textsc", ChunkFormat { position: None, small_caps: true }),
+];
+
+/// Push a string, translating the `\textsuperscript`, `\textsubscript`, and
+/// `\textsc` commands to formatted chunks and `\(…\)` spans to math chunks.
+/// Returns the format of a command at the end of the string whose argument
+/// follows in the next chunk.
+fn push_tex_str(
+    res: &mut ChunkedString,
+    mut s: &str,
+    kind: ChunkKind,
+    format: ChunkFormat,
+) -> Option<ChunkFormat> {
+    loop {
+        let next_command = TEX_FORMAT_COMMANDS
+            .iter()
+            .filter_map(|(cmd, f)| s.find(cmd).map(|idx| (idx, *cmd, *f)))
+            .filter(|(idx, cmd, _)| {
+                !s[idx + cmd.len()..].starts_with(|c: char| c.is_ascii_alphabetic())
+            })
+            .min_by_key(|(idx, _, _)| *idx);
+
+        let math = s
+            .find("\\(")
+            .and_then(|start| s[start + 2..].find("\\)").map(|len| (start, len)))
+            .filter(|&(start, _)| next_command.is_none_or(|(idx, _, _)| start < idx));
+
+        if let Some((start, len)) = math {
+            res.push_formatted_str(&s[..start], kind, format);
+            res.push_formatted_str(
+                &s[start + 2..start + 2 + len],
+                ChunkKind::Math,
+                format,
+            );
+            s = &s[start + 2 + len + 2..];
+            continue;
+        }
+
+        let Some((idx, command, cmd_format)) = next_command else {
+            res.push_formatted_str(s, kind, format);
+            return None;
+        };
+
+        res.push_formatted_str(&s[..idx], kind, format);
+        let inner = ChunkFormat {
+            position: cmd_format.position.or(format.position),
+            small_caps: cmd_format.small_caps || format.small_caps,
+        };
+
+        let rest = s[idx + command.len()..].trim_start();
+        let Some(arg) = rest.strip_prefix('{') else {
+            if rest.is_empty() {
+                // The argument is a separate chunk.
+                return Some(inner);
+            }
+
+            res.push_formatted_str(command, kind, format);
+            s = &s[idx + command.len()..];
+            continue;
+        };
+
+        let mut depth = 1;
+        let end = arg
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map_or(arg.len(), |(i, _)| i);
+
+        res.push_formatted_str(&arg[..end], kind, inner);
+        s = arg.get(end + 1..).unwrap_or_default();
+    }
+}
+
 impl From<&[Spanned<Chunk>]> for FormatString {
     fn from(chunks: &[Spanned<Chunk>]) -> Self {
-        Self { value: chunks.into(), short: None }
+        Self {
+            value: chunks.into(),
+            short: None,
+            transliterated: None,
+        }
     }
 }
 
@@ -136,6 +252,7 @@ fn ed_role(role: EditorType, entry_type: &tex::EntryType) -> Option<PersonRole>
             match (role.to_ascii_lowercase().as_str(), other_entry_type.as_deref()) {
                 // See p. 26 of the biblatex-chicago manual and biblatex-apa
                 ("producer", _) => Some(PersonRole::Producer),
+                ("interviewer", _) => Some(PersonRole::Interviewer),
                 // The pervasive Zotero plugin zotero-better-biblatex produces this.
                 ("scriptwriter", _) => Some(PersonRole::Writer),
                 // The biblatex-apa style expects `writer` for videos.
@@ -176,6 +293,139 @@ fn map_res<T>(result: Result<T, RetrievalError>) -> Result<Option<T>, TypeError>
     }
 }
 
+/// Fields that are never inherited through `crossref` or `xdata`.
+const NOT_INHERITED: &[&str] = &[
+    "ids",
+    "crossref",
+    "xref",
+    "xdata",
+    "entryset",
+    "entrysubtype",
+    "execute",
+    "label",
+    "options",
+    "presort",
+    "related",
+    "relatedoptions",
+    "relatedstring",
+    "relatedtype",
+    "shorthand",
+    "shorthandintro",
+    "sortkey",
+];
+
+/// The fields of a child entry that inherit a field of the entry it
+/// cross-references, following the default data inheritance rules of
+/// BibLaTeX (Appendix B of the manual).
+fn inherited_fields(
+    parent: &tex::EntryType,
+    child: &tex::EntryType,
+    field: &str,
+) -> Vec<String> {
+    use tex::EntryType as T;
+
+    if NOT_INHERITED.contains(&field) {
+        return vec![];
+    }
+
+    let title_prefix = match (parent, child) {
+        (T::MvBook, T::Book | T::InBook | T::BookInBook | T::SuppBook)
+        | (
+            T::MvCollection | T::MvReference,
+            T::Collection
+            | T::Reference
+            | T::InCollection
+            | T::InReference
+            | T::SuppCollection,
+        )
+        | (T::MvProceedings, T::Proceedings | T::InProceedings) => Some("main"),
+        (T::Book, T::InBook | T::BookInBook | T::SuppBook)
+        | (
+            T::Collection | T::Reference,
+            T::InCollection | T::InReference | T::SuppCollection,
+        )
+        | (T::Proceedings, T::InProceedings) => Some("book"),
+        (T::Periodical, T::Article | T::SuppPeriodical) => Some("journal"),
+        _ => None,
+    };
+
+    if let Some(prefix) = title_prefix {
+        match field {
+            "title" | "subtitle" | "titleaddon" => {
+                return vec![format!("{prefix}{field}")]
+            }
+            "shorttitle" | "sorttitle" | "indextitle" | "indexsorttitle" => return vec![],
+            _ => {}
+        }
+    }
+
+    if field == "author"
+        && matches!(parent, T::MvBook | T::Book)
+        && matches!(child, T::InBook | T::BookInBook | T::SuppBook)
+    {
+        return vec!["author".into(), "bookauthor".into()];
+    }
+
+    vec![field.into()]
+}
+
+/// Resolve the `xdata` and `crossref` references of all entries in a
+/// bibliography by merging the inherited fields into the referencing
+/// entries. Fields set on an entry itself always take precedence.
+///
+/// `@string` macros, including the predefined month macros, are already
+/// expanded by the parser.
+pub(crate) fn resolve_references(
+    bibliography: &tex::Bibliography,
+) -> Result<Vec<tex::Entry>, BibLaTeXError> {
+    bibliography
+        .iter()
+        .filter(|entry| entry.entry_type != tex::EntryType::XData)
+        .map(|entry| resolve_entry(bibliography, entry, &mut vec![]))
+        .collect()
+}
+
+/// Resolve the references of a single entry. The stack holds the keys of the
+/// entries currently being resolved to detect cycles.
+fn resolve_entry(
+    bibliography: &tex::Bibliography,
+    entry: &tex::Entry,
+    stack: &mut Vec<String>,
+) -> Result<tex::Entry, BibLaTeXError> {
+    if stack.contains(&entry.key) {
+        return Err(BibLaTeXError::CyclicReference(entry.key.clone()));
+    }
+    stack.push(entry.key.clone());
+
+    let mut resolved = entry.clone();
+
+    // Data containers are copied without any field mapping.
+    let xdata = entry.get("xdata").map(|keys| keys.format_verbatim());
+    for key in xdata.iter().flat_map(|keys| keys.split(',')) {
+        let Some(source) = bibliography.get(key.trim()) else { continue };
+        let source = resolve_entry(bibliography, source, stack)?;
+        for (field, value) in source.fields {
+            if !NOT_INHERITED.contains(&field.as_str()) {
+                resolved.fields.entry(field).or_insert(value);
+            }
+        }
+    }
+
+    let crossref = entry.get("crossref").map(|key| key.format_verbatim());
+    if let Some(source) = crossref.and_then(|key| bibliography.get(key.trim())) {
+        let source = resolve_entry(bibliography, source, stack)?;
+        for (field, value) in &source.fields {
+            for target in inherited_fields(&source.entry_type, &entry.entry_type, field) {
+                resolved.fields.entry(target).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    resolved.fields.remove("xdata");
+    stack.pop();
+    Ok(resolved)
+}
+
 impl TryFrom<&tex::Entry> for Entry {
     type Error = TypeError;
 
@@ -213,13 +463,23 @@ impl TryFrom<&tex::Entry> for Entry {
             { tex::EntryType::MvCollection, EntryType::Anthology, None, false },
             { tex::EntryType::Patent, EntryType::Patent, None, false },
             { tex::EntryType::Online, EntryType::Web, None, false },
-            { tex::EntryType::Software, EntryType::Misc, None, false },
-            { tex::EntryType::Dataset, EntryType::Repository, None, false },
+            { tex::EntryType::Software, EntryType::Software, None, false },
+            { tex::EntryType::Dataset, EntryType::Dataset, None, false },
             { tex::EntryType::Set, EntryType::Misc, None, false },
             { tex::EntryType::XData, EntryType::Misc, None, false },
             { tex::EntryType::Unknown(_), EntryType::Misc, None, false },
         ]);
 
+        // BibLaTeX has no legal entry types, but styles like biblatex-oscola
+        // and biblatex-juradiss define them.
+        if let tex::EntryType::Unknown(kind) = &entry.entry_type {
+            match kind.to_ascii_lowercase().as_str() {
+                "jurisdiction" => item.entry_type = EntryType::Case,
+                "legislation" | "legal" => item.entry_type = EntryType::Legislation,
+                _ => {}
+            }
+        }
+
         if let Ok(a) = entry.author().map(|a| a.iter().map(Into::into).collect()) {
             item.set_authors(a);
         }
@@ -301,9 +561,14 @@ impl TryFrom<&tex::Entry> for Entry {
                 item.set_title(FormatString {
                     value: title,
                     short: Some(Box::new(short_title)),
+                    transliterated: None,
                 });
             } else {
-                item.set_title(FormatString { value: title, short: None });
+                item.set_title(FormatString {
+                    value: title,
+                    short: None,
+                    transliterated: None,
+                });
             }
         }
 
@@ -318,21 +583,22 @@ impl TryFrom<&tex::Entry> for Entry {
         if let Some(parent) = book(&mut item, parent) {
             if entry.entry_type == tex::EntryType::Article {
                 if let Some(title) = map_res(entry.journal_title())?.map(Into::into) {
-                    parent.set_title(title);
+                    parent.set_title(FormatString {
+                        value: title,
+                        short: entry.get("shortjournal").map(|s| Box::new(s.into())),
+                        transliterated: None,
+                    });
                 }
             } else if let Some(title) = map_res(entry.book_title())?.map(Into::into) {
                 parent.set_title(title);
             }
         }
 
-        if matches!(
-            entry.entry_type,
-            tex::EntryType::Proceedings
-                | tex::EntryType::MvProceedings
-                | tex::EntryType::InProceedings
-        ) && (map_res(entry.event_date())?.is_some()
+        // Papers and talks may be presented at an event, independently of
+        // whether they were published in proceedings.
+        if map_res(entry.event_date())?.is_some()
             || map_res(entry.eventtitle())?.is_some()
-            || map_res(entry.venue())?.is_some())
+            || map_res(entry.venue())?.is_some()
         {
             let mut conference = Entry::new(&entry.key, EntryType::Conference);
 
@@ -408,7 +674,11 @@ impl TryFrom<&tex::Entry> for Entry {
                     EntryType::Report
                     | EntryType::Patent
                     | EntryType::Entry
-                    | EntryType::Reference => {
+                    | EntryType::Reference
+                    | EntryType::Dataset
+                    | EntryType::Standard
+                    | EntryType::Case
+                    | EntryType::Legislation => {
                         item.set_keyed_serial_number("serial", number.to_string())
                     }
                     _ => item.set_issue(number),
@@ -560,15 +830,42 @@ impl TryFrom<&tex::Entry> for Entry {
             item.set_note(note);
         }
 
-        if let Some(note) = map_res(entry.annotation())?
-            .or_else(|| entry.addendum().ok())
-            .map(Into::into)
-        {
+        if let Some(note) = entry.addendum().ok().map(Into::into) {
             if item.note.is_none() {
                 item.set_note(note);
             }
         }
 
+        // The original edition of a reprinted or translated work.
+        let mut original = Entry::new(&entry.key, EntryType::Original);
+        if let Some(title) = map_res(entry.orig_title())? {
+            original.set_title(title.into());
+        }
+
+        if let Some(date) = map_res(entry.orig_date())?.and_then(|d| match d {
+            PermissiveType::Typed(d) => Some(d),
+            PermissiveType::Chunks(_) => None,
+        }) {
+            original.set_date(date.into());
+        }
+
+        let orig_publisher = map_res(entry.orig_publisher())?.map(|p| comma_list(&p));
+        let orig_location = map_res(entry.orig_location())?.map(|l| comma_list(&l));
+        if orig_publisher.is_some() || orig_location.is_some() {
+            original.set_publisher(Publisher::new(orig_publisher, orig_location));
+        }
+
+        if original.title.is_some()
+            || original.date.is_some()
+            || original.publisher.is_some()
+        {
+            item.add_parent(original);
+        }
+
+        if let Some(annote) = map_res(entry.annotation())? {
+            item.set_annote(annote.into());
+        }
+
         if let Some(abstract_) = map_res(entry.abstract_())? {
             item.set_abstract_(abstract_.into())
         }
@@ -598,6 +895,16 @@ impl TryFrom<&tex::Entry> for Entry {
             }
         }
 
+        // The law report that a case is published in holds the volume.
+        if item.entry_type == EntryType::Case {
+            if let Some(title) = map_res(entry.journal_title())? {
+                let mut reporter = Entry::new(&entry.key, EntryType::Reference);
+                reporter.set_title(title.into());
+                reporter.volume = item.volume.take();
+                item.add_parent(reporter);
+            }
+        }
+
         if let Some(chapter) =
             map_res(entry.chapter())?.or_else(|| map_res(entry.part()).ok().flatten())
         {
@@ -612,6 +919,324 @@ impl TryFrom<&tex::Entry> for Entry {
     }
 }
 
+impl From<&Person> for Vec<Spanned<Chunk>> {
+    fn from(person: &Person) -> Self {
+        // Names without further parts are protected with braces so that
+        // institutional names with commas or "and" survive re-parsing.
+        if person.is_institutional() && person.name_particle().is_none() {
+            return vec![Spanned::detached(Chunk::Verbatim(person.name.clone()))];
+        }
+
+        let mut name = String::new();
+        if let Some(prefix) = &person.prefix {
+            name.push_str(prefix);
+            name.push(' ');
+        }
+        name.push_str(&person.name);
+        if let Some(suffix) = &person.suffix {
+            name.push_str(", ");
+            name.push_str(suffix);
+        }
+        if let Some(given_name) = &person.given_name {
+            name.push_str(", ");
+            name.push_str(given_name);
+        }
+
+        tex_str(name)
+    }
+}
+
+impl From<&ChunkedString> for Vec<Spanned<Chunk>> {
+    fn from(string: &ChunkedString) -> Self {
+        string
+            .0
+            .iter()
+            .map(|chunk| {
+                Spanned::detached(match chunk.kind {
+                    ChunkKind::Normal => Chunk::Normal(chunk.value.clone()),
+                    ChunkKind::Verbatim => Chunk::Verbatim(chunk.value.clone()),
+                    ChunkKind::Math => Chunk::Math(chunk.value.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<&Entry> for tex::Entry {
+    fn from(item: &Entry) -> Self {
+        let has_parent =
+            |kind: EntryType| item.parents.iter().any(|p| p.entry_type == kind);
+        let (entry_type, containers): (_, &[EntryType]) = match item.entry_type {
+            EntryType::Article if has_parent(EntryType::Proceedings) => {
+                (tex::EntryType::InProceedings, &[EntryType::Proceedings])
+            }
+            EntryType::Article => (
+                tex::EntryType::Article,
+                &[EntryType::Periodical, EntryType::Newspaper, EntryType::Blog],
+            ),
+            EntryType::Chapter => (tex::EntryType::InBook, &[EntryType::Book]),
+            EntryType::Anthos => {
+                (tex::EntryType::InCollection, &[EntryType::Anthology, EntryType::Book])
+            }
+            EntryType::Entry if has_parent(EntryType::Reference) => {
+                (tex::EntryType::InReference, &[EntryType::Reference])
+            }
+            EntryType::Book => (tex::EntryType::Book, &[]),
+            EntryType::Anthology => (tex::EntryType::Collection, &[]),
+            EntryType::Proceedings => (tex::EntryType::Proceedings, &[]),
+            EntryType::Periodical => (tex::EntryType::Periodical, &[]),
+            EntryType::Reference => (tex::EntryType::Reference, &[]),
+            EntryType::Thesis => (tex::EntryType::Thesis, &[]),
+            EntryType::Report => (tex::EntryType::Report, &[]),
+            EntryType::Patent => (tex::EntryType::Patent, &[]),
+            EntryType::Web => (tex::EntryType::Online, &[]),
+            EntryType::Dataset => (tex::EntryType::Dataset, &[]),
+            EntryType::Software | EntryType::Repository => {
+                (tex::EntryType::Software, &[])
+            }
+            EntryType::Manuscript => (tex::EntryType::Unpublished, &[]),
+            EntryType::Case => {
+                (tex::EntryType::Unknown("jurisdiction".into()), &[EntryType::Reference])
+            }
+            EntryType::Legislation => {
+                (tex::EntryType::Unknown("legislation".into()), &[])
+            }
+            _ => (tex::EntryType::Misc, &[]),
+        };
+
+        let parent = item.parents.iter().find(|p| containers.contains(&p.entry_type));
+        // Fields that the import places on the container of the item.
+        let container = parent.unwrap_or(item);
+        // Law reports are serials like journals.
+        let is_article =
+            entry_type == tex::EntryType::Article || item.entry_type == EntryType::Case;
+        let mut entry = tex::Entry::new(item.key.clone(), entry_type);
+
+        if let Some(authors) = &item.authors {
+            entry.set("author", tex_persons(authors));
+        }
+
+        if let Some(editors) = &item.editors {
+            entry.set("editor", tex_persons(editors));
+        }
+
+        if let Some(title) = &item.title {
+            entry.set("title", (&title.value).into());
+            if let Some(short) = &title.short {
+                entry.set("shorttitle", (&**short).into());
+            }
+        }
+
+        if let Some(title) = parent.and_then(|p| p.title.as_ref()) {
+            let field = if is_article { "journaltitle" } else { "booktitle" };
+            entry.set(field, (&title.value).into());
+            if let Some(short) = title.short.as_ref().filter(|_| is_article) {
+                entry.set("shortjournal", (&**short).into());
+            }
+        }
+
+        if let Some(conference) =
+            item.parents.iter().find(|p| p.entry_type == EntryType::Conference)
+        {
+            if let Some(title) = &conference.title {
+                entry.set("eventtitle", (&title.value).into());
+            }
+            if let Some(date) = &conference.date {
+                entry.set("eventdate", tex_str(tex_date(date)));
+            }
+            if let Some(venue) = &conference.location {
+                entry.set("venue", (&venue.value).into());
+            }
+        }
+
+        if let Some(date) = &item.date {
+            entry.set("date", tex_str(tex_date(date)));
+        }
+
+        if let Some(edition) = &container.edition {
+            entry.set("edition", tex_str(edition.to_string()));
+        }
+
+        if let Some(volume) = &container.volume {
+            entry.set("volume", tex_str(volume.to_string()));
+        }
+
+        if let Some(issue) = &container.issue {
+            entry.set("number", tex_str(issue.to_string()));
+        } else if let Some(number) = item.keyed_serial_number("serial") {
+            entry.set("number", tex_str(number));
+        }
+
+        if let Some(version) = item.keyed_serial_number("version") {
+            entry.set("version", tex_str(version));
+        }
+
+        if let Some(doi) = item.doi() {
+            entry.set("doi", tex_verbatim(doi));
+        }
+
+        if let Some(isbn) = item.isbn() {
+            entry.set("isbn", tex_str(isbn));
+        }
+
+        if let Some(issn) = item.issn() {
+            entry.set("issn", tex_str(issn));
+        }
+
+        if let Some(arxiv) = item.arxiv() {
+            entry.set("eprint", tex_verbatim(arxiv));
+            entry.set("eprinttype", tex_str("arxiv"));
+        } else if let Some(pmid) = item.pmid() {
+            entry.set("eprint", tex_verbatim(pmid));
+            entry.set("eprinttype", tex_str("pubmed"));
+        }
+
+        if let Some(url) = &item.url {
+            entry.set("url", tex_verbatim(url.value.as_str()));
+            if let Some(date) = &url.visit_date {
+                entry.set("urldate", tex_str(tex_date(date)));
+            }
+        }
+
+        if let Some(publisher) = &container.publisher {
+            if let Some(name) = publisher.name() {
+                entry.set("publisher", (&name.value).into());
+            }
+            if let Some(location) = publisher.location() {
+                entry.set("location", (&location.value).into());
+            }
+        }
+
+        // The court of a case is not part of the law report.
+        let organizer = if item.entry_type == EntryType::Case { item } else { container };
+        if let Some(organization) = &organizer.organization {
+            let field = if matches!(
+                item.entry_type,
+                EntryType::Thesis | EntryType::Report | EntryType::Case
+            ) {
+                "institution"
+            } else {
+                "organization"
+            };
+            entry.set(field, (&organization.value).into());
+        }
+
+        if let Some(pages) = &item.page_range {
+            let pages = match pages {
+                MaybeTyped::Typed(pages) => {
+                    pages.ranges.iter().map(tex_page_range_part).collect()
+                }
+                MaybeTyped::String(pages) => pages.clone(),
+            };
+            entry.set("pages", tex_str(pages));
+        }
+
+        if let Some(page_total) = &container.page_total {
+            entry.set("pagetotal", tex_str(page_total.to_string()));
+        }
+
+        if let Some(note) = &item.note {
+            entry.set("note", (&note.value).into());
+        }
+
+        if let Some(abstract_) = &item.abstract_ {
+            entry.set("abstract", (&abstract_.value).into());
+        }
+
+        if let Some(annote) = &item.annote {
+            entry.set("annotation", (&annote.value).into());
+        }
+
+        if let Some(original) =
+            item.parents.iter().find(|p| p.entry_type == EntryType::Original)
+        {
+            if let Some(title) = &original.title {
+                entry.set("origtitle", (&title.value).into());
+            }
+            if let Some(date) = &original.date {
+                entry.set("origdate", tex_str(tex_date(date)));
+            }
+            if let Some(publisher) = &original.publisher {
+                if let Some(name) = publisher.name() {
+                    entry.set("origpublisher", (&name.value).into());
+                }
+                if let Some(location) = publisher.location() {
+                    entry.set("origlocation", (&location.value).into());
+                }
+            }
+        }
+
+        if let Some(genre) = &item.genre {
+            entry.set("type", (&genre.value).into());
+        }
+
+        entry
+    }
+}
+
+impl Entry {
+    /// Serialize the entry as a BibLaTeX `.bib` entry.
+    ///
+    /// Entry types and fields are mapped back to their BibLaTeX counterparts,
+    /// the inverse of what happens when parsing a BibLaTeX file. Fields
+    /// without a BibLaTeX equivalent are dropped.
+    pub fn to_biblatex_string(&self) -> String {
+        tex::Entry::from(self).to_biblatex_string()
+    }
+
+    /// Serialize the entry as a BibTeX `.bib` entry.
+    ///
+    /// Fails if a field cannot be expressed in BibTeX.
+    pub fn to_bibtex_string(&self) -> Result<String, TypeError> {
+        tex::Entry::from(self).to_bibtex_string()
+    }
+}
+
+fn tex_str(value: impl Into<String>) -> Vec<Spanned<Chunk>> {
+    vec![Spanned::detached(Chunk::Normal(value.into()))]
+}
+
+fn tex_verbatim(value: impl Into<String>) -> Vec<Spanned<Chunk>> {
+    vec![Spanned::detached(Chunk::Verbatim(value.into()))]
+}
+
+fn tex_persons(persons: &[Person]) -> Vec<Spanned<Chunk>> {
+    let mut chunks = vec![];
+    for (i, person) in persons.iter().enumerate() {
+        if i != 0 {
+            chunks.extend(tex_str(" and "));
+        }
+        chunks.extend(Vec::<Spanned<Chunk>>::from(person));
+    }
+    chunks
+}
+
+/// Format a date in the ISO 8601-2 form BibLaTeX expects.
+fn tex_date(date: &Date) -> String {
+    let sign = if date.year < 0 { "-" } else { "" };
+    let mut res = format!("{sign}{:04}", date.year.unsigned_abs());
+    if let Some(month) = date.month {
+        res.push_str(&format!("-{:02}", month + 1));
+        if let Some(day) = date.day {
+            res.push_str(&format!("-{:02}", day + 1));
+        }
+    }
+    if date.approximate {
+        res.push('~');
+    }
+    res
+}
+
+fn tex_page_range_part(part: &PageRangesPart) -> String {
+    match part {
+        PageRangesPart::Ampersand => "&".to_string(),
+        PageRangesPart::Comma => ",".to_string(),
+        PageRangesPart::EscapedRange(s, e) => format!("{s}\\-{e}"),
+        PageRangesPart::SinglePage(s) => s.to_string(),
+        PageRangesPart::Range(s, e) => format!("{s}--{e}"),
+    }
+}
+
 fn comma_list(items: &[Vec<Spanned<Chunk>>]) -> FormatString {
     let mut value = ChunkedString::new();
     for (i, entity) in items.iter().enumerate() {
@@ -623,12 +1248,19 @@ fn comma_list(items: &[Vec<Spanned<Chunk>>]) -> FormatString {
         value.extend(chunked);
     }
 
-    FormatString { value, short: None }
+    FormatString { value, short: None, transliterated: None }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::types::PersonRole;
+    use biblatex::{Chunk, Spanned};
+    use citationberg::LongShortForm;
+
+    use crate::lang::{SentenceCase, TitleCase};
+    use crate::types::{
+        ChunkFormat, ChunkKind, ChunkedString, Date, EntryType, PersonRole,
+        ScriptPosition, StringChunk,
+    };
 
     #[test]
     fn test_pmid_from_biblatex() {
@@ -651,6 +1283,237 @@ mod tests {
         assert_eq!(Some("54678"), entry.pmid());
     }
 
+    #[test]
+    fn brace_protected_case() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@book{dna, title = {The {DNA} of {LaTeX} Documents}}"#,
+        )
+        .unwrap();
+        let title = &entries.get("dna").unwrap().title().unwrap().value;
+        assert_eq!(title.0[1].kind, ChunkKind::Verbatim);
+        assert_eq!(title.0[1].value, "DNA");
+        assert_eq!(
+            title.format_sentence_case(SentenceCase::new()),
+            "The DNA of LaTeX documents"
+        );
+        assert_eq!(
+            title.format_title_case(TitleCase::new()),
+            "The DNA of LaTeX Documents"
+        );
+
+        // The protection survives a round trip through YAML.
+        let yaml = crate::io::to_yaml_str(&entries).unwrap();
+        assert!(yaml.contains("The {DNA} of {LaTeX} Documents"));
+        let entries = crate::io::from_yaml_str(&yaml).unwrap();
+        assert_eq!(&entries.get("dna").unwrap().title().unwrap().value, title);
+
+        let entries = crate::io::from_biblatex_str(
+            r#"@article{vax, title = {{mRNA} Vaccines and Serum {IgG} Responses}}"#,
+        )
+        .unwrap();
+        let title = &entries.get("vax").unwrap().title().unwrap().value;
+        assert_eq!(
+            title.format_sentence_case(SentenceCase::new()),
+            "mRNA vaccines and serum IgG responses"
+        );
+    }
+
+    #[test]
+    fn tex_format_commands() {
+        let sup = ChunkFormat {
+            position: Some(ScriptPosition::Superscript),
+            ..Default::default()
+        };
+        let sub = ChunkFormat {
+            position: Some(ScriptPosition::Subscript),
+            ..Default::default()
+        };
+        let sc = ChunkFormat { small_caps: true, ..Default::default() };
+
+        let chunks = [
+            Spanned::detached(Chunk::Normal(
+                "H\\textsubscript{2}O by \\textsc{Doe} and E=mc\\textsuperscript".into(),
+            )),
+            Spanned::detached(Chunk::Verbatim("2".into())),
+            Spanned::detached(Chunk::Normal(" \\textbf{x}".into())),
+        ];
+        let string = ChunkedString::from(&chunks[..]);
+        assert_eq!(
+            string.0,
+            [
+                StringChunk::normal("H"),
+                StringChunk::normal("2").with_format(sub),
+                StringChunk::normal("O by "),
+                StringChunk::normal("Doe").with_format(sc),
+                StringChunk::normal(" and E=mc"),
+                StringChunk::verbatim("2").with_format(sup),
+                StringChunk::normal(" \\textbf{x}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tex_math() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@article{l2, title = {On the $L^2$ boundedness of operators}}"#,
+        )
+        .unwrap();
+        let title = &entries.get("l2").unwrap().title().unwrap().value;
+        assert_eq!(title.0[1], StringChunk::math("L^2"));
+        assert_eq!(title.to_string(), "On the $L^2$ boundedness of operators");
+
+        let chunks =
+            [Spanned::detached(Chunk::Normal("Spaces of \\(H^s\\) functions".into()))];
+        let string = ChunkedString::from(&chunks[..]);
+        assert_eq!(
+            string.0,
+            [
+                StringChunk::normal("Spaces of "),
+                StringChunk::math("H^s"),
+                StringChunk::normal(" functions"),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_titles() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@article{short,
+            title = {A Very Long Title About Many Things},
+            shorttitle = {Long Title},
+            journaltitle = {Journal of Interesting Studies},
+            shortjournal = {J. Interest. Stud.},
+            }"#,
+        )
+        .unwrap();
+
+        let entry = entries.get("short").unwrap();
+        let title = entry.title().unwrap();
+        assert_eq!(title.select(LongShortForm::Short).to_str(), "Long Title");
+        let journal = entry.parents[0].title().unwrap();
+        assert_eq!(journal.value.to_str(), "Journal of Interesting Studies");
+        assert_eq!(journal.select(LongShortForm::Short).to_str(), "J. Interest. Stud.");
+
+        let tex = biblatex::Entry::from(entry);
+        assert_eq!(tex.get("shorttitle").unwrap().format_verbatim(), "Long Title");
+        assert_eq!(
+            tex.get("shortjournal").unwrap().format_verbatim(),
+            "J. Interest. Stud."
+        );
+    }
+
+    #[test]
+    fn dataset_and_software() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@dataset{survey,
+            title = {Global Survey of Reading Habits},
+            version = {2.1},
+            }
+
+            @software{tool,
+            title = {Tool},
+            }"#,
+        )
+        .unwrap();
+
+        let survey = entries.get("survey").unwrap();
+        assert_eq!(survey.entry_type, EntryType::Dataset);
+        assert_eq!(survey.keyed_serial_number("version"), Some("2.1"));
+        assert_eq!(entries.get("tool").unwrap().entry_type, EntryType::Software);
+
+        let tex = biblatex::Entry::from(survey);
+        assert_eq!(tex.entry_type, biblatex::EntryType::Dataset);
+    }
+
+    #[test]
+    fn event_of_talk() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@unpublished{doe2019,
+            author = {Doe, Jane},
+            title = {Reading between the lines},
+            date = {2019-05},
+            eventtitle = {12th Conference on X},
+            eventdate = {2019-05-14},
+            venue = {Berlin},
+            }"#,
+        )
+        .unwrap();
+
+        let entry = entries.get("doe2019").unwrap();
+        let event = entry.get_event().unwrap();
+        assert_eq!(event.entry_type, EntryType::Conference);
+        assert_eq!(event.title().unwrap().value.to_str(), "12th Conference on X");
+        assert_eq!(event.location().unwrap().value.to_str(), "Berlin");
+        assert_eq!(event.date().unwrap().day, Some(13));
+
+        let tex = biblatex::Entry::from(entry);
+        assert_eq!(tex.get("venue").unwrap().format_verbatim(), "Berlin");
+    }
+
+    #[test]
+    fn original_publication() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@book{james2010,
+            author = {James, William},
+            title = {The Principles of Psychology},
+            date = {2010},
+            publisher = {Cosimo},
+            origdate = {1890},
+            origtitle = {The Principles of Psychology},
+            origpublisher = {Henry Holt},
+            origlocation = {New York},
+            }"#,
+        )
+        .unwrap();
+
+        let entry = entries.get("james2010").unwrap();
+        assert_eq!(entry.date().unwrap().year, 2010);
+        let original = entry.get_original().unwrap();
+        assert_eq!(original.date().unwrap().year, 1890);
+        assert_eq!(
+            original.title().unwrap().value.to_str(),
+            "The Principles of Psychology"
+        );
+        let publisher = original.publisher().unwrap();
+        assert_eq!(publisher.name().unwrap().value.to_str(), "Henry Holt");
+        assert_eq!(publisher.location().unwrap().value.to_str(), "New York");
+
+        let tex = biblatex::Entry::from(entry);
+        assert_eq!(tex.get("origdate").unwrap().format_verbatim(), "1890");
+        assert_eq!(tex.get("origpublisher").unwrap().format_verbatim(), "Henry Holt");
+    }
+
+    #[test]
+    fn abstract_annotation_and_type() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@thesis{knuth1963,
+            author = {Knuth, Donald},
+            title = {Finite Semifields and Projective Planes},
+            type = {PhD dissertation},
+            institution = {California Institute of Technology},
+            date = {1963},
+            abstract = {On the construction of projective planes.},
+            annotation = {Read alongside the later papers.},
+            }"#,
+        )
+        .unwrap();
+
+        let entry = entries.get("knuth1963").unwrap();
+        assert_eq!(entry.genre().unwrap().value.to_str(), "PhD dissertation");
+        assert_eq!(
+            entry.abstract_().unwrap().value.to_str(),
+            "On the construction of projective planes."
+        );
+        assert_eq!(
+            entry.annote().unwrap().value.to_str(),
+            "Read alongside the later papers."
+        );
+        assert!(entry.note().is_none());
+
+        let tex = biblatex::Entry::from(entry);
+        assert!(tex.get("annotation").is_some());
+    }
+
     #[test]
     /// See https://github.com/typst/hayagriva/issues/266
     fn issue_266() {
@@ -687,4 +1550,62 @@ mod tests {
 
         serde_json::to_value(entry).unwrap();
     }
+
+    #[test]
+    fn legal_entries() {
+        let entries = crate::io::from_biblatex_str(
+            r#"@jurisdiction{deboer,
+            title = {DeBoer v. Snyder},
+            institution = {6th Cir.},
+            number = {14-1341},
+            journaltitle = {F.3d},
+            volume = {772},
+            pages = {388},
+            date = {2014-11-06}
+            }
+
+            @legislation{aca,
+            title = {Patient Protection and Affordable Care Act},
+            number = {111-148},
+            date = {2010-03-23}
+            }"#,
+        )
+        .unwrap();
+
+        let case = entries.get("deboer").unwrap();
+        assert_eq!(case.entry_type, EntryType::Case);
+        assert_eq!(case.keyed_serial_number("serial"), Some("14-1341"));
+        assert_eq!(case.organization.as_ref().unwrap().value.to_str(), "6th Cir.");
+        assert!(case.volume.is_none());
+        let reporter = &case.parents[0];
+        assert_eq!(reporter.entry_type, EntryType::Reference);
+        assert_eq!(reporter.title.as_ref().unwrap().value.to_str(), "F.3d");
+        assert_eq!(reporter.volume.as_ref().unwrap().to_string(), "772");
+
+        let legislation = entries.get("aca").unwrap();
+        assert_eq!(legislation.entry_type, EntryType::Legislation);
+        assert_eq!(legislation.keyed_serial_number("serial"), Some("111-148"));
+
+        let tex = biblatex::Entry::from(case);
+        assert_eq!(tex.entry_type, biblatex::EntryType::Unknown("jurisdiction".into()));
+        assert!(tex.get("journaltitle").is_some());
+        assert!(tex.get("institution").is_some());
+        assert!(tex.get("volume").is_some());
+        let tex = biblatex::Entry::from(legislation);
+        assert_eq!(tex.entry_type, biblatex::EntryType::Unknown("legislation".into()));
+    }
+
+    #[test]
+    fn tex_date_before_common_era() {
+        let date = Date {
+            year: -44,
+            month: Some(2),
+            day: Some(14),
+            approximate: false,
+        };
+        assert_eq!(super::tex_date(&date), "-0044-03-15");
+
+        let date = Date { year: 5, month: None, day: None, approximate: true };
+        assert_eq!(super::tex_date(&date), "0005~");
+    }
 }
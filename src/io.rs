@@ -2,10 +2,12 @@
 
 #[cfg(feature = "biblatex")]
 use biblatex::{Bibliography, TypeError};
+use std::collections::HashMap;
+use std::fmt;
 
-#[cfg(feature = "biblatex")]
-use crate::Entry;
-use crate::Library;
+use serde_yaml::{Mapping, Value};
+
+use crate::{Entry, Library, Location};
 
 /// Parse a bibliography from a YAML string.
 ///
@@ -28,11 +30,315 @@ pub fn from_yaml_str(s: &str) -> Result<Library, serde_yaml::Error> {
     serde_yaml::from_str(s)
 }
 
+/// An error in a YAML bibliography, located in its source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct YamlError {
+    /// The key of the entry the error occurred in, if any.
+    pub key: Option<String>,
+    /// The field the error occurred in. Fields of parents are prefixed with
+    /// `parent.`.
+    pub field: Option<String>,
+    /// Where the field, entry, or syntax error is in the source, if known.
+    pub location: Option<Location>,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = &self.key {
+            write!(f, "entry `{key}`")?;
+            if let Some(field) = &self.field {
+                write!(f, ", field `{field}`")?;
+            }
+            if let Some(location) = self.location {
+                write!(f, " (line {})", location.line)?;
+            }
+        } else if let Some(location) = self.location {
+            write!(f, "line {}", location.line)?;
+        } else {
+            f.write_str("bibliography")?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+/// Parse a bibliography from a YAML string, collecting all errors along with
+/// the entries and fields they occurred in.
+///
+/// This does the same as [`from_yaml_str`], but instead of stopping at the
+/// first error, all entries are checked. For each entry that cannot be read,
+/// the offending fields are reported with their line and column.
+///
+/// ```
+/// use hayagriva::io::from_yaml_str_detailed;
+///
+/// let yaml = r#"
+/// crazy-rich:
+///     type: Book
+///     title: Crazy Rich Asians
+///     date: not-a-date
+/// "#;
+///
+/// let errors = from_yaml_str_detailed(yaml).unwrap_err();
+/// assert_eq!(errors[0].key.as_deref(), Some("crazy-rich"));
+/// assert_eq!(errors[0].field.as_deref(), Some("date"));
+/// assert_eq!(errors[0].location.unwrap().line, 5);
+/// ```
+pub fn from_yaml_str_detailed(s: &str) -> Result<Library, Vec<YamlError>> {
+    let value: Value = serde_yaml::from_str(s).map_err(|err| {
+        vec![YamlError {
+            key: None,
+            field: None,
+            location: err
+                .location()
+                .map(|l| Location { line: l.line(), column: l.column() }),
+            message: err.to_string(),
+        }]
+    })?;
+
+    let err = match serde_yaml::from_value(value.clone()) {
+        Ok(library) => return Ok(library),
+        Err(err) => err,
+    };
+
+    let Value::Mapping(entries) = value else {
+        return Err(vec![YamlError {
+            key: None,
+            field: None,
+            location: None,
+            message: err.to_string(),
+        }]);
+    };
+
+    let locations = key_locations(s);
+    let mut errors = vec![];
+    for (key, entry) in &entries {
+        let Some(key) = key.as_str() else { continue };
+        let before = errors.len();
+        entry_errors(key, entry, "", &|value| value, &locations, &mut errors);
+        if errors.len() == before {
+            if let Err(err) = check_entry(key, entry.clone()) {
+                errors.push(YamlError {
+                    key: Some(key.to_string()),
+                    field: None,
+                    location: locations.get(key).copied(),
+                    message: err.to_string(),
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        errors.push(YamlError {
+            key: None,
+            field: None,
+            location: None,
+            message: err.to_string(),
+        });
+    }
+
+    Err(errors)
+}
+
+/// Try to deserialize a single entry.
+fn check_entry(key: &str, entry: Value) -> Result<Library, serde_yaml::Error> {
+    let mut map = Mapping::new();
+    map.insert(key.into(), entry);
+    serde_yaml::from_value(Value::Mapping(map))
+}
+
+/// Find the fields of an entry that cannot be deserialized by checking them one
+/// by one. `wrap` nests the fields of a parent in its children.
+fn entry_errors(
+    key: &str,
+    entry: &Value,
+    prefix: &str,
+    wrap: &dyn Fn(Value) -> Value,
+    locations: &HashMap<String, Location>,
+    errors: &mut Vec<YamlError>,
+) {
+    if check_entry(key, wrap(entry.clone())).is_ok() {
+        return;
+    }
+
+    let Value::Mapping(fields) = entry else { return };
+    let error = |field: &str, value: &Value, err: serde_yaml::Error| {
+        let field = format!("{prefix}{field}");
+        let location = locations
+            .get(&format!("{key}.{field}"))
+            .or_else(|| locations.get(key))
+            .copied();
+        let mut message = err.to_string();
+        if let Some(found) = scalar(value) {
+            message.push_str(&format!(", found `{found}`"));
+        }
+
+        YamlError {
+            key: Some(key.to_string()),
+            field: Some(field),
+            location,
+            message,
+        }
+    };
+
+    // Check the type on its own first since all other fields are checked
+    // along with it.
+    let entry_type = fields.get("type").cloned().unwrap_or_else(|| "misc".into());
+    let mut single = Mapping::new();
+    single.insert("type".into(), entry_type.clone());
+    if let Err(err) = check_entry(key, wrap(Value::Mapping(single))) {
+        errors.push(error("type", &entry_type, err));
+        return;
+    }
+
+    for (field, value) in fields {
+        let Some(name) = field.as_str() else { continue };
+        if name == "type" {
+            continue;
+        } else if name == "parent" {
+            let parents = match value {
+                Value::Sequence(seq) => seq.iter().collect(),
+                value => vec![value],
+            };
+
+            let prefix = format!("{prefix}parent.");
+            for parent in parents {
+                let wrap_parent = |parent: Value| {
+                    let mut child = Mapping::new();
+                    child.insert("type".into(), entry_type.clone());
+                    child.insert("parent".into(), parent);
+                    wrap(Value::Mapping(child))
+                };
+                entry_errors(key, parent, &prefix, &wrap_parent, locations, errors);
+            }
+            continue;
+        }
+
+        let mut single = Mapping::new();
+        single.insert("type".into(), entry_type.clone());
+        single.insert(field.clone(), value.clone());
+        if let Err(err) = check_entry(key, wrap(Value::Mapping(single))) {
+            errors.push(error(name, value, err));
+        }
+    }
+}
+
+/// Format a scalar YAML value for an error message.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Map the dot-separated paths of the keys in a block-style YAML document to
+/// their locations. Only the first occurrence of each path is recorded.
+fn key_locations(src: &str) -> HashMap<String, Location> {
+    let mut locations = HashMap::new();
+    let mut stack: Vec<(usize, &str)> = vec![];
+
+    for (i, line) in src.lines().enumerate() {
+        let mut indent = line.len() - line.trim_start().len();
+        let mut rest = line.trim_start();
+        if rest.is_empty() || rest.starts_with('#') || rest.starts_with("---") {
+            continue;
+        }
+
+        while let Some(item) = rest.strip_prefix('-') {
+            let trimmed = item.trim_start();
+            if trimmed.len() == item.len() && !item.is_empty() {
+                break;
+            }
+            indent += rest.len() - trimmed.len();
+            rest = trimmed;
+        }
+
+        let Some(colon) =
+            rest.find(": ").or_else(|| rest.strip_suffix(':').map(str::len))
+        else {
+            continue;
+        };
+
+        let key = rest[..colon].trim().trim_matches(|c: char| c == '"' || c == '\'');
+        while stack.last().is_some_and(|&(depth, _)| depth >= indent) {
+            stack.pop();
+        }
+        stack.push((indent, key));
+
+        let path = stack.iter().map(|(_, key)| *key).collect::<Vec<_>>().join(".");
+        let column = line[..indent].chars().count() + 1;
+        locations.entry(path).or_insert(Location { line: i + 1, column });
+    }
+
+    locations
+}
+
 /// Serialize a bibliography to a YAML string.
+///
+/// The output is canonical: Entries keep the order of the library and their
+/// fields are always written in the same order, starting with `type`,
+/// `title`, `author`, and `date`. Custom fields follow the known fields in
+/// alphabetical order and parents come last. Serializing a library that was
+/// read from canonical YAML thus yields the same string.
 pub fn to_yaml_str(entries: &Library) -> Result<String, serde_yaml::Error> {
     serde_yaml::to_string(&entries)
 }
 
+/// List the fields of an entry that are missing after converting it to
+/// another format and back, for example because the format cannot represent
+/// them. Fields of parents are prefixed with `parent.`.
+///
+/// Only the presence of fields is compared, so fields whose values have been
+/// normalized by the conversion are not reported.
+pub fn dropped_fields(original: &Entry, converted: &Entry) -> Vec<String> {
+    let original = serde_yaml::to_value(original).unwrap_or(Value::Null);
+    let converted = serde_yaml::to_value(converted).unwrap_or(Value::Null);
+    let mut dropped = vec![];
+    diff_fields(&original, &converted, "", &mut dropped);
+    dropped
+}
+
+/// Add the fields of `original` that are missing in `converted` to `dropped`.
+fn diff_fields(
+    original: &Value,
+    converted: &Value,
+    prefix: &str,
+    dropped: &mut Vec<String>,
+) {
+    fn parents(value: &Value) -> Vec<&Value> {
+        match value {
+            Value::Sequence(seq) => seq.iter().collect(),
+            value => vec![value],
+        }
+    }
+
+    let Value::Mapping(original) = original else { return };
+    for (field, value) in original {
+        let Some(name) = field.as_str() else { continue };
+        let Some(other) = converted.get(field) else {
+            dropped.push(format!("{prefix}{name}"));
+            continue;
+        };
+
+        if name == "parent" {
+            let prefix = format!("{prefix}parent.");
+            let others = parents(other);
+            for (i, parent) in parents(value).into_iter().enumerate() {
+                match others.get(i) {
+                    Some(other) => diff_fields(parent, other, &prefix, dropped),
+                    None => dropped.push(format!("{prefix}{i}")),
+                }
+            }
+        }
+    }
+}
+
 /// Errors that may occur when parsing a BibLaTeX file.
 #[cfg(feature = "biblatex")]
 #[derive(Clone, Debug)]
@@ -41,6 +347,9 @@ pub enum BibLaTeXError {
     Parse(biblatex::ParseError),
     /// One of the BibLaTeX fields was malformed for its type.
     Type(biblatex::TypeError),
+    /// The `crossref` or `xdata` references of the entry with the given key
+    /// form a cycle.
+    CyclicReference(String),
 }
 
 #[cfg(feature = "biblatex")]
@@ -49,30 +358,42 @@ impl std::fmt::Display for BibLaTeXError {
         match self {
             Self::Parse(err) => write!(f, "biblatex parse error: {err}"),
             Self::Type(err) => write!(f, "biblatex type error: {err}"),
+            Self::CyclicReference(key) => {
+                write!(f, "cyclic crossref or xdata reference in entry {key}")
+            }
         }
     }
 }
 
 /// Parse a bibliography from a BibLaTeX source string.
+///
+/// See [`from_biblatex`] for how references between entries are handled.
 #[cfg(feature = "biblatex")]
 pub fn from_biblatex_str(biblatex: &str) -> Result<Library, Vec<BibLaTeXError>> {
     let bibliography =
         Bibliography::parse(biblatex).map_err(|e| vec![BibLaTeXError::Parse(e)])?;
 
     from_biblatex(&bibliography)
-        .map_err(|e| e.into_iter().map(BibLaTeXError::Type).collect())
 }
 
 /// Parse a bibliography from a BibLaTeX [`Bibliography`].
+///
+/// Fields referenced through `crossref` and `xdata` are merged into the
+/// referencing entries following BibLaTeX's data inheritance rules, so that,
+/// for example, the `title` of a proceedings becomes the `booktitle` of the
+/// papers that cross-reference it. `@xdata` entries themselves are not part of
+/// the resulting library.
 #[cfg(feature = "biblatex")]
-pub fn from_biblatex(bibliography: &Bibliography) -> Result<Library, Vec<TypeError>> {
+pub fn from_biblatex(bibliography: &Bibliography) -> Result<Library, Vec<BibLaTeXError>> {
+    let entries =
+        crate::interop::resolve_references(bibliography).map_err(|e| vec![e])?;
     let res: Vec<Result<Entry, TypeError>> =
-        bibliography.iter().map(TryInto::try_into).collect();
-    let errors: Vec<TypeError> = res
+        entries.iter().map(TryInto::try_into).collect();
+    let errors: Vec<BibLaTeXError> = res
         .iter()
         .filter_map(|item| match item {
             Ok(_) => None,
-            Err(err) => Some(err.clone()),
+            Err(err) => Some(BibLaTeXError::Type(err.clone())),
         })
         .collect();
 
@@ -83,6 +404,149 @@ pub fn from_biblatex(bibliography: &Bibliography) -> Result<Library, Vec<TypeErr
     }
 }
 
+/// Errors that may occur when parsing a RIS file.
+#[cfg(feature = "ris")]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RisError {
+    /// A tag appeared outside of a record, that is, before its `TY` tag.
+    #[error("tag outside of a record on line {0}")]
+    TagOutsideRecord(usize),
+    /// The record starting on the given line is not closed by an `ER` tag.
+    #[error("record starting on line {0} is not terminated")]
+    UnterminatedRecord(usize),
+}
+
+/// A RIS tag that could not be converted to a field of an
+/// [`Entry`](crate::Entry).
+#[cfg(feature = "ris")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownRisTag {
+    /// The key of the entry the tag belongs to.
+    pub key: String,
+    /// The two-character tag, e.g. `KW`.
+    pub tag: String,
+    /// The value of the tag.
+    pub value: String,
+}
+
+/// The result of parsing a RIS file.
+#[cfg(feature = "ris")]
+#[derive(Clone, Debug)]
+pub struct RisImport {
+    /// The converted entries.
+    pub library: Library,
+    /// All tags that could not be converted, in the order of their
+    /// occurrence.
+    pub unknown: Vec<UnknownRisTag>,
+}
+
+/// Parse a bibliography from a RIS string.
+///
+/// Records are keyed by their `ID` tag or, if it is missing, by the last name
+/// of the first author and the year. Tags without an equivalent field are
+/// collected in [`RisImport::unknown`], as are access dates (`Y2`) of records
+/// without a URL.
+///
+/// ```
+/// use hayagriva::io::from_ris_str;
+///
+/// let ris = "TY  - JOUR
+/// AU  - Doe, Jane
+/// TI  - On the Origin of Citations
+/// JO  - Journal of Examples
+/// PY  - 2014
+/// KW  - citations
+/// ER  - ";
+/// let import = from_ris_str(ris).unwrap();
+/// let entry = import.library.get("doe2014").unwrap();
+/// assert_eq!(entry.date().unwrap().year, 2014);
+/// assert_eq!(import.unknown[0].tag, "KW");
+/// ```
+#[cfg(feature = "ris")]
+pub fn from_ris_str(ris: &str) -> Result<RisImport, RisError> {
+    crate::ris::parse(ris)
+}
+
+/// Parse a bibliography from a CSL-JSON string, as exported by Zotero,
+/// citeproc-js, and pandoc.
+///
+/// Items are keyed by their `id`. Items whose CSL type has no exact
+/// equivalent are mapped to the closest entry type and keep the original CSL
+/// type in the custom `csl-type` field, which the CSL-JSON export writes back.
+///
+/// ```
+/// use hayagriva::io::from_csl_json_str;
+///
+/// let json = r#"[{
+///     "id": "kwan2014",
+///     "type": "book",
+///     "title": "Crazy Rich Asians",
+///     "author": [{ "family": "Kwan", "given": "Kevin" }],
+///     "issued": { "date-parts": [[2014]] }
+/// }]"#;
+/// let bib = from_csl_json_str(json).unwrap();
+/// assert_eq!(bib.get("kwan2014").unwrap().date().unwrap().year, 2014);
+/// ```
+#[cfg(feature = "csl-json")]
+pub fn from_csl_json_str(json: &str) -> Result<Library, serde_json::Error> {
+    let items: Vec<citationberg::json::Item> = serde_json::from_str(json)?;
+    Ok(from_csl_json(&items))
+}
+
+/// Parse a bibliography from CSL-JSON items.
+///
+/// Items without an `id` are keyed by their position, starting at `item-1`.
+#[cfg(feature = "csl-json")]
+pub fn from_csl_json(items: &[citationberg::json::Item]) -> Library {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let mut entry = crate::Entry::from(item);
+            if entry.key.is_empty() {
+                entry.key = format!("item-{}", i + 1);
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Serialize a bibliography to a CSL-JSON string.
+///
+/// See [`Entry::to_csl_json_string`](crate::Entry::to_csl_json_string) for
+/// details on the conversion.
+#[cfg(feature = "csl-json")]
+pub fn to_csl_json_str(entries: &Library) -> String {
+    let items: Vec<_> = entries.iter().map(crate::csl_json::to_csl_json).collect();
+    serde_json::to_string_pretty(&items).unwrap()
+}
+
+/// Serialize a bibliography to a BibLaTeX [`Bibliography`].
+#[cfg(feature = "biblatex")]
+pub fn to_biblatex(entries: &Library) -> Bibliography {
+    let mut bibliography = Bibliography::new();
+    for entry in entries.iter() {
+        bibliography.insert(entry.into());
+    }
+    bibliography
+}
+
+/// Serialize a bibliography to a BibLaTeX string.
+///
+/// See [`Entry::to_biblatex_string`] for details on the conversion.
+#[cfg(feature = "biblatex")]
+pub fn to_biblatex_str(entries: &Library) -> String {
+    to_biblatex(entries).to_biblatex_string()
+}
+
+/// Serialize a bibliography to a BibTeX string.
+///
+/// Fails if one of the fields cannot be expressed in BibTeX.
+#[cfg(feature = "biblatex")]
+pub fn to_bibtex_str(entries: &Library) -> Result<String, TypeError> {
+    to_biblatex(entries).to_bibtex_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +567,456 @@ mod tests {
             assert_eq!(match_e, &entry);
         }
     }
+
+    #[test]
+    fn canonical_yaml() {
+        let yaml = r#"
+zeta:
+    parent:
+        title: Journal of Examples
+        type: Periodical
+    date: 2020-01-02
+    author: Doe, Jane
+    type: Article
+    title: Last
+alpha:
+    title: First
+    type: Book
+"#;
+
+        let mut library = from_yaml_str(yaml).unwrap();
+        let canonical = to_yaml_str(&library).unwrap();
+        let fields: Vec<_> = canonical
+            .lines()
+            .filter_map(|line| line.trim_start().split(':').next())
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "zeta", "type", "title", "author", "date", "parent", "type", "title",
+                "alpha", "type", "title"
+            ]
+        );
+
+        let twice = to_yaml_str(&from_yaml_str(&canonical).unwrap()).unwrap();
+        assert_eq!(canonical, twice);
+
+        library.sort_keys();
+        let sorted = to_yaml_str(&library).unwrap();
+        assert!(sorted.starts_with("alpha:"));
+
+        let contents = fs::read_to_string("tests/data/basic.yml").unwrap();
+        let once = to_yaml_str(&from_yaml_str(&contents).unwrap()).unwrap();
+        let twice = to_yaml_str(&from_yaml_str(&once).unwrap()).unwrap();
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn custom_fields_roundtrip() {
+        let contents = fs::read_to_string("tests/data/custom-fields.yml").unwrap();
+        let library = from_yaml_str(&contents).unwrap();
+        let entry = library.get("mcguire2019").unwrap();
+        assert_eq!(entry.custom_field("zotero-key"), Some(&Value::from("ABC123")));
+        assert_eq!(entry.custom_fields().len(), 4);
+        assert!(entry.has("annote"));
+        assert!(entry.annote().is_some());
+        assert_eq!(
+            entry.parents()[0].custom_field("openalex-id"),
+            Some(&Value::from("W2741809807"))
+        );
+
+        let yaml = to_yaml_str(&library).unwrap();
+        let original: Value = serde_yaml::from_str(&contents).unwrap();
+        let written: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(original, written);
+        assert_eq!(from_yaml_str(&yaml).unwrap(), library);
+    }
+
+    #[test]
+    fn yaml_error_locations() {
+        let yaml = r#"
+good:
+    type: Book
+    title: Fine
+smith2020:
+    type: Article
+    title: Broken
+    date: not-a-date
+    parent:
+        - type: Periodical
+          volume: 3
+          date: 2020-13
+typo:
+    type: Bok
+    title: Unknown type
+"#;
+
+        let errors = from_yaml_str_detailed(yaml).unwrap_err();
+        let found: Vec<_> = errors
+            .iter()
+            .map(|e| {
+                (
+                    e.key.as_deref().unwrap(),
+                    e.field.as_deref().unwrap(),
+                    e.location.unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                ("smith2020", "date", Location { line: 8, column: 5 }),
+                ("smith2020", "parent.date", Location { line: 12, column: 11 }),
+                ("typo", "type", Location { line: 14, column: 5 }),
+            ]
+        );
+
+        assert!(errors[0]
+            .to_string()
+            .starts_with("entry `smith2020`, field `date` (line 8): "));
+        assert!(errors[0].message.ends_with(", found `not-a-date`"));
+
+        let syntax = from_yaml_str_detailed("a:\n  b: [").unwrap_err();
+        assert_eq!(syntax.len(), 1);
+        assert!(syntax[0].key.is_none());
+        assert!(syntax[0].location.is_some());
+
+        let library = from_yaml_str_detailed("good:\n    type: Book\n").unwrap();
+        assert_eq!(library.len(), 1);
+    }
+
+    #[test]
+    fn dropped_fields_per_format() {
+        let yaml = r#"
+            lovelace1843:
+                type: article
+                title: Sketch of the Analytical Engine
+                author: Lovelace, Ada
+                date: 1843
+                call-number: QA76.17
+                archive: Bodleian Library
+                parent:
+                    type: periodical
+                    title: Scientific Memoirs
+                    volume: 3
+        "#;
+        let library = from_yaml_str(yaml).unwrap();
+        let original = library.get("lovelace1843").unwrap();
+
+        let check = |converted: Library, expected: &[&str]| {
+            let converted = converted.get("lovelace1843").unwrap();
+            assert_eq!(dropped_fields(original, converted), expected);
+        };
+
+        check(from_yaml_str(&to_yaml_str(&library).unwrap()).unwrap(), &[]);
+
+        #[cfg(feature = "biblatex")]
+        check(
+            from_biblatex_str(&to_biblatex_str(&library)).unwrap(),
+            &["archive", "call-number"],
+        );
+
+        #[cfg(feature = "csl-json")]
+        check(from_csl_json_str(&to_csl_json_str(&library)).unwrap(), &[]);
+
+        let mut bare = original.clone();
+        bare.parents.clear();
+        assert_eq!(dropped_fields(original, &bare), ["parent"]);
+    }
+
+    #[test]
+    #[cfg(feature = "ris")]
+    fn ris_import() {
+        use crate::types::{EntryType, MaybeTyped, PageRanges, PageRangesPart};
+
+        let contents = fs::read_to_string("tests/data/records.ris").unwrap();
+        let import = from_ris_str(&contents).unwrap();
+        assert_eq!(import.library.len(), 3);
+
+        let article = import.library.get("hopper1952").unwrap();
+        assert_eq!(article.entry_type(), &EntryType::Article);
+        let journal = &article.parents()[0];
+        assert_eq!(journal.entry_type(), &EntryType::Periodical);
+        assert_eq!(
+            journal.title().unwrap().value.to_str(),
+            "Journal of the Association for Computing Machinery"
+        );
+        assert_eq!(journal.volume().unwrap().to_string(), "3");
+        assert_eq!(
+            article.page_range(),
+            Some(&MaybeTyped::Typed(PageRanges::new(vec![PageRangesPart::Range(
+                12.into(),
+                20.into()
+            )])))
+        );
+        let date = article.date().unwrap();
+        assert_eq!((date.year, date.month, date.day), (1952, Some(4), Some(1)));
+        assert_eq!(article.authors().unwrap()[0].given_name.as_deref(), Some("Grace"));
+
+        let chapter = import.library.get("lovelace1843").unwrap();
+        assert_eq!(chapter.entry_type(), &EntryType::Chapter);
+        let book = &chapter.parents()[0];
+        assert_eq!(book.entry_type(), &EntryType::Book);
+        assert_eq!(book.editors().unwrap()[0].name, "Taylor");
+        assert_eq!(
+            book.publisher().unwrap().location().unwrap().value.to_str(),
+            "London"
+        );
+        assert_eq!(
+            chapter.title().unwrap().value.to_str(),
+            "Notes by the translator of the memoir on the analytical engine"
+        );
+
+        let paper = import.library.get("dijkstra1967").unwrap();
+        assert_eq!(paper.parents()[0].entry_type(), &EntryType::Proceedings);
+        assert_eq!(paper.authors().unwrap()[0].prefix, None);
+        assert_eq!(paper.doi(), Some("10.1145/363347.363387"));
+
+        let unknown: Vec<_> = import
+            .unknown
+            .iter()
+            .map(|t| (t.key.as_str(), t.tag.as_str(), t.value.as_str()))
+            .collect();
+        assert_eq!(
+            unknown,
+            [
+                ("hopper1952", "KW", "compilers"),
+                ("hopper1952", "KW", "programming languages"),
+                ("lovelace1843", "Y2", "2023/11/20"),
+                ("dijkstra1967", "AN", "ACM-1968-0042"),
+            ]
+        );
+
+        let yaml = to_yaml_str(&import.library).unwrap();
+        let reconstructed = from_yaml_str(&yaml).unwrap();
+        for entry in import.library {
+            let match_e = reconstructed.iter().find(|x| x.key == entry.key).unwrap();
+            assert_eq!(match_e, &entry);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ris")]
+    fn ris_errors() {
+        assert_eq!(
+            from_ris_str("AU  - Doe, Jane\nER  - ").unwrap_err(),
+            RisError::TagOutsideRecord(1)
+        );
+        assert_eq!(
+            from_ris_str("TY  - BOOK\nTI  - Title\nTY  - BOOK\nER  - ").unwrap_err(),
+            RisError::UnterminatedRecord(1)
+        );
+        assert_eq!(
+            from_ris_str("\nTY  - BOOK\nTI  - Title").unwrap_err(),
+            RisError::UnterminatedRecord(2)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "csl-json")]
+    fn csl_json_import() {
+        use crate::types::{EntryType, MaybeTyped, PersonRole};
+
+        let contents = fs::read_to_string("tests/data/items.json").unwrap();
+        let library = from_csl_json_str(&contents).unwrap();
+        assert_eq!(library.len(), 4);
+
+        let article = library.get("knuth1974").unwrap();
+        assert_eq!(article.entry_type(), &EntryType::Article);
+        assert_eq!(article.genre(), None);
+        let journal = &article.parents()[0];
+        assert_eq!(journal.entry_type(), &EntryType::Periodical);
+        assert_eq!(journal.title().unwrap().value.to_str(), "Computing Surveys");
+        assert_eq!(
+            journal.title().unwrap().short.as_ref().unwrap().to_str(),
+            "ACM Comput. Surv."
+        );
+        assert_eq!(journal.volume(), Some(&MaybeTyped::Typed(6.into())));
+        assert_eq!(journal.issue(), Some(&MaybeTyped::Typed(4.into())));
+        let date = article.date().unwrap();
+        assert_eq!((date.year, date.month, date.day), (1974, Some(11), Some(0)));
+        assert_eq!(article.page_range().unwrap().to_string(), "261-301");
+        assert_eq!(article.doi(), Some("10.1145/356635.356640"));
+
+        let chapter = library.get("vanrossum1995").unwrap();
+        assert_eq!(chapter.entry_type(), &EntryType::Chapter);
+        let author = &chapter.authors().unwrap()[0];
+        assert_eq!(author.name, "van Rossum");
+        assert_eq!(author.given_name.as_deref(), Some("Guido"));
+        let book = &chapter.parents()[0];
+        assert_eq!(book.entry_type(), &EntryType::Book);
+        assert_eq!(book.publisher().unwrap().name().unwrap().value.to_str(), "CWI");
+        assert_eq!(book.parents()[0].title().unwrap().value.to_str(), "CWI Reports");
+        assert_eq!(chapter.affiliated_with_role(PersonRole::Translator).len(), 1);
+
+        let speech = library.get("hopper1982").unwrap();
+        assert_eq!(speech.entry_type(), &EntryType::Misc);
+        assert_eq!(speech.genre(), None);
+        assert_eq!(
+            speech.custom_field("csl-type"),
+            Some(&serde_yaml::Value::from("speech"))
+        );
+        let exported = crate::csl_json::to_csl_json(speech);
+        assert_eq!(exported["type"], "speech");
+        assert_eq!(exported.get("genre"), None);
+        let event = &speech.parents()[0];
+        assert_eq!(event.entry_type(), &EntryType::Conference);
+        assert_eq!(event.location().unwrap().value.to_str(), "Washington, DC");
+        assert_eq!(speech.date().unwrap().year, 1982);
+
+        let page = library.get("item-4").unwrap();
+        assert_eq!(page.entry_type(), &EntryType::Web);
+        assert_eq!(page.authors().unwrap()[0].name, "World Wide Web Consortium");
+        let url = page.url().unwrap();
+        assert_eq!(url.value.as_str(), "https://www.w3.org/");
+        assert_eq!(url.visit_date.as_ref().unwrap().year, 2024);
+        assert_eq!(page.date().unwrap().month, Some(2));
+    }
+
+    #[test]
+    #[cfg(feature = "csl-json")]
+    fn csl_json_volume_and_part() {
+        let library = from_csl_json_str(
+            r#"[{
+                "id": "chapter",
+                "type": "chapter",
+                "title": "Astronomy",
+                "container-title": "The Cambridge History of Science",
+                "volume": 3,
+                "volume-title": "Early Modern Science",
+                "part-number": 2,
+                "part-title": "Instruments"
+            }]"#,
+        )
+        .unwrap();
+
+        let chapter = library.get("chapter").unwrap();
+        assert_eq!(chapter.part_number().unwrap().to_string(), "2");
+        assert_eq!(chapter.part_title().unwrap().value.to_str(), "Instruments");
+        let book = &chapter.parents()[0];
+        assert_eq!(book.volume().unwrap().to_string(), "3");
+        assert_eq!(book.volume_title().unwrap().value.to_str(), "Early Modern Science");
+    }
+
+    #[test]
+    #[cfg(feature = "csl-json")]
+    fn csl_json_export() {
+        let contents = fs::read_to_string("tests/data/csl-export.yml").unwrap();
+        let library = from_yaml_str(&contents).unwrap();
+        let exported: serde_json::Value =
+            serde_json::from_str(&to_csl_json_str(&library)).unwrap();
+
+        let expected = fs::read_to_string("tests/data/csl-export.json").unwrap();
+        let expected: serde_json::Value = serde_json::from_str(&expected).unwrap();
+        assert_eq!(exported, expected);
+
+        // The export can be read back in.
+        let reimported = from_csl_json_str(&to_csl_json_str(&library)).unwrap();
+        assert_eq!(reimported.len(), library.len());
+        let knuth = reimported.get("knuth1974").unwrap();
+        assert_eq!(knuth.parents()[0].volume().unwrap().to_string(), "6");
+    }
+
+    #[test]
+    #[cfg(feature = "biblatex")]
+    fn biblatex_references() {
+        use crate::types::EntryType;
+
+        let contents = fs::read_to_string("tests/data/crossref.bib").unwrap();
+        let library = from_biblatex_str(&contents).unwrap();
+        assert!(library.get("cacm").is_none());
+
+        let paper = library.get("hewitt1973").unwrap();
+        let proceedings = &paper.parents()[0];
+        assert_eq!(proceedings.entry_type(), &EntryType::Proceedings);
+        assert_eq!(
+            proceedings.title().unwrap().value.to_str(),
+            "Proceedings of the 1st Annual ACM SIGACT-SIGPLAN Symposium on Principles of \
+             Programming Languages"
+        );
+        assert_eq!(
+            proceedings.publisher().unwrap().name().unwrap().value.to_str(),
+            "Association for Computing Machinery"
+        );
+        assert_eq!(paper.editors().unwrap()[1].name, "Ullman");
+        let date = paper.date().unwrap();
+        assert_eq!((date.year, date.month), (1973, Some(9)));
+
+        let article = library.get("hoare1969").unwrap();
+        let journal = &article.parents()[0];
+        assert_eq!(journal.title().unwrap().value.to_str(), "Communications of the ACM");
+        assert_eq!(journal.volume().unwrap().to_string(), "12");
+
+        // Inheritance through a chain of cross-references.
+        let chapter = library.get("taocp1-info").unwrap();
+        let book = &chapter.parents()[0];
+        assert_eq!(book.title().unwrap().value.to_str(), "Fundamental Algorithms");
+        assert_eq!(book.authors().unwrap()[0].name, "Knuth");
+        assert_eq!(
+            book.parents()[0].title().unwrap().value.to_str(),
+            "The Art of Computer Programming"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "biblatex")]
+    fn biblatex_cyclic_references() {
+        let bib = r#"
+            @book{a, title = {A}, crossref = {b}}
+            @book{b, title = {B}, xdata = {c}}
+            @xdata{c, crossref = {a}}
+        "#;
+
+        let errors = from_biblatex_str(bib).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [BibLaTeXError::CyclicReference(key)] if key == "a"
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "biblatex")]
+    fn roundtrip_biblatex() {
+        let contents = fs::read_to_string("tests/data/export.bib").unwrap();
+        let entries = from_biblatex_str(&contents).unwrap();
+        let biblatex = to_biblatex_str(&entries);
+
+        let reconstructed = from_biblatex_str(&biblatex).unwrap();
+        assert_eq!(entries.len(), reconstructed.len());
+
+        for entry in entries {
+            let match_e = reconstructed.iter().find(|x| x.key == entry.key).unwrap();
+            assert_eq!(match_e, &entry);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "biblatex")]
+    fn export_biblatex_fields() {
+        let contents = fs::read_to_string("tests/data/export.bib").unwrap();
+        let entries = from_biblatex_str(&contents).unwrap();
+
+        let knuth = entries.get("knuth1984").unwrap().to_biblatex_string();
+        assert!(knuth.starts_with("@book{knuth1984,"));
+        assert!(knuth.contains("title = {The {TeX}book}"));
+        assert!(knuth.contains("author = {Knuth, Donald E.}"));
+        assert!(knuth.contains("date = {1984-05-03}"));
+
+        let article = entries.get("lamport1986").unwrap().to_biblatex_string();
+        assert!(article.starts_with("@article{lamport1986,"));
+        assert!(article.contains("journaltitle = {Journal of Typesetting}"));
+        assert!(article.contains("pages = {19--42}"));
+
+        let proceedings = entries.get("vonneumann1946").unwrap();
+        let biblatex = proceedings.to_biblatex_string();
+        assert!(
+            biblatex.contains("author = {von Neumann, John and Goldstine, Herman H.}")
+        );
+        assert!(
+            biblatex.contains("booktitle = {Proceedings of the Moore School Lectures}")
+        );
+        let bibtex = proceedings.to_bibtex_string().unwrap();
+        assert!(bibtex.starts_with("@inproceedings{vonneumann1946,"));
+    }
 }
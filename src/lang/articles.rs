@@ -0,0 +1,65 @@
+//! Leading articles of titles, which are ignored when sorting.
+
+/// The articles of a language, given by its primary language subtag. Articles
+/// ending with an apostrophe are elided and attach to the next word.
+fn articles(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "en" => &["a", "an", "the"],
+        "de" => &[
+            "das", "dem", "den", "der", "des", "die", "ein", "eine", "einem", "einen",
+            "einer", "eines",
+        ],
+        "fr" => &["des", "l'", "la", "le", "les", "un", "une"],
+        "es" => &["el", "la", "las", "los", "un", "una", "unas", "unos"],
+        "it" => &["gli", "i", "il", "l'", "la", "le", "lo", "un", "un'", "una", "uno"],
+        "pt" => &["a", "as", "o", "os", "um", "uma", "umas", "uns"],
+        "nl" => &["'t", "de", "een", "het"],
+        _ => &[],
+    }
+}
+
+/// Remove the leading article of a title in the language with the given
+/// locale code, e.g. `de-AT`.
+pub(crate) fn strip_leading_article<'a>(title: &'a str, locale: &str) -> &'a str {
+    let lang = locale.split(['-', '_']).next().unwrap_or_default();
+    let title = title.trim_start();
+
+    for article in articles(&lang.to_ascii_lowercase()) {
+        let rest = if let Some(stem) = article.strip_suffix('\'') {
+            title
+                .get(..stem.len())
+                .filter(|s| s.eq_ignore_ascii_case(stem))
+                .and_then(|_| title[stem.len()..].strip_prefix(['\'', '’']))
+        } else {
+            title
+                .split_once(char::is_whitespace)
+                .filter(|(word, _)| word.eq_ignore_ascii_case(article))
+                .map(|(_, rest)| rest)
+        };
+
+        if let Some(rest) = rest.map(str::trim_start).filter(|r| !r.is_empty()) {
+            return rest;
+        }
+    }
+
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_articles() {
+        assert_eq!(strip_leading_article("The Great Gatsby", "en-US"), "Great Gatsby");
+        assert_eq!(strip_leading_article("A Study in Scarlet", "en"), "Study in Scarlet");
+        assert_eq!(strip_leading_article("Anathem", "en"), "Anathem");
+        assert_eq!(strip_leading_article("The", "en"), "The");
+        assert_eq!(strip_leading_article("Der Process", "de-DE"), "Process");
+        assert_eq!(strip_leading_article("Der Process", "en-US"), "Der Process");
+        assert_eq!(strip_leading_article("Les Misérables", "fr"), "Misérables");
+        assert_eq!(strip_leading_article("L’Étranger", "fr-FR"), "Étranger");
+        assert_eq!(strip_leading_article("L'Étranger", "fr"), "Étranger");
+        assert_eq!(strip_leading_article("Lettres persanes", "fr"), "Lettres persanes");
+    }
+}
@@ -0,0 +1,271 @@
+//! Language-dependant string comparison for sorting.
+
+use std::cmp::Ordering;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Compares strings following the alphabetical order of a language.
+///
+/// Like the Unicode Collation Algorithm, strings are first compared by their
+/// base letters only, ignoring punctuation, whitespace, and letter case. Ties
+/// are broken by diacritics, then by case (lowercase first). Letters that a
+/// language treats as distinct from their base letter, like the Swedish "å",
+/// are tailored to their place in that language's alphabet. Strings are
+/// compared in NFC, so precomposed and decomposed characters sort the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Collator {
+    /// Letters that sort after a base letter, with their rank after it.
+    tailoring: &'static [(char, char, u32)],
+}
+
+impl Collator {
+    /// Create a collator for a language from its locale code, e.g. `sv-SE`.
+    pub fn new(locale: &str) -> Self {
+        let lang = locale.split(['-', '_']).next().unwrap_or_default();
+        let tailoring: &'static [(char, char, u32)] = match lang
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "sv" | "fi" => &[
+                ('å', 'z', 1),
+                ('ä', 'z', 2),
+                ('æ', 'z', 2),
+                ('ö', 'z', 3),
+                ('ø', 'z', 3),
+            ],
+            "da" | "nb" | "nn" | "no" => &[
+                ('æ', 'z', 1),
+                ('ä', 'z', 1),
+                ('ø', 'z', 2),
+                ('ö', 'z', 2),
+                ('å', 'z', 3),
+            ],
+            "es" => &[('ñ', 'n', 1)],
+            "et" => &[('õ', 'w', 1), ('ä', 'w', 2), ('ö', 'w', 3), ('ü', 'w', 4)],
+            "is" => &[('þ', 'z', 1), ('æ', 'z', 2), ('ö', 'z', 3)],
+            "pl" => &[
+                ('ą', 'a', 1),
+                ('ć', 'c', 1),
+                ('ę', 'e', 1),
+                ('ł', 'l', 1),
+                ('ń', 'n', 1),
+                ('ó', 'o', 1),
+                ('ś', 's', 1),
+                ('ź', 'z', 1),
+                ('ż', 'z', 2),
+            ],
+            "cs" | "sk" => &[('č', 'c', 1), ('ř', 'r', 1), ('š', 's', 1), ('ž', 'z', 1)],
+            "tr" => &[
+                ('ç', 'c', 1),
+                ('ğ', 'g', 1),
+                ('ı', 'h', 1),
+                ('ö', 'o', 1),
+                ('ş', 's', 1),
+                ('ü', 'u', 1),
+            ],
+            _ => &[],
+        };
+
+        Self { tailoring }
+    }
+
+    /// Compare two strings.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let a_key = self.key(a);
+        let b_key = self.key(b);
+
+        a_key
+            .primary
+            .cmp(&b_key.primary)
+            .then_with(|| a_key.secondary.cmp(&b_key.secondary))
+            .then_with(|| a_key.tertiary.cmp(&b_key.tertiary))
+            .then_with(|| a.nfc().cmp(b.nfc()))
+    }
+
+    /// Compute the collation key of a string.
+    fn key(&self, s: &str) -> CollationKey {
+        let mut key = CollationKey::default();
+
+        for c in s.nfc() {
+            let upper = c.is_uppercase();
+            for lower in c.to_lowercase() {
+                // Combining diacritics only affect the secondary level.
+                if ('\u{300}'..='\u{36F}').contains(&lower) {
+                    key.secondary.push(lower as u32);
+                    continue;
+                }
+
+                // Punctuation and whitespace are ignored.
+                if !lower.is_alphanumeric() {
+                    continue;
+                }
+
+                if let Some(&(_, after, rank)) =
+                    self.tailoring.iter().find(|(t, _, _)| *t == lower)
+                {
+                    key.push(weight(after) + rank, 0, upper);
+                } else if let Some(base) = base_letters(lower) {
+                    for b in base.chars() {
+                        key.push(weight(b), lower as u32, upper);
+                    }
+                } else {
+                    key.push(weight(lower), 0, upper);
+                }
+            }
+        }
+
+        key
+    }
+}
+
+/// Reduce a string to its lowercase base letters and digits, dropping
+/// diacritics, punctuation, and whitespace. Strings with the same base text
+/// compare equal at the primary level of an untailored [`Collator`].
+pub(crate) fn base_text(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars().flat_map(char::to_lowercase) {
+        if ('\u{300}'..='\u{36F}').contains(&c) || !c.is_alphanumeric() {
+            continue;
+        }
+
+        match base_letters(c) {
+            Some(base) => res.push_str(base),
+            None => res.push(c),
+        }
+    }
+
+    res
+}
+
+/// Remove all diacritics from a string, keeping its case and punctuation.
+pub(crate) fn strip_diacritics(s: &str) -> String {
+    s.nfd().filter(|&c| !is_combining_mark(c)).nfc().collect()
+}
+
+/// The primary weight of a character, leaving room for tailored letters.
+fn weight(c: char) -> u32 {
+    (c as u32) << 8
+}
+
+/// The weights of a string at each level of comparison.
+#[derive(Debug, Default)]
+struct CollationKey {
+    primary: Vec<u32>,
+    secondary: Vec<u32>,
+    tertiary: Vec<bool>,
+}
+
+impl CollationKey {
+    fn push(&mut self, primary: u32, secondary: u32, upper: bool) {
+        self.primary.push(primary);
+        self.secondary.push(secondary);
+        self.tertiary.push(upper);
+    }
+}
+
+/// The base letters of a lowercase Latin letter with diacritics or ligature.
+fn base_letters(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĳ' => "ij",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<'a>(locale: &str, mut words: Vec<&'a str>) -> Vec<&'a str> {
+        let collator = Collator::new(locale);
+        words.sort_by(|a, b| collator.compare(a, b));
+        words
+    }
+
+    #[test]
+    fn collate_diacritics() {
+        assert_eq!(
+            sorted("en-US", vec!["Zebra", "Élan", "Ofen", "Öl", "Eagle", "Oz"]),
+            ["Eagle", "Élan", "Ofen", "Öl", "Oz", "Zebra"]
+        );
+        assert_eq!(
+            sorted("de-DE", vec!["Straße", "Strasse", "Strauß", "Strand"]),
+            ["Strand", "Strasse", "Straße", "Strauß"]
+        );
+    }
+
+    #[test]
+    fn collate_ignores_punctuation_and_case() {
+        assert_eq!(
+            sorted("en-US", vec!["antz", "antimatter", "anti-matter", "antic"]),
+            ["antic", "anti-matter", "antimatter", "antz"]
+        );
+        assert_eq!(
+            sorted("en-US", vec!["antimatter", "Anti-matter", "antz"]),
+            ["antimatter", "Anti-matter", "antz"]
+        );
+        assert_eq!(sorted("en-US", vec!["b", "A", "a", "B"]), ["a", "A", "b", "B"]);
+    }
+
+    #[test]
+    fn base_text_folds() {
+        assert_eq!(base_text("Élan vital: A Study"), "elanvitalastudy");
+        assert_eq!(base_text("Straße, 2nd ed."), "strasse2nded");
+        assert_eq!(base_text("Cafe\u{301}"), base_text("Café"));
+    }
+
+    #[test]
+    fn strip_diacritics_keeps_case() {
+        assert_eq!(strip_diacritics("Müller, Zoë"), "Muller, Zoe");
+        assert_eq!(strip_diacritics("Mu\u{308}ller"), "Muller");
+        assert_eq!(strip_diacritics("Łódź"), "Łodz");
+    }
+
+    #[test]
+    fn collate_decomposed() {
+        let collator = Collator::new("en-US");
+        assert_eq!(collator.compare("E\u{301}lan", "Élan"), Ordering::Equal);
+        assert_eq!(
+            sorted("en-US", vec!["Zebra", "E\u{301}lan", "Eagle"]),
+            ["Eagle", "E\u{301}lan", "Zebra"]
+        );
+        assert_eq!(
+            sorted("sv-SE", vec!["A\u{30a}sa", "Zeta", "Anna"]),
+            ["Anna", "Zeta", "A\u{30a}sa"]
+        );
+    }
+
+    #[test]
+    fn collate_tailoring() {
+        let words = vec!["Örn", "Zeta", "Åsa", "Anna", "Ärlig"];
+        assert_eq!(
+            sorted("sv-SE", words.clone()),
+            ["Anna", "Zeta", "Åsa", "Ärlig", "Örn"]
+        );
+        assert_eq!(sorted("de-DE", words), ["Anna", "Ärlig", "Åsa", "Örn", "Zeta"]);
+        assert_eq!(sorted("es", vec!["ñu", "nube", "oso"]), ["nube", "ñu", "oso"]);
+    }
+}
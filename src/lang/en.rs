@@ -1,5 +1,13 @@
 pub const ARTICLES: [&str; 8] = ["A", "AN", "An", "THE", "The", "a", "an", "the"];
 
+/// Prefixes after which the next part of a hyphenated compound is not
+/// capitalized in title case.
+pub const PREFIXES: [&str; 31] = [
+    "anti", "bi", "co", "counter", "de", "extra", "hyper", "infra", "inter", "intra",
+    "macro", "meta", "micro", "mid", "mini", "multi", "neo", "non", "post", "pre", "pro",
+    "proto", "pseudo", "re", "semi", "sub", "supra", "trans", "tri", "ultra", "un",
+];
+
 pub const NEVER_CAPITALIZE: [&str; 50] = [
     "A",
     "Above",
@@ -1,5 +1,7 @@
 //! Language-dependant string transformations.
 
+pub(crate) mod articles;
+pub(crate) mod collation;
 pub(crate) mod en;
 pub(crate) mod name;
 
@@ -24,11 +26,19 @@ pub struct TitleCase {
     /// Treat the hyphen as a word separator, i. e. capitalize in hyphenated
     /// expressions.
     pub hyphen_word_separator: bool,
+    /// Do not capitalize the part of a hyphenated expression that follows a
+    /// prefix like "re" or "anti" (e. g. `Re-introduction`).
+    pub lowercase_after_prefix: bool,
     /// Do not touch all-uppercase words like "USA" if the title contains
     /// lower-case characters at all.
     pub keep_all_uppercase_words: bool,
     /// Enable usage of the capitalization blacklist.
     pub use_exception_dictionary: bool,
+    /// The capitalization blacklist: Words that are not capitalized unless
+    /// they start the title or follow punctuation. Compared
+    /// case-insensitively. Defaults to the articles, conjunctions, and
+    /// prepositions that the Chicago Manual of Style lowercases.
+    pub stop_words: &'static [&'static str],
     /// Discard whitespace at the start of the title.
     pub trim_start: bool,
     /// Discard whitespace at the end of the title.
@@ -42,8 +52,10 @@ impl Default for TitleCase {
             always_capitalize_last_word: false,
             always_capitalize_min_len: None,
             hyphen_word_separator: true,
+            lowercase_after_prefix: true,
             keep_all_uppercase_words: true,
             use_exception_dictionary: true,
+            stop_words: &en::NEVER_CAPITALIZE,
             trim_start: true,
             trim_end: true,
         }
@@ -61,6 +73,10 @@ impl TitleCase {
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct SentenceCase {
+    /// Do not touch words that contain caps in a non-start position (e. g.
+    /// `mRNA` or `IgG`). Takes precedence over
+    /// [`Self::capitalize_words_with_caps_inside`].
+    pub keep_words_with_caps_inside: bool,
     /// Capitalize words that contain caps
     /// in a non-start position (e. g. `fahrCard`).
     pub capitalize_words_with_caps_inside: bool,
@@ -81,6 +97,7 @@ pub struct SentenceCase {
 impl Default for SentenceCase {
     fn default() -> Self {
         Self {
+            keep_words_with_caps_inside: true,
             capitalize_words_with_caps_inside: true,
             do_not_format_after_dot: true,
             keep_all_uppercase_words: true,
@@ -141,6 +158,74 @@ impl From<SentenceCase> for Case {
     }
 }
 
+/// Language-specific exceptions to the default Unicode case mappings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SpecialCasing {
+    /// Use the default Unicode case mappings.
+    #[default]
+    Default,
+    /// Turkish and Azerbaijani, which distinguish the dotted `i`/`İ` from the
+    /// dotless `ı`/`I`.
+    Turkic,
+    /// Lithuanian, which drops the combining dot above of an accented `i` or
+    /// `j` when uppercasing it.
+    Lithuanian,
+}
+
+impl SpecialCasing {
+    /// Select the special casing rules for a locale code, e.g. `tr-TR`.
+    pub fn from_locale(locale: &str) -> Self {
+        let lang = locale.split(['-', '_']).next().unwrap_or_default();
+        match lang.to_ascii_lowercase().as_str() {
+            "tr" | "az" => Self::Turkic,
+            "lt" => Self::Lithuanian,
+            _ => Self::Default,
+        }
+    }
+
+    /// Map a character to uppercase. `prev` is the character that preceded it
+    /// before the transformation.
+    fn to_uppercase(self, c: char, prev: Option<char>) -> CaseMapping {
+        match (self, c) {
+            (Self::Turkic, 'i') => CaseMapping::Char(Some('İ')),
+            (Self::Lithuanian, '\u{307}')
+                if matches!(prev, Some('i' | 'j' | 'į' | 'ɨ')) =>
+            {
+                CaseMapping::Char(None)
+            }
+            _ => CaseMapping::Upper(c.to_uppercase()),
+        }
+    }
+
+    /// Map a character to lowercase.
+    fn to_lowercase(self, c: char) -> CaseMapping {
+        match (self, c) {
+            (Self::Turkic, 'I') => CaseMapping::Char(Some('ı')),
+            (Self::Turkic, 'İ') => CaseMapping::Char(Some('i')),
+            _ => CaseMapping::Lower(c.to_lowercase()),
+        }
+    }
+}
+
+/// The result of mapping a character with [`SpecialCasing`].
+enum CaseMapping {
+    Upper(std::char::ToUppercase),
+    Lower(std::char::ToLowercase),
+    Char(Option<char>),
+}
+
+impl Iterator for CaseMapping {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self {
+            Self::Upper(iter) => iter.next(),
+            Self::Lower(iter) => iter.next(),
+            Self::Char(c) => c.take(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 enum CharClass {
     #[default]
@@ -162,9 +247,13 @@ impl CharClass {
                     self
                 }
             }
+            // Keep capitalizing the first word behind opening brackets at the
+            // start of a sentence.
+            '(' | '[' | '{' if self.is_new_sentence() => self,
             '(' | '[' | '{' => Self::NewSubclause,
             ')' | ']' | '}' => self,
-            '"' | '”' | '“' | '»' | '›' | '«' | '‹' | '‘' | '\'' => self,
+            '"' | '”' | '“' | '„' | '»' | '›' | '«' | '‹' | '‘' | '‚' | '\'' | '¿'
+            | '¡' => self,
             ',' | ';' => {
                 if self.is_in_word() {
                     Self::MaybeSubclause
@@ -216,6 +305,14 @@ pub struct CaseFolder {
     last_reconfig: usize,
     /// Index of the last word in the buffer.
     last_word: Option<WordData>,
+    /// The last word as it was pushed, before any case transformation.
+    word_source: String,
+    /// Whether the last character was a hyphen that followed a prefix.
+    after_prefix: bool,
+    /// Language-specific case mappings.
+    special_casing: SpecialCasing,
+    /// The last character that was pushed, before any case transformation.
+    prev_char: Option<char>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -240,9 +337,14 @@ impl WordCase {
 struct WordData {
     start: usize,
     end: usize,
+    /// The number of characters in the word.
+    chars: usize,
     case: WordCase,
     follows_punctuation: bool,
     start_was_upper: bool,
+    /// Whether the last character was a hyphen. The case of the next
+    /// character is not considered because it starts a compound part.
+    after_hyphen: bool,
 }
 
 impl WordData {
@@ -257,9 +359,11 @@ impl WordData {
         char_class.is_in_word().then(|| Self {
             start: buf_before,
             end: buf_now,
+            chars: 1,
             case: if start_was_upper { WordCase::AllUpper } else { WordCase::Lowercase },
             follows_punctuation: prev_class.is_new_subclause(),
             start_was_upper,
+            after_hyphen: false,
         })
     }
 
@@ -280,6 +384,7 @@ impl WordData {
             return Some(Self {
                 start: buf_before,
                 end: buf_now,
+                chars: 1,
                 case: if start_was_upper {
                     WordCase::AllUpper
                 } else {
@@ -287,11 +392,12 @@ impl WordData {
                 },
                 follows_punctuation: prev_class.is_new_subclause(),
                 start_was_upper,
+                after_hyphen: false,
             });
         }
 
-        let second = self.end - self.start == 1;
-        let case = if c.is_ascii_punctuation() {
+        let second = self.chars == 1;
+        let case = if c.is_ascii_punctuation() || self.after_hyphen {
             self.case
         } else {
             self.case.feed(c.is_uppercase(), second)
@@ -299,14 +405,16 @@ impl WordData {
         Some(Self {
             start: self.start,
             end: buf_now,
+            chars: self.chars + 1,
             case,
             follows_punctuation: self.follows_punctuation,
             start_was_upper: self.start_was_upper,
+            after_hyphen: matches!(c, '-' | '–' | '—'),
         })
     }
 
     fn is_all_upper(self) -> bool {
-        matches!(self.case, WordCase::AllUpper) && self.end - self.start > 1
+        matches!(self.case, WordCase::AllUpper) && self.chars > 1
     }
 
     fn is_continuing_word(self, other: Option<Self>) -> bool {
@@ -323,6 +431,8 @@ enum WordVerdict {
     Capitalize,
     Lowercase,
     Keep,
+    /// Restore the word as it was pushed.
+    Original,
 }
 
 impl Default for CaseFolder {
@@ -334,6 +444,10 @@ impl Default for CaseFolder {
             buf: String::new(),
             last_reconfig: 0,
             last_word: None,
+            word_source: String::new(),
+            after_prefix: false,
+            special_casing: SpecialCasing::default(),
+            prev_char: None,
         }
     }
 }
@@ -354,14 +468,21 @@ impl CaseFolder {
         match self.case {
             Case::NoTransform => {
                 self.buf.push_str(s);
+                self.prev_char = s.chars().last().or(self.prev_char);
                 self.char_class = CharClass::default();
             }
             Case::Uppercase => {
-                self.buf.extend(s.chars().flat_map(char::to_uppercase));
+                for c in s.chars() {
+                    self.push_upper(c);
+                    self.prev_char = Some(c);
+                }
                 self.char_class = CharClass::default();
             }
             Case::Lowercase => {
-                self.buf.extend(s.chars().flat_map(char::to_lowercase));
+                for c in s.chars() {
+                    self.push_lower(c);
+                    self.prev_char = Some(c);
+                }
                 self.char_class = CharClass::default();
             }
             _ => {
@@ -380,8 +501,13 @@ impl CaseFolder {
         self.pristine = false;
     }
 
-    /// Add a string chunk to the buffer.
+    /// Add a string chunk to the buffer without transforming its case.
     pub fn push_verbatim(&mut self, value: &str) {
+        // Finish the current word so that word-level case changes cannot
+        // reach into the protected text.
+        self.process_word();
+        self.last_word = None;
+
         let conf = mem::replace(&mut self.case, Case::NoTransform);
         self.last_reconfig = self.buf.len();
         self.push_str(value);
@@ -415,17 +541,17 @@ impl CaseFolder {
             Case::Title(config)
                 if config.trim_start && self.pristine && c.is_whitespace() => {}
             Case::Title(_) => {
-                if prev_class.is_new_word() {
-                    self.buf.extend(c.to_uppercase());
+                if prev_class.is_new_word() && !self.after_prefix {
+                    self.push_upper(c);
                 } else {
-                    self.buf.extend(c.to_lowercase());
+                    self.push_lower(c);
                 }
             }
             Case::Sentence(config)
                 if config.trim_start && self.pristine && c.is_whitespace() => {}
             Case::Sentence(config) => {
                 if prev_class.is_new_sentence() {
-                    self.buf.extend(c.to_uppercase());
+                    self.push_upper(c);
                 } else if config.do_not_format_after_dot
                     && matches!(
                         prev_class,
@@ -434,31 +560,43 @@ impl CaseFolder {
                 {
                     self.buf.push(c);
                 } else {
-                    self.buf.extend(c.to_lowercase());
+                    self.push_lower(c);
                 }
             }
             Case::FirstUpper => {
                 if self.pristine && c.is_lowercase() {
-                    self.buf.extend(c.to_uppercase());
+                    self.push_upper(c);
                 } else {
                     self.buf.push(c);
                 }
             }
             Case::AllUpper => {
                 if prev_class.is_new_word() && c.is_lowercase() {
-                    self.buf.extend(c.to_uppercase());
+                    self.push_upper(c);
                 } else {
                     self.buf.push(c);
                 }
             }
-            Case::Uppercase => self.buf.extend(c.to_uppercase()),
-            Case::Lowercase => self.buf.extend(c.to_lowercase()),
+            Case::Uppercase => self.push_upper(c),
+            Case::Lowercase => self.push_lower(c),
             Case::NoTransform => self.buf.push(c),
         }
 
+        self.after_prefix = match self.case {
+            Case::Title(config) => {
+                config.hyphen_word_separator
+                    && config.lowercase_after_prefix
+                    && c == '-'
+                    && self.find_word().is_some_and(|word| {
+                        en::PREFIXES.binary_search(&word.to_lowercase().as_str()).is_ok()
+                    })
+            }
+            _ => false,
+        };
+
         self.last_word = match self.last_word {
             Some(data) => {
-                let new_data = data.feed(
+                let mut new_data = data.feed(
                     c,
                     old_buf_len,
                     self.buf.len(),
@@ -467,19 +605,57 @@ impl CaseFolder {
                 );
 
                 if !data.is_continuing_word(new_data) {
-                    self.process_word()
+                    let len = self.buf.len();
+                    self.process_word();
+                    self.word_source.clear();
+
+                    // Processing may have changed the length of the word.
+                    if let Some(new_data) = &mut new_data {
+                        new_data.start = new_data.start + self.buf.len() - len;
+                        new_data.end = new_data.end + self.buf.len() - len;
+                    }
                 }
 
                 new_data
             }
             None => {
+                self.word_source.clear();
                 WordData::new(c, old_buf_len, self.buf.len(), prev_class, self.char_class)
             }
         };
 
+        if self.last_word.is_some() {
+            self.word_source.push(c);
+        }
+
+        self.prev_char = Some(c);
         self.pristine = self.pristine && c.is_whitespace();
     }
 
+    /// Push the uppercase form of a character, honoring the special casing
+    /// rules.
+    fn push_upper(&mut self, c: char) {
+        self.buf.extend(self.special_casing.to_uppercase(c, self.prev_char));
+    }
+
+    /// Push the lowercase form of a character, honoring the special casing
+    /// rules.
+    fn push_lower(&mut self, c: char) {
+        // In Turkic languages, a combining dot above an `I` spells a dotted
+        // `i` instead of the dotless `ı` that the `I` was lowercased to.
+        if self.special_casing == SpecialCasing::Turkic
+            && c == '\u{307}'
+            && self.prev_char == Some('I')
+            && self.buf.ends_with('ı')
+        {
+            self.buf.pop();
+            self.buf.push('i');
+            return;
+        }
+
+        self.buf.extend(self.special_casing.to_lowercase(c));
+    }
+
     /// Find the word with only alphabetic characters that starts at `self.last_word.start`.
     fn find_word(&self) -> Option<&str> {
         let data = self.last_word?;
@@ -528,7 +704,11 @@ impl CaseFolder {
                     let term = self.find_word().unwrap_or_default();
 
                     if lookup {
-                        verdict = if en::NEVER_CAPITALIZE.binary_search(&term).is_ok() {
+                        verdict = if config
+                            .stop_words
+                            .iter()
+                            .any(|word| word.eq_ignore_ascii_case(term))
+                        {
                             WordVerdict::Lowercase
                         } else {
                             WordVerdict::Keep
@@ -537,7 +717,11 @@ impl CaseFolder {
                 }
             }
             Case::Sentence(config) => {
-                if config.capitalize_words_with_caps_inside
+                if config.keep_words_with_caps_inside
+                    && data.case == WordCase::HasNonStartUpper
+                {
+                    verdict = WordVerdict::Original;
+                } else if config.capitalize_words_with_caps_inside
                     && data.case == WordCase::HasNonStartUpper
                 {
                     verdict = WordVerdict::Capitalize;
@@ -557,27 +741,34 @@ impl CaseFolder {
 
         match verdict {
             WordVerdict::AllUpper => {
+                let casing = self.special_casing;
                 map_chars(&mut self.buf, data.start..data.end, |c| {
                     if c.is_lowercase() {
-                        Some(c.to_uppercase())
+                        Some(casing.to_uppercase(c, None))
                     } else {
                         None
                     }
                 });
             }
-            WordVerdict::Capitalize => capitalize_char(&mut self.buf, data.start),
+            WordVerdict::Capitalize => {
+                capitalize_char(&mut self.buf, data.start, self.special_casing)
+            }
             WordVerdict::Lowercase => match &self.buf[data.start..].char_indices().next()
             {
                 Some((_, c)) if c.is_uppercase() => {
+                    let casing = self.special_casing;
                     map_chars(
                         &mut self.buf,
                         data.start..data.start + c.len_utf8(),
-                        |c| Some(c.to_lowercase()),
+                        |c| Some(casing.to_lowercase(c)),
                     );
                 }
                 _ => {}
             },
             WordVerdict::Keep => {}
+            WordVerdict::Original => {
+                self.buf.replace_range(data.start..data.end, &self.word_source)
+            }
         }
     }
 
@@ -610,7 +801,7 @@ impl CaseFolder {
                     return;
                 }
 
-                capitalize_char(&mut self.buf, start);
+                capitalize_char(&mut self.buf, start, self.special_casing);
             }
             _ => {}
         }
@@ -621,6 +812,16 @@ impl CaseFolder {
         self.case
     }
 
+    /// Retrieve the language-specific case mappings of the CaseFolder.
+    pub fn special_casing(&self) -> SpecialCasing {
+        self.special_casing
+    }
+
+    /// Change the language-specific case mappings of the CaseFolder.
+    pub fn set_special_casing(&mut self, special_casing: SpecialCasing) {
+        self.special_casing = special_casing;
+    }
+
     /// Change the configuration of the CaseFolder.
     pub fn reconfigure(&mut self, case: Case) {
         if self.case == case {
@@ -722,19 +923,27 @@ where
     }
 }
 
-fn capitalize_char(buf: &mut String, start: usize) {
+fn capitalize_char(buf: &mut String, start: usize, casing: SpecialCasing) {
     match &buf[start..].char_indices().next() {
         Some((_, c)) if c.is_lowercase() => {
-            map_chars(buf, start..start + c.len_utf8(), |c| Some(c.to_uppercase()));
+            map_chars(buf, start..start + c.len_utf8(), |c| {
+                Some(casing.to_uppercase(c, None))
+            });
         }
         _ => {}
     }
 }
 
-/// Check if a character is a CJK character.
+/// Check if a character is a CJK character. This includes the Han
+/// ideographs as well as the Japanese kana and the Korean Hangul.
 pub(crate) fn is_cjk(c: char) -> bool {
     let cp: u32 = c.into();
     (0x4E00..=0x9FFF).contains(&cp)
+        || (0x3040..=0x30FF).contains(&cp)
+        || (0x31F0..=0x31FF).contains(&cp)
+        || (0xAC00..=0xD7AF).contains(&cp)
+        || (0x1100..=0x11FF).contains(&cp)
+        || (0x3130..=0x318F).contains(&cp)
         || (0x3400..=0x4DBF).contains(&cp)
         || (0x20000..=0x2A6DF).contains(&cp)
         || (0x2A700..=0x2B73F).contains(&cp)
@@ -905,6 +1114,30 @@ mod tests {
         assert_eq!("UK", title);
     }
 
+    #[test]
+    fn case_folding_skips_verbatim() {
+        let mut c = CaseFolder::with_config(SentenceCase::new().into());
+        c.push_str("The ");
+        c.push_verbatim("DNA");
+        c.push_str(" of ");
+        c.push_verbatim("LaTeX");
+        c.push_str(" Documents");
+        assert_eq!(c.finish(), "The DNA of LaTeX documents");
+
+        let mut c = CaseFolder::with_config(SentenceCase::new().into());
+        c.push_str("Programming In ");
+        c.push_verbatim("Ada");
+        c.push_str(" Today");
+        assert_eq!(c.finish(), "Programming in Ada today");
+
+        let mut c = CaseFolder::with_config(TitleCase::new().into());
+        c.push_str("a study of ");
+        c.push_verbatim("pH");
+        c.push_str(" values in ");
+        c.push_verbatim("iOS");
+        assert_eq!(c.finish(), "A Study of pH Values in iOS");
+    }
+
     #[test]
     fn title_case_punctuation() {
         let case: Case = TitleCase::new().into();
@@ -976,6 +1209,42 @@ mod tests {
         assert_eq!("Comparative Study of Self-Reporting Students' Performance", title);
     }
 
+    #[test]
+    fn title_case_chicago() {
+        let case: Case = TitleCase::new().into();
+        let cases = [
+            ("state-of-the-art methods", "State-of-the-Art Methods"),
+            ("the re-introduction of wolves", "The Re-introduction of Wolves"),
+            ("Pre-War Years in anti-NATO Circles", "Pre-war Years in Anti-NATO Circles"),
+            ("self-made men of the west", "Self-Made Men of the West"),
+            ("mother-in-law jokes", "Mother-in-Law Jokes"),
+            ("the end: a new hope", "The End: A New Hope"),
+            ("what now? the aftermath", "What Now? The Aftermath"),
+            ("travelling via the north sea", "Travelling via the North Sea"),
+            ("the history of the USA in brief", "The History of the USA in Brief"),
+            ("THE END OF THE WORLD", "THE END OF THE WORLD"),
+            ("a study in scarlet", "A Study in Scarlet"),
+            ("out of the blue and into the black", "Out of the Blue and into the Black"),
+        ];
+
+        for (title, expected) in cases {
+            assert_eq!(case.transform(title), expected, "{title}");
+        }
+    }
+
+    #[test]
+    fn title_case_stop_words() {
+        let case: Case = TitleCase {
+            stop_words: &["of", "in"],
+            lowercase_after_prefix: false,
+            ..Default::default()
+        }
+        .into();
+
+        let title = case.transform("going via the web of lies in pre-war times");
+        assert_eq!("Going Via The Web of Lies in Pre-War Times", title);
+    }
+
     #[test]
     fn sentence_case() {
         let case: Case = SentenceCase::new().into();
@@ -1029,7 +1298,11 @@ mod tests {
 
     #[test]
     fn sentence_case_dictionary() {
-        let case: Case = SentenceCase::new().into();
+        let case: Case = SentenceCase {
+            keep_words_with_caps_inside: false,
+            ..Default::default()
+        }
+        .into();
 
         let title = case.transform(
             "if i must distance myself from the euroPe-centric mindset for a moment",
@@ -1065,6 +1338,18 @@ mod tests {
             title
         );
 
+        let title = case
+            .transform("Ubiquity airMAX is the next generation of networking hardware");
+        assert_eq!(
+            "Ubiquity airMAX is the next generation of networking hardware",
+            title
+        );
+
+        let case: Case = SentenceCase {
+            keep_words_with_caps_inside: false,
+            ..Default::default()
+        }
+        .into();
         let title = case
             .transform("Ubiquity airMAX is the next generation of networking hardware");
         assert_eq!(
@@ -1086,4 +1371,111 @@ mod tests {
             title
         );
     }
+
+    /// Convert to sentence case, treating text in braces as verbatim.
+    fn sentence_case_with_verbatim(title: &str) -> String {
+        let mut folder = CaseFolder::with_config(SentenceCase::new().into());
+        for (i, part) in title.split(['{', '}']).enumerate() {
+            if i % 2 == 0 {
+                folder.push_str(part);
+            } else {
+                folder.push_verbatim(part);
+            }
+        }
+        folder.finish()
+    }
+
+    #[test]
+    fn sentence_case_regressions() {
+        let cases = [
+            ("The Structure of mRNA Vaccines", "The structure of mRNA vaccines"),
+            ("mRNA Vaccines Against Influenza", "mRNA vaccines against influenza"),
+            ("Serum IgG Levels After Vaccination", "Serum IgG levels after vaccination"),
+            ("Using iPhones in the Classroom", "Using iPhones in the classroom"),
+            ("The History of McDonald's", "The history of McDonald's"),
+            ("Tracing O’Brien’s Ancestors", "Tracing O’Brien’s ancestors"),
+            ("A Study of Self-Reporting Students", "A study of self-reporting students"),
+            ("Long-Term Effects of COVID-19", "Long-term effects of COVID-19"),
+            ("The NASA Mission to Mars", "The NASA mission to mars"),
+            ("What Is DNA? A Primer", "What is DNA? A primer"),
+            ("Results: The Final Chapter", "Results: The final chapter"),
+            ("A Visit to Paris in April", "A visit to Paris in April"),
+            ("“The Great Debate” Revisited", "“The great debate” revisited"),
+            ("„Neue Wege“ in der Forschung", "„Neue wege“ in der forschung"),
+            ("(Re)Thinking the Museum", "(Re)Thinking the museum"),
+            ("[the Unknown Author]", "[The unknown author]"),
+            ("¿Qué Pasa Aquí?", "¿Qué pasa aquí?"),
+            ("the Beginning of the End", "The beginning of the end"),
+            ("Notes on the eBay Marketplace", "Notes on the eBay marketplace"),
+            ("A Review of LaTeX Editors", "A review of LaTeX editors"),
+            ("The pH of Sea Water", "The pH of sea water"),
+            ("An Introduction to JavaScript", "An introduction to JavaScript"),
+            ("WHY WE SLEEP", "WHY WE SLEEP"),
+            ("Élan Vital in Modern Thought", "Élan vital in modern thought"),
+            ("As Seen in Figure 4.A", "As seen in figure 4.A"),
+        ];
+
+        let case: Case = SentenceCase::new().into();
+        for (title, expected) in cases {
+            assert_eq!(case.transform(title), expected, "{title}");
+        }
+
+        let verbatim = [
+            ("{mRNA} Vaccines", "mRNA vaccines"),
+            ("The {DNA} of {LaTeX} Documents", "The DNA of LaTeX documents"),
+            ("Programming In {Ada} Today", "Programming in Ada today"),
+            ("A History of {the Netherlands}", "A history of the Netherlands"),
+            ("{iOS} Development", "iOS development"),
+            ("Travels In {Mexico}", "Travels in Mexico"),
+        ];
+
+        for (title, expected) in verbatim {
+            assert_eq!(sentence_case_with_verbatim(title), expected, "{title}");
+        }
+    }
+
+    fn transform_in(case: Case, locale: &str, s: &str) -> String {
+        let mut buf = CaseFolder::with_config(case);
+        buf.set_special_casing(SpecialCasing::from_locale(locale));
+        buf.push_str(s);
+        buf.finish()
+    }
+
+    #[test]
+    fn special_casing() {
+        assert_eq!(SpecialCasing::from_locale("tr-TR"), SpecialCasing::Turkic);
+        assert_eq!(SpecialCasing::from_locale("az"), SpecialCasing::Turkic);
+        assert_eq!(SpecialCasing::from_locale("lt-LT"), SpecialCasing::Lithuanian);
+        assert_eq!(SpecialCasing::from_locale("en-US"), SpecialCasing::Default);
+
+        assert_eq!(transform_in(Case::Uppercase, "tr-TR", "istanbul"), "İSTANBUL");
+        assert_eq!(transform_in(Case::Uppercase, "en-US", "istanbul"), "ISTANBUL");
+        assert_eq!(transform_in(Case::Lowercase, "tr-TR", "I"), "ı");
+        assert_eq!(transform_in(Case::Lowercase, "en-US", "I"), "i");
+        assert_eq!(transform_in(Case::Lowercase, "tr", "İSTANBUL"), "istanbul");
+        assert_eq!(transform_in(Case::Lowercase, "tr", "DİYARBAKIR"), "diyarbakır");
+        assert_eq!(transform_in(Case::Lowercase, "az", "I\u{307}ki"), "iki");
+        assert_eq!(transform_in(Case::FirstUpper, "tr", "istanbul"), "İstanbul");
+        assert_eq!(
+            transform_in(Case::AllUpper, "tr", "istanbul ve izmir"),
+            "İstanbul Ve İzmir"
+        );
+        assert_eq!(
+            transform_in(TitleCase::new().into(), "tr", "istanbul and izmir"),
+            "İstanbul and İzmir"
+        );
+        assert_eq!(
+            transform_in(SentenceCase::new().into(), "tr", "IRMAK İLE ISPARTA"),
+            "IRMAK İLE ISPARTA"
+        );
+        assert_eq!(
+            transform_in(SentenceCase::new().into(), "tr", "Irmak Ile Isparta"),
+            "Irmak ıle ısparta"
+        );
+        assert_eq!(transform_in(Case::Uppercase, "lt", "i\u{307}\u{301}"), "I\u{301}");
+        assert_eq!(
+            transform_in(Case::Uppercase, "en", "i\u{307}\u{301}"),
+            "I\u{307}\u{301}"
+        );
+    }
 }
@@ -56,11 +56,7 @@ for entry in bib.iter() {
     driver.citation(CitationRequest::from_items(items, &style, &locales));
 }
 
-let result = driver.finish(BibliographyRequest {
-    style: &style,
-    locale: None,
-    locale_files: &locales,
-});
+let result = driver.finish(BibliographyRequest::new(&style, None, &locales));
 
 for cite in result.citations {
     println!("{}", cite.citation)
@@ -94,6 +90,9 @@ default features by writing this in your `Cargo.toml`:
 hayagriva = { version = "0.8", default-features = false }
 ```
 
+Large bibliographies can be rendered in parallel by enabling the `rayon`
+feature. The output is the same as with sequential rendering.
+
 # Selectors
 
 Hayagriva uses a custom selector language that enables you to filter
@@ -140,10 +139,18 @@ return `Some` even if no sub-entry was bound / if the hash map is empty.
 
 #[macro_use]
 mod selectors;
+#[cfg(feature = "csl-json")]
+mod csl_json;
 #[cfg(feature = "biblatex")]
 mod interop;
+#[cfg(feature = "ris")]
+mod ris;
 
 mod csl;
+pub mod dedupe;
+#[cfg(feature = "doi-lookup")]
+pub mod doi;
+mod fingerprint;
 pub mod io;
 pub mod lang;
 pub mod types;
@@ -155,14 +162,22 @@ use std::collections::BTreeMap;
 pub use crate::csl::archive;
 pub use citationberg;
 pub use csl::{
-    standalone_citation, BibliographyDriver, BibliographyItem, BibliographyRequest,
+    check_style, standalone_bibliography_item, standalone_citation, Abbreviations,
+    BibliographyCategory, BibliographyDriver, BibliographyItem, BibliographyRequest,
     Brackets, BufWriteFormat, CitationItem, CitationRequest, CitePurpose, Elem,
-    ElemChild, ElemChildren, ElemMeta, Formatted, Formatting, LocatorPayload, Rendered,
-    RenderedBibliography, RenderedCitation, SpecificLocator,
+    ElemChild, ElemChildren, ElemMeta, Formatted, Formatting, IntextLayout,
+    LinkBehaviour, Location, LocatorPayload, NameCap, NameOverflow, NoteContext,
+    PreferredScript, RenderDiagnostic, Rendered, RenderedBibliography, RenderedCategory,
+    RenderedCitation, Severity, SpecificLocator, StyleDiagnostic, StyleLoadError,
+    StyleLoader, TermOverrides,
+};
+pub use fingerprint::Fingerprint;
+pub use selectors::{
+    Attribute, AttributeOp, AttributeValue, Selector, SelectorDiagnostic, SelectorError,
 };
-pub use selectors::{Selector, SelectorError};
 
 use indexmap::IndexMap;
+use lang::{Case, CaseFolder};
 use paste::paste;
 use serde::{de::Visitor, Deserialize, Serialize};
 use types::*;
@@ -192,6 +207,29 @@ impl Library {
         self.0.get(key)
     }
 
+    /// Retrieve the entries for all keys in order. If any keys are not in the
+    /// library, the error lists all of them at once.
+    pub fn get_all<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<&Entry>, UnknownKeys> {
+        let mut entries = vec![];
+        let mut unknown: Vec<String> = vec![];
+        for key in keys {
+            match self.get(key) {
+                Some(entry) => entries.push(entry),
+                None if !unknown.iter().any(|k| k == key) => unknown.push(key.into()),
+                None => {}
+            }
+        }
+
+        if unknown.is_empty() {
+            Ok(entries)
+        } else {
+            Err(UnknownKeys(unknown))
+        }
+    }
+
     /// Get an iterator over the entries in the library.
     pub fn iter(&self) -> impl Iterator<Item = &Entry> {
         self.0.values()
@@ -221,8 +259,19 @@ impl Library {
     pub fn nth(&self, n: usize) -> Option<&Entry> {
         self.0.get_index(n).map(|(_, v)| v)
     }
+
+    /// Sort the entries alphabetically by their keys. Otherwise, entries keep
+    /// the order in which they were added.
+    pub fn sort_keys(&mut self) {
+        self.0.sort_keys();
+    }
 }
 
+/// Keys that were looked up in a [`Library`] but are not part of it.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown keys: {}", .0.join(", "))]
+pub struct UnknownKeys(pub Vec<String>);
+
 impl<'a> IntoIterator for &'a Library {
     type Item = &'a Entry;
     type IntoIter = indexmap::map::Values<'a, String, Entry>;
@@ -275,6 +324,10 @@ macro_rules! entry {
                 #[serde(rename = $s)]
                 $i: Option<$t>,
             )*
+            /// Fields that are not part of the data model, kept as they were
+            /// read so that they survive a round trip.
+            #[serde(flatten)]
+            custom: BTreeMap<String, serde_yaml::Value>,
             /// Item in which the item was published / to which it is strongly
             /// associated to.
             #[serde(serialize_with = "serialize_one_or_many")]
@@ -297,23 +350,62 @@ macro_rules! entry {
                     $(
                         $i: None,
                     )*
+                    custom: BTreeMap::new(),
                     parents: Vec::new(),
                 }
             }
 
-            /// Check whether the entry has some key.
+            /// The number of fields that are set, including custom fields.
+            pub(crate) fn field_count(&self) -> usize {
+                [$(self.$i.is_some()),*].into_iter().filter(|&set| set).count()
+                    + self.custom.len()
+            }
+
+            /// Fill in the fields of this entry from another one, keeping the
+            /// more complete value if both have a field. Custom fields that
+            /// this entry lacks are copied, as are parents if it has none.
+            pub(crate) fn merge_fields(&mut self, other: &Entry) {
+                $(
+                    dedupe::merge_field(&mut self.$i, &other.$i);
+                )*
+
+                for (name, value) in &other.custom {
+                    self.custom.entry(name.clone()).or_insert_with(|| value.clone());
+                }
+
+                if self.parents.is_empty() {
+                    self.parents = other.parents.clone();
+                }
+            }
+
+            /// Check whether the entry has some key. Custom fields are
+            /// considered, too.
             pub fn has(&self, key: &str) -> bool {
                 match key {
                     $(
                         $s => self.$i.is_some(),
                     )*
-                    _ => false,
+                    _ => self.custom.contains_key(key),
                 }
             }
         }
 
         /// Getters.
         impl Entry {
+            /// Get a field as it is serialized. Identifiers in the serial
+            /// number, like `doi`, can be retrieved by their name, too.
+            pub(crate) fn field_value(&self, key: &str) -> Option<serde_yaml::Value> {
+                match key {
+                    $(
+                        $s => self.$i.as_ref().and_then(|v| serde_yaml::to_value(v).ok()),
+                    )*
+                    _ => self.custom.get(key).cloned().or_else(|| {
+                        self.keyed_serial_number(key)
+                            .map(|s| serde_yaml::Value::String(s.into()))
+                    }),
+                }
+            }
+
             /// Get the type of the entry.
             pub fn entry_type(&self) -> &EntryType {
                 &self.entry_type
@@ -324,6 +416,17 @@ macro_rules! entry {
                 &self.parents
             }
 
+            /// Get the fields that are not part of the data model, like
+            /// vendor-specific identifiers.
+            pub fn custom_fields(&self) -> &BTreeMap<String, serde_yaml::Value> {
+                &self.custom
+            }
+
+            /// Get a field that is not part of the data model.
+            pub fn custom_field(&self, name: &str) -> Option<&serde_yaml::Value> {
+                self.custom.get(name)
+            }
+
             $(
                 entry!(@get $(#[doc = $doc])* $s => $i : $t $(| $d)?);
             )*
@@ -336,12 +439,37 @@ macro_rules! entry {
                 self.parents = parents;
             }
 
+            /// Set a field that is not part of the data model, returning its
+            /// previous value.
+            pub fn set_custom_field(
+                &mut self,
+                name: impl Into<String>,
+                value: serde_yaml::Value,
+            ) -> Option<serde_yaml::Value> {
+                self.custom.insert(name.into(), value)
+            }
+
+            /// Remove a field that is not part of the data model.
+            pub fn remove_custom_field(
+                &mut self,
+                name: &str,
+            ) -> Option<serde_yaml::Value> {
+                self.custom.remove(name)
+            }
+
 
             $(
                 entry!(@set $s => $i : $t);
             )*
         }
 
+        /// Typed setters for the builder.
+        impl EntryBuilder {
+            $(
+                entry!(@build $s => $i : $t);
+            )*
+        }
+
         /// The library deserialization also handles entries.
         ///
         /// Entries do not implement [`Deserialize`] because they have a data
@@ -367,6 +495,8 @@ macro_rules! entry {
                         #[serde(default)]
                         $i: Option<$t>,
                     )*
+                    #[serde(flatten)]
+                    custom: BTreeMap<String, serde_yaml::Value>,
                 }
 
                 impl NakedEntry {
@@ -395,6 +525,7 @@ macro_rules! entry {
                             $(
                                 $i: self.$i,
                             )*
+                            custom: self.custom,
                         })
                     }
                 }
@@ -477,6 +608,17 @@ macro_rules! entry {
         }
     };
 
+    // Builder setter for all types.
+    (@build $s:literal => $i:ident : $t:ty $(,)?) => {
+        paste! {
+            #[doc = "Set the `" $s "` field."]
+            pub fn $i(mut self, $i: impl Into<$t>) -> Self {
+                self.entry.$i = Some($i.into());
+                self
+            }
+        }
+    };
+
     // Setter for all types.
     (@set $s:literal => $i:ident : $t:ty $(,)?) => {
         paste! {
@@ -495,6 +637,9 @@ entry! {
     #[serde(serialize_with = "serialize_one_or_many_opt")]
     #[serde(deserialize_with = "deserialize_one_or_many_opt")]
     "author" => authors: Vec<Person> | [Person],
+    /// User name of the account that published a post, without the leading
+    /// `@`.
+    "handle" => handle: String,
     /// Date at which the item was published.
     "date" => date: Date,
     /// Persons responsible for selecting and revising the content of the item.
@@ -509,7 +654,9 @@ entry! {
     "publisher" => publisher: Publisher,
     /// Physical location at which an entry is physically located or took place.
     "location" => location: FormatString,
-    /// Organization at/for which the item was created.
+    /// Organization at/for which the item was created. For a legal case, the
+    /// court, and for legislation, the legislative body.
+    #[serde(alias = "authority")]
     "organization" => organization: FormatString,
     /// For an item whose parent has multiple issues, indicates the position in
     /// the issue sequence. Also used to indicate the episode number for TV.
@@ -519,6 +666,15 @@ entry! {
     "volume" => volume: MaybeTyped<Numeric>,
     /// Total number of volumes/parts/seasons ... this item consists of.
     "volume-total" => volume_total: Numeric,
+    /// Title of the volume of a multi-volume work that this item is or is
+    /// part of, if the title of the item or its parent is that of the whole
+    /// work.
+    "volume-title" => volume_title: FormatString,
+    /// For an item that is published in multiple parts, like an article
+    /// split over several issues, indicates which part it is.
+    "part-number" => part_number: MaybeTyped<Numeric>,
+    /// Title of the part of an item that is published in multiple parts.
+    "part-title" => part_title: FormatString,
     /// Published version of an item.
     "edition" => edition: MaybeTyped<Numeric>,
     /// The range of pages within the parent this item occupies
@@ -544,17 +700,104 @@ entry! {
     "archive-location" => archive_location: FormatString,
     /// The call number of the item in the institution/collection.
     "call-number" => call_number: FormatString,
+    /// A label that replaces the generated alphanumeric citation label.
+    #[serde(alias = "label")]
+    "citation-label" => citation_label: String,
     /// Additional description to be appended in the bibliographic entry.
     "note" => note: FormatString,
     /// Abstract of the item (e.g. the abstract of a journal article).
     "abstract" => abstract_: FormatString,
+    /// Personal notes on the item, e.g. the commentary of an annotated
+    /// bibliography.
+    "annote" => annote: FormatString,
     /// Type, class, or subtype of the item (e.g. “Doctoral dissertation” for
     /// a PhD thesis; “NIH Publication” for an NIH technical report);
     /// Do not use for topical descriptions or categories (e.g. “adventure” for an adventure movie).
     "genre" => genre: FormatString,
 }
 
+/// A builder for an [`Entry`] with typed setters for all fields.
+///
+/// ```
+/// use hayagriva::types::{Date, EntryType, Person};
+/// use hayagriva::Entry;
+///
+/// let entry = Entry::builder(EntryType::Article, "smith2020")
+///     .title("Mapping the Deep Sea")
+///     .authors(vec![Person::from_strings(vec!["Smith", "Jane"]).unwrap()])
+///     .date(Date::from_ymd(2020, 5, 3))
+///     .parent(Entry::builder(EntryType::Periodical, "smith2020").title("Nature").build().unwrap())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(entry.date().unwrap().month, Some(4));
+/// assert_eq!(entry.parents()[0].title().unwrap().value.to_str(), "Nature");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryBuilder {
+    entry: Entry,
+}
+
+impl EntryBuilder {
+    /// Add a parent to the entry.
+    pub fn parent(mut self, parent: Entry) -> Self {
+        self.entry.parents.push(parent);
+        self
+    }
+
+    /// Set a field that is not part of the data model.
+    pub fn custom_field(
+        mut self,
+        name: impl Into<String>,
+        value: serde_yaml::Value,
+    ) -> Self {
+        self.entry.custom.insert(name.into(), value);
+        self
+    }
+
+    /// Check the values and create the entry.
+    ///
+    /// Dates must exist in the calendar and page ranges that were given as
+    /// strings must parse.
+    pub fn build(mut self) -> Result<Entry, EntryBuildError> {
+        if let Some(date) = &self.entry.date {
+            date.validate().map_err(|err| EntryBuildError::Date("date", err))?;
+        }
+
+        if let Some(date) =
+            self.entry.url.as_ref().and_then(|url| url.visit_date.as_ref())
+        {
+            date.validate().map_err(|err| EntryBuildError::Date("url", err))?;
+        }
+
+        if let Some(MaybeTyped::String(range)) = &self.entry.page_range {
+            let parsed = range
+                .parse::<PageRanges>()
+                .map_err(|_| EntryBuildError::PageRange(range.clone()))?;
+            self.entry.page_range = Some(MaybeTyped::Typed(parsed));
+        }
+
+        Ok(self.entry)
+    }
+}
+
+/// The error when an [`EntryBuilder`] contains invalid values.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EntryBuildError {
+    /// The date in the given field does not exist.
+    #[error("invalid {0}: {1}")]
+    Date(&'static str, DateError),
+    /// The page range could not be parsed.
+    #[error("invalid page range `{0}`")]
+    PageRange(String),
+}
+
 impl Entry {
+    /// Start building an entry with the given type and key.
+    pub fn builder(entry_type: EntryType, key: &str) -> EntryBuilder {
+        EntryBuilder { entry: Entry::new(key, entry_type) }
+    }
+
     /// Get and parse the `affiliated` field and only return persons of a given
     /// [role](PersonRole).
     pub(crate) fn affiliated_with_role(&self, role: PersonRole) -> Vec<&Person> {
@@ -645,11 +888,64 @@ impl Entry {
         self.map(|e| e.url.as_ref())
     }
 
+    /// Format the authors of this entry as a comma-separated list with the
+    /// given names first, e.g. `"Jane Doe, John Roe"`.
+    ///
+    /// If there are more than `max` authors, only the first `max` (but at
+    /// least one) are listed and `et_al` is appended. As in CSL, the term is
+    /// preceded by a comma if more than one name is shown.
+    pub fn formatted_authors(&self, max: usize, et_al: &str) -> String {
+        let Some(authors) = self.authors() else {
+            return String::new();
+        };
+
+        let shown = if authors.len() > max { max.max(1) } else { authors.len() };
+        let mut res = authors[..shown]
+            .iter()
+            .map(|author| author.given_first(false))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if shown < authors.len() && !et_al.is_empty() {
+            res += if shown > 1 { ", " } else { " " };
+            res += et_al;
+        }
+
+        res
+    }
+
+    /// The year of this entry or any of its ancestors.
+    pub fn year(&self) -> Option<i32> {
+        self.date_any().map(|date| date.year)
+    }
+
+    /// Format the title of this entry in the given case. Verbatim and math
+    /// parts of the title keep their case.
+    pub fn formatted_title(&self, case: Case) -> String {
+        let Some(title) = self.title() else {
+            return String::new();
+        };
+
+        let mut folder = CaseFolder::with_config(case);
+        title.value.fold_case(&mut folder);
+        folder.finish()
+    }
+
     /// Retrieve a keyed serial number.
+    ///
+    /// Keys are matched case-insensitively, so that `ISBN` can be retrieved as
+    /// `isbn`.
     pub fn keyed_serial_number(&self, key: &str) -> Option<&str> {
-        self.serial_number
-            .as_ref()
-            .and_then(|s| s.0.get(key).map(|s| s.as_str()))
+        let numbers = &self.serial_number.as_ref()?.0;
+        numbers
+            .get(key)
+            .or_else(|| {
+                numbers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map(|(_, v)| v)
+            })
+            .map(String::as_str)
     }
 
     /// Set a keyed serial number.
@@ -779,6 +1075,11 @@ impl Entry {
                 EntryType::Reference,
                 EntryType::Report,
             ]),
+            EntryType::Legislation => retrieve_container(&[
+                EntryType::Anthology,
+                EntryType::Reference,
+                EntryType::Book,
+            ]),
             EntryType::Post => {
                 retrieve_container(&[EntryType::Thread, EntryType::Blog, EntryType::Web])
             }
@@ -806,7 +1107,38 @@ impl Entry {
     }
 
     /// Get the collection of an entry like CSL defines it.
+    ///
+    /// If the types of the entry and its parents do not determine the
+    /// collection, a series-like parent of the container is used. Failing
+    /// that, the first series-like direct parent next to the container is
+    /// used, so that an article can have both a `proceedings` and an
+    /// `anthology` parent for the series the proceedings appeared in.
     pub(crate) fn get_collection(&self) -> Option<&Self> {
+        self.typed_collection().or_else(|| {
+            let container = self.get_container();
+            let is_series = |e: &&Self| {
+                matches!(
+                    e.entry_type,
+                    EntryType::Anthology
+                        | EntryType::Book
+                        | EntryType::Periodical
+                        | EntryType::Proceedings
+                        | EntryType::Reference
+                )
+            };
+
+            container.and_then(|c| c.parents.iter().find(is_series)).or_else(|| {
+                self.parents
+                    .iter()
+                    .filter(|e| !container.is_some_and(|c| std::ptr::eq(*e, c)))
+                    .find(|e| is_series(e) || e.entry_type == self.entry_type)
+            })
+        })
+    }
+
+    /// Get the collection of an entry from its type and the types of its
+    /// parents.
+    fn typed_collection(&self) -> Option<&Self> {
         match &self.entry_type {
             EntryType::Anthology
             | EntryType::Newspaper
@@ -818,10 +1150,19 @@ impl Entry {
             | EntryType::Exhibition => self.parents.iter().find(|e| {
                 e.entry_type == self.entry_type || e.entry_type == EntryType::Anthology
             }),
-            _ => self.parents.iter().find_map(|e| e.get_collection()),
+            _ => self.parents.iter().find_map(|e| e.typed_collection()),
         }
     }
 
+    /// Get the publisher of an entry. The entry itself takes precedence over
+    /// its container and the container's ancestors, which in turn take
+    /// precedence over all other parents.
+    pub(crate) fn get_publisher(&self) -> Option<&Publisher> {
+        self.publisher()
+            .or_else(|| self.get_container().and_then(|c| c.map(|e| e.publisher())))
+            .or_else(|| self.map(|e| e.publisher()))
+    }
+
     /// Search a parent by DFS.
     pub(crate) fn dfs_parent(&self, kind: EntryType) -> Option<&Self> {
         if self.entry_type == kind {
@@ -841,9 +1182,20 @@ impl Entry {
     pub(crate) fn get_original(&self) -> Option<&Self> {
         self.dfs_parent(EntryType::Original)
     }
+
+    /// Get the event at which the entry was presented. Conferences and
+    /// exhibitions take precedence over miscellaneous parents.
+    pub(crate) fn get_event(&self) -> Option<&Self> {
+        self.parents
+            .iter()
+            .find(|e| {
+                matches!(e.entry_type, EntryType::Conference | EntryType::Exhibition)
+            })
+            .or_else(|| self.parents.iter().find(|e| e.entry_type == EntryType::Misc))
+    }
 }
 
-#[cfg(feature = "biblatex")]
+#[cfg(any(feature = "biblatex", feature = "ris", feature = "csl-json"))]
 impl Entry {
     /// Adds a parent to the current entry. The parent
     /// list will be created if there is none.
@@ -864,6 +1216,7 @@ impl Entry {
         }
     }
 
+    #[cfg(feature = "biblatex")]
     pub(crate) fn parents_mut(&mut self) -> &mut [Self] {
         &mut self.parents
     }
@@ -907,6 +1260,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn library_get_all() {
+        let contents = fs::read_to_string("tests/data/basic.yml").unwrap();
+        let entries = from_yaml_str(&contents).unwrap();
+
+        let found = entries.get_all(["kinetics", "zygos"]).unwrap();
+        let keys: Vec<_> = found.iter().map(|e| e.key()).collect();
+        assert_eq!(keys, ["kinetics", "zygos"]);
+
+        let err = entries
+            .get_all(["zygos", "smith2020", "kinetics", "nope", "smith2020"])
+            .unwrap_err();
+        assert_eq!(err, UnknownKeys(vec!["smith2020".into(), "nope".into()]));
+        assert_eq!(err.to_string(), "unknown keys: smith2020, nope");
+    }
+
     #[test]
     fn selectors() {
         let contents = fs::read_to_string("tests/data/basic.yml").unwrap();
@@ -969,6 +1338,24 @@ mod tests {
             ]
         );
         select_all!("*[abstract, note, genre]", entries, ["wire"]);
+        select_all!(
+            "*[date >= 2020]",
+            entries,
+            [
+                "wwdc-network",
+                "science-e-issue",
+                "kinetics",
+                "plaque",
+                "oiseau",
+                "georgia",
+                "really-habitable",
+                "barb",
+            ]
+        );
+        select_all!("book[volume > 3]", entries, ["donne"]);
+        select_all!("*[language = \"de-DE\"]", entries, ["barb"]);
+        select_all!("!book[date < 2000]", entries, ["terminator-2", "roe-wade", "foia"]);
+        select_all!("article[doi] > periodical", entries, ["kinetics", "house"]);
     }
 
     #[test]
@@ -981,6 +1368,245 @@ mod tests {
             entries >> "wwdc-network",
             ["a", "b", "c"]
         );
+
+        select!("a:article[doi] > b:periodical", entries >> "kinetics", ["a", "b"]);
+
+        // Only the alternative that matched binds its names.
+        let selector = Selector::parse("article > (p:blog | newspaper)").unwrap();
+        let bound = selector.apply(entries.get("omarova-libra").unwrap()).unwrap();
+        assert!(bound.is_empty());
+
+        // Bindings reach through multiple levels of parents.
+        let plaque = entries.get("plaque").unwrap();
+        let selector = Selector::parse("m:misc > a:artwork > b:anthology").unwrap();
+        let bound = selector.apply(plaque).unwrap();
+        assert!(std::ptr::eq(bound["m"], plaque));
+        assert!(std::ptr::eq(bound["a"], &plaque.parents[0]));
+        assert!(std::ptr::eq(bound["b"], &plaque.parents[0].parents[0]));
+        assert!(Selector::parse("misc > artwork > book")
+            .unwrap()
+            .apply(plaque)
+            .is_none());
+
+        let entries = from_yaml_str(
+            r#"
+            nested:
+                type: article
+                parent:
+                    - type: periodical
+                      title: First
+                    - type: periodical
+                      title: Second
+                      parent:
+                          type: book
+                          parent:
+                              type: anthology
+            "#,
+        )
+        .unwrap();
+        let nested = entries.get("nested").unwrap();
+        let periodicals = &nested.parents;
+
+        // The first matching parent is bound.
+        let bound = Selector::parse("article > p:periodical").unwrap().apply(nested);
+        assert!(std::ptr::eq(bound.unwrap()["p"], &periodicals[0]));
+
+        // Unless only a later one matches the rest of the selector.
+        let selector =
+            Selector::parse("article > p:periodical > b:book > c:anthology").unwrap();
+        let bound = selector.apply(nested).unwrap();
+        assert!(std::ptr::eq(bound["p"], &periodicals[1]));
+        assert!(std::ptr::eq(bound["b"], &periodicals[1].parents[0]));
+        assert!(std::ptr::eq(bound["c"], &periodicals[1].parents[0].parents[0]));
+
+        let selector =
+            Selector::parse("article > (p:periodical & q:periodical)").unwrap();
+        let bound = selector.apply(nested).unwrap();
+        assert!(std::ptr::eq(bound["p"], &periodicals[0]));
+        assert!(std::ptr::eq(bound["q"], &periodicals[1]));
+    }
+
+    /// A deterministic random number generator for generated tests.
+    struct Xorshift(u64);
+
+    impl Xorshift {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Generate a selector along with its source code. Selectors in parent
+    /// position may require multiple parents.
+    fn random_selector(
+        rng: &mut Xorshift,
+        depth: usize,
+        parent: bool,
+    ) -> (Selector, String) {
+        const TYPES: &[&str] = &[
+            "article",
+            "book",
+            "chapter",
+            "web",
+            "video",
+            "periodical",
+            "proceedings",
+            "anthology",
+            "newspaper",
+            "conference",
+        ];
+
+        let atom = |rng: &mut Xorshift| {
+            if rng.below(4) == 0 {
+                (Selector::Wildcard, "*".to_string())
+            } else {
+                let name = TYPES[rng.below(TYPES.len())];
+                (Selector::Entry(name.parse().unwrap()), name.to_string())
+            }
+        };
+
+        let list = |rng: &mut Xorshift, op: &str| {
+            let (mut items, mut srcs) = (vec![], vec![]);
+            for _ in 0..2 + rng.below(2) {
+                let (sel, src) = random_selector(rng, depth - 1, parent);
+                items.push(sel);
+                srcs.push(format!("({src})"));
+            }
+            (items, srcs.join(op))
+        };
+
+        match if depth == 0 { 0 } else { rng.below(8) } {
+            0 | 1 => atom(rng),
+            2 => {
+                let (inner, src) = random_selector(rng, depth - 1, parent);
+                (Selector::Neg(Box::new(inner)), format!("!({src})"))
+            }
+            3 => {
+                let name = ["a", "b", "c"][rng.below(3)];
+                let (inner, src) = random_selector(rng, depth - 1, parent);
+                (
+                    Selector::Binding(name.into(), Box::new(inner)),
+                    format!("{name}:({src})"),
+                )
+            }
+            4 => {
+                let (inner, src) = atom(rng);
+                let (attr, attr_src) = match rng.below(4) {
+                    0 => (Attribute::from("url"), "url"),
+                    1 => (Attribute::from("doi"), "doi"),
+                    2 => (
+                        Attribute::Compare(
+                            "date".into(),
+                            AttributeOp::Ge,
+                            AttributeValue::Number(2015),
+                        ),
+                        "date >= 2015",
+                    ),
+                    _ => (
+                        Attribute::Compare(
+                            "volume".into(),
+                            AttributeOp::Lt,
+                            AttributeValue::Number(3),
+                        ),
+                        "volume < 3",
+                    ),
+                };
+                (
+                    Selector::Attr(Box::new(inner), vec![attr]),
+                    format!("{src}[{attr_src}]"),
+                )
+            }
+            5 => {
+                let (lhs, lhs_src) = random_selector(rng, depth - 1, false);
+                let (rhs, rhs_src) = random_selector(rng, depth - 1, true);
+                (
+                    Selector::Ancestrage(Box::new(lhs), Box::new(rhs)),
+                    format!("({lhs_src}) > ({rhs_src})"),
+                )
+            }
+            6 if parent => {
+                let (items, src) = list(rng, " & ");
+                (Selector::Multi(items), src)
+            }
+            _ => {
+                let (items, src) = list(rng, " | ");
+                (Selector::Alt(items), src)
+            }
+        }
+    }
+
+    /// Whether a selector matches an entry, evaluated as directly as possible.
+    fn brute_force(selector: &Selector, entry: &Entry) -> bool {
+        match selector {
+            Selector::Wildcard => true,
+            Selector::Entry(entry_type) => entry.entry_type() == entry_type,
+            Selector::Neg(inner) => !brute_force(inner, entry),
+            Selector::Binding(_, inner) => brute_force(inner, entry),
+            Selector::Attr(inner, attrs) => {
+                brute_force(inner, entry) && attrs.iter().all(|a| a.matches(entry))
+            }
+            Selector::Alt(items) => items.iter().any(|s| brute_force(s, entry)),
+            Selector::Multi(_) => false,
+            Selector::Ancestrage(lhs, rhs) => {
+                brute_force(lhs, entry) && brute_force_parents(rhs, entry.parents())
+            }
+        }
+    }
+
+    /// Whether a selector matches the parents of an entry.
+    fn brute_force_parents(selector: &Selector, parents: &[Entry]) -> bool {
+        fn distinct(
+            items: &[Selector],
+            parents: &[Entry],
+            used: &mut Vec<usize>,
+        ) -> bool {
+            let Some((first, rest)) = items.split_first() else { return true };
+            (0..parents.len()).any(|i| {
+                if used.contains(&i) || !brute_force(first, &parents[i]) {
+                    return false;
+                }
+                used.push(i);
+                let found = distinct(rest, parents, used);
+                used.pop();
+                found
+            })
+        }
+
+        match selector {
+            Selector::Neg(inner) => !brute_force_parents(inner, parents),
+            Selector::Binding(_, inner) => brute_force_parents(inner, parents),
+            Selector::Alt(items) => items.iter().any(|s| brute_force_parents(s, parents)),
+            Selector::Multi(items) => distinct(items, parents, &mut vec![]),
+            _ => parents.iter().any(|p| brute_force(selector, p)),
+        }
+    }
+
+    #[test]
+    fn selectors_brute_force() {
+        let contents = fs::read_to_string("tests/data/basic.yml").unwrap();
+        let entries = from_yaml_str(&contents).unwrap();
+        let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..2000 {
+            let (selector, src) = random_selector(&mut rng, 3, false);
+            assert_eq!(Selector::parse(&src).as_ref(), Ok(&selector), "{src}");
+
+            for entry in entries.iter() {
+                let bound = selector.apply(entry);
+                assert_eq!(
+                    bound.is_some(),
+                    brute_force(&selector, entry),
+                    "{src} on {}",
+                    entry.key()
+                );
+
+                if let (Some(bound), Selector::Binding(name, _)) = (&bound, &selector) {
+                    assert_eq!(bound.get(name), Some(&entry), "{src}");
+                }
+            }
+        }
     }
 
     #[test]
@@ -1001,4 +1627,95 @@ mod tests {
             assert!(entry.page_range.is_some())
         }
     }
+
+    #[test]
+    fn builder() {
+        let entry = Entry::builder(EntryType::Chapter, "doe2021")
+            .title("Typed Setters")
+            .authors(vec![Person::from_strings(vec!["Doe", "Jane"]).unwrap()])
+            .date(Date::from_ymd(2021, 2, 28))
+            .page_range(MaybeTyped::String("12-15".into()))
+            .volume(Numeric::from(3))
+            .parent(
+                Entry::builder(EntryType::Book, "doe2021")
+                    .title("Builders")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let yaml = r#"
+doe2021:
+    type: chapter
+    title: Typed Setters
+    author: Doe, Jane
+    date: 2021-02-28
+    page-range: 12-15
+    volume: 3
+    parent:
+        type: book
+        title: Builders
+"#;
+        assert_eq!(&entry, from_yaml_str(yaml).unwrap().get("doe2021").unwrap());
+
+        let date = Entry::builder(EntryType::Misc, "a")
+            .date(Date::from_ymd(2021, 2, 29))
+            .build();
+        assert_eq!(
+            date,
+            Err(EntryBuildError::Date("date", DateError::DayOutOfBounds(29)))
+        );
+
+        let month = Entry::builder(EntryType::Misc, "a")
+            .date(Date::from_ymd(2021, 0, 1))
+            .build();
+        assert_eq!(
+            month,
+            Err(EntryBuildError::Date("date", DateError::MonthOutOfBounds))
+        );
+
+        let pages = Entry::builder(EntryType::Misc, "a")
+            .page_range(MaybeTyped::String("12--x--".into()))
+            .build();
+        assert_eq!(pages, Err(EntryBuildError::PageRange("12--x--".into())));
+    }
+
+    #[test]
+    fn reference_accessors() {
+        let contents = fs::read_to_string("tests/data/basic.yml").unwrap();
+        let entries = from_yaml_str(&contents).unwrap();
+
+        let libra = entries.get("omarova-libra").unwrap();
+        assert_eq!(libra.formatted_authors(3, "et al."), "Saule Omarova, Graham Steele");
+        assert_eq!(libra.formatted_authors(1, "et al."), "Saule Omarova et al.");
+        assert_eq!(libra.formatted_authors(0, "et al."), "Saule Omarova et al.");
+        assert_eq!(libra.year(), Some(2019));
+        assert_eq!(
+            libra.formatted_title(Case::Uppercase),
+            "THERE’S A LOT WE STILL DON’T KNOW ABOUT LIBRA"
+        );
+
+        let entry = Entry::builder(EntryType::Misc, "a")
+            .authors(vec![
+                Person::from_strings(vec!["Doe", "Jane"]).unwrap(),
+                Person::from_strings(vec!["Roe", "John"]).unwrap(),
+                Person::from_strings(vec!["Poe", "Edgar Allan"]).unwrap(),
+            ])
+            .title("a tour of {NASA}".parse::<FormatString>().unwrap())
+            .parent(
+                Entry::builder(EntryType::Book, "b")
+                    .date(Date::from_year(1999))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(entry.formatted_authors(2, "et al."), "Jane Doe, John Roe, et al.");
+        assert_eq!(entry.formatted_authors(2, ""), "Jane Doe, John Roe");
+        assert_eq!(entry.year(), Some(1999));
+        assert_eq!(entry.formatted_title(Case::Lowercase), "a tour of NASA");
+        assert_eq!(entry.formatted_title(Case::FirstUpper), "A tour of NASA");
+        assert_eq!(Entry::new("c", EntryType::Misc).formatted_authors(2, "et al."), "");
+    }
 }
@@ -1,21 +1,29 @@
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, read_to_string};
 use std::io::ErrorKind as IoErrorKind;
 use std::path::Path;
 use std::process::exit;
 
-use citationberg::taxonomy::Locator;
+use citationberg::taxonomy::{Locator, Term};
 use citationberg::{
-    IndependentStyle, Locale, LocaleCode, LocaleFile, LongShortForm, Style,
+    IndependentStyle, Locale, LocaleCode, LongShortForm, Style, StyleCategory,
+    StyleClass, TermForm,
 };
 use clap::builder::PossibleValue;
 use clap::{crate_version, Arg, ArgAction, Command, ValueEnum};
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
 use strum::VariantNames;
 
-use hayagriva::archive::{locales, ArchivedStyle};
+use hayagriva::archive::ArchivedStyle;
+use hayagriva::dedupe::{find_duplicates, merge};
 use hayagriva::{
-    io, BibliographyDriver, CitationItem, CitationRequest, LocatorPayload,
-    SpecificLocator,
+    check_style, io, Abbreviations, BibliographyDriver, CitationItem, CitationRequest,
+    Entry, Library, LinkBehaviour, Location, LocatorPayload, RenderDiagnostic,
+    RenderedBibliography, Severity, SpecificLocator, StyleDiagnostic, StyleLoader,
+    TermOverrides,
 };
 use hayagriva::{BibliographyRequest, Selector};
 
@@ -26,16 +34,26 @@ pub enum Format {
     Bibtex,
     #[cfg(feature = "biblatex")]
     Biblatex,
+    #[cfg(feature = "ris")]
+    Ris,
+    #[cfg(feature = "csl-json")]
+    CslJson,
     Yaml,
 }
 
 impl ValueEnum for Format {
     fn value_variants<'a>() -> &'a [Self] {
-        if cfg!(feature = "biblatex") {
-            &[Self::Bibtex, Self::Biblatex, Self::Yaml]
-        } else {
-            &[Self::Yaml]
-        }
+        &[
+            #[cfg(feature = "biblatex")]
+            Self::Bibtex,
+            #[cfg(feature = "biblatex")]
+            Self::Biblatex,
+            #[cfg(feature = "ris")]
+            Self::Ris,
+            #[cfg(feature = "csl-json")]
+            Self::CslJson,
+            Self::Yaml,
+        ]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -44,6 +62,10 @@ impl ValueEnum for Format {
             Format::Bibtex => "bibtex",
             #[cfg(feature = "biblatex")]
             Format::Biblatex => "biblatex",
+            #[cfg(feature = "ris")]
+            Format::Ris => "ris",
+            #[cfg(feature = "csl-json")]
+            Format::CslJson => "csl-json",
             Format::Yaml => "yaml",
         };
 
@@ -51,16 +73,33 @@ impl ValueEnum for Format {
     }
 }
 
+/// The exit codes of the CLI, listed in its help.
+const EXIT_CODES: &str = "\
+Exit codes:
+  2   No bibliography file was given
+  4   The style or locale is invalid or `check` found errors
+  5   A file was not found
+  6   A file could not be read or written
+  7   The selector is invalid
+  8   The bibliography could not be exported
+  9   The bibliography or abbreviation file could not be parsed
+  10  The DOI lookup failed
+  11  `convert` skipped duplicate keys or dropped fields
+  12  `fmt --check` found unformatted files
+  13  `--key` names entries that are not in the bibliography
+  14  A locator in `--key` is invalid
+  15  A `--term` argument is invalid";
+
 /// Main function of the Hayagriva CLI.
 fn main() {
-    let matches = Command::new("Hayagriva CLI")
+    let command = Command::new("Hayagriva CLI")
             .version(crate_version!())
             .author("The Typst Project Developers <hi@typst.app>")
             .about("Format references and citations for your YAML-encoded or BibLaTeX bibliography files and query bibliographies using selectors.")
+            .after_help(EXIT_CODES)
             .arg(
                 Arg::new("INPUT")
                     .help("Sets the bibliography file to use")
-                    .required(true)
                     .index(1)
             ).arg(
                 Arg::new("format")
@@ -81,8 +120,16 @@ fn main() {
                 Arg::new("key")
                     .long("key")
                     .short('k')
-                    .help("Filter the bibliography using a comma-separated list of keys")
+                    .help("Filter the bibliography using a comma-separated list of keys. Can be repeated. When citing, `key:14` adds a page locator and `key:label=chapter,value=3` a locator of another type")
                     .num_args(1)
+                    .action(ArgAction::Append)
+                    .global(true)
+            )
+            .arg(
+                Arg::new("missing-placeholder")
+                    .long("missing-placeholder")
+                    .help("Cite keys that are not in the bibliography as `[missing: key]` instead of failing")
+                    .action(ArgAction::SetTrue)
                     .global(true)
             )
             .arg(
@@ -99,6 +146,37 @@ fn main() {
                     .action(ArgAction::SetTrue)
                     .global(true)
             )
+            .arg(
+                Arg::new("links")
+                    .long("links")
+                    .help("Which variables to render as hyperlinks")
+                    .value_parser(["never", "identifiers-only", "urls-and-identifiers"])
+                    .ignore_case(true)
+                    .num_args(1)
+                    .global(true)
+            )
+            .arg(
+                Arg::new("suppress-url-with-doi")
+                    .long("suppress-url-with-doi")
+                    .help("Omit the URL and access date of entries with a DOI in the bibliography")
+                    .action(ArgAction::SetTrue)
+                    .global(true)
+            )
+            .arg(
+                Arg::new("abbreviations")
+                    .long("abbreviations")
+                    .help("A JSON or YAML file with abbreviations for container titles, collection titles, and institutions")
+                    .num_args(1)
+                    .global(true)
+            )
+            .arg(
+                Arg::new("term")
+                    .long("term")
+                    .help("Replace a term of the style and its locales, like `et-al=and others`. Append a form to the name to replace another form than the long one, like `editor:short=editor`. Can be repeated")
+                    .num_args(1)
+                    .action(ArgAction::Append)
+                    .global(true)
+            )
             .arg(
                 Arg::new("no-fmt")
                     .long("no-fmt")
@@ -107,6 +185,14 @@ fn main() {
                     .action(ArgAction::SetTrue)
                     .global(true)
             )
+            .arg(
+                Arg::new("verbose")
+                    .long("verbose")
+                    .short('v')
+                    .help("Print problems found while rendering to stderr")
+                    .action(ArgAction::SetTrue)
+                    .global(true)
+            )
             .subcommand(
                 Command::new("cite")
                     .about("Format citations for all filtered entries")
@@ -122,7 +208,7 @@ fn main() {
                     .arg(
                         Arg::new("csl")
                             .long("csl")
-                            .help("Set a CSL file to use the style therein")
+                            .help("Set a CSL file to use the style therein. Dependent styles are resolved to their parent")
                             .num_args(1)
                     )
                     .arg(
@@ -134,7 +220,7 @@ fn main() {
                     .arg(
                         Arg::new("locales")
                             .long("locales")
-                            .help("Set a comma-separated list of CSL locales")
+                            .help("Add a comma-separated list of CSL locale files to the bundled ones")
                             .num_args(1)
                     )
                     .arg(
@@ -150,10 +236,16 @@ fn main() {
                             .action(ArgAction::SetTrue)
                             .help("Combine all keys into one citation (ignored for Chicago Notes)")
                     )
+                    .arg(
+                        Arg::new("with-references")
+                            .long("with-references")
+                            .action(ArgAction::SetTrue)
+                            .help("Also print the bibliography entries of the cited works")
+                    )
             )
             .subcommand(
                 Command::new("reference")
-                    .about("Format a bibliography of all filtered entries")
+                    .about("Format a bibliography of all filtered entries. Entries filtered with `--select` keep the citation numbers they have among all entries")
                     .arg(
                         Arg::new("style")
                             .long("style")
@@ -166,7 +258,7 @@ fn main() {
                     .arg(
                         Arg::new("csl")
                             .long("csl")
-                            .help("Set a CSL file to use the style therein")
+                            .help("Set a CSL file to use the style therein. Dependent styles are resolved to their parent")
                             .num_args(1)
                     )
                     .arg(
@@ -178,89 +270,289 @@ fn main() {
                     .arg(
                         Arg::new("locales")
                             .long("locales")
-                            .help("Set a space-separated list of CSL locales")
+                            .help("Add a comma-separated list of CSL locale files to the bundled ones")
                             .num_args(1)
                     )
             )
             .subcommand(
                 Command::new("styles")
                     .about("List all available citation styles")
+                    .arg(
+                        Arg::new("search")
+                            .long("search")
+                            .help("Only list styles whose name, title, or ID contains this text")
+                            .num_args(1)
+                    )
+                    .arg(
+                        Arg::new("info")
+                            .long("info")
+                            .help("Show details about the style with this name")
+                            .num_args(1)
+                            .conflicts_with("search")
+                    )
             )
-            .get_matches();
+            .subcommand(
+                Command::new("check-style")
+                    .about("Check a CSL style for unknown variables, terms, and macros")
+                    .arg(
+                        Arg::new("FILE")
+                            .help("The CSL file to check")
+                            .required(true)
+                            .index(1)
+                    )
+            )
+            .subcommand(
+                Command::new("convert")
+                    .about("Merge bibliography files and convert them to another format")
+                    .arg(
+                        Arg::new("FILES")
+                            .help("The bibliography files to convert")
+                            .required(true)
+                            .num_args(1..)
+                    )
+                    .arg(
+                        Arg::new("to")
+                            .long("to")
+                            .help("Which output format to write")
+                            .value_parser(clap::value_parser!(Format))
+                            .ignore_case(true)
+                            .num_args(1)
+                    )
+            )
+            .subcommand(
+                Command::new("fmt")
+                    .about("Rewrite YAML bibliography files in canonical form")
+                    .arg(
+                        Arg::new("FILES")
+                            .help("The YAML files to format")
+                            .required(true)
+                            .num_args(1..)
+                    )
+                    .arg(
+                        Arg::new("sort")
+                            .long("sort")
+                            .help("Sort the entries alphabetically by key")
+                            .action(ArgAction::SetTrue)
+                    )
+                    .arg(
+                        Arg::new("check")
+                            .long("check")
+                            .help("Only report files that are not formatted instead of rewriting them")
+                            .action(ArgAction::SetTrue)
+                    )
+            )
+            .subcommand(
+                Command::new("select")
+                    .about("Print the entries matching a selector and the entries it bound as JSON")
+                    .arg(
+                        Arg::new("SELECTOR")
+                            .help("The selector to apply")
+                            .required(true)
+                            .index(1)
+                    )
+                    .arg(
+                        Arg::new("FILE")
+                            .help("The bibliography file to search")
+                            .required(true)
+                            .index(2)
+                    )
+            )
+            .subcommand(
+                Command::new("dedupe")
+                    .about("List entries that likely describe the same work and merge them")
+                    .arg(
+                        Arg::new("FILE")
+                            .help("The bibliography file to check")
+                            .required(true)
+                            .index(1)
+                    )
+                    .arg(
+                        Arg::new("write")
+                            .long("write")
+                            .help("Replace each group of duplicates with the merged entry in the file")
+                            .action(ArgAction::SetTrue)
+                    )
+            );
 
-    let input = Path::new(matches.get_one::<String>("INPUT").unwrap());
+    #[cfg(feature = "cli-doi")]
+    let command = command.subcommand(
+        Command::new("add")
+            .about("Print a YAML entry for a DOI, fetched from doi.org")
+            .arg(
+                Arg::new("doi")
+                    .long("doi")
+                    .help("The DOI to look up, e.g. `10.1145/362929.362947`")
+                    .required(true)
+                    .num_args(1),
+            ),
+    );
 
-    let format = matches.get_one("format").cloned().unwrap_or_else(|| {
-        #[allow(unused_mut)]
-        let mut format = Format::Yaml;
+    let matches = command.get_matches();
 
-        #[cfg(feature = "biblatex")]
-        if input
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .is_some_and(|ext| ext.to_lowercase() == "bib")
-        {
-            format = Format::Bibtex;
-        }
+    #[cfg(feature = "cli-doi")]
+    if let Some(("add", sub_matches)) = matches.subcommand() {
+        let doi = sub_matches.get_one::<String>("doi").unwrap();
+        let fetch = |url: &str| {
+            ureq::get(url)
+                .set("Accept", hayagriva::doi::CSL_JSON_MEDIA_TYPE)
+                .call()
+                .map_err(|e| e.to_string())?
+                .into_string()
+                .map_err(|e| e.to_string())
+        };
 
-        format
-    });
+        match hayagriva::doi::entry_from_doi(doi, fetch) {
+            Ok(entry) => {
+                let library: Library = std::iter::once(entry).collect();
+                print!("{}", io::to_yaml_str(&library).unwrap());
+                exit(0);
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                exit(10);
+            }
+        }
+    }
 
-    let bibliography = {
-        let input = match read_to_string(input) {
-            Ok(s) => s,
-            Err(e) => {
-                if e.kind() == IoErrorKind::NotFound {
-                    eprintln!("Bibliography file \"{}\" not found.", input.display());
-                    exit(5);
-                } else if let Some(os) = e.raw_os_error() {
-                    eprintln!(
-                        "Error while reading the bibliography file \"{}\": {}",
-                        input.display(),
-                        os
-                    );
-                    exit(6);
-                } else {
-                    eprintln!(
-                        "Error while reading the bibliography file \"{}\".",
-                        input.display()
-                    );
-                    exit(6);
+    if let Some(("styles", sub_matches)) = matches.subcommand() {
+        if let Some(name) = sub_matches.get_one::<String>("info") {
+            let Some(style) = ArchivedStyle::by_name(name) else {
+                eprintln!("Unknown style \"{name}\".");
+                exit(4);
+            };
+            print_style_info(style);
+        } else {
+            let styles: Vec<ArchivedStyle> = match sub_matches.get_one::<String>("search")
+            {
+                Some(query) => ArchivedStyle::search(query).collect(),
+                None => ArchivedStyle::all().to_vec(),
+            };
+            for key in styles {
+                println!("- {}", key.names()[0]);
+                println!("  Full name: {}", key.display_name());
+                if key.names().len() > 1 {
+                    println!("  Aliases: {}", key.names()[1..].join(", "));
                 }
             }
+        }
+        exit(0);
+    }
+
+    if let Some(("check-style", sub_matches)) = matches.subcommand() {
+        let path = sub_matches.get_one::<String>("FILE").unwrap();
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(err) if err.kind() == IoErrorKind::NotFound => {
+                eprintln!("Style file \"{path}\" not found.");
+                exit(5);
+            }
+            Err(err) => {
+                eprintln!("Could not read style file: {err}");
+                exit(6);
+            }
         };
 
-        match format {
-            Format::Yaml => io::from_yaml_str(&input).unwrap(),
-            #[cfg(feature = "biblatex")]
-            Format::Biblatex | Format::Bibtex => io::from_biblatex_str(&input).unwrap(),
+        let diagnostics = check_style(&src);
+        for diagnostic in &diagnostics {
+            print_diagnostic(path, &src, diagnostic);
         }
+
+        if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            exit(4);
+        } else if diagnostics.is_empty() {
+            println!("No problems found.");
+        }
+        exit(0);
+    }
+
+    if let Some(("convert", sub_matches)) = matches.subcommand() {
+        let files: Vec<&Path> = sub_matches
+            .get_many::<String>("FILES")
+            .unwrap()
+            .map(Path::new)
+            .collect();
+        let to = sub_matches.get_one("to").copied().unwrap_or(Format::Yaml);
+        convert(&files, matches.get_one("format").copied(), to);
+    }
+
+    if let Some(("fmt", sub_matches)) = matches.subcommand() {
+        let files: Vec<&Path> = sub_matches
+            .get_many::<String>("FILES")
+            .unwrap()
+            .map(Path::new)
+            .collect();
+        format_files(&files, sub_matches.get_flag("sort"), sub_matches.get_flag("check"));
+    }
+
+    if let Some(("select", sub_matches)) = matches.subcommand() {
+        let selector = parse_selector(sub_matches.get_one::<String>("SELECTOR").unwrap());
+        let path = Path::new(sub_matches.get_one::<String>("FILE").unwrap());
+        let format = matches
+            .get_one("format")
+            .copied()
+            .unwrap_or_else(|| infer_format(path));
+        let (bibliography, _) = read_bibliography(path, format);
+        print_selection(&selector, &bibliography);
+    }
+
+    if let Some(("dedupe", sub_matches)) = matches.subcommand() {
+        let path = Path::new(sub_matches.get_one::<String>("FILE").unwrap());
+        let format = matches
+            .get_one("format")
+            .copied()
+            .unwrap_or_else(|| infer_format(path));
+        dedupe_file(path, format, sub_matches.get_flag("write"));
+    }
+
+    let Some(input) = matches.get_one::<String>("INPUT") else {
+        eprintln!("No bibliography file given.");
+        exit(2);
     };
+    let input = Path::new(input);
+
+    let format = matches
+        .get_one("format")
+        .copied()
+        .unwrap_or_else(|| infer_format(input));
+    let (bibliography, _) = read_bibliography(input, format);
 
     let bib_len = bibliography.len();
 
-    let selector =
-        matches
-            .get_one("selector")
-            .cloned()
-            .map(|src| match Selector::parse(src) {
-                Ok(selector) => selector,
-                Err(err) => {
-                    eprintln!("Error while parsing selector: {err}");
-                    exit(7);
-                }
-            });
+    let selector = matches.get_one::<String>("selector").map(|src| parse_selector(src));
 
-    let bibliography = if let Some(keys) = matches.get_one::<String>("key") {
-        let mut res = vec![];
-        for key in keys.split(',') {
-            if let Some(entry) = bibliography.iter().find(|e| e.key() == key) {
+    let key_specs: Option<Vec<KeySpec>> = matches
+        .get_many::<String>("key")
+        .map(|args| args.flat_map(|arg| parse_key_arg(arg)).collect());
+
+    let missing_placeholder = matches.get_flag("missing-placeholder");
+    if let Some(specs) = &key_specs {
+        if let Err(err) = bibliography.get_all(specs.iter().map(|spec| spec.key)) {
+            if !missing_placeholder {
+                eprintln!("{err}");
+                exit(13);
+            }
+        }
+    }
+
+    // The entries before applying the selector. A bibliography filtered by a
+    // selector still numbers its entries as if all of them were cited.
+    let mut unselected = None;
+    let bibliography = if let Some(specs) = &key_specs {
+        let mut res: Vec<Entry> = vec![];
+        for spec in specs {
+            if res.iter().any(|e| e.key() == spec.key) {
+                continue;
+            }
+            if let Some(entry) = bibliography.get(spec.key) {
                 res.push(entry.clone());
             }
         }
         res.into_iter().collect()
     } else if let Some(selector) = &selector {
-        bibliography.into_iter().filter(|e| selector.matches(e)).collect()
+        let selected =
+            bibliography.iter().filter(|e| selector.matches(e)).cloned().collect();
+        unselected = Some(bibliography);
+        selected
     } else {
         bibliography
     };
@@ -276,7 +568,10 @@ fn main() {
             println!("{}", entry.key());
             if matches.get_flag("show-bound") {
                 if let Some(selector) = &selector {
-                    for (k, v) in selector.apply(entry).unwrap() {
+                    let mut bound: Vec<_> =
+                        selector.apply(entry).unwrap().into_iter().collect();
+                    bound.sort_by(|(a, _), (b, _)| a.cmp(b));
+                    for (k, v) in bound {
                         println!(
                             "\t{} => [{:?}] {}, {}",
                             k,
@@ -309,6 +604,25 @@ fn main() {
         exit(0);
     }
 
+    let link_behaviour =
+        match matches.get_one::<String>("links").map(|s| s.to_lowercase()) {
+            Some(links) if links == "never" => LinkBehaviour::Never,
+            Some(links) if links == "identifiers-only" => LinkBehaviour::IdentifiersOnly,
+            _ => LinkBehaviour::UrlsAndIdentifiers,
+        };
+
+    let abbreviations = matches
+        .get_one::<String>("abbreviations")
+        .map(|path| read_abbreviations(Path::new(path)))
+        .unwrap_or_default();
+
+    let mut term_overrides = TermOverrides::new();
+    for arg in matches.get_many::<String>("term").into_iter().flatten() {
+        let (term, form, value) = parse_term_arg(arg);
+        term_overrides.insert(term, form, false, value);
+        term_overrides.insert(term, form, true, value);
+    }
+
     match matches.subcommand() {
         Some(("reference", sub_matches)) => {
             let style: Option<&String> = sub_matches.get_one("style");
@@ -326,38 +640,37 @@ fn main() {
             }
 
             let mut driver = BibliographyDriver::new();
-            for entry in &bibliography {
-                driver.citation(CitationRequest::new(
-                    vec![CitationItem::with_entry(entry)],
-                    &style,
-                    locale.clone(),
-                    &locales,
-                    None,
-                ))
+            for entry in unselected.as_ref().unwrap_or(&bibliography) {
+                driver.citation(
+                    CitationRequest::new(
+                        vec![CitationItem::with_entry(entry)],
+                        &style,
+                        locale.clone(),
+                        &locales,
+                        None,
+                    )
+                    .with_link_behaviour(link_behaviour)
+                    .with_abbreviations(&abbreviations)
+                    .with_term_overrides(&term_overrides),
+                )
             }
 
-            for row in driver
-                .finish(BibliographyRequest::new(&style, locale, &locales))
-                .bibliography
-                .map(|b| b.items)
-                .unwrap_or_default()
-            {
-                let alternate = matches.get_flag("no-fmt");
-
-                if let Some(prefix) = row.first_field {
-                    if alternate {
-                        println!("{prefix:#}")
-                    } else {
-                        println!("{prefix}")
-                    }
-                }
+            let keys: Vec<String> =
+                bibliography.keys().map(ToString::to_string).collect();
+            let mut request = BibliographyRequest::new(&style, locale, &locales)
+                .with_link_behaviour(link_behaviour)
+                .with_suppress_url_with_doi(matches.get_flag("suppress-url-with-doi"))
+                .with_abbreviations(&abbreviations)
+                .with_term_overrides(&term_overrides);
+            if unselected.is_some() {
+                request = request.with_keys(&keys);
+            }
 
-                if alternate {
-                    println!("{:#}", row.content)
-                } else {
-                    println!("{}", row.content)
-                }
+            let rendered = driver.finish(request);
+            if matches.get_flag("verbose") {
+                print_diagnostics(&rendered.diagnostics);
             }
+            print_bibliography(rendered.bibliography, matches.get_flag("no-fmt"));
         }
         Some(("cite", sub_matches)) => {
             let style: Option<&String> = sub_matches.get_one("style");
@@ -375,44 +688,85 @@ fn main() {
             let (style, locales, locale) =
                 retrieve_assets(style, csl, locale_path, locale_str);
 
-            let assign_locator = |(i, e)| {
+            // Cite in the order of the keys, with their locators if given. Keys
+            // that are not in the bibliography are kept to print placeholders.
+            let keyed: Vec<Result<(&Entry, Option<SpecificLocator>), &str>> =
+                match &key_specs {
+                    Some(specs) => specs
+                        .iter()
+                        .map(|spec| {
+                            bibliography
+                                .get(spec.key)
+                                .map(|entry| (entry, spec.locator))
+                                .ok_or(spec.key)
+                        })
+                        .collect(),
+                    None => bibliography.iter().map(|e| Ok((e, None))).collect(),
+                };
+            let cites: Vec<(usize, &(&Entry, Option<SpecificLocator>))> = keyed
+                .iter()
+                .enumerate()
+                .filter_map(|(i, cite)| Some((i, cite.as_ref().ok()?)))
+                .collect();
+
+            let assign_locator = |&(i, &(e, locator)): &(usize, &(&Entry, _))| {
                 let mut item = CitationItem::with_entry(e);
-                if let Some(&locator) = locators.get(i) {
-                    item.locator = Some(SpecificLocator(
+                if let Some(locator) = locator {
+                    item.locators = vec![locator];
+                } else if let Some(&locator) = locators.get(i) {
+                    item.locators = vec![SpecificLocator(
                         Locator::Custom,
                         LocatorPayload::Str(locator),
-                    ));
+                    )];
                 }
                 item
             };
 
             let mut driver = BibliographyDriver::new();
             if collapse {
-                driver.citation(CitationRequest::new(
-                    bibliography.iter().enumerate().map(assign_locator).collect(),
-                    &style,
-                    locale.clone(),
-                    &locales,
-                    None,
-                ));
-            } else {
-                for (i, entry) in bibliography.iter().enumerate() {
-                    driver.citation(CitationRequest::new(
-                        vec![assign_locator((i, entry))],
+                driver.citation(
+                    CitationRequest::new(
+                        cites.iter().map(assign_locator).collect(),
                         &style,
                         locale.clone(),
                         &locales,
                         None,
-                    ))
+                    )
+                    .with_link_behaviour(link_behaviour)
+                    .with_abbreviations(&abbreviations)
+                    .with_term_overrides(&term_overrides),
+                );
+            } else {
+                for cite in &cites {
+                    driver.citation(
+                        CitationRequest::new(
+                            vec![assign_locator(cite)],
+                            &style,
+                            locale.clone(),
+                            &locales,
+                            None,
+                        )
+                        .with_link_behaviour(link_behaviour)
+                        .with_abbreviations(&abbreviations)
+                        .with_term_overrides(&term_overrides),
+                    )
                 }
             }
 
-            for row in driver
-                .finish(BibliographyRequest::new(&style, locale, &locales))
-                .citations
-            {
-                let alternate = matches.get_flag("no-fmt");
-
+            let rendered = driver.finish(
+                BibliographyRequest::new(&style, locale, &locales)
+                    .with_link_behaviour(link_behaviour)
+                    .with_suppress_url_with_doi(matches.get_flag("suppress-url-with-doi"))
+                    .with_abbreviations(&abbreviations)
+                    .with_term_overrides(&term_overrides),
+            );
+            if matches.get_flag("verbose") {
+                print_diagnostics(&rendered.diagnostics);
+            }
+            let alternate = matches.get_flag("no-fmt");
+            let mut rows = rendered.citations.into_iter();
+            let mut print_row = || {
+                let Some(row) = rows.next() else { return };
                 if let Some(note_number) = row.note_number {
                     if alternate {
                         println!("{note_number:#}")
@@ -426,26 +780,25 @@ fn main() {
                 } else {
                     println!("{}", row.citation)
                 }
+            };
+
+            // A combined citation is printed first, followed by the
+            // placeholders of all missing keys.
+            if collapse {
+                print_row();
             }
-        }
-        Some(("styles", _)) => {
-            for key in ArchivedStyle::all() {
-                let style = key.get();
-                println!("- {}", &key.names()[0]);
-                println!("  Full name: {}", &style.info().title.value);
-                println!("  Authors:");
-                for author in style.info().authors.iter() {
-                    print!("  - {}", author.name);
-                    if let Some(email) = &author.email {
-                        println!(" <{email}>");
-                    } else {
-                        println!();
-                    }
-                }
-                if let Some(desc) = &style.info().summary {
-                    println!("  Summary: {}", &desc.value);
+            for cite in &keyed {
+                match cite {
+                    Ok(_) if !collapse => print_row(),
+                    Ok(_) => {}
+                    Err(key) => println!("[missing: {key}]"),
                 }
             }
+
+            if sub_matches.get_flag("with-references") {
+                println!();
+                print_bibliography(rendered.bibliography, alternate);
+            }
         }
         _ => {
             let bib = io::to_yaml_str(&bibliography).unwrap();
@@ -454,6 +807,518 @@ fn main() {
     }
 }
 
+/// Guess the format of a bibliography file from its extension.
+fn infer_format(path: &Path) -> Format {
+    #[allow(unused_variables)]
+    let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+
+    #[cfg(feature = "biblatex")]
+    if extension.as_deref() == Some("bib") {
+        return Format::Bibtex;
+    }
+
+    #[cfg(feature = "ris")]
+    if extension.as_deref() == Some("ris") {
+        return Format::Ris;
+    }
+
+    #[cfg(feature = "csl-json")]
+    if extension.as_deref() == Some("json") {
+        return Format::CslJson;
+    }
+
+    Format::Yaml
+}
+
+/// Read and parse a bibliography file, exiting if this fails.
+///
+/// Also returns the keys and names of the fields that were dropped during
+/// the import.
+fn read_bibliography(path: &Path, format: Format) -> (Library, Vec<(String, String)>) {
+    let src = match read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            if e.kind() == IoErrorKind::NotFound {
+                eprintln!("Bibliography file \"{}\" not found.", path.display());
+                exit(5);
+            } else if let Some(os) = e.raw_os_error() {
+                eprintln!(
+                    "Error while reading the bibliography file \"{}\": {}",
+                    path.display(),
+                    os
+                );
+                exit(6);
+            } else {
+                eprintln!(
+                    "Error while reading the bibliography file \"{}\".",
+                    path.display()
+                );
+                exit(6);
+            }
+        }
+    };
+
+    match parse_bibliography(&src, format) {
+        Ok(res) => res,
+        Err(err) => {
+            eprintln!("Error while parsing \"{}\": {err}", path.display());
+            exit(9);
+        }
+    }
+}
+
+/// Read and parse a file with abbreviations, exiting if this fails.
+fn read_abbreviations(path: &Path) -> Abbreviations {
+    let src = match read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == IoErrorKind::NotFound => {
+            eprintln!("Abbreviation file \"{}\" not found.", path.display());
+            exit(5);
+        }
+        Err(e) => {
+            eprintln!(
+                "Error while reading the abbreviation file \"{}\": {e}",
+                path.display()
+            );
+            exit(6);
+        }
+    };
+
+    // JSON is a subset of YAML, so this reads both formats.
+    match serde_yaml::from_str(&src) {
+        Ok(abbreviations) => abbreviations,
+        Err(err) => {
+            eprintln!("Error while parsing \"{}\": {err}", path.display());
+            exit(9);
+        }
+    }
+}
+
+/// Parse a bibliography in the given format.
+fn parse_bibliography(
+    src: &str,
+    format: Format,
+) -> Result<(Library, Vec<(String, String)>), String> {
+    match format {
+        Format::Yaml => io::from_yaml_str_detailed(src)
+            .map(|lib| (lib, vec![]))
+            .map_err(|errs| errs.iter().map(|e| format!("\n  {e}")).collect()),
+        #[cfg(feature = "biblatex")]
+        Format::Biblatex | Format::Bibtex => {
+            io::from_biblatex_str(src).map(|lib| (lib, vec![])).map_err(|errs| {
+                errs.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            })
+        }
+        #[cfg(feature = "ris")]
+        Format::Ris => {
+            let import = io::from_ris_str(src).map_err(|e| e.to_string())?;
+            let mut dropped = vec![];
+            for tag in import.unknown {
+                eprintln!(
+                    "Ignored RIS tag {} in entry {}: {}",
+                    tag.tag, tag.key, tag.value
+                );
+                dropped.push((tag.key, format!("RIS tag {}", tag.tag)));
+            }
+            Ok((import.library, dropped))
+        }
+        #[cfg(feature = "csl-json")]
+        Format::CslJson => io::from_csl_json_str(src)
+            .map(|lib| (lib, vec![]))
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Serialize a bibliography in the given format, exiting if this fails.
+fn write_bibliography(bibliography: &Library, format: Format) -> String {
+    match format {
+        Format::Yaml => io::to_yaml_str(bibliography).unwrap(),
+        #[cfg(feature = "biblatex")]
+        Format::Biblatex => io::to_biblatex_str(bibliography),
+        #[cfg(feature = "biblatex")]
+        Format::Bibtex => match io::to_bibtex_str(bibliography) {
+            Ok(bib) => bib,
+            Err(err) => {
+                eprintln!("Error while exporting to BibTeX: {err}");
+                exit(8);
+            }
+        },
+        #[cfg(feature = "ris")]
+        Format::Ris => {
+            eprintln!("Exporting to RIS is not supported");
+            exit(8);
+        }
+        #[cfg(feature = "csl-json")]
+        Format::CslJson => io::to_csl_json_str(bibliography),
+    }
+}
+
+/// Merge the given files into one bibliography and print it in the target
+/// format. Exits with an error code if keys are duplicated or fields were
+/// dropped during the conversion.
+fn convert(files: &[&Path], format: Option<Format>, to: Format) -> ! {
+    let mut merged = Library::new();
+    let mut sources: HashMap<String, &Path> = HashMap::new();
+    let mut duplicates = vec![];
+    let mut dropped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for &file in files {
+        let format = format.unwrap_or_else(|| infer_format(file));
+        let (library, lost) = read_bibliography(file, format);
+        for (key, field) in lost {
+            dropped.entry(key).or_default().push(field);
+        }
+
+        for entry in library {
+            if let Some(first) = sources.get(entry.key()) {
+                duplicates.push(format!(
+                    "{} in \"{}\" (first defined in \"{}\")",
+                    entry.key(),
+                    file.display(),
+                    first.display()
+                ));
+                continue;
+            }
+
+            sources.insert(entry.key().to_string(), file);
+            merged.push(&entry);
+        }
+    }
+
+    let output = write_bibliography(&merged, to);
+
+    // Read the output back in to find out what did not survive.
+    let converted = match parse_bibliography(&output, to) {
+        Ok((converted, _)) => converted,
+        Err(err) => {
+            eprintln!("Error while checking the converted bibliography: {err}");
+            exit(8);
+        }
+    };
+    for entry in merged.iter() {
+        let lost = match converted.get(entry.key()) {
+            Some(converted) => io::dropped_fields(entry, converted),
+            None => vec!["entire entry".to_string()],
+        };
+        if !lost.is_empty() {
+            dropped.entry(entry.key().to_string()).or_default().extend(lost);
+        }
+    }
+
+    println!("{output}");
+
+    for duplicate in &duplicates {
+        eprintln!("Skipped duplicate key {duplicate}");
+    }
+    for (key, fields) in &dropped {
+        eprintln!(
+            "Could not convert entry {key} losslessly, dropped: {}",
+            fields.join(", ")
+        );
+    }
+
+    exit(if duplicates.is_empty() && dropped.is_empty() { 0 } else { 11 })
+}
+
+/// Parse a selector or exit with all errors in it.
+fn parse_selector(src: &str) -> Selector {
+    match Selector::parse_with_diagnostics(src) {
+        Ok(selector) => selector,
+        Err(diagnostics) => {
+            for diagnostic in diagnostics {
+                eprintln!("Error while parsing selector: {diagnostic}");
+            }
+            exit(7);
+        }
+    }
+}
+
+/// Print the keys of all entries matching the selector along with the entries
+/// bound to each name as JSON and exit.
+fn print_selection(selector: &Selector, bibliography: &Library) -> ! {
+    let describe = |entry: &Entry| {
+        serde_json::json!({
+            "key": (!entry.key().is_empty()).then_some(entry.key()),
+            "type": entry.entry_type(),
+            "title": entry.title().map(|title| title.to_string()),
+        })
+    };
+
+    let selected: Vec<_> = bibliography
+        .iter()
+        .filter_map(|entry| {
+            let bindings: serde_json::Map<_, _> = selector
+                .apply(entry)?
+                .into_iter()
+                .map(|(name, bound)| (name, describe(bound)))
+                .collect();
+            Some(serde_json::json!({ "key": entry.key(), "bindings": bindings }))
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&selected).unwrap());
+    exit(0)
+}
+
+/// Print the groups of duplicates in a bibliography file along with the key
+/// that survives merging them and exit.
+///
+/// With `write`, each group is replaced by its merged entry at the position of
+/// the surviving entry and the file is rewritten.
+fn dedupe_file(path: &Path, format: Format, write: bool) -> ! {
+    let (bibliography, _) = read_bibliography(path, format);
+    let mut merged = vec![];
+    for group in find_duplicates(&bibliography) {
+        let entries: Vec<&Entry> =
+            group.keys.iter().filter_map(|key| bibliography.get(key)).collect();
+        let entry = merge(&entries).unwrap();
+        let others: Vec<&str> = group
+            .keys
+            .iter()
+            .map(String::as_str)
+            .filter(|&key| key != entry.key())
+            .collect();
+        println!("{} <- {}", entry.key(), others.join(", "));
+        merged.push((entry, group.keys));
+    }
+
+    if write && !merged.is_empty() {
+        let deduped: Library = bibliography
+            .iter()
+            .filter_map(|entry| {
+                let Some((merged, _)) =
+                    merged.iter().find(|(_, keys)| keys.iter().any(|k| k == entry.key()))
+                else {
+                    return Some(entry.clone());
+                };
+                (merged.key() == entry.key()).then(|| merged.clone())
+            })
+            .collect();
+
+        if let Err(err) = fs::write(path, write_bibliography(&deduped, format)) {
+            eprintln!("Could not write \"{}\": {err}", path.display());
+            exit(6);
+        }
+    }
+
+    exit(0)
+}
+
+/// Rewrite YAML bibliography files in canonical form and exit.
+///
+/// With `check`, the files are left as they are and the command fails if any
+/// of them is not formatted.
+fn format_files(files: &[&Path], sort: bool, check: bool) -> ! {
+    let mut unformatted = false;
+    for &path in files {
+        let (mut bibliography, _) = read_bibliography(path, Format::Yaml);
+        if sort {
+            bibliography.sort_keys();
+        }
+
+        let formatted = write_bibliography(&bibliography, Format::Yaml);
+        if fs::read_to_string(path).is_ok_and(|original| original == formatted) {
+            continue;
+        }
+
+        if check {
+            eprintln!("\"{}\" is not formatted.", path.display());
+            unformatted = true;
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("Could not write \"{}\": {err}", path.display());
+            exit(6);
+        }
+    }
+
+    exit(if unformatted { 12 } else { 0 });
+}
+
+/// Print a style diagnostic along with the offending source line.
+fn print_diagnostic(path: &str, src: &str, diagnostic: &StyleDiagnostic) {
+    let Location { line, column } = diagnostic.location;
+    println!("{}: {}", diagnostic.severity, diagnostic.message);
+    println!("  --> {path}:{line}:{column}");
+
+    let source_line = src.lines().nth(line - 1).unwrap_or_default();
+    let width = src[diagnostic.span.clone()].chars().count().max(1);
+    let gutter = " ".repeat(line.to_string().len());
+    println!("{gutter} |");
+    println!("{line} | {source_line}");
+    println!("{gutter} | {}{}", " ".repeat(column - 1), "^".repeat(width));
+    println!();
+}
+
+/// Print the metadata of an archived style.
+fn print_style_info(key: ArchivedStyle) {
+    let style = key.get();
+    let info = style.info();
+    println!("Name: {}", key.names()[0]);
+    if key.names().len() > 1 {
+        println!("Aliases: {}", key.names()[1..].join(", "));
+    }
+    println!("Title: {}", info.title.value);
+    println!("ID: {}", info.id);
+
+    for category in &info.category {
+        if let StyleCategory::CitationFormat { format } = category {
+            println!("Citation format: {format:?}");
+        }
+    }
+
+    match &style {
+        Style::Independent(indep) => {
+            let class = match indep.settings.class {
+                StyleClass::InText => "in-text",
+                StyleClass::Note => "note",
+            };
+            println!("Class: {class}");
+            if let Some(locale) = &indep.default_locale {
+                println!("Default locale: {}", locale.0);
+            }
+            println!("Dependent: no");
+        }
+        Style::Dependent(dep) => {
+            if let Some(locale) = &dep.default_locale {
+                println!("Default locale: {}", locale.0);
+            }
+            println!("Dependent: yes, on {}", dep.parent_link.href);
+        }
+    }
+
+    if !info.authors.is_empty() {
+        println!("Authors:");
+        for author in &info.authors {
+            print!("- {}", author.name);
+            if let Some(email) = &author.email {
+                println!(" <{email}>");
+            } else {
+                println!();
+            }
+        }
+    }
+
+    if let Some(desc) = &info.summary {
+        println!("Summary: {}", desc.value);
+    }
+}
+
+/// Print the problems found while rendering to stderr.
+fn print_diagnostics(diagnostics: &[RenderDiagnostic]) {
+    for diagnostic in diagnostics {
+        eprintln!("warning: {diagnostic}");
+    }
+}
+
+/// Print the rows of a rendered bibliography.
+fn print_bibliography(bibliography: Option<RenderedBibliography>, alternate: bool) {
+    for row in bibliography.map(|b| b.items).unwrap_or_default() {
+        if let Some(prefix) = row.first_field {
+            if alternate {
+                println!("{prefix:#}")
+            } else {
+                println!("{prefix}")
+            }
+        }
+
+        if alternate {
+            println!("{:#}", row.content)
+        } else {
+            println!("{}", row.content)
+        }
+    }
+}
+
+/// A key passed with `--key`, optionally with a locator for citations.
+struct KeySpec<'a> {
+    key: &'a str,
+    locator: Option<SpecificLocator<'a>>,
+}
+
+/// Parse a `--key` argument. It is either a comma-separated list of keys or a
+/// single key with a locator like `smith2020:14`, `smith2020:p. 14`, or
+/// `smith2020:label=chapter,value=3`.
+fn parse_key_arg(arg: &str) -> Vec<KeySpec<'_>> {
+    match arg.split_once(':') {
+        Some((key, locator)) => {
+            vec![KeySpec {
+                key: key.trim(),
+                locator: Some(parse_locator(locator)),
+            }]
+        }
+        None => arg
+            .split(',')
+            .map(|key| KeySpec { key: key.trim(), locator: None })
+            .collect(),
+    }
+}
+
+/// Parse the locator part of a `--key` argument.
+fn parse_locator(src: &str) -> SpecificLocator<'_> {
+    if !src.contains('=') {
+        let page = src.trim();
+        let page = page
+            .strip_prefix("pp.")
+            .or_else(|| page.strip_prefix("p."))
+            .unwrap_or(page)
+            .trim_start();
+        return SpecificLocator(Locator::Page, LocatorPayload::Str(page));
+    }
+
+    let mut label = Locator::Page;
+    let mut value = None;
+    for part in src.split(',') {
+        match part.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+            Some(("label", name)) => {
+                let deserializer: StrDeserializer<serde::de::value::Error> =
+                    name.into_deserializer();
+                label = Locator::deserialize(deserializer)
+                    .unwrap_or_else(|_| invalid_locator(src));
+            }
+            Some(("value", v)) => value = Some(v),
+            _ => invalid_locator(src),
+        }
+    }
+
+    SpecificLocator(
+        label,
+        LocatorPayload::Str(value.unwrap_or_else(|| invalid_locator(src))),
+    )
+}
+
+/// Parse a `--term` argument of the form `name[:form]=value`.
+fn parse_term_arg(arg: &str) -> (Term, TermForm, &str) {
+    fn parse<T: for<'de> Deserialize<'de>>(src: &str) -> Option<T> {
+        let deserializer: StrDeserializer<serde::de::value::Error> =
+            src.trim().into_deserializer();
+        T::deserialize(deserializer).ok()
+    }
+
+    let parsed = arg.split_once('=').and_then(|(name, value)| {
+        let (term, form) = match name.split_once(':') {
+            Some((term, form)) => (parse(term)?, parse(form)?),
+            None => (parse(name)?, TermForm::Long),
+        };
+        Some((term, form, value))
+    });
+
+    parsed.unwrap_or_else(|| {
+        eprintln!(
+            "Invalid term \"{arg}\". Expected `<term>=<value>` or \
+             `<term>:<form>=<value>`."
+        );
+        exit(15);
+    })
+}
+
+/// Report a malformed locator and exit.
+fn invalid_locator(src: &str) -> ! {
+    eprintln!(
+        "Invalid locator \"{src}\". Expected a page or \
+         `label=<locator type>,value=<locator>`."
+    );
+    exit(14);
+}
+
 fn retrieve_assets<'a>(
     style: Option<&String>,
     csl: Option<&String>,
@@ -462,15 +1327,30 @@ fn retrieve_assets<'a>(
 ) -> (IndependentStyle, Vec<Locale>, Option<LocaleCode>) {
     let locale: Option<_> = locale_str.map(|l: &String| LocaleCode(l.into()));
 
-    let style = match (style, csl) {
-        (_, Some(csl)) => {
-            let file_str = fs::read_to_string(csl).expect("could not read CSL file");
-            IndependentStyle::from_xml(&file_str).expect("CSL file malformed")
+    let mut loader = StyleLoader::new();
+    if let Some(locale_paths) = locale_paths {
+        for locale_path in locale_paths {
+            if let Err(err) = loader.add_locale_path(locale_path) {
+                eprintln!("Could not load locale: {err}");
+                exit(4);
+            }
         }
+    }
+
+    let style = match (style, csl) {
+        (_, Some(csl)) => loader.load_path(csl).unwrap_or_else(|err| {
+            eprintln!("Could not load style: {err}");
+            exit(4);
+        }),
         (Some(style), _) => {
-            let Style::Independent(indep) =
-                ArchivedStyle::by_name(style.as_str()).expect("no style found").get()
-            else {
+            let Some(archived) = ArchivedStyle::by_name(style.as_str()) else {
+                eprintln!(
+                    "Unknown style \"{style}\". Run `hayagriva <file> styles` to list all \
+                     available styles."
+                );
+                exit(4);
+            };
+            let Style::Independent(indep) = archived.get() else {
                 panic!("dependent style in archive")
             };
             indep
@@ -478,17 +1358,7 @@ fn retrieve_assets<'a>(
         (None, None) => panic!("must specify style or CSL file"),
     };
 
-    let locales: Vec<Locale> = match locale_paths {
-        Some(locale_paths) => locale_paths
-            .into_iter()
-            .map(|locale_path| {
-                let file_str =
-                    fs::read_to_string(locale_path).expect("could not read locale file");
-                LocaleFile::from_xml(&file_str).expect("locale file malformed").into()
-            })
-            .collect(),
-        None => locales(),
-    };
+    let locales = loader.locales().to_vec();
 
     (style, locales, locale)
 }
@@ -0,0 +1,374 @@
+//! Provides conversion methods for RIS.
+
+use unic_langid::LanguageIdentifier;
+use url::Url;
+
+use crate::io::{RisError, RisImport, UnknownRisTag};
+use crate::types::*;
+use crate::{Entry, Library};
+
+/// A single `XY  - value` line of a RIS record, including its continuation
+/// lines.
+struct Field<'a> {
+    tag: &'a str,
+    value: String,
+}
+
+/// Parse a RIS file into a library, collecting all tags that could not be
+/// mapped to an entry field.
+pub(crate) fn parse(src: &str) -> Result<RisImport, RisError> {
+    let mut library = Library::new();
+    let mut unknown = vec![];
+    let mut record: Option<(usize, Vec<Field>)> = None;
+
+    for (i, line) in src.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line_number = i + 1;
+        let Some((tag, value)) = split_tag(line) else {
+            // Lines without a tag continue the value of the previous tag.
+            if let Some(last) = record.as_mut().and_then(|(_, fields)| fields.last_mut())
+            {
+                let line = line.trim();
+                if !line.is_empty() {
+                    if !last.value.is_empty() {
+                        last.value.push(' ');
+                    }
+                    last.value.push_str(line);
+                }
+            }
+            continue;
+        };
+
+        if tag == "TY" {
+            if let Some((start, _)) = record {
+                return Err(RisError::UnterminatedRecord(start));
+            }
+            record = Some((line_number, vec![Field { tag, value: value.into() }]));
+        } else if tag == "ER" {
+            let Some((_, fields)) = record.take() else {
+                return Err(RisError::TagOutsideRecord(line_number));
+            };
+            let (entry, lost) = convert(&fields, &library);
+            unknown.extend(lost.into_iter().map(|(tag, value)| UnknownRisTag {
+                key: entry.key().to_string(),
+                tag,
+                value,
+            }));
+            library.push(&entry);
+        } else if let Some((_, fields)) = &mut record {
+            fields.push(Field { tag, value: value.into() });
+        } else {
+            return Err(RisError::TagOutsideRecord(line_number));
+        }
+    }
+
+    if let Some((start, _)) = record {
+        return Err(RisError::UnterminatedRecord(start));
+    }
+
+    Ok(RisImport { library, unknown })
+}
+
+/// Split a line of the form `XY  - value` into its tag and value.
+fn split_tag(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end();
+    let tag = line.get(..2)?;
+    if !tag.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        || !line.get(2..)?.starts_with("  -")
+    {
+        return None;
+    }
+
+    Some((tag, line.get(5..).unwrap_or_default().trim()))
+}
+
+/// Map the `TY` tag to an entry type and, if applicable, the type of its
+/// parent.
+fn entry_type(ty: &str) -> (EntryType, Option<EntryType>) {
+    match ty {
+        "JOUR" | "JFULL" | "EJOUR" | "ABST" | "INPR" => {
+            (EntryType::Article, Some(EntryType::Periodical))
+        }
+        "MGZN" => (EntryType::Article, Some(EntryType::Periodical)),
+        "NEWS" => (EntryType::Article, Some(EntryType::Newspaper)),
+        "CHAP" | "ECHAP" => (EntryType::Chapter, Some(EntryType::Book)),
+        "CONF" | "CPAPER" => (EntryType::Article, Some(EntryType::Proceedings)),
+        "BOOK" | "EBOOK" => (EntryType::Book, None),
+        "EDBOOK" => (EntryType::Anthology, None),
+        "THES" => (EntryType::Thesis, None),
+        "RPRT" => (EntryType::Report, None),
+        "ELEC" | "WEB" | "BLOG" => (EntryType::Web, None),
+        "PAT" => (EntryType::Patent, None),
+        "DATA" => (EntryType::Dataset, None),
+        "DBASE" => (EntryType::Repository, None),
+        "COMP" => (EntryType::Software, None),
+        "STAND" => (EntryType::Standard, None),
+        "HEAR" => (EntryType::Hearing, None),
+        "UNPB" | "MANSCPT" => (EntryType::Manuscript, None),
+        "CASE" => (EntryType::Case, None),
+        "BILL" | "STAT" => (EntryType::Legislation, None),
+        "ART" => (EntryType::Artwork, None),
+        "VIDEO" | "MPCT" => (EntryType::Video, None),
+        "SOUND" | "MUSIC" => (EntryType::Audio, None),
+        _ => (EntryType::Misc, None),
+    }
+}
+
+/// Parse a RIS name like `Last, First, Suffix`.
+fn person(value: &str) -> Option<Person> {
+    let mut parts: Vec<&str> = value.split(',').collect();
+    // RIS puts the suffix last, while the name parser expects it in the
+    // middle like BibTeX does.
+    if parts.len() == 3 {
+        parts.swap(1, 2);
+    }
+    Person::from_strings(parts).ok().filter(|p| !p.name.is_empty())
+}
+
+/// Parse a RIS date like `2001/05/03/other info` or `2001-05-03`.
+fn date(value: &str) -> Option<Date> {
+    let mut parts = value.split(['/', '-']).map(str::trim);
+    let year = parts.next()?.parse().ok()?;
+    let month = parts
+        .next()
+        .and_then(|m| m.parse::<u8>().ok())
+        .filter(|m| (1..=12).contains(m))
+        .map(|m| m - 1);
+    let day = month
+        .and(parts.next())
+        .and_then(|d| d.parse::<u8>().ok())
+        .filter(|d| (1..=31).contains(d))
+        .map(|d| d - 1);
+
+    Some(Date { year, month, day, approximate: false })
+}
+
+/// Derive a key for records without an `ID` tag from the first author and the
+/// year.
+fn derive_key(fields: &[Field], library: &Library) -> String {
+    let value = |tags: &[&str]| {
+        fields
+            .iter()
+            .find(|f| tags.contains(&f.tag))
+            .map(|f| f.value.as_str())
+    };
+
+    let mut key: String = value(&["ID"]).map(ToString::to_string).unwrap_or_else(|| {
+        let name = value(&["AU", "A1", "ED", "A2"])
+            .and_then(person)
+            .map(|p| p.name.to_lowercase())
+            .unwrap_or_else(|| "ris".to_string());
+        let year = value(&["PY", "Y1", "DA"]).and_then(date).map(|d| d.year);
+        let mut key: String = name.chars().filter(|c| c.is_alphanumeric()).collect();
+        if let Some(year) = year {
+            key.push_str(&year.to_string());
+        }
+        key
+    });
+
+    let base = key.clone();
+    let mut i = 2;
+    while library.get(&key).is_some() {
+        key = format!("{base}-{i}");
+        i += 1;
+    }
+
+    key
+}
+
+/// The entry that holds the volume, issue, and publisher of an item.
+fn container<'a>(item: &'a mut Entry, parent: &'a mut Option<Entry>) -> &'a mut Entry {
+    match parent {
+        Some(parent) => parent,
+        None => item,
+    }
+}
+
+/// Convert the fields of a record into an entry and a list of the tags that
+/// could not be converted.
+fn convert(fields: &[Field], library: &Library) -> (Entry, Vec<(String, String)>) {
+    let key = derive_key(fields, library);
+    let (kind, parent_kind) = entry_type(&fields[0].value);
+    let mut item = Entry::new(&key, kind);
+    let mut parent = parent_kind.map(|kind| Entry::new(&key, kind));
+    let mut unknown = vec![];
+
+    let mut authors = vec![];
+    let mut editors = vec![];
+    let mut translators = vec![];
+    let mut published = None;
+    let mut dated = None;
+    let mut accessed = None;
+    let mut start_page = None;
+    let mut end_page = None;
+
+    for Field { tag, value } in &fields[1..] {
+        let known = match *tag {
+            "ID" => true,
+            "AU" | "A1" => person(value).map(|p| authors.push(p)).is_some(),
+            "A2" | "ED" => person(value).map(|p| editors.push(p)).is_some(),
+            "A4" => person(value).map(|p| translators.push(p)).is_some(),
+            "TI" | "T1" => {
+                let title = item.title.get_or_insert_with(FormatString::new);
+                title.value = value.clone().into();
+                true
+            }
+            "ST" => {
+                let title = item.title.get_or_insert_with(FormatString::new);
+                title.short = Some(Box::new(value.clone().into()));
+                true
+            }
+            "T2" | "JO" | "JF" | "BT" if parent.is_some() => {
+                let parent = parent.as_mut().unwrap();
+                let title = parent.title.get_or_insert_with(FormatString::new);
+                if title.value.is_empty() {
+                    title.value = value.clone().into();
+                }
+                true
+            }
+            "BT" if item.title.is_none() => {
+                item.set_title(value.clone().into());
+                true
+            }
+            "J2" | "JA" if parent.is_some() => {
+                let parent = parent.as_mut().unwrap();
+                let title = parent.title.get_or_insert_with(FormatString::new);
+                title.short = Some(Box::new(value.clone().into()));
+                true
+            }
+            "PY" | "Y1" => date(value).map(|d| published = Some(d)).is_some(),
+            "DA" => date(value).map(|d| dated = Some(d)).is_some(),
+            "Y2" => date(value).map(|d| accessed = Some((d, value))).is_some(),
+            "SP" => {
+                start_page = Some(value.as_str());
+                true
+            }
+            "EP" => {
+                end_page = Some(value.as_str());
+                true
+            }
+            "VL" => {
+                container(&mut item, &mut parent)
+                    .set_volume(MaybeTyped::infallible_from_str(value));
+                true
+            }
+            "IS" => {
+                container(&mut item, &mut parent)
+                    .set_issue(MaybeTyped::infallible_from_str(value));
+                true
+            }
+            "ET" => {
+                item.set_edition(MaybeTyped::infallible_from_str(value));
+                true
+            }
+            "PB" => {
+                let container = container(&mut item, &mut parent);
+                let location = container.publisher.as_ref().and_then(|p| p.location());
+                let publisher =
+                    Publisher::new(Some(value.clone().into()), location.cloned());
+                container.set_publisher(publisher);
+                true
+            }
+            "CY" | "PP" => {
+                let container = container(&mut item, &mut parent);
+                let name = container.publisher.as_ref().and_then(|p| p.name());
+                let publisher = Publisher::new(name.cloned(), Some(value.clone().into()));
+                container.set_publisher(publisher);
+                true
+            }
+            "SN" => {
+                if matches!(
+                    kind,
+                    EntryType::Book | EntryType::Anthology | EntryType::Chapter
+                ) {
+                    item.set_isbn(value.clone());
+                } else {
+                    item.set_issn(value.clone());
+                }
+                true
+            }
+            "DO" => {
+                item.set_doi(value.clone());
+                true
+            }
+            "UR" if item.url.is_none() => Url::parse(value)
+                .map(|url| item.set_url(QualifiedUrl { value: url, visit_date: None }))
+                .is_ok(),
+            "AB" | "N2" if item.abstract_.is_none() => {
+                item.set_abstract_(value.clone().into());
+                true
+            }
+            "N1" if item.note.is_none() => {
+                item.set_note(value.clone().into());
+                true
+            }
+            "LA" => value
+                .parse::<LanguageIdentifier>()
+                .map(|language| item.set_language(language))
+                .is_ok(),
+            "M3" => {
+                item.set_genre(value.clone().into());
+                true
+            }
+            "CN" => {
+                item.set_call_number(value.clone().into());
+                true
+            }
+            _ => false,
+        };
+
+        if !known {
+            unknown.push((tag.to_string(), value.clone()));
+        }
+    }
+
+    if !authors.is_empty() {
+        item.set_authors(authors);
+    }
+
+    if !editors.is_empty() {
+        // The editors of a chapter or conference paper edited the book or
+        // proceedings it appeared in.
+        match &mut parent {
+            Some(parent)
+                if matches!(
+                    parent.entry_type,
+                    EntryType::Book | EntryType::Proceedings
+                ) =>
+            {
+                parent.set_editors(editors)
+            }
+            _ => item.set_editors(editors),
+        }
+    }
+
+    if !translators.is_empty() {
+        item.add_affiliated_persons((translators, PersonRole::Translator));
+    }
+
+    if let Some(date) = dated.or(published) {
+        item.set_date(date);
+    }
+
+    // Entries only have an access date as part of their URL.
+    if let Some((visit_date, value)) = accessed {
+        match &mut item.url {
+            Some(url) => url.visit_date = Some(visit_date),
+            None => unknown.push(("Y2".to_string(), value.clone())),
+        }
+    }
+
+    match (start_page, end_page) {
+        (Some(start), Some(end)) if start != end => item
+            .set_page_range(MaybeTyped::infallible_from_str(&format!("{start}-{end}"))),
+        (Some(page), _) | (None, Some(page)) => {
+            item.set_page_range(MaybeTyped::infallible_from_str(page))
+        }
+        (None, None) => {}
+    }
+
+    if let Some(parent) = parent {
+        item.add_parent(parent);
+    }
+
+    (item, unknown)
+}
@@ -9,7 +9,8 @@
 ///
 /// There are three main differences:
 /// - Binding names and attributes have to be strings and thus wrapped in double
-///   quotes
+///   quotes. Attributes can only require a field to be set; comparisons like
+///   `[date >= 2000]` are only available through [`Selector::parse`]
 /// - Type names are case sensitive and have to start with a capital letter
 ///   since they are just variants of [`EntryType`][EType]
 /// - Any non-atomic selector (`*` or an [`EntryType`][EType] variant are
@@ -64,7 +65,7 @@ macro_rules! select {
     ($expr:tt[$($attr:literal),* $(,)?]) => {
         $crate::Selector::Attr(
             Box::new(select!($expr)),
-            vec![$($attr.to_string()),*],
+            vec![$($crate::Attribute::from($attr)),*],
         )
     };
 
@@ -90,12 +91,16 @@ macro_rules! select {
 
 mod parser;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
 
+use serde_yaml::Value;
 use thiserror::Error;
 
 use crate::types::EntryType;
-use crate::Entry;
+use crate::{Entry, Location};
 
 /// A selector used to filter bibliographies and match on entries.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -109,9 +114,11 @@ pub enum Selector {
     Neg(Box<Self>),
     /// A binding: `x:misc`.
     Binding(String, Box<Self>),
-    /// An attribute filtering: `abc[att1, attr2]`.
-    Attr(Box<Self>, Vec<String>),
-    /// An alternate selector: `a | b`.
+    /// An attribute filtering: `abc[att1, attr2 >= 3]`.
+    Attr(Box<Self>, Vec<Attribute>),
+    /// An alternate selector: `a | b`. It binds the names of the first
+    /// alternative that matches. Names that only other alternatives bind stay
+    /// unbound.
     Alt(Vec<Self>),
     /// A multi-parent selector: `a & b`.
     Multi(Vec<Self>),
@@ -121,7 +128,38 @@ pub enum Selector {
 
 impl Selector {
     /// Parse a selector from a string.
+    ///
+    /// The grammar is as follows, with `>` binding loosest and `!` binding
+    /// tightest. Chained ancestrage selectors like `a > b > c` look at the
+    /// parents of parents:
+    ///
+    /// ```text
+    /// expr      = term ('>' expr)?
+    /// term      = binding (('|' | '&') binding)*
+    /// binding   = (ident ':')? attrs
+    /// attrs     = factor ('[' (attribute (',' attribute)*)? ']')?
+    /// factor    = '!' factor | value
+    /// value     = '(' expr ')' | '*' | entry-type
+    /// attribute = field (op literal)?
+    /// op        = '=' | '!=' | '<' | '<=' | '>' | '>='
+    /// literal   = integer | '"' text '"' | ident
+    /// ```
+    ///
+    /// An attribute without a comparison requires the field to be set. With an
+    /// integer, the comparison uses the number the field starts with, like the
+    /// year of a date. Otherwise, it compares the text of the field. Besides
+    /// the fields of an entry, attributes can name the identifiers in its
+    /// serial number, like `doi`. For example, `a:article[doi] > b:periodical`
+    /// matches articles with a DOI in a periodical and binds both of them and
+    /// `*[date >= 2000, language = "de"]` matches German works published in or
+    /// after 2000.
     pub fn parse(src: &str) -> SelectorResult<Self> {
+        parser::parse(src).map_err(|mut diagnostics| diagnostics.remove(0).error)
+    }
+
+    /// Parse a selector from a string like [`Selector::parse`], but report all
+    /// errors with their location in the source instead of only the first one.
+    pub fn parse_with_diagnostics(src: &str) -> Result<Self, Vec<SelectorDiagnostic>> {
         parser::parse(src)
     }
 
@@ -140,6 +178,11 @@ impl Selector {
 
     /// Applies the selector to an [`Entry`] and returns the bound variables
     /// in a hash map if there was a match.
+    ///
+    /// The map contains the entry or parent that each binding of the selector
+    /// matched, so that fields can be read from the right one. If several
+    /// parents of an entry match, the first one in the order of its parents is
+    /// bound. Bindings in alternatives that did not match are left out.
     pub fn apply<'s>(&self, entry: &'s Entry) -> Option<HashMap<String, &'s Entry>> {
         match self {
             Self::Wildcard => Some(HashMap::new()),
@@ -166,7 +209,7 @@ impl Selector {
             }),
 
             Self::Attr(expr, attributes) => expr.apply(entry).and_then(|bound| {
-                if attributes.iter().all(|arg| entry.has(arg.as_ref())) {
+                if attributes.iter().all(|attr| attr.matches(entry)) {
                     Some(bound)
                 } else {
                     None
@@ -232,12 +275,13 @@ impl Selector {
                 })
             }
 
-            Self::Attr(expr, attributes) => {
+            // The attributes of multiple parents apply to any of them.
+            Self::Attr(expr, attributes) if matches!(**expr, Self::Multi(_)) => {
                 expr.apply_any(entries).and_then(|(bound, es)| {
                     if !es.is_empty() {
                         if es
                             .iter()
-                            .any(|e| attributes.iter().all(|arg| e.has(arg.as_ref())))
+                            .any(|e| attributes.iter().all(|attr| attr.matches(e)))
                         {
                             Some((bound, es))
                         } else {
@@ -249,6 +293,11 @@ impl Selector {
                 })
             }
 
+            Self::Attr(_, _) => entries
+                .iter()
+                .filter_map(|e| self.apply(e).map(|r| (r, vec![e])))
+                .next(),
+
             Self::Alt(exprs) => {
                 for expr in exprs {
                     let applied = expr.apply_any(entries);
@@ -261,28 +310,7 @@ impl Selector {
 
             Self::Multi(exprs) => {
                 let mut consumed = vec![];
-                let mut res = HashMap::new();
-
-                for spec in exprs {
-                    let mut item = None;
-                    for (i, e) in entries.iter().enumerate() {
-                        if consumed.contains(&i) {
-                            continue;
-                        }
-
-                        item = spec.apply(e).map(|v| (i, v));
-                        if item.is_some() {
-                            break;
-                        }
-                    }
-
-                    if let Some((index, bound)) = item {
-                        res.extend(bound);
-                        consumed.push(index);
-                    } else {
-                        return None;
-                    }
-                }
+                let res = Self::apply_distinct(exprs, entries, &mut consumed)?;
 
                 let mut es = vec![];
                 for i in consumed.into_iter() {
@@ -298,6 +326,137 @@ impl Selector {
                 .next(),
         }
     }
+
+    /// Match each selector with a different entry. If the first entry that
+    /// matches a selector is needed by a later one, other assignments are
+    /// tried.
+    fn apply_distinct<'s>(
+        exprs: &[Self],
+        entries: &'s [Entry],
+        consumed: &mut Vec<usize>,
+    ) -> Option<HashMap<String, &'s Entry>> {
+        let Some((first, rest)) = exprs.split_first() else {
+            return Some(HashMap::new());
+        };
+
+        for (i, e) in entries.iter().enumerate() {
+            if consumed.contains(&i) {
+                continue;
+            }
+
+            let Some(mut bound) = first.apply(e) else { continue };
+            consumed.push(i);
+            if let Some(others) = Self::apply_distinct(rest, entries, consumed) {
+                bound.extend(others);
+                return Some(bound);
+            }
+            consumed.pop();
+        }
+
+        None
+    }
+}
+
+/// A condition on a field in an attribute selector.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Attribute {
+    /// The field is set: `doi`.
+    Set(String),
+    /// The value of the field compares to a literal: `date >= 2000`.
+    Compare(String, AttributeOp, AttributeValue),
+}
+
+impl Attribute {
+    /// Checks if the provided [`Entry`] satisfies the condition.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Self::Set(field) => {
+                entry.has(field) || entry.keyed_serial_number(field).is_some()
+            }
+            Self::Compare(field, op, value) => {
+                let Some(field) = entry.field_value(field) else { return false };
+                let ordering = match value {
+                    AttributeValue::Number(n) => leading_number(&field).map(|f| f.cmp(n)),
+                    AttributeValue::String(s) => {
+                        scalar_text(&field).map(|f| f.as_str().cmp(s))
+                    }
+                };
+                ordering.is_some_and(|ordering| op.accepts(ordering))
+            }
+        }
+    }
+}
+
+impl From<&str> for Attribute {
+    fn from(field: &str) -> Self {
+        Self::Set(field.into())
+    }
+}
+
+/// A comparison operator in an attribute selector.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AttributeOp {
+    /// Equality: `=`.
+    Eq,
+    /// Inequality: `!=`.
+    Ne,
+    /// Less than: `<`.
+    Lt,
+    /// Less than or equal: `<=`.
+    Le,
+    /// Greater than: `>`.
+    Gt,
+    /// Greater than or equal: `>=`.
+    Ge,
+}
+
+impl AttributeOp {
+    /// Whether the operator holds for a field that compares to the literal
+    /// with the given ordering.
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// The literal on the right side of an attribute comparison.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AttributeValue {
+    /// An integer: `2000`.
+    Number(i64),
+    /// A text: `"de"`.
+    String(String),
+}
+
+/// The number a field value starts with, like the year of a date.
+fn leading_number(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => {
+            let digits = s.strip_prefix('-').unwrap_or(s);
+            let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+            let n: i64 = digits[..end].parse().ok()?;
+            Some(if digits.len() < s.len() { -n } else { n })
+        }
+        _ => None,
+    }
+}
+
+/// The text of a scalar field value.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
 }
 
 /// A specialized result type with a selector error.
@@ -307,7 +466,7 @@ type SelectorResult<T> = Result<T, SelectorError>;
 #[derive(Debug, Clone, Eq, PartialEq, Error)]
 pub enum SelectorError {
     /// A value (entry type or subexpression) was expected.
-    #[error("missing value")]
+    #[error("expected entry type or `*`")]
     MissingValue,
     /// An attribute list contained something expected.
     #[error("malformed attribute")]
@@ -321,4 +480,31 @@ pub enum SelectorError {
     /// The entry type is not known.
     #[error("unknown entry type: `{0}`")]
     UnknownEntryType(String),
+    /// The selector continues after a complete expression.
+    #[error("unexpected `{0}`")]
+    UnexpectedToken(String),
+}
+
+/// An error in the source of a selector, as reported by
+/// [`Selector::parse_with_diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorDiagnostic {
+    /// The error.
+    pub error: SelectorError,
+    /// The byte range in the source the error applies to.
+    pub span: Range<usize>,
+    /// The line and column at which the span starts.
+    pub location: Location,
+    /// A suggestion for fixing the error.
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for SelectorDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.error, self.location)?;
+        if let Some(hint) = &self.hint {
+            write!(f, " ({hint})")?;
+        }
+        Ok(())
+    }
 }
@@ -1,32 +1,108 @@
+use std::ops::Range;
 use std::str::FromStr;
 
-use super::{Selector, SelectorError, SelectorResult};
+use super::{
+    Attribute, AttributeOp, AttributeValue, Selector, SelectorDiagnostic, SelectorError,
+};
+use crate::csl::suggest;
 use crate::types::EntryType;
-
-/// Parse a selector.
-pub fn parse(src: &str) -> SelectorResult<Selector> {
+use crate::Location;
+
+/// The names of all entry types, used to suggest a fix for misspelled types.
+const ENTRY_TYPES: &[&str] = &[
+    "article",
+    "chapter",
+    "entry",
+    "anthos",
+    "report",
+    "thesis",
+    "web",
+    "scene",
+    "artwork",
+    "patent",
+    "case",
+    "newspaper",
+    "legislation",
+    "manuscript",
+    "post",
+    "misc",
+    "performance",
+    "periodical",
+    "proceedings",
+    "book",
+    "blog",
+    "reference",
+    "conference",
+    "anthology",
+    "repository",
+    "thread",
+    "video",
+    "audio",
+    "exhibition",
+    "original",
+    "dataset",
+    "software",
+    "standard",
+    "hearing",
+    "regulation",
+];
+
+/// Parse a selector. Parsing continues after an error where possible so that
+/// all errors are reported at once.
+pub fn parse(src: &str) -> Result<Selector, Vec<SelectorDiagnostic>> {
     let mut p = Parser::new(src);
-    expr(&mut p)
+    let selector = expr(&mut p);
+    if let Some((_, span)) = p.peek_spanned() {
+        p.error(SelectorError::UnexpectedToken(src[span.clone()].into()), span);
+    }
+
+    if p.errors.is_empty() {
+        return Ok(selector);
+    }
+
+    // Errors for unclosed parentheses are only found after their contents.
+    p.errors.sort_by_key(|(_, span)| span.start);
+    Err(p
+        .errors
+        .into_iter()
+        .map(|(error, span)| {
+            let hint = match &error {
+                SelectorError::UnknownEntryType(name) => {
+                    suggest(name, ENTRY_TYPES.iter().copied())
+                        .map(|s| format!("did you mean `{s}`?"))
+                }
+                SelectorError::MissingComma => {
+                    Some("separate attributes with `,`".into())
+                }
+                SelectorError::UnbalancedParens => Some("add a closing `)`".into()),
+                _ => None,
+            };
+            let location = Location::from_offset(src, span.start);
+            SelectorDiagnostic { error, span, location, hint }
+        })
+        .collect())
 }
 
-/// Parse an expression, with optional ancestrage relation.
-fn expr(p: &mut Parser) -> SelectorResult<Selector> {
-    let mut lhs = term(p)?;
-    while p.eat_if(Token::Chevron) {
-        lhs = Selector::Ancestrage(Box::new(lhs), Box::new(term(p)?));
+/// Parse an expression, with optional ancestrage relation. The relation is
+/// right-associative, so that `a > b > c` requires `b` to have a `c` parent.
+fn expr(p: &mut Parser) -> Selector {
+    let lhs = term(p);
+    if p.eat_if(Token::Chevron) {
+        Selector::Ancestrage(Box::new(lhs), Box::new(expr(p)))
+    } else {
+        lhs
     }
-    Ok(lhs)
 }
 
 /// Parse a term, consisting of alternatives or multi-parents.
-fn term(p: &mut Parser) -> SelectorResult<Selector> {
-    let mut lhs = binding(p)?;
+fn term(p: &mut Parser) -> Selector {
+    let mut lhs = binding(p);
 
     loop {
         if p.eat_if(Token::Pipe) {
             let mut alternatives = vec![lhs];
             loop {
-                alternatives.push(binding(p)?);
+                alternatives.push(binding(p));
                 if !p.eat_if(Token::Pipe) {
                     break;
                 }
@@ -35,7 +111,7 @@ fn term(p: &mut Parser) -> SelectorResult<Selector> {
         } else if p.eat_if(Token::Ampersand) {
             let mut parents = vec![lhs];
             loop {
-                parents.push(binding(p)?);
+                parents.push(binding(p));
                 if !p.eat_if(Token::Ampersand) {
                     break;
                 }
@@ -46,15 +122,15 @@ fn term(p: &mut Parser) -> SelectorResult<Selector> {
         }
     }
 
-    Ok(lhs)
+    lhs
 }
 
 /// Parse an expression with an optional binding `a:expr`.
-fn binding(p: &mut Parser) -> SelectorResult<Selector> {
+fn binding(p: &mut Parser) -> Selector {
     let start = p.index();
     if let Some(id) = ident(p) {
         if p.eat_if(Token::Colon) {
-            return Ok(Selector::Binding(id, Box::new(attributes(p)?)));
+            return Selector::Binding(id, Box::new(attributes(p)));
         } else {
             p.jump(start);
         }
@@ -63,58 +139,159 @@ fn binding(p: &mut Parser) -> SelectorResult<Selector> {
 }
 
 /// Parse a factor with optional attributes: `factor[attr, ...]`.
-fn attributes(p: &mut Parser) -> SelectorResult<Selector> {
-    let inner = factor(p)?;
-    if p.eat_if(Token::LeftBracket) {
-        let mut attrs: Vec<String> = vec![];
-        loop {
-            match p.eat() {
-                Some(Token::RightBracket) => break,
-                Some(Token::Ident(id)) => attrs.push(id.into()),
-                _ => return Err(SelectorError::MalformedAttribute),
+fn attributes(p: &mut Parser) -> Selector {
+    let inner = factor(p);
+    if !p.eat_if(Token::LeftBracket) {
+        return inner;
+    }
+
+    let mut attrs: Vec<Attribute> = vec![];
+    loop {
+        match p.peek_spanned() {
+            Some((Token::RightBracket, _)) => {
+                p.eat();
+                break;
+            }
+            Some((Token::Ident(id), _)) => {
+                p.eat();
+                attrs.extend(attribute(p, id));
+            }
+            Some((_, span)) => {
+                p.error(SelectorError::MalformedAttribute, span);
+                while !matches!(p.peek(), None | Some(Token::Comma | Token::RightBracket))
+                {
+                    p.eat();
+                }
+            }
+            None => {
+                p.error(SelectorError::MalformedAttribute, p.end());
+                break;
             }
+        }
 
-            match p.eat() {
-                Some(Token::RightBracket) => break,
-                Some(Token::Comma) => continue,
-                _ => return Err(SelectorError::MissingComma),
+        match p.peek_spanned() {
+            Some((Token::RightBracket, _)) => {
+                p.eat();
+                break;
+            }
+            Some((Token::Comma, _)) => {
+                p.eat();
+            }
+            // Continue as if the comma was there.
+            Some((_, span)) => p.error(SelectorError::MissingComma, span),
+            None => {
+                p.error(SelectorError::MissingComma, p.end());
+                break;
             }
         }
-        Ok(Selector::Attr(Box::new(inner), attrs))
-    } else {
-        Ok(inner)
     }
+
+    Selector::Attr(Box::new(inner), attrs)
+}
+
+/// Parse the optional comparison after a field name: `date >= 2000`.
+fn attribute(p: &mut Parser, field: &str) -> Option<Attribute> {
+    let op = match p.peek() {
+        Some(Token::Equals) => AttributeOp::Eq,
+        Some(Token::NotEquals) => AttributeOp::Ne,
+        Some(Token::Less) => AttributeOp::Lt,
+        Some(Token::LessEquals) => AttributeOp::Le,
+        Some(Token::Chevron) => AttributeOp::Gt,
+        Some(Token::GreaterEquals) => AttributeOp::Ge,
+        _ => return Some(Attribute::Set(field.into())),
+    };
+    p.eat();
+
+    let value = match p.peek_spanned() {
+        Some((Token::Number(n), span)) => {
+            p.eat();
+            let Ok(n) = n.parse() else {
+                p.error(SelectorError::MalformedAttribute, span);
+                return None;
+            };
+            AttributeValue::Number(n)
+        }
+        Some((Token::Ident(id), _)) => {
+            p.eat();
+            id.parse()
+                .map(AttributeValue::Number)
+                .unwrap_or_else(|_| AttributeValue::String(id.into()))
+        }
+        Some((Token::Str(s), _)) => {
+            p.eat();
+            AttributeValue::String(s.into())
+        }
+        Some((token, span)) => {
+            if !matches!(token, Token::Comma | Token::RightBracket) {
+                p.eat();
+            }
+            p.error(SelectorError::MalformedAttribute, span);
+            return None;
+        }
+        None => {
+            p.error(SelectorError::MalformedAttribute, p.end());
+            return None;
+        }
+    };
+
+    Some(Attribute::Compare(field.into(), op, value))
 }
 
 /// Parse a value with optional negation: `!value`.
-fn factor(p: &mut Parser) -> SelectorResult<Selector> {
+fn factor(p: &mut Parser) -> Selector {
     if p.eat_if(Token::ExclamationMark) {
-        Ok(Selector::Neg(Box::new(factor(p)?)))
+        Selector::Neg(Box::new(factor(p)))
     } else {
         value(p)
     }
 }
 
-/// Parse a parenthesized or atomic value: `book`, `(expr)`.
-fn value(p: &mut Parser) -> SelectorResult<Selector> {
-    match p.eat() {
-        Some(Token::LeftParen) => {
-            let expr = expr(p)?;
+/// Parse a parenthesized or atomic value: `book`, `(expr)`. After an error,
+/// this returns a wildcard so that parsing can continue.
+fn value(p: &mut Parser) -> Selector {
+    let Some((token, span)) = p.peek_spanned() else {
+        p.error(SelectorError::MissingValue, p.end());
+        return Selector::Wildcard;
+    };
+
+    match token {
+        Token::LeftParen => {
+            p.eat();
+            let expr = expr(p);
             if !p.eat_if(Token::RightParen) {
-                return Err(SelectorError::UnbalancedParens);
+                p.error(SelectorError::UnbalancedParens, span);
             }
-            Ok(expr)
+            expr
         }
-        Some(Token::Star) => Ok(Selector::Wildcard),
-        Some(Token::Ident(id)) => {
+        Token::Star => {
+            p.eat();
+            Selector::Wildcard
+        }
+        Token::Ident(id) => {
+            p.eat();
             let lower = id.to_lowercase();
             if let Ok(kind) = EntryType::from_str(&lower) {
-                Ok(Selector::Entry(kind))
+                Selector::Entry(kind)
             } else {
-                Err(SelectorError::UnknownEntryType(lower))
+                p.error(SelectorError::UnknownEntryType(lower), span);
+                Selector::Wildcard
             }
         }
-        _ => Err(SelectorError::MissingValue),
+        _ => {
+            // Leave operators and closing delimiters to the enclosing rule.
+            if !matches!(
+                token,
+                Token::Pipe
+                    | Token::Ampersand
+                    | Token::Chevron
+                    | Token::RightParen
+                    | Token::RightBracket
+            ) {
+                p.eat();
+            }
+            p.error(SelectorError::MissingValue, span);
+            Selector::Wildcard
+        }
     }
 }
 
@@ -130,12 +307,25 @@ fn ident(p: &mut Parser) -> Option<String> {
 #[derive(Debug)]
 struct Parser<'s> {
     tokens: Tokens<'s>,
+    errors: Vec<(SelectorError, Range<usize>)>,
 }
 
 impl<'s> Parser<'s> {
     /// Create a new parser for the source string.
     fn new(src: &'s str) -> Self {
-        Self { tokens: Tokens::new(src) }
+        Self { tokens: Tokens::new(src), errors: vec![] }
+    }
+
+    /// Record an error, unless it overlaps or directly follows the previous one
+    /// and is thus likely a consequence of it.
+    fn error(&mut self, error: SelectorError, span: Range<usize>) {
+        if let Some((_, last)) = self.errors.last() {
+            let overlaps = span.start < last.end && last.start < span.end;
+            if overlaps || span == last.clone() || span == (last.end..last.end) {
+                return;
+            }
+        }
+        self.errors.push((error, span));
     }
 
     /// Consume the next token.
@@ -166,6 +356,17 @@ impl<'s> Parser<'s> {
         self.tokens.clone().next()
     }
 
+    /// Peek at the next token and its span without consuming it.
+    fn peek_spanned(&mut self) -> Option<(Token<'s>, Range<usize>)> {
+        self.tokens.clone().next_spanned()
+    }
+
+    /// The empty span at the end of the source string.
+    fn end(&self) -> Range<usize> {
+        let len = self.tokens.s.string().len();
+        len..len
+    }
+
     /// The position in the string at which the last token ends and next token
     /// will start.
     fn index(&self) -> usize {
@@ -211,8 +412,23 @@ enum Token<'s> {
     /// A exclamation mark in a negation: `!`.
     ExclamationMark,
 
+    /// An equals sign in an attribute comparison: `=`.
+    Equals,
+    /// An inequality sign in an attribute comparison: `!=`.
+    NotEquals,
+    /// A less-than sign in an attribute comparison: `<`.
+    Less,
+    /// A less-than-or-equal sign in an attribute comparison: `<=`.
+    LessEquals,
+    /// A greater-than-or-equal sign in an attribute comparison: `>=`.
+    GreaterEquals,
+
     /// An identifier in a function header: `Periodical`.
     Ident(&'s str),
+    /// An integer in an attribute comparison: `2000`.
+    Number(&'s str),
+    /// A quoted text in an attribute comparison: `"de"`.
+    Str(&'s str),
 
     /// Things that are not valid in the context they appeared in.
     Invalid,
@@ -223,6 +439,14 @@ impl<'s> Tokens<'s> {
     fn new(src: &'s str) -> Self {
         Self { s: unscanny::Scanner::new(src) }
     }
+
+    /// Parse the next token in the source code along with its byte range.
+    fn next_spanned(&mut self) -> Option<(Token<'s>, Range<usize>)> {
+        self.s.eat_whitespace();
+        let start = self.s.cursor();
+        let token = self.next()?;
+        Some((token, start..self.s.cursor()))
+    }
 }
 
 impl<'s> Iterator for Tokens<'s> {
@@ -253,9 +477,29 @@ impl<'s> Iterator for Tokens<'s> {
             // Misc.
             '*' => Token::Star,
             ':' => Token::Colon,
+            '>' if self.s.eat_if('=') => Token::GreaterEquals,
             '>' => Token::Chevron,
+            '!' if self.s.eat_if('=') => Token::NotEquals,
             '!' => Token::ExclamationMark,
 
+            // Comparisons.
+            '=' => Token::Equals,
+            '<' if self.s.eat_if('=') => Token::LessEquals,
+            '<' => Token::Less,
+
+            // Literals.
+            '"' => {
+                let text = self.s.eat_until('"');
+                if !self.s.eat_if('"') {
+                    return Some(Token::Invalid);
+                }
+                Token::Str(text)
+            }
+            c if c.is_ascii_digit() => {
+                self.s.eat_while(|c: char| c.is_ascii_digit());
+                Token::Number(self.s.from(start))
+            }
+
             // Identifiers.
             c if is_id_start(c) => {
                 let mut end = self.s.cursor();
@@ -321,6 +565,10 @@ mod tests {
         t!("anthology[editor,date]" => Id("anthology"), LB, Id("editor"), Comma, Id("date"), RB);
         t!("alpha:!* > (a & b)"     => Id("alpha"), Colon, ExclamationMark, Star, Chevron,
                                        LeftParen, Id("a"), A, Id("b"), R);
+        t!("*[date>=2000,lang!=\"de\"]" => Star, LB, Id("date"), GreaterEquals, Number("2000"),
+                                       Comma, Id("lang"), NotEquals, Str("de"), RB);
+        t!("web[volume < 3 <= 4 = 5]" => Id("web"), LB, Id("volume"), Less, Number("3"),
+                                       LessEquals, Number("4"), Equals, Number("5"), RB);
     }
 
     #[test]
@@ -339,6 +587,8 @@ mod tests {
         t!("anthology[title, author]"     => select!(Anthology["title", "author"]));
         t!("article > proceedings"        => select!(Article > Proceedings));
         t!("artwork | audio > exhibition" => select!((Artwork | Audio) > Exhibition));
+        t!("chapter > book > web"         => select!(Chapter > (Book > Web)));
+        t!("(chapter > book) > web"       => select!((Chapter > Book) > Web));
 
         t!("article > (book & (repository | anthology > blog) & web[url, title])"
             => select!(Article > (Book & ((Repository | Anthology) > Blog) & (Web["url", "title"]))));
@@ -350,12 +600,118 @@ mod tests {
             => select!(("a":(!Audio)) > (((Blog["author"]) & Web) | (Video > Web))));
     }
 
+    #[test]
+    fn test_parse_comparisons() {
+        use AttributeOp::*;
+        use AttributeValue::Number as N;
+        use AttributeValue::String as S;
+
+        macro_rules! t {
+            ($src:expr => $inner:expr, [$($attr:expr),*]) => {
+                check(
+                    $src,
+                    Selector::Attr(Box::new($inner), vec![$($attr),*]),
+                    Selector::parse($src).unwrap(),
+                );
+            };
+        }
+
+        let cmp = |field: &str, op, value| Attribute::Compare(field.into(), op, value);
+
+        t!("*[date >= 2000]"           => select!(*), [cmp("date", Ge, N(2000))]);
+        t!("*[date < -44]"             => select!(*), [cmp("date", Lt, N(-44))]);
+        t!("*[language = \"de\"]"      => select!(*), [cmp("language", Eq, S("de".into()))]);
+        t!("*[language != de]"         => select!(*), [cmp("language", Ne, S("de".into()))]);
+        t!("book[volume > 3, doi]"     => select!(Book),
+                                          [cmp("volume", Gt, N(3)), "doi".into()]);
+        t!("web[edition <= 2]"         => select!(Web), [cmp("edition", Le, N(2))]);
+
+        // Negation binds tighter than attributes.
+        t!("!book[date > 1900]"        => select!(!Book), [cmp("date", Gt, N(1900))]);
+
+        // Attributes bind tighter than bindings, alternatives, and ancestrage.
+        check(
+            "a:article[doi] > b:periodical",
+            select!(("a":(Article["doi"])) > ("b":Periodical)),
+            Selector::parse("a:article[doi] > b:periodical").unwrap(),
+        );
+        check(
+            "blog | web[date > 2010] > periodical",
+            Selector::Ancestrage(
+                Box::new(Selector::Alt(vec![
+                    select!(Blog),
+                    Selector::Attr(
+                        Box::new(select!(Web)),
+                        vec![cmp("date", Gt, N(2010))],
+                    ),
+                ])),
+                Box::new(select!(Periodical)),
+            ),
+            Selector::parse("blog | web[date > 2010] > periodical").unwrap(),
+        );
+    }
+
     #[test]
     fn test_parse_errors() {
-        assert_eq!(parse("()"), Err(SelectorError::MissingValue));
-        assert_eq!(parse("book[*]"), Err(SelectorError::MalformedAttribute));
-        assert_eq!(parse("book[date url]"), Err(SelectorError::MissingComma));
-        assert_eq!(parse("(book | blog"), Err(SelectorError::UnbalancedParens));
-        assert_eq!(parse("a"), Err(SelectorError::UnknownEntryType("a".into())));
+        assert_eq!(Selector::parse("()"), Err(SelectorError::MissingValue));
+        assert_eq!(Selector::parse("book[*]"), Err(SelectorError::MalformedAttribute));
+        assert_eq!(Selector::parse("book[date url]"), Err(SelectorError::MissingComma));
+        assert_eq!(Selector::parse("book[date 2000]"), Err(SelectorError::MissingComma));
+        assert_eq!(
+            Selector::parse("book[date >=]"),
+            Err(SelectorError::MalformedAttribute)
+        );
+        assert_eq!(
+            Selector::parse("book[date > *]"),
+            Err(SelectorError::MalformedAttribute)
+        );
+        assert_eq!(
+            Selector::parse("book[title = \"a]"),
+            Err(SelectorError::MalformedAttribute)
+        );
+        assert_eq!(Selector::parse("(book | blog"), Err(SelectorError::UnbalancedParens));
+        assert_eq!(
+            Selector::parse("a"),
+            Err(SelectorError::UnknownEntryType("a".into()))
+        );
+        assert_eq!(
+            Selector::parse("book blog"),
+            Err(SelectorError::UnexpectedToken("blog".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let errors = |src: &str| {
+            parse(src)
+                .unwrap_err()
+                .into_iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(errors("article > "), ["expected entry type or `*` at 1:11"]);
+        assert_eq!(
+            errors("artcle > (book | anthologie)"),
+            [
+                "unknown entry type: `artcle` at 1:1 (did you mean `article`?)",
+                "unknown entry type: `anthologie` at 1:18 (did you mean `anthology`?)",
+            ]
+        );
+        assert_eq!(
+            errors("(book[date url, *] | | blog"),
+            [
+                "unbalanced parentheses at 1:1 (add a closing `)`)",
+                "missing comma at 1:12 (separate attributes with `,`)",
+                "malformed attribute at 1:17",
+                "expected entry type or `*` at 1:22",
+            ]
+        );
+        assert_eq!(errors("book[title = \"a"), ["malformed attribute at 1:14"]);
+        assert_eq!(errors("book > )"), ["expected entry type or `*` at 1:8"]);
+
+        let diagnostic = &parse("*\n  > bok").unwrap_err()[0];
+        assert_eq!(diagnostic.span, 6..9);
+        assert_eq!(diagnostic.location, Location { line: 2, column: 5 });
     }
 }
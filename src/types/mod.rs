@@ -248,6 +248,23 @@ pub enum EntryType {
     /// A prior publication of the same item.
     #[serde(alias = "Original")]
     Original,
+    /// A collection of data, like a statistical survey or the measurements of
+    /// an experiment, possibly with multiple versions.
+    #[serde(alias = "Dataset")]
+    Dataset,
+    /// A computer program, app, or library. Use a `Repository` parent for the
+    /// storage of its source code.
+    #[serde(alias = "Software")]
+    Software,
+    /// A technical standard or specification published by a standards body.
+    #[serde(alias = "Standard")]
+    Standard,
+    /// A formal hearing or testimony before a legislative or other body.
+    #[serde(alias = "Hearing")]
+    Hearing,
+    /// An executive or administrative regulation.
+    #[serde(alias = "Regulation")]
+    Regulation,
 }
 
 impl EntryType {
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use unicode_segmentation::UnicodeSegmentation;
 
+use crate::lang::collation::Collator;
 use crate::lang::en::ARTICLES;
 use crate::lang::is_cjk;
 use crate::lang::name::NAME_PARTICLES;
@@ -79,6 +80,8 @@ pub enum PersonRole {
     Illustrator,
     /// Provided narration or voice-over for the cited item.
     Narrator,
+    /// Conducted the interview that is the cited item.
+    Interviewer,
 
     /// Various other roles described by the contained string.
     #[serde(skip)]
@@ -93,12 +96,18 @@ derive_or_from_str! {
         pub name: String,
         /// The given name / forename.
         pub given_name: Option<String>,
-        /// A prefix of the family name such as 'van' or 'de'.
+        /// A dropping particle such as 'de' in 'Charles de Gaulle' that is
+        /// placed after the given name when the name is inverted. Particles
+        /// like 'van' in 'van Beethoven' that belong to the family name are
+        /// part of `name` instead.
         pub prefix: Option<String>,
         /// A suffix of the family name such as 'Jr.' or 'IV'.
         pub suffix: Option<String>,
         /// Another name (often user name) the person might be known under.
         pub alias: Option<String>,
+        /// The name transliterated to the Latin script, for example
+        /// `Ivanov, Ivan` for `Иванов, Иван`.
+        pub transliterated: Option<Box<Person>>,
     }
 }
 
@@ -107,10 +116,35 @@ impl Serialize for Person {
     where
         S: serde::Serializer,
     {
-        // Aliases are not represented in the string, prefixes can create
-        // ambiguity.
-        if self.alias.is_none() && self.prefix.is_none() {
-            serializer.serialize_str(&self.name_first(false, false))
+        // Aliases and transliterations are not represented in the string,
+        // prefixes can create ambiguity. A suffix is written between the family
+        // and the given name like in BibTeX and needs a given name to be
+        // recognized.
+        if self.alias.is_none()
+            && self.transliterated.is_none()
+            && self.prefix.is_none()
+            && (self.suffix.is_none() || self.given_name.is_some())
+        {
+            // Institutional names that would be split when they are parsed
+            // again are protected with braces.
+            if self.is_institutional()
+                && (self.name.contains(',')
+                    || Self::from_strings(vec![self.name.as_str()]).ok().as_ref()
+                        != Some(self))
+            {
+                return serializer.serialize_str(&format!("{{{}}}", self.name));
+            }
+
+            let mut res = self.name.clone();
+            if let Some(suffix) = &self.suffix {
+                res += ", ";
+                res += suffix;
+            }
+            if let Some(given_name) = &self.given_name {
+                res += ", ";
+                res += given_name;
+            }
+            serializer.serialize_str(&res)
         } else {
             let entries = [
                 ("name", Some(&self.name)),
@@ -120,7 +154,8 @@ impl Serialize for Person {
                 ("alias", self.alias.as_ref()),
             ];
 
-            let map_len = entries.iter().filter(|(_, v)| v.is_some()).count();
+            let map_len = entries.iter().filter(|(_, v)| v.is_some()).count()
+                + usize::from(self.transliterated.is_some());
 
             let mut map = serializer.serialize_map(Some(map_len))?;
 
@@ -130,6 +165,10 @@ impl Serialize for Person {
                 }
             }
 
+            if let Some(transliterated) = &self.transliterated {
+                map.serialize_entry("transliterated", transliterated)?;
+            }
+
             map.end()
         }
     }
@@ -151,18 +190,23 @@ pub enum PersonError {
 
 impl Person {
     /// This function expects a list of strings with its length between one and
-    /// three. The first part will be interpreted as the `<prefix> <Name>`, the
-    /// second part as the given name and the third part as the suffix.
+    /// three. Like in BibTeX, the first part will be interpreted as the
+    /// family name, the last part as the given name and a middle part as
+    /// the suffix, for example `"King, Jr., Martin Luther"`. If the middle
+    /// part looks like a given name and the last part like a suffix, they are
+    /// swapped to also accept the `"King, Martin Luther, Jr."` order. With two
+    /// parts, a trailing suffix like `Jr.` or `III` in the first part is split
+    /// off from the family name.
     ///
-    /// The prefix and name are separated just like in BiBTeX, as described
-    /// [Nicolas Markey describes in "Tame the BeaST"][taming], p. 24. The gist
-    /// is that the given name will start at the first word with a capital
-    /// letter, if there are any such words.
+    /// Particles like `van den` or `de la` are kept as part of the family
+    /// name. They are treated as non-dropping particles (see
+    /// [`Self::name_particle`]), so the `demote-non-dropping-particle` option
+    /// of the CSL style decides whether they are displayed and sorted with
+    /// the family name. Dropping particles can only be set explicitly with the
+    /// `prefix` field.
     ///
     /// The call site of this function in the library obtains the slice by
     /// calling `split(",")` on a string like `"Des Egdens, Britta"`.
-    ///
-    /// [taming]: https://ftp.rrze.uni-erlangen.de/ctan/info/bibtex/tamethebeast/ttb_en.pdf
     pub fn from_strings(mut parts: Vec<&str>) -> Result<Self, PersonError> {
         if parts.is_empty() {
             return Err(PersonError::Empty);
@@ -174,61 +218,34 @@ impl Person {
             *part = part.trim();
         }
 
-        let last_pre = parts[0];
+        if parts.len() == 3 && is_suffix(parts[2]) && !is_suffix(parts[1]) {
+            parts.swap(1, 2);
+        }
+
+        let mut name = parts[0];
         let given_name = (parts.len() > 1)
             .then(|| parts.last().map(|last| last.to_string()))
             .flatten();
 
-        let suffix = if parts.len() > 2 { Some(parts[1].to_string()) } else { None };
-
-        let mut word_start = true;
-        let mut last_lower_case_end: i32 = -1;
-        let mut is_lowercase = false;
-        let mut last_word_start = 0;
-        let mut has_seen_uppercase_words = false;
-
-        for (index, c) in last_pre.chars().enumerate() {
-            if c.is_whitespace() {
-                word_start = true;
-                continue;
-            }
-
-            if word_start {
-                last_word_start = index;
-
-                if c.is_lowercase() {
-                    is_lowercase = true;
-                } else {
-                    is_lowercase = false;
-                    has_seen_uppercase_words = true;
+        let mut suffix =
+            (parts.len() > 2 && !parts[1].is_empty()).then(|| parts[1].to_string());
+        if parts.len() == 2 {
+            if let Some((family, last)) = name.rsplit_once(' ') {
+                if is_suffix(last) && !family.trim().is_empty() {
+                    name = family.trim_end();
+                    suffix = Some(last.to_string());
                 }
             }
-
-            if is_lowercase {
-                last_lower_case_end = index as i32;
-            }
-
-            word_start = false;
-        }
-
-        let mut name = String::new();
-        let mut prefix = String::new();
-        for (index, c) in last_pre.chars().enumerate() {
-            if (index as i32 <= last_lower_case_end && has_seen_uppercase_words)
-                || (!has_seen_uppercase_words && index < last_word_start)
-            {
-                prefix.push(c);
-            } else if has_seen_uppercase_words || index >= last_word_start {
-                name.push(c);
-            }
-        }
-
-        let prefix = if prefix.is_empty() { None } else { Some(prefix) };
-        if prefix.is_some() {
-            name = name.trim_start().to_string();
         }
 
-        Ok(Person { name, given_name, prefix, suffix, alias: None })
+        Ok(Person {
+            name: name.to_string(),
+            given_name,
+            prefix: None,
+            suffix,
+            alias: None,
+            transliterated: None,
+        })
     }
 
     /// Formats the given name into initials.
@@ -252,42 +269,41 @@ impl Person {
             return Ok(());
         };
 
-        let mut collect = true;
-        let mut non_empty = false;
-
-        for (_, gr) in gn.grapheme_indices(true) {
-            if let Some(c) = gr.chars().next() {
-                if c.is_whitespace() || c == '-' {
-                    if !collect {
-                        let hyphenate = with_hyphen && c == '-';
-                        if let Some(delimiter) = delimiter {
-                            // Use the given delimiter, including any spaces at
-                            // its end if there was a whitespace, but not if we
-                            // should add a hyphen in a compound given name.
-                            buf.write_str(if hyphenate {
-                                delimiter.trim_end()
-                            } else {
-                                delimiter
-                            })?;
-                        }
-
-                        collect = true;
-                        if hyphenate {
-                            buf.write_char('-')?;
-                        }
-                    }
+        let mut first = true;
+        // Existing initials like `J.R.R.` are split at their periods so that
+        // each letter yields its own initial.
+        for word in gn.split(|c: char| c.is_whitespace() || c == '.') {
+            let mut first_part = true;
+            for part in word.split('-') {
+                let Some(initial) = first_letter(part) else {
                     continue;
+                };
+
+                if !first {
+                    let hyphenate = with_hyphen && !first_part;
+                    if let Some(delimiter) = delimiter {
+                        // Use the given delimiter, including any spaces at
+                        // its end if there was a whitespace, but not if we
+                        // should add a hyphen in a compound given name.
+                        buf.write_str(if hyphenate {
+                            delimiter.trim_end()
+                        } else {
+                            delimiter
+                        })?;
+                    }
+
+                    if hyphenate {
+                        buf.write_char('-')?;
+                    }
                 }
-            }
 
-            if collect {
-                buf.write_str(gr)?;
-                collect = false;
-                non_empty = true;
+                buf.write_str(initial)?;
+                first = false;
+                first_part = false;
             }
         }
 
-        if non_empty && !collect {
+        if !first {
             if let Some(delim) = delimiter {
                 buf.write_str(delim.trim_end())?;
             }
@@ -298,6 +314,12 @@ impl Person {
 
     /// Yields the first name of a person. Will add the delimiter after initials
     /// / single letters.
+    ///
+    /// Names that already consist of initials (like `"J.R.R."` or `"T"`) are
+    /// normalized to use the delimiter while all other names are kept as-is.
+    /// Consecutive initials are separated by the whitespace at the end of the
+    /// delimiter, so `"J R"` yields `"J.R."` for `Some(".")` and `"J. R."` for
+    /// `Some(". ")`.
     pub fn first_name_with_delimiter(
         &self,
         buf: &mut impl std::fmt::Write,
@@ -307,21 +329,39 @@ impl Person {
             return Ok(());
         };
 
-        let mut first = true;
-        for item in name.split(' ') {
-            if !first {
-                buf.write_char(' ')?;
-            }
+        let Some(delimiter) = delimiter else {
+            return buf.write_str(name.trim());
+        };
 
-            buf.write_str(item)?;
+        let trimmed = delimiter.trim_end();
+        let mut prev_initial = None;
+        for item in name.split_whitespace() {
+            let initials = item
+                .split('.')
+                .filter(|part| !part.is_empty())
+                .map(|part| is_initial(part).then_some(part))
+                .collect::<Option<Vec<_>>>()
+                .filter(|initials| !initials.is_empty());
+
+            match (prev_initial, &initials) {
+                (None, _) => {}
+                (Some(true), Some(_)) => buf.write_str(&delimiter[trimmed.len()..])?,
+                _ => buf.write_char(' ')?,
+            }
 
-            if let Some(delimiter) = delimiter {
-                if item.graphemes(true).count() == 1 {
-                    buf.write_str(delimiter)?;
+            if let Some(initials) = &initials {
+                for (i, initial) in initials.iter().enumerate() {
+                    if i > 0 {
+                        buf.write_str(delimiter)?;
+                    }
+                    buf.write_str(initial)?;
                 }
+                buf.write_str(trimmed)?;
+            } else {
+                buf.write_str(item)?;
             }
 
-            first = false;
+            prev_initial = Some(initials.is_some());
         }
 
         Ok(())
@@ -410,7 +450,18 @@ impl Person {
             }
         }
 
-        None
+        // Like BibTeX, treat leading words that start with a lowercase letter
+        // as the particle if they are followed by the actual family name.
+        let mut end = 0;
+        for word in self.name.split(' ') {
+            if !word.chars().next().is_some_and(char::is_lowercase) {
+                break;
+            }
+
+            end += word.len() + 1;
+        }
+
+        (end > 0 && end < self.name.len()).then(|| &self.name[..end - 1])
     }
 
     /// Get the family name without the non-dropping particle.
@@ -434,7 +485,23 @@ impl Person {
         }
     }
 
+    /// Create a literal name, like the name of an organization, that is never
+    /// split into a given and a family name.
+    pub fn literal(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            given_name: None,
+            prefix: None,
+            suffix: None,
+            alias: None,
+            transliterated: None,
+        }
+    }
+
     /// Whether to treat this as an institutional name.
+    ///
+    /// Institutional names are rendered literally without initials and sorted
+    /// without their leading article.
     pub fn is_institutional(&self) -> bool {
         self.given_name.is_none() && self.suffix.is_none() && self.prefix.is_none()
     }
@@ -467,14 +534,18 @@ impl Person {
         other: &Self,
         form: LongShortForm,
         demote_particle: bool,
+        collator: &Collator,
     ) -> std::cmp::Ordering {
         let self_cjk = self.is_cjk();
         let other_cjk = other.is_cjk();
 
-        let name_a = self.name.to_lowercase();
-        let name_b = other.name.to_lowercase();
-        let given_a = self.given_name.as_ref().map(|s| s.to_lowercase());
-        let given_b = other.given_name.as_ref().map(|s| s.to_lowercase());
+        let cmp_opt = |a: Option<&str>, b: Option<&str>| match (a, b) {
+            (Some(a), Some(b)) => collator.compare(a, b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        };
+
+        let given_a = self.given_name.as_deref();
+        let given_b = other.given_name.as_deref();
 
         if self_cjk != other_cjk {
             // Put CJK names last.
@@ -482,35 +553,33 @@ impl Person {
         } else if self_cjk && other_cjk {
             // Apply special CJK rules.
             return match form {
-                LongShortForm::Long => {
-                    name_a.cmp(&name_b).then_with(|| given_a.cmp(&given_b))
-                }
-                LongShortForm::Short => name_a.cmp(&name_b),
+                LongShortForm::Long => collator
+                    .compare(&self.name, &other.name)
+                    .then_with(|| cmp_opt(given_a, given_b)),
+                LongShortForm::Short => collator.compare(&self.name, &other.name),
             };
         }
 
-        let prefix_a = self.prefix.as_ref().map(|s| s.to_lowercase());
-        let prefix_b = other.prefix.as_ref().map(|s| s.to_lowercase());
-        let suffix_a = self.suffix.as_ref().map(|s| s.to_lowercase());
-        let suffix_b = other.suffix.as_ref().map(|s| s.to_lowercase());
+        let suffix_a = self.suffix.as_deref();
+        let suffix_b = other.suffix.as_deref();
 
         if demote_particle {
-            self.name_without_particle()
-                .to_lowercase()
-                .cmp(&other.name_without_particle().to_lowercase())
+            collator
+                .compare(self.name_without_particle(), other.name_without_particle())
                 .then_with(|| {
-                    self.name_particles()
-                        .map(|c| c.to_lowercase())
-                        .cmp(&other.name_particles().map(|c| c.to_lowercase()))
+                    cmp_opt(
+                        self.name_particles().as_deref(),
+                        other.name_particles().as_deref(),
+                    )
                 })
-                .then_with(|| given_a.cmp(&given_b))
-                .then_with(|| suffix_a.cmp(&suffix_b))
+                .then_with(|| cmp_opt(given_a, given_b))
+                .then_with(|| cmp_opt(suffix_a, suffix_b))
         } else {
-            name_a
-                .cmp(&name_b)
-                .then_with(|| prefix_a.cmp(&prefix_b))
-                .then_with(|| given_a.cmp(&given_b))
-                .then_with(|| suffix_a.cmp(&suffix_b))
+            collator
+                .compare(&self.name, &other.name)
+                .then_with(|| cmp_opt(self.prefix.as_deref(), other.prefix.as_deref()))
+                .then_with(|| cmp_opt(given_a, given_b))
+                .then_with(|| cmp_opt(suffix_a, suffix_b))
         }
     }
 }
@@ -531,10 +600,50 @@ impl PartialOrd for Person {
     }
 }
 
+/// Whether a part of a name is a generational suffix like `Jr.` or `III`.
+fn is_suffix(part: &str) -> bool {
+    const SUFFIXES: &[&str] = &["jr", "jr.", "jnr", "jnr.", "sr", "sr.", "snr", "snr."];
+    const NUMERALS: &[&str] = &["II", "III", "IV", "VI", "VII", "VIII", "IX"];
+
+    let part = part.trim();
+    SUFFIXES.iter().any(|s| s.eq_ignore_ascii_case(part)) || NUMERALS.contains(&part)
+}
+
+/// The first letter of a part of a name, skipping leading punctuation like
+/// quotes or parentheses. Returns a whole grapheme so that letters with
+/// combining marks are kept intact.
+fn first_letter(part: &str) -> Option<&str> {
+    part.graphemes(true)
+        .find(|gr| gr.chars().next().is_some_and(char::is_alphanumeric))
+}
+
+/// Whether a part of a name is a single letter, i.e. an initial.
+fn is_initial(part: &str) -> bool {
+    let mut graphemes = part.graphemes(true);
+    graphemes
+        .next()
+        .and_then(|gr| gr.chars().next())
+        .is_some_and(char::is_alphabetic)
+        && graphemes.next().is_none()
+}
+
 impl FromStr for Person {
     type Err = PersonError;
 
+    /// Parses a name like `"Doe, Jane"`. A name in braces like `"{Ministère de
+    /// la Culture}"` is taken literally, just like in BibTeX.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(literal) =
+            trimmed.strip_prefix('{').and_then(|rest| rest.strip_suffix('}'))
+        {
+            let literal = literal.trim();
+            if literal.is_empty() {
+                return Err(PersonError::Empty);
+            }
+            return Ok(Self::literal(literal));
+        }
+
         Self::from_strings(s.split(',').collect())
     }
 }
@@ -604,6 +713,31 @@ mod tests {
         let p = Person::from_strings(vec!["Günther", "Hans-Joseph"]).unwrap();
         p.initials(&mut s, None, false).unwrap();
         assert_eq!("HJ", s);
+
+        let mut s = String::new();
+        let p = Person::from_strings(vec!["Tolkien", "J.R.R."]).unwrap();
+        p.initials(&mut s, Some(". "), true).unwrap();
+        assert_eq!("J. R. R.", s);
+
+        let mut s = String::new();
+        let p = Person::from_strings(vec!["Tolkien", "J. R. R."]).unwrap();
+        p.initials(&mut s, Some("."), true).unwrap();
+        assert_eq!("J.R.R.", s);
+
+        let mut s = String::new();
+        let p = Person::from_strings(vec!["Nasser", "'Abd al-Nasir"]).unwrap();
+        p.initials(&mut s, Some(". "), false).unwrap();
+        assert_eq!("A. a. N.", s);
+
+        let mut s = String::new();
+        let p = Person::from_strings(vec!["Gunnarsdóttir", "Þóra"]).unwrap();
+        p.initials(&mut s, Some(". "), true).unwrap();
+        assert_eq!("Þ.", s);
+
+        let mut s = String::new();
+        let p = Person::from_strings(vec!["Nowak", "Łukasz Ádám"]).unwrap();
+        p.initials(&mut s, Some("."), true).unwrap();
+        assert_eq!("Ł.Á.", s);
     }
 
     #[test]
@@ -618,12 +752,134 @@ mod tests {
         let mut s = String::new();
         p.first_name_with_delimiter(&mut s, Some(".")).unwrap();
         assert_eq!("James T.", s);
+
+        let cases = [
+            ("J.R.R.", Some(". "), "J. R. R."),
+            ("J R R", Some("."), "J.R.R."),
+            ("J. R.", Some(". "), "J. R."),
+            ("John  R", Some(". "), "John R."),
+            ("T Jean-Luc", Some("."), "T. Jean-Luc"),
+            ("Þóra Ł", Some(". "), "Þóra Ł."),
+            ("John  Ronald", None, "John  Ronald"),
+        ];
+
+        for (given, delim, expected) in cases {
+            let p = Person::from_strings(vec!["Doe", given]).unwrap();
+            let mut s = String::new();
+            p.first_name_with_delimiter(&mut s, delim).unwrap();
+            assert_eq!(expected, s, "given name: {given}");
+        }
+    }
+
+    #[test]
+    fn person_tricky_names() {
+        #[rustfmt::skip]
+        let cases = [
+            // (input, family, particle, given, suffix)
+            ("Doe", "Doe", None, None, None),
+            ("Doe, Jane", "Doe", None, Some("Jane"), None),
+            ("King, Jr., Martin Luther", "King", None, Some("Martin Luther"), Some("Jr.")),
+            ("King, Martin Luther, Jr.", "King", None, Some("Martin Luther"), Some("Jr.")),
+            ("King Jr., Martin Luther", "King", None, Some("Martin Luther"), Some("Jr.")),
+            ("Gates, III, William Henry", "Gates", None, Some("William Henry"), Some("III")),
+            ("Gates, William Henry, III", "Gates", None, Some("William Henry"), Some("III")),
+            ("Dumas, Sr., Alexandre", "Dumas", None, Some("Alexandre"), Some("Sr.")),
+            ("Smith, Jr, John", "Smith", None, Some("John"), Some("Jr")),
+            ("Smith, John, jr.", "Smith", None, Some("John"), Some("jr.")),
+            ("de la Cruz, Juan", "de la Cruz", Some("de la"), Some("Juan"), None),
+            ("van den Berg, Hans", "van den Berg", Some("van den"), Some("Hans"), None),
+            ("van der Waals, Johannes Diderik", "van der Waals", Some("van der"), Some("Johannes Diderik"), None),
+            ("von der Leyen, Ursula", "von der Leyen", Some("von der"), Some("Ursula"), None),
+            ("Von Der Leyen, Ursula", "Von Der Leyen", Some("Von Der"), Some("Ursula"), None),
+            ("De la Fontaine, Jean", "De la Fontaine", Some("De la"), Some("Jean"), None),
+            ("van Beethoven, Ludwig", "van Beethoven", Some("van"), Some("Ludwig"), None),
+            ("ter Horst, Anna", "ter Horst", Some("ter"), Some("Anna"), None),
+            ("dela Cruz, Maria", "dela Cruz", Some("dela"), Some("Maria"), None),
+            ("vd Berg, Jan", "vd Berg", Some("vd"), Some("Jan"), None),
+            ("d'Alembert, Jean", "d'Alembert", None, Some("Jean"), None),
+            ("Ford Coppola, Francis", "Ford Coppola", None, Some("Francis"), None),
+            ("van der Berg, Jr., Hans", "van der Berg", Some("van der"), Some("Hans"), Some("Jr.")),
+            ("  Nguyen ,  Thi Minh Khai ", "Nguyen", None, Some("Thi Minh Khai"), None),
+        ];
+
+        for (input, name, particle, given, suffix) in cases {
+            let p: Person = input.parse().unwrap();
+            assert_eq!(p.name, name, "{input}");
+            assert_eq!(p.name_particle(), particle, "{input}");
+            assert_eq!(p.prefix, None, "{input}");
+            assert_eq!(p.given_name.as_deref(), given, "{input}");
+            assert_eq!(p.suffix.as_deref(), suffix, "{input}");
+
+            let yaml = serde_yaml::to_string(&p).unwrap();
+            let back: Person = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(back, p, "{input}");
+        }
+
+        let p: Person = "van den Berg, Hans".parse().unwrap();
+        assert_eq!(p.name_without_particle(), "Berg");
+        assert_eq!(p.given_first(false), "Hans van den Berg");
+
+        let p: Person = "King, Jr., Martin Luther".parse().unwrap();
+        assert_eq!(p.name_first(false, false), "King, Martin Luther, Jr.");
+        assert_eq!(serde_yaml::to_string(&p).unwrap(), "King, Jr., Martin Luther\n");
+    }
+
+    #[test]
+    fn person_literal() {
+        let p: Person = "{Ministère de la Culture}".parse().unwrap();
+        assert_eq!(p, Person::literal("Ministère de la Culture"));
+        assert!(p.is_institutional());
+
+        let p: Person = "Ministère de la Culture".parse().unwrap();
+        assert_eq!(p.name_particle(), None);
+
+        for name in ["Ministère de la Culture", "Ministry of Health, Labour and Welfare"]
+        {
+            let p = Person::literal(name);
+            let yaml = serde_yaml::to_string(&p).unwrap();
+            assert_eq!(serde_yaml::from_str::<Person>(&yaml).unwrap(), p);
+        }
+
+        let p = Person::literal("World Health Organization");
+        assert_eq!(serde_yaml::to_string(&p).unwrap(), "World Health Organization\n");
+        let p: Person = serde_yaml::from_str("name: Ministère de la Culture").unwrap();
+        assert!(p.is_institutional());
+    }
+
+    #[test]
+    fn person_transliterated() {
+        let p: Person = serde_yaml::from_str(
+            "name: Иванов\ngiven-name: Иван\ntransliterated: Ivanov, Ivan",
+        )
+        .unwrap();
+        assert_eq!(p.name, "Иванов");
+        assert_eq!(
+            p.transliterated.as_deref(),
+            Some(&Person::from_strings(vec!["Ivanov", "Ivan"]).unwrap())
+        );
+
+        let yaml = serde_yaml::to_string(&p).unwrap();
+        assert_eq!(serde_yaml::from_str::<Person>(&yaml).unwrap(), p);
+    }
+
+    #[test]
+    fn person_cjk() {
+        assert!(Person::from_strings(vec!["山田", "太郎"]).unwrap().is_cjk());
+        assert!(Person::from_strings(vec!["さとう", "はなこ"]).unwrap().is_cjk());
+        assert!(Person::from_strings(vec!["김", "민준"]).unwrap().is_cjk());
+        assert!(!Person::from_strings(vec!["Doe", "Jane"]).unwrap().is_cjk());
     }
 
     #[test]
     fn person_name_retrieval_order() {
-        let p =
-            Person::from_strings(vec!["van Dissmer", "Jr.", "Courtney Deliah"]).unwrap();
+        let p = Person {
+            name: "Dissmer".into(),
+            given_name: Some("Courtney Deliah".into()),
+            prefix: Some("van".into()),
+            suffix: Some("Jr.".into()),
+            alias: None,
+            transliterated: None,
+        };
         assert_eq!("van Dissmer, Courtney Deliah, Jr.", p.name_first(false, false));
         assert_eq!("Dissmer, Courtney Deliah van, Jr.", p.name_first(false, true));
         assert_eq!("van Dissmer, C. D., Jr.", p.name_first(true, false));
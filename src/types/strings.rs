@@ -12,13 +12,17 @@ use crate::lang::{Case, CaseFolder, SentenceCase, TitleCase};
 
 /// A string for presentation.
 ///
-/// It can contain an optional short version and control case folding.
+/// It can contain an optional short version, a transliteration, and control
+/// case folding.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct FormatString {
     /// The canonical version of the string.
     pub value: ChunkedString,
     /// The short version of the string.
     pub short: Option<Box<ChunkedString>>,
+    /// The string transliterated to the Latin script, for example
+    /// `Voyna i mir` for `Война и мир`.
+    pub transliterated: Option<Box<ChunkedString>>,
 }
 
 impl<'de> Deserialize<'de> for FormatString {
@@ -52,16 +56,24 @@ impl<'de> Deserialize<'de> for FormatString {
                 #[derive(Deserialize)]
                 #[serde(untagged)]
                 enum Inner {
-                    Full { value: ChunkedString, short: Option<ChunkedString> },
+                    Full {
+                        value: ChunkedString,
+                        short: Option<ChunkedString>,
+                        transliterated: Option<ChunkedString>,
+                    },
                     Val(ChunkedString),
                 }
 
                 Deserialize::deserialize(de::value::MapAccessDeserializer::new(map)).map(
                     |inner: Inner| match inner {
-                        Inner::Val(value) => Self::Value { value, short: None },
-                        Inner::Full { value, short } => {
-                            Self::Value { value, short: short.map(Box::new) }
+                        Inner::Val(value) => {
+                            Self::Value { value, short: None, transliterated: None }
                         }
+                        Inner::Full { value, short, transliterated } => Self::Value {
+                            value,
+                            short: short.map(Box::new),
+                            transliterated: transliterated.map(Box::new),
+                        },
                     },
                 )
             }
@@ -76,10 +88,18 @@ impl Serialize for FormatString {
     where
         S: serde::Serializer,
     {
-        if let Some(short) = &self.short {
-            let mut map = serializer.serialize_map(Some(2))?;
+        if self.short.is_some() || self.transliterated.is_some() {
+            let len = 1
+                + usize::from(self.short.is_some())
+                + usize::from(self.transliterated.is_some());
+            let mut map = serializer.serialize_map(Some(len))?;
             map.serialize_entry("value", &self.value)?;
-            map.serialize_entry("short", short)?;
+            if let Some(short) = &self.short {
+                map.serialize_entry("short", short)?;
+            }
+            if let Some(transliterated) = &self.transliterated {
+                map.serialize_entry("transliterated", transliterated)?;
+            }
             map.end()
         } else {
             self.value.serialize(serializer)
@@ -98,6 +118,7 @@ impl FormatString {
         Self {
             value: StringChunk::normal(value).into(),
             short: None,
+            transliterated: None,
         }
     }
 
@@ -106,6 +127,7 @@ impl FormatString {
         Self {
             value: StringChunk::normal(value).into(),
             short: Some(Box::new(StringChunk::normal(short).into())),
+            transliterated: None,
         }
     }
 
@@ -153,9 +175,19 @@ impl From<String> for FormatString {
     }
 }
 
+impl From<&str> for FormatString {
+    fn from(s: &str) -> Self {
+        Self::with_value(s)
+    }
+}
+
 impl From<StringChunk> for FormatString {
     fn from(chunk: StringChunk) -> Self {
-        Self { value: chunk.into(), short: None }
+        Self {
+            value: chunk.into(),
+            short: None,
+            transliterated: None,
+        }
     }
 }
 
@@ -163,7 +195,11 @@ impl FromStr for FormatString {
     type Err = ChunkedStrParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self { value: ChunkedString::from_str(s)?, short: None })
+        Ok(Self {
+            value: ChunkedString::from_str(s)?,
+            short: None,
+            transliterated: None,
+        })
     }
 }
 
@@ -260,12 +296,18 @@ impl ChunkedString {
     /// Appends a string to the last chunk if it has the same kind or starts a
     /// new chunk if the types differ.
     pub fn push_str(&mut self, s: &str, kind: ChunkKind) {
+        self.push_formatted_str(s, kind, ChunkFormat::default());
+    }
+
+    /// Appends a string to the last chunk if it has the same kind and format
+    /// or starts a new chunk if they differ.
+    pub fn push_formatted_str(&mut self, s: &str, kind: ChunkKind, format: ChunkFormat) {
         match self.0.last_mut() {
-            Some(StringChunk { value, kind: target_kind }) if target_kind == &kind => {
-                value.push_str(s);
+            Some(chunk) if chunk.kind == kind && chunk.format == format => {
+                chunk.value.push_str(s);
             }
             _ => {
-                self.0.push(StringChunk::new(s, kind));
+                self.0.push(StringChunk::new(s, kind).with_format(format));
             }
         }
     }
@@ -273,12 +315,18 @@ impl ChunkedString {
     /// Appends a character to the last chunk if it has the same kind or starts
     /// a new chunk if the types differ.
     pub fn push_char(&mut self, c: char, kind: ChunkKind) {
+        self.push_formatted_char(c, kind, ChunkFormat::default());
+    }
+
+    /// Appends a character to the last chunk if it has the same kind and
+    /// format or starts a new chunk if they differ.
+    pub fn push_formatted_char(&mut self, c: char, kind: ChunkKind, format: ChunkFormat) {
         match self.0.last_mut() {
-            Some(StringChunk { value, kind: target_kind }) if target_kind == &kind => {
-                value.push(c);
+            Some(chunk) if chunk.kind == kind && chunk.format == format => {
+                chunk.value.push(c);
             }
             _ => {
-                self.0.push(StringChunk::new(c.to_string(), kind));
+                self.0.push(StringChunk::new(c.to_string(), kind).with_format(format));
             }
         }
     }
@@ -302,11 +350,14 @@ impl ChunkedString {
 
     /// Write the chunked string as a parenthesized string.
     pub fn fmt_serialized(&self, buf: &mut impl fmt::Write) -> fmt::Result {
+        let mut open = ChunkFormat::default();
         for chunk in &self.0 {
+            open.fmt_transition(chunk.format, buf)?;
+            open = chunk.format;
             chunk.fmt_serialized(buf)?;
         }
 
-        Ok(())
+        open.fmt_transition(ChunkFormat::default(), buf)
     }
 
     /// Return the length of the string.
@@ -319,6 +370,11 @@ impl ChunkedString {
         self.0.iter().all(|c| c.is_empty())
     }
 
+    /// Returns whether the string is empty or only contains whitespace.
+    pub fn is_blank(&self) -> bool {
+        self.0.iter().all(|c| c.value.chars().all(char::is_whitespace))
+    }
+
     /// Extend with another `ChunkedString`.
     pub fn extend(&mut self, other: Self) {
         self.0.extend(other.0);
@@ -352,16 +408,13 @@ impl ChunkedString {
         c.finish()
     }
 
-    /// Apply case-folding.
+    /// Apply case-folding. Verbatim and math chunks keep their case.
     pub fn fold_case(&self, c: &mut CaseFolder) {
-        let config = c.case();
         for chunk in &self.0 {
             match chunk.kind {
-                ChunkKind::Normal => c.reconfigure(config),
-                ChunkKind::Verbatim | ChunkKind::Math => c.reconfigure(Case::NoTransform),
-            };
-
-            c.push_str(&chunk.value);
+                ChunkKind::Normal => c.push_str(&chunk.value),
+                ChunkKind::Verbatim | ChunkKind::Math => c.push_verbatim(&chunk.value),
+            }
         }
     }
 }
@@ -371,6 +424,55 @@ fn is_chunk_control(c: char) -> bool {
     c == '\\' || c == '{' || c == '}' || c == '$'
 }
 
+/// A markup tag that sets the [`ChunkFormat`] of the enclosed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkupTag {
+    /// `<sup>`
+    Superscript,
+    /// `<sub>`
+    Subscript,
+    /// `<sc>`
+    SmallCaps,
+}
+
+impl MarkupTag {
+    /// The name of the tag.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Superscript => "sup",
+            Self::Subscript => "sub",
+            Self::SmallCaps => "sc",
+        }
+    }
+
+    /// Parse an opening or closing tag at the start of the string. Returns
+    /// the tag, whether it is a closing tag, and its length.
+    fn parse(s: &str) -> Option<(Self, bool, usize)> {
+        let rest = s.strip_prefix('<')?;
+        let (closing, rest) = match rest.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+
+        [Self::Superscript, Self::Subscript, Self::SmallCaps]
+            .into_iter()
+            .find(|tag| rest.strip_prefix(tag.name()).is_some_and(|r| r.starts_with('>')))
+            .map(|tag| (tag, closing, tag.name().len() + 2 + usize::from(closing)))
+    }
+
+    /// Compute the format of text inside of a stack of open tags.
+    fn format(stack: &[Self]) -> ChunkFormat {
+        ChunkFormat {
+            small_caps: stack.contains(&Self::SmallCaps),
+            position: stack.iter().rev().find_map(|tag| match tag {
+                Self::Superscript => Some(ScriptPosition::Superscript),
+                Self::Subscript => Some(ScriptPosition::Subscript),
+                Self::SmallCaps => None,
+            }),
+        }
+    }
+}
+
 /// Error that occurs when parsing a [`ChunkedString`].
 ///
 /// Occurs when there are unbalanced braces. The field contains the position of
@@ -387,13 +489,40 @@ impl FromStr for ChunkedString {
         let mut chunks = Self::new();
         let mut kind = ChunkKind::Normal;
         let mut depth = 0;
+        let mut tags = vec![];
 
         while let Some(c) = s.eat() {
+            let format = MarkupTag::format(&tags);
             if c == '\\' && s.peek().is_some_and(is_chunk_control) {
-                chunks.push_char(s.eat().unwrap(), kind);
+                chunks.push_formatted_char(s.eat().unwrap(), kind, format);
                 continue;
             }
 
+            if c == '\\'
+                && kind != ChunkKind::Math
+                && MarkupTag::parse(s.after()).is_some()
+            {
+                s.eat();
+                chunks.push_formatted_char('<', kind, format);
+                continue;
+            }
+
+            if c == '<' && kind != ChunkKind::Math {
+                let start = s.cursor() - 1;
+                if let Some((tag, closing, len)) = MarkupTag::parse(&s.string()[start..])
+                {
+                    if !closing {
+                        tags.push(tag);
+                        s.jump(start + len);
+                        continue;
+                    } else if let Some(pos) = tags.iter().rposition(|t| *t == tag) {
+                        tags.remove(pos);
+                        s.jump(start + len);
+                        continue;
+                    }
+                }
+            }
+
             match c {
                 '{' if kind != ChunkKind::Math => {
                     depth += 1;
@@ -416,7 +545,7 @@ impl FromStr for ChunkedString {
                 '$' => {
                     kind = ChunkKind::Math;
                 }
-                _ => chunks.push_char(c, kind),
+                _ => chunks.push_formatted_char(c, kind, format),
             }
         }
 
@@ -465,6 +594,8 @@ pub struct StringChunk {
     pub value: String,
     /// Whether the chunk is subject to case folding or contains math.
     pub kind: ChunkKind,
+    /// Inline formatting of the chunk, like superscript.
+    pub format: ChunkFormat,
 }
 
 /// A chunk of a string.
@@ -486,14 +617,28 @@ impl TryFrom<StringChunk> for FoldableStringChunk {
 
 impl From<FoldableStringChunk> for StringChunk {
     fn from(value: FoldableStringChunk) -> Self {
-        Self { value: value.value, kind: value.kind.into() }
+        Self {
+            value: value.value,
+            kind: value.kind.into(),
+            format: ChunkFormat::default(),
+        }
     }
 }
 
 impl StringChunk {
     /// Creates a new `StrChunk` from a string and a kind.
     pub fn new(value: impl Into<String>, kind: ChunkKind) -> Self {
-        Self { value: value.into(), kind }
+        Self {
+            value: value.into(),
+            kind,
+            format: ChunkFormat::default(),
+        }
+    }
+
+    /// Sets the inline formatting of the chunk.
+    pub fn with_format(mut self, format: ChunkFormat) -> Self {
+        self.format = format;
+        self
     }
 
     /// Creates a new `StrChunk` with the `ChunkKind::Normal` kind.
@@ -548,8 +693,11 @@ impl StringChunk {
     /// Writes the chunk as a parenthesized string.
     fn fmt_serialized(&self, buf: &mut impl fmt::Write) -> fmt::Result {
         fn write_escaped(val: &StringChunk, buf: &mut impl fmt::Write) -> fmt::Result {
-            for char in val.value.chars() {
-                if is_chunk_control(char) {
+            for (i, char) in val.value.char_indices() {
+                if is_chunk_control(char)
+                    || (val.kind != ChunkKind::Math
+                        && MarkupTag::parse(&val.value[i..]).is_some())
+                {
                     buf.write_char('\\')?;
                 }
 
@@ -587,11 +735,89 @@ pub enum ChunkKind {
     Normal,
     /// Case-folding will not be applied.
     Verbatim,
-    /// The contained markup is expected to be evaluated using
-    /// [Typst](https://typst.app/).
+    /// Math markup like `L^2`, written between dollars. It is kept as written
+    /// and neither case-folded nor stripped of periods. Output formats decide
+    /// how to present it, e.g. by evaluating it with [Typst](https://typst.app/).
     Math,
 }
 
+/// Inline formatting of a string chunk.
+///
+/// In the string syntax, the formatting is set with the `<sup>`, `<sub>`, and
+/// `<sc>` tags, for example `H<sub>2</sub>O`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ChunkFormat {
+    /// Whether the chunk is raised or lowered.
+    pub position: Option<ScriptPosition>,
+    /// Whether the chunk is set in small capitals.
+    pub small_caps: bool,
+}
+
+impl ChunkFormat {
+    /// Whether the chunk has no special formatting.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Write the tags that close the formatting of `self` and open that of
+    /// `next`.
+    fn fmt_transition(self, next: Self, buf: &mut impl fmt::Write) -> fmt::Result {
+        // The position tag is innermost, so it must be reopened if the small
+        // caps change.
+        let reopen = self.position != next.position || self.small_caps != next.small_caps;
+
+        if let Some(position) = self.position.filter(|_| reopen) {
+            write!(buf, "</{}>", position.tag().name())?;
+        }
+
+        match (self.small_caps, next.small_caps) {
+            (true, false) => buf.write_str("</sc>")?,
+            (false, true) => buf.write_str("<sc>")?,
+            _ => {}
+        }
+
+        if let Some(position) = next.position.filter(|_| reopen) {
+            write!(buf, "<{}>", position.tag().name())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<ChunkFormat> for citationberg::Formatting {
+    fn from(format: ChunkFormat) -> Self {
+        let mut formatting = Self::default();
+        if format.small_caps {
+            formatting.font_variant = Some(citationberg::FontVariant::SmallCaps);
+        }
+
+        formatting.vertical_align = format.position.map(|position| match position {
+            ScriptPosition::Superscript => citationberg::VerticalAlign::Sup,
+            ScriptPosition::Subscript => citationberg::VerticalAlign::Sub,
+        });
+        formatting
+    }
+}
+
+/// A vertical position of text relative to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptPosition {
+    /// Raised text, like the exponent in `mc²`.
+    Superscript,
+    /// Lowered text, like the index in `H₂O`.
+    Subscript,
+}
+
+impl ScriptPosition {
+    /// The markup tag that sets this position.
+    fn tag(self) -> MarkupTag {
+        match self {
+            Self::Superscript => MarkupTag::Superscript,
+            Self::Subscript => MarkupTag::Subscript,
+        }
+    }
+}
+
 /// The kind of a string chunk for use with the case folder.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum FoldableKind {
@@ -627,6 +853,19 @@ impl From<FoldableKind> for ChunkKind {
 mod tests {
     use super::*;
 
+    #[test]
+    fn format_string_transliterated() {
+        let s: FormatString =
+            serde_yaml::from_str("value: Война и мир\ntransliterated: Voyna i mir")
+                .unwrap();
+        assert_eq!(s.value.to_str(), "Война и мир");
+        assert_eq!(s.transliterated.as_ref().unwrap().to_str(), "Voyna i mir");
+        assert_eq!(s.short, None);
+
+        let yaml = serde_yaml::to_string(&s).unwrap();
+        assert_eq!(serde_yaml::from_str::<FormatString>(&yaml).unwrap(), s);
+    }
+
     #[test]
     fn escaped_brace() {
         let str = "Hello {World\\{}";
@@ -680,4 +919,75 @@ mod tests {
         c.fmt_serialized(&mut buf).unwrap();
         assert_eq!(buf, str2);
     }
+
+    #[test]
+    fn markup_tags() {
+        let sup = ChunkFormat {
+            position: Some(ScriptPosition::Superscript),
+            ..Default::default()
+        };
+        let sub = ChunkFormat {
+            position: Some(ScriptPosition::Subscript),
+            ..Default::default()
+        };
+        let sc = ChunkFormat { small_caps: true, ..Default::default() };
+
+        let str = "H<sub>2</sub>O and E=mc<sup>2</sup>";
+        let c = ChunkedString::from_str(str).unwrap();
+        assert_eq!(
+            c.0,
+            [
+                StringChunk::normal("H"),
+                StringChunk::normal("2").with_format(sub),
+                StringChunk::normal("O and E=mc"),
+                StringChunk::normal("2").with_format(sup),
+            ]
+        );
+        assert_eq!(c.to_string(), "H2O and E=mc2");
+
+        let mut buf = String::new();
+        c.fmt_serialized(&mut buf).unwrap();
+        assert_eq!(buf, str);
+
+        let str = "<sc>Smith {NASA}<sup>a</sup></sc>";
+        let c = ChunkedString::from_str(str).unwrap();
+        assert_eq!(
+            c.0,
+            [
+                StringChunk::normal("Smith ").with_format(sc),
+                StringChunk::verbatim("NASA").with_format(sc),
+                StringChunk::normal("a")
+                    .with_format(ChunkFormat { small_caps: true, ..sup }),
+            ]
+        );
+
+        let mut buf = String::new();
+        c.fmt_serialized(&mut buf).unwrap();
+        assert_eq!(buf, "<sc>Smith {NASA}<sup>a</sup></sc>");
+    }
+
+    #[test]
+    fn literal_angle_brackets() {
+        let c = ChunkedString::from_str("a <b> c < d </sup> e").unwrap();
+        assert_eq!(c.0, [StringChunk::normal("a <b> c < d </sup> e")]);
+
+        let str = "The \\<sup> tag and $x<sup>$";
+        let c = ChunkedString::from_str(str).unwrap();
+        assert_eq!(
+            c.0,
+            [StringChunk::normal("The <sup> tag and "), StringChunk::math("x<sup>")]
+        );
+
+        let mut buf = String::new();
+        c.fmt_serialized(&mut buf).unwrap();
+        assert_eq!(buf, str);
+    }
+
+    #[test]
+    fn blank() {
+        assert!(ChunkedString::new().is_blank());
+        assert!(ChunkedString::from_str(" {  } ").unwrap().is_blank());
+        assert!(!ChunkedString::from_str(" {x} ").unwrap().is_blank());
+        assert!(!ChunkedString::from_str("$ $a").unwrap().is_blank());
+    }
 }
@@ -228,6 +228,40 @@ impl Date {
         Self { year, month: None, day: None, approximate: false }
     }
 
+    /// Get a date from a year, a one-based month, and a one-based day.
+    ///
+    /// The values are not checked. Dates that do not exist are rejected by
+    /// [`EntryBuilder::build`](crate::EntryBuilder::build).
+    pub fn from_ymd(year: i32, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month: Some(month.wrapping_sub(1)),
+            day: Some(day.wrapping_sub(1)),
+            approximate: false,
+        }
+    }
+
+    /// Check that the month and day exist.
+    pub(crate) fn validate(&self) -> Result<(), DateError> {
+        let Some(month) = self.month else {
+            return match self.day {
+                Some(_) => Err(DateError::UnknownFormat),
+                None => Ok(()),
+            };
+        };
+
+        if month > 11 {
+            return Err(DateError::MonthOutOfBounds);
+        }
+
+        match self.day {
+            Some(day) if day >= days_in_month(month, self.year) => {
+                Err(DateError::DayOutOfBounds(day.wrapping_add(1)))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the year as a human-readable gregorian year.
     ///
     /// Non-positive values will be marked with a "BCE" postfix.
@@ -1,4 +1,4 @@
-//! Helpers for serializing and deserializing.
+//! Helpers for serializing and deserializing and for normalizing DOIs.
 
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
 
@@ -185,3 +185,31 @@ where
 {
     <Option<MapOneOrMany<T>>>::deserialize(deserializer).map(|v| v.map(|v| v.into()))
 }
+
+/// Resolver and URI prefixes that are stripped from DOIs.
+const DOI_PREFIXES: &[&str] = &[
+    "https://doi.org/",
+    "http://doi.org/",
+    "https://dx.doi.org/",
+    "http://dx.doi.org/",
+    "doi:",
+];
+
+/// Normalize a DOI by removing resolver and `doi:` prefixes. Returns `None` if
+/// the string is not a DOI.
+pub fn normalize_doi(doi: &str) -> Option<&str> {
+    let mut doi = doi.trim();
+    for prefix in DOI_PREFIXES {
+        if doi
+            .get(..prefix.len())
+            .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+        {
+            doi = doi[prefix.len()..].trim_start();
+            break;
+        }
+    }
+
+    let (registrant, suffix) = doi.split_once('/')?;
+    (registrant.starts_with("10.") && registrant.len() > 3 && !suffix.is_empty())
+        .then_some(doi)
+}
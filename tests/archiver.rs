@@ -11,6 +11,7 @@
 //! files.
 
 use citationberg::{IndependentStyle, LocaleCode, Style};
+use hayagriva::archive::ArchivedStyle;
 use citationberg::{Locale, LocaleFile, XmlError};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
@@ -18,6 +19,7 @@ use std::fmt::Write;
 use std::fs;
 use std::io::{self, BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{fmt, iter};
 
 mod common;
@@ -58,6 +60,38 @@ fn no_dupe_id() {
     assert_eq!(set.len(), STYLE_IDS.len());
 }
 
+#[test]
+fn archived_locale() {
+    let locale = hayagriva::archive::locale("de-DE").unwrap();
+    assert_eq!(locale.lang, Some(LocaleCode("de-DE".into())));
+    assert!(hayagriva::archive::locale("de").is_none());
+}
+
+#[test]
+fn cached_styles() {
+    let apa = ArchivedStyle::AmericanPsychologicalAssociation;
+    let cached = apa.get_cached();
+    assert!(Arc::ptr_eq(&cached, &apa.get_cached()));
+
+    // Retrieving more styles than fit in the cache evicts the least recently
+    // used one.
+    for style in &ArchivedStyle::all()[..hayagriva::archive::STYLE_CACHE_CAPACITY] {
+        if *style != apa {
+            style.get_cached();
+        }
+    }
+    assert!(!Arc::ptr_eq(&cached, &apa.get_cached()));
+}
+
+#[test]
+fn search_styles() {
+    let apa = ArchivedStyle::AmericanPsychologicalAssociation;
+    assert!(ArchivedStyle::search("apa").any(|s| s == apa));
+    assert!(ArchivedStyle::search("Psychological").any(|s| s == apa));
+    assert!(ArchivedStyle::search("styles/apa").any(|s| s == apa));
+    assert_eq!(ArchivedStyle::search("no such style").count(), 0);
+}
+
 /// Download the CSL styles and locales repos.
 fn ensure_repos() -> Result<(), ArchivalError> {
     ensure_repo(CSL_REPO, STYLES_REPO_NAME, "master")?;
@@ -215,6 +249,8 @@ fn write_styles_section(
     writeln!(w, "// This file is generated by tests/generate.rs")?;
     writeln!(w, "// Do not edit by hand!")?;
     writeln!(w)?;
+    writeln!(w, "use std::sync::{{Arc, Mutex, PoisonError}};")?;
+    writeln!(w)?;
     writeln!(w, "use citationberg::{{Locale, Style}};")?;
     writeln!(w, "use serde::de::DeserializeOwned;")?;
     writeln!(w)?;
@@ -277,8 +313,12 @@ fn write_styles_section(
     writeln!(w, "    }}")?;
     writeln!(w)?;
 
-    writeln!(w, "    /// Get the CBOR representation of this style.")?;
-    writeln!(w, "    fn bytes(self) -> &'static [u8] {{")?;
+    writeln!(w, "    /// Get the CBOR representation of this style without deserializing")?;
+    writeln!(w, "    /// it.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// The archive only contains the CBOR encoding of each style, not the XML")?;
+    writeln!(w, "    /// it was created from, so the XML of an archived style is not available.")?;
+    writeln!(w, "    pub fn bytes(self) -> &'static [u8] {{")?;
     writeln!(w, "        match self {{")?;
     for (_, style, _, variant) in items {
         let stripped_id = strip_id(style.info.id.as_str());
@@ -297,6 +337,34 @@ fn write_styles_section(
     writeln!(w, "        from_cbor(self.bytes()).unwrap()")?;
     writeln!(w, "    }}")?;
     writeln!(w)?;
+    writeln!(w, "    /// Retrieve the style from a cache of the most recently used styles.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// Unlike [`get`](Self::get), repeated calls for the same style only")?;
+    writeln!(w, "    /// deserialize it once, as long as it is among the last")?;
+    writeln!(w, "    /// [`STYLE_CACHE_CAPACITY`] styles retrieved this way.")?;
+    writeln!(w, "    pub fn get_cached(self) -> Arc<Style> {{")?;
+    writeln!(w, "        let lock = || STYLE_CACHE.lock().unwrap_or_else(PoisonError::into_inner);")?;
+    writeln!(w, "        let hit = touch_cached_style(&mut lock(), self);")?;
+    writeln!(w, "        if let Some(style) = hit {{")?;
+    writeln!(w, "            return style;")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        // Deserialize without holding the lock so that different styles can")?;
+    writeln!(w, "        // be loaded at the same time. If another thread loaded this style in")?;
+    writeln!(w, "        // the meantime, its copy is returned instead.")?;
+    writeln!(w, "        let style = Arc::new(self.get());")?;
+    writeln!(w, "        let mut cache = lock();")?;
+    writeln!(w, "        if let Some(style) = touch_cached_style(&mut cache, self) {{")?;
+    writeln!(w, "            return style;")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        if cache.len() >= STYLE_CACHE_CAPACITY {{")?;
+    writeln!(w, "            cache.remove(0);")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        cache.push((self, Arc::clone(&style)));")?;
+    writeln!(w, "        style")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
 
     writeln!(w, "    /// Get the style's names in Hayagriva.")?;
     writeln!(w, "    pub fn names(self) -> &'static [&'static str] {{")?;
@@ -330,7 +398,39 @@ fn write_styles_section(
     }
     writeln!(w, "        }}")?;
     writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    /// Find all styles whose full name, CSL ID, or names contain the")?;
+    writeln!(w, "    /// query, ignoring case.")?;
+    writeln!(w, "    pub fn search(query: &str) -> impl Iterator<Item = Self> {{")?;
+    writeln!(w, "        let query = query.to_lowercase();")?;
+    writeln!(w, "        Self::all().iter().copied().filter(move |style| {{")?;
+    writeln!(w, "            style.display_name().to_lowercase().contains(&query)")?;
+    writeln!(w, "                || style.csl_id().to_lowercase().contains(&query)")?;
+    writeln!(w, "                || style.names().iter().any(|name| name.contains(&query))")?;
+    writeln!(w, "        }})")?;
+    writeln!(w, "    }}")?;
     writeln!(w, "}}")?;
+    writeln!(w)?;
+    writeln!(w, "/// How many parsed styles [`ArchivedStyle::get_cached`] keeps.")?;
+    writeln!(w, "pub const STYLE_CACHE_CAPACITY: usize = 8;")?;
+    writeln!(w)?;
+    writeln!(w, "/// The styles parsed by [`ArchivedStyle::get_cached`], the most recently used")?;
+    writeln!(w, "/// last.")?;
+    writeln!(w, "static STYLE_CACHE: Mutex<Vec<(ArchivedStyle, Arc<Style>)>> = Mutex::new(Vec::new());")?;
+    writeln!(w)?;
+    writeln!(w, "/// Look up a style in the cache and mark it as the most recently used.")?;
+    writeln!(w, "fn touch_cached_style(")?;
+    writeln!(w, "    cache: &mut Vec<(ArchivedStyle, Arc<Style>)>,")?;
+    writeln!(w, "    style: ArchivedStyle,")?;
+    writeln!(w, ") -> Option<Arc<Style>> {{")?;
+    writeln!(w, "    let idx = cache.iter().position(|(cached, _)| *cached == style)?;")?;
+    writeln!(w, "    let hit = cache.remove(idx);")?;
+    writeln!(w, "    let style = Arc::clone(&hit.1);")?;
+    writeln!(w, "    cache.push(hit);")?;
+    writeln!(w, "    Some(style)")?;
+    writeln!(w, "}}")?;
+    writeln!(w)?;
 
     writeln!(w, "fn from_cbor<T: DeserializeOwned>(")?;
     writeln!(w, "    reader: &[u8],")?;
@@ -356,6 +456,23 @@ fn write_locales_section(w: &mut String, items: &[(Vec<u8>, Locale)]) -> fmt::Re
     writeln!(w, "];")?;
     writeln!(w)?;
 
+    writeln!(w, "/// The codes of the locales in [`LOCALES`], in the same order.")?;
+    writeln!(w, "#[rustfmt::skip]")?;
+    writeln!(w, "const LOCALE_CODES: &[&str] = &[")?;
+    for (_, locale) in items {
+        writeln!(w, "    {:?},", locale.lang.as_ref().unwrap().0)?;
+    }
+    writeln!(w, "];")?;
+    writeln!(w)?;
+
+    writeln!(w, "/// Get the CSL locale with exactly the given code, like `de-DE`, without")?;
+    writeln!(w, "/// deserializing any of the others.")?;
+    writeln!(w, "pub fn locale(code: &str) -> Option<Locale> {{")?;
+    writeln!(w, "    let index = LOCALE_CODES.iter().position(|&c| c == code)?;")?;
+    writeln!(w, "    Some(from_cbor(LOCALES[index]).unwrap())")?;
+    writeln!(w, "}}")?;
+    writeln!(w)?;
+
     writeln!(w, "/// Get all CSL locales.")?;
     writeln!(w, "pub fn locales() -> Vec<Locale> {{")?;
     writeln!(w, "    LOCALES")?;